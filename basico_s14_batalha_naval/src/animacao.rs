@@ -0,0 +1,104 @@
+/*
+	Animações dos tiros
+
+	Depois de cada ataque, antes da pausa de '--atraso', as células atingidas
+	ganham alguns quadros curtos:
+
+	- Tiro na água: respingo em anéis, com os glifos 'respingo' do tema
+	- Acerto: explosão piscando entre o desenho do acerto e o clarão
+	- Navio perdido: o navio inteiro afunda, com os glifos 'afundando'
+
+	No fim sempre ficam os desenhos normais de acerto e água. Cada quadro espera
+	com 'tem_evento', sem ler o evento: uma tecla apertada pula o resto da
+	animação e continua na fila para a batalha tratar.
+	Desligadas com '--sem-animacao' ou na tela de opções. Com '--som' cada
+	acerto toca a campainha do terminal uma vez e um navio afundado, duas.
+*/
+
+
+use std::time::Duration;
+
+use crossterm::{QueueableCommand,style};
+
+use crate::{Impacto,Jogador,Navio,Opcoes,posicao_na_tela};
+use crate::console::Console;
+use crate::erro::ErroJogo;
+
+
+// Duração de cada quadro
+const DURACAO_QUADRO: Duration = Duration::from_millis(70);
+
+// Quadros da explosão, alternando clarão e acerto
+const QUADROS_EXPLOSAO: usize = 4;
+
+const CAMPAINHA: &str = "\x07";
+
+
+// Anima um ataque já aplicado ao mapa e desenhado, ver 'realiza_ataque'
+pub fn anima(con:&mut dyn Console, alvo:&Jogador, impacto:&Impacto, navios:&[Navio], lado:usize, opcoes:&Opcoes) -> Result<(), ErroJogo> {
+	let tema = &opcoes.tema;
+	if opcoes.som && !impacto.acertos.is_empty() {
+		write!(con, "{}", CAMPAINHA.repeat(if impacto.afundados.is_empty() { 1 } else { 2 }))?;
+	}
+	if !opcoes.animacao || impacto.agua.len() + impacto.acertos.len() == 0 {
+		return Ok(());
+	}
+
+	// Respingos e explosões juntos, depois cada navio perdido afunda
+	let respingos = tema.glifos.respingo.len();
+	for quadro in 0..respingos.max(QUADROS_EXPLOSAO) {
+		for &(x,y) in impacto.agua.iter() {
+			let desenho = match tema.respingo(quadro) {
+				Some(respingo) => respingo,
+				None => tema.agua(),
+			};
+			con.queue( posicao_na_tela(alvo, x, y, lado) )?.queue( style::PrintStyledContent(desenho) )?;
+		}
+		for &(x,y,navio) in impacto.acertos_por_navio().iter() {
+			let desenho = match quadro % 2 == 0 && quadro < QUADROS_EXPLOSAO {
+				true => tema.clarao(),
+				false => tema.acerto(&navios[navio]),
+			};
+			con.queue( posicao_na_tela(alvo, x, y, lado) )?.queue( style::PrintStyledContent(desenho) )?;
+		}
+		if espera(con)? {
+			return finaliza(con, alvo, impacto, navios, lado, opcoes);
+		}
+	}
+	for quadro in 0..tema.glifos.afundando.len() {
+		for &navio in impacto.afundados.iter() {
+			for (x,y) in navios[navio].celulas() {
+				con.queue( posicao_na_tela(alvo, x, y, lado) )?.queue( style::PrintStyledContent(tema.afundando(quadro)) )?;
+			}
+		}
+		if impacto.afundados.is_empty() || espera(con)? {
+			break;
+		}
+	}
+	finaliza(con, alvo, impacto, navios, lado, opcoes)
+}
+
+
+// Mostra o quadro e espera, diz se chegou alguma tecla
+fn espera(con:&mut dyn Console) -> Result<bool, ErroJogo> {
+	con.flush()?;
+	Ok(con.tem_evento(DURACAO_QUADRO)?)
+}
+
+
+// Desenhos de depois do ataque, como 'realiza_ataque' deixou
+fn finaliza(con:&mut dyn Console, alvo:&Jogador, impacto:&Impacto, navios:&[Navio], lado:usize, opcoes:&Opcoes) -> Result<(), ErroJogo> {
+	for &(x,y) in impacto.agua.iter() {
+		con.queue( posicao_na_tela(alvo, x, y, lado) )?.queue( style::PrintStyledContent(opcoes.tema.agua()) )?;
+	}
+	for &navio in impacto.afundados.iter() {
+		for (x,y) in navios[navio].celulas() {
+			con.queue( posicao_na_tela(alvo, x, y, lado) )?.queue( style::PrintStyledContent(opcoes.tema.acerto(&navios[navio])) )?;
+		}
+	}
+	for &(x,y,navio) in impacto.acertos_por_navio().iter() {
+		con.queue( posicao_na_tela(alvo, x, y, lado) )?.queue( style::PrintStyledContent(opcoes.tema.acerto(&navios[navio])) )?;
+	}
+	con.flush()?;
+	Ok(())
+}
@@ -0,0 +1,255 @@
+/*
+	Estratégias de tiro do bot
+
+	- Fácil: tiros aleatórios
+	- Normal: procura aleatória, depois de acertar atira nas vizinhas (caça e alvo)
+	- Difícil: como o normal, mas procura primeiro nas casas de uma só cor do
+	tabuleiro de xadrez, pois quase todo navio ocupa duas casas vizinhas
+	- Adaptativo: como o difícil, mas procura antes nas casas onde o humano
+	costuma pôr navios, lembradas das partidas anteriores (ver memoria.rs);
+	sem memória, ou em partidas de vários jogadores, joga como o difícil
+
+	No tabuleiro hexagonal (ver hexagonal.rs) as vizinhas são as seis do hexágono
+	e o difícil procura primeiro nas casas de uma das três cores, por onde passa
+	todo navio de três ou mais casas; a memória não é usada.
+
+	O bot só usa o que um jogador saberia: onde já atirou, onde acertou e o terreno,
+	e com '--bot-revelacao' o que a regra de revelação contar dos acertos (ver
+	revelacao.rs): as células dos navios afundados ou qual navio cada acerto atingiu.
+*/
+
+
+use rand::Rng;
+
+use crate::{Mapa,Arma,Arsenal,Direcao,alvo_valido,celulas_do_ataque};
+use crate::hexagonal;
+
+
+// Fração mínima das partidas lembradas com navio numa casa para o adaptativo procurar nela
+const LIMIAR_MEMORIA: f64 = 0.3;
+
+
+// Nível de dificuldade do bot
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Dificuldade {
+	FACIL,
+	NORMAL,
+	DIFICIL,
+	ADAPTATIVO,
+}
+
+impl Dificuldade {
+	pub const TODAS: [Dificuldade;4] = [Dificuldade::FACIL, Dificuldade::NORMAL, Dificuldade::DIFICIL, Dificuldade::ADAPTATIVO];
+
+	// Nome usado na linha de comando e nos perfis
+	pub fn nome(&self) -> &'static str {
+		match self {
+			Dificuldade::FACIL => "facil",
+			Dificuldade::NORMAL => "normal",
+			Dificuldade::DIFICIL => "dificil",
+			Dificuldade::ADAPTATIVO => "adaptativo",
+		}
+	}
+
+	pub fn por_nome(nome: &str) -> Option<Dificuldade> {
+		Dificuldade::TODAS.into_iter().find(|d| d.nome() == nome)
+	}
+}
+
+
+pub struct Bot {
+	pub dificuldade: Dificuldade,
+	lado: usize,						// Lado do tabuleiro
+	suspeita: Option<(usize,usize)>,	// Área onde o radar detectou navio
+	pendentes: Vec<(usize,usize)>,		// Vizinhas de acertos, ainda por tentar
+	abertos: Vec<(usize,usize,Option<usize>)>,	// Acertos em navios que não se sabe se afundaram, com o navio se revelado
+	memoria: Option<Vec<Vec<f64>>>,		// Fração das partidas com navio do humano em cada casa
+	hexagonal: bool,					// Tabuleiro hexagonal, seis vizinhas
+}
+
+impl Bot {
+	pub fn new(dificuldade: Dificuldade, lado: usize) -> Bot {
+		Bot { dificuldade, lado, suspeita: None, pendentes: Vec::new(), abertos: Vec::new(), memoria: None, hexagonal: false }
+	}
+
+	// Bot para o tabuleiro hexagonal
+	pub fn hexagonal(dificuldade: Dificuldade, lado: usize) -> Bot {
+		Bot { hexagonal: true, ..Bot::new(dificuldade, lado) }
+	}
+
+	// O adaptativo passa a procurar onde o humano costuma pôr navios
+	pub fn lembra(&mut self, frequencias: Vec<Vec<f64>>) {
+		self.memoria = Some(frequencias);
+	}
+
+	// Radar encontrou navio em torno de (x,y)
+	pub fn registra_radar(&mut self, x:usize, y:usize) {
+		self.suspeita = Some((x,y));
+	}
+
+	// Guarda as vizinhas dos acertos para os próximos tiros, 'navios' diz o navio de cada acerto se a regra revela
+	pub fn registra_acertos(&mut self, acertos:&[(usize,usize)], navios:Option<&[usize]>) {
+		if self.dificuldade == Dificuldade::FACIL {
+			return;
+		}
+		for (i,&(x,y)) in acertos.iter().enumerate() {
+			self.abertos.push((x, y, navios.map(|navios| navios[i])));
+			self.pendentes.extend(self.vizinhas(x, y));
+		}
+	}
+
+	// Navio afundado nas células dadas: só sobram as vizinhas dos outros acertos
+	pub fn registra_afundado(&mut self, celulas:&[(usize,usize)]) {
+		self.abertos.retain(|&(x,y,_)| !celulas.contains(&(x,y)));
+		let vizinhas: Vec<(usize,usize)> = self.abertos.iter().flat_map(|&(x,y,_)| self.vizinhas(x, y)).collect();
+		self.pendentes.retain(|pendente| vizinhas.contains(pendente));
+	}
+
+	// Ainda tem vizinhas de acertos por tentar, usado para não trocar de alvo no meio da caça
+	pub fn perseguindo(&self) -> bool {
+		!self.pendentes.is_empty()
+	}
+
+	// Escolhe arma e mira, nunca mira em posição já tentada
+	pub fn escolhe_ataque(&mut self, mapa:&Mapa, arsenal:&Arsenal, rng:&mut impl Rng) -> (Arma,usize,usize,Direcao) {
+		// Dois acertos do mesmo navio mostram a linha dele
+		if let Some((x,y)) = self.ponta_da_linha(mapa) {
+			return (Arma::TIRO, x, y, Direcao::LESTE);
+		}
+
+		// Termina de afundar o que já foi atingido
+		while let Some((x,y)) = self.pendentes.pop() {
+			if alvo_valido(mapa[y][x]) {
+				return (Arma::TIRO, x, y, Direcao::LESTE);
+			}
+		}
+
+		// Continua atirando na área onde o radar detectou navio
+		if let Some((suspeita_x,suspeita_y)) = self.suspeita {
+			for (x,y) in celulas_do_ataque(mapa, Arma::RADAR, suspeita_x, suspeita_y, Direcao::LESTE) {
+				if alvo_valido(mapa[y][x]) {
+					return (Arma::TIRO, x, y, Direcao::LESTE);
+				}
+			}
+			self.suspeita = None;
+		}
+
+		let (mira_x,mira_y) = match self.dificuldade {
+			Dificuldade::DIFICIL => posicao_xadrez(mapa, self.hexagonal, rng).unwrap_or_else(|| posicao_aleatoria(mapa, rng)),
+			Dificuldade::ADAPTATIVO => self.memoria.as_ref()
+				.and_then(|memoria| posicao_lembrada(mapa, memoria, rng))
+				.or_else(|| posicao_xadrez(mapa, self.hexagonal, rng))
+				.unwrap_or_else(|| posicao_aleatoria(mapa, rng)),
+			_ => posicao_aleatoria(mapa, rng),
+		};
+
+		// De vez em quando usa uma arma especial, se ainda houver
+		let arma = match rng.gen_range(0..10) {
+			0 if arsenal.radares > 0 => Arma::RADAR,
+			1 if arsenal.bombas > 0 => Arma::BOMBA,
+			2 if arsenal.torpedos > 0 => Arma::TORPEDO,
+			_ => Arma::TIRO,
+		};
+
+		(arma, mira_x, mira_y, Direcao::aleatoria(rng))
+	}
+
+	// Casas vizinhas de (x,y), no quadrado sem as diagonais
+	fn vizinhas(&self, x:usize, y:usize) -> Vec<(usize,usize)> {
+		match self.hexagonal {
+			true => hexagonal::vizinhas(x, y, self.lado),
+			false => vizinhas(x, y, self.lado),
+		}
+	}
+
+	// Próxima casa na linha de acertos de um mesmo navio revelado, se houver
+	fn ponta_da_linha(&self, mapa:&Mapa) -> Option<(usize,usize)> {
+		for &(_,_,navio) in self.abertos.iter().filter(|a| a.2.is_some()) {
+			let celulas: Vec<(usize,usize)> = self.abertos.iter().filter(|a| a.2 == navio).map(|&(x,y,_)| (x,y)).collect();
+			if celulas.len() < 2 {
+				continue;
+			}
+			let (min_x,max_x) = (celulas.iter().map(|c| c.0).min()?, celulas.iter().map(|c| c.0).max()?);
+			let (min_y,max_y) = (celulas.iter().map(|c| c.1).min()?, celulas.iter().map(|c| c.1).max()?);
+			let pontas = match (min_y == max_y, min_x == max_x) {
+				(true,_) => [min_x.checked_sub(1).map(|x| (x,min_y)), Some((max_x+1,min_y))],
+				(_,true) => [min_y.checked_sub(1).map(|y| (min_x,y)), Some((min_x,max_y+1))],
+				_ => continue,
+			};
+			if let Some(ponta) = pontas.into_iter().flatten().find(|&(x,y)| x < self.lado && y < self.lado && alvo_valido(mapa[y][x])) {
+				return Some(ponta);
+			}
+		}
+		None
+	}
+}
+
+
+// Casas vizinhas de (x,y), sem as diagonais
+fn vizinhas(x:usize, y:usize, lado:usize) -> Vec<(usize,usize)> {
+	let mut vizinhas = Vec::new();
+	if y > 0 { vizinhas.push((x,y-1)); }
+	if y < lado-1 { vizinhas.push((x,y+1)); }
+	if x > 0 { vizinhas.push((x-1,y)); }
+	if x < lado-1 { vizinhas.push((x+1,y)); }
+	vizinhas
+}
+
+
+// Sorteia uma posição que ainda não recebeu tiro
+pub fn posicao_aleatoria(mapa:&Mapa, rng:&mut impl Rng) -> (usize,usize) {
+	loop {
+		let x = rng.gen_range(0..mapa.len());
+		let y = rng.gen_range(0..mapa.len());
+		if alvo_valido(mapa[y][x]) {
+			return (x,y);
+		}
+	}
+}
+
+
+// Sorteia entre as posições 'pretas' do xadrez ainda não tentadas, se houver; no hexágono as de cor 0
+fn posicao_xadrez(mapa:&Mapa, hexagonal:bool, rng:&mut impl Rng) -> Option<(usize,usize)> {
+	let mut candidatas = Vec::new();
+	for (y,linha) in mapa.iter().enumerate() {
+		for (x,&celula) in linha.iter().enumerate() {
+			let preta = match hexagonal {
+				true => hexagonal::cor(x, y) == 0,
+				false => (x+y) % 2 == 0,
+			};
+			if preta && alvo_valido(celula) {
+				candidatas.push((x,y));
+			}
+		}
+	}
+	if candidatas.is_empty() {
+		None
+	} else {
+		Some(candidatas[rng.gen_range(0..candidatas.len())])
+	}
+}
+
+
+// Sorteia entre as casas ainda não tentadas onde o humano mais vezes pôs navio, se passarem do limiar
+fn posicao_lembrada(mapa:&Mapa, memoria:&[Vec<f64>], rng:&mut impl Rng) -> Option<(usize,usize)> {
+	let mut maior = LIMIAR_MEMORIA;
+	let mut candidatas = Vec::new();
+	for (y,linha) in mapa.iter().enumerate() {
+		for (x,&celula) in linha.iter().enumerate() {
+			let frequencia = memoria[y][x];
+			if !alvo_valido(celula) || frequencia < maior {
+				continue;
+			}
+			if frequencia > maior {
+				maior = frequencia;
+				candidatas.clear();
+			}
+			candidatas.push((x,y));
+		}
+	}
+	if candidatas.is_empty() {
+		None
+	} else {
+		Some(candidatas[rng.gen_range(0..candidatas.len())])
+	}
+}
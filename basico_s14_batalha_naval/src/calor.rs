@@ -0,0 +1,134 @@
+/*
+	Mapa de calor e dicas do humano
+
+	Para cada navio da frota inimiga são contadas as posições em que ele ainda
+	pode estar: dentro do tabuleiro, sem ilha, recife ou tiro na água. Cada
+	acerto coberto multiplica o peso da posição por PESO_ACERTO, pois o navio
+	atingido está perto dos acertos. A chance de uma célula sem tiro ter navio
+	é a soma, navio a navio, da fração do peso das posições que a cobrem,
+	limitada a 100%.
+
+	O cálculo só usa o que o humano sabe: tiros na água, acertos e o terreno.
+	Não sabe qual navio foi atingido nem quais afundaram; um navio afundado
+	fica com quase todo o peso nas posições sobre os acertos e pouco muda o resto.
+
+	Na batalha 'c' liga e desliga o mapa, com a chance em décimos, de 0 a 9, em
+	cada célula sem tiro e a melhor destacada, e 'p' gasta uma dica e leva a
+	mira para a melhor.
+	Cada partida tem '--dicas <n>' dicas; contra outra pessoa não há mapa nem dicas, e
+	com neblina também não, porque os navios do bot mudam de lugar (ver neblina.rs).
+*/
+
+
+use crossterm::{QueueableCommand,style};
+
+use crate::{Mapa,Navio,Direcao,Jogador,AGUA,DESTRUIDO,eh_obstaculo,posicao_na_tela};
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::tema::Tema;
+
+
+// Quanto vale a mais cada acerto coberto por uma posição de navio
+const PESO_ACERTO: f64 = 20.0;
+
+// Dicas em cada partida, se '--dicas' não for dado
+pub const DICAS_PADRAO: u32 = 3;
+
+
+// Chance de cada célula ter navio, None nas que já receberam tiro e no terreno
+// 'mapa' tem os tiros do humano, 'original' diz se um tiro foi acerto ou água
+pub fn chances(mapa:&Mapa, original:&Mapa, navios:&[Navio]) -> Vec<Vec<Option<f64>>> {
+	let lado = mapa.len();
+	let proibida = |x:usize, y:usize| eh_obstaculo(mapa[y][x]) || (mapa[y][x] == DESTRUIDO && original[y][x] == AGUA);
+	let mut soma = vec![vec![0.0; lado]; lado];
+
+	for navio in navios.iter() {
+		// Cada posição uma vez só, formas simétricas repetem células
+		let mut posicoes: Vec<(Vec<(usize,usize)>, f64)> = Vec::new();
+		for y in 0..lado as i64 {
+			for x in 0..lado as i64 {
+				for direcao in [Direcao::NORTE, Direcao::SUL, Direcao::LESTE, Direcao::OESTE] {
+					for espelhado in [false, true] {
+						let celulas = navio.celulas_em(x, y, direcao, espelhado);
+						if celulas.iter().any(|&(cx,cy)| cx < 0 || cy < 0 || cx >= lado as i64 || cy >= lado as i64) {
+							continue;
+						}
+						let mut celulas: Vec<(usize,usize)> = celulas.iter().map(|&(cx,cy)| (cx as usize, cy as usize)).collect();
+						celulas.sort();
+						if celulas.iter().any(|&(cx,cy)| proibida(cx,cy)) || posicoes.iter().any(|(outras,_)| *outras == celulas) {
+							continue;
+						}
+						let acertos = celulas.iter().filter(|&&(cx,cy)| mapa[cy][cx] == DESTRUIDO).count();
+						posicoes.push((celulas, PESO_ACERTO.powi(acertos as i32)));
+					}
+				}
+			}
+		}
+
+		let total: f64 = posicoes.iter().map(|(_,peso)| peso).sum();
+		for (celulas,peso) in posicoes.iter() {
+			for &(x,y) in celulas.iter() {
+				soma[y][x] += peso / total;
+			}
+		}
+	}
+
+	(0..lado).map(|y| (0..lado).map(|x| {
+		match mapa[y][x] == DESTRUIDO || eh_obstaculo(mapa[y][x]) {
+			true => None,
+			false => Some(soma[y][x].min(1.0)),
+		}
+	}).collect()).collect()
+}
+
+
+// Célula com a maior chance, a primeira na ordem de leitura quando empata
+pub fn melhor(chances:&[Vec<Option<f64>>]) -> Option<(usize,usize)> {
+	let mut melhor: Option<(usize,usize,f64)> = None;
+	for (y,linha) in chances.iter().enumerate() {
+		for (x,&chance) in linha.iter().enumerate() {
+			if let Some(chance) = chance {
+				if melhor.is_none_or(|(_,_,maior)| chance > maior) {
+					melhor = Some((x,y,chance));
+				}
+			}
+		}
+	}
+	melhor.map(|(x,y,_)| (x,y))
+}
+
+
+// Mostra a chance em décimos nas células sem tiro do tabuleiro do bot, um dígito deixa as células separadas
+pub fn desenha(con:&mut dyn Console, chances:&[Vec<Option<f64>>], tema:&Tema) -> Result<(), ErroJogo> {
+	let lado = chances.len();
+	let destaque = melhor(chances);
+	for (y,linha) in chances.iter().enumerate() {
+		for (x,&chance) in linha.iter().enumerate() {
+			if let Some(chance) = chance {
+				let texto = format!("{} ", ((chance*10.0).round() as u32).min(9));
+				con
+					.queue( posicao_na_tela(&Jogador::BOT, x, y, lado) )?
+					.queue( style::PrintStyledContent(tema.calor(texto, destaque == Some((x,y)))) )?;
+			}
+		}
+	}
+	con.flush()?;
+	Ok(())
+}
+
+
+// Volta a desenhar o mar nas células onde estava o mapa
+pub fn apaga(con:&mut dyn Console, chances:&[Vec<Option<f64>>], tema:&Tema) -> Result<(), ErroJogo> {
+	let lado = chances.len();
+	for (y,linha) in chances.iter().enumerate() {
+		for (x,chance) in linha.iter().enumerate() {
+			if chance.is_some() {
+				con
+					.queue( posicao_na_tela(&Jogador::BOT, x, y, lado) )?
+					.queue( style::PrintStyledContent(tema.mar()) )?;
+			}
+		}
+	}
+	con.flush()?;
+	Ok(())
+}
@@ -0,0 +1,81 @@
+/*
+	Console: por onde o jogo desenha e lê o teclado
+
+	- O desenho continua sendo feito com os comandos do crossterm ('queue', 'execute'),
+	que escrevem sequências ANSI em qualquer 'Write'
+	- 'ConsoleReal' escreve no terminal e lê os eventos do crossterm
+	- Nos testes 'ConsoleVirtual' interpreta as sequências em uma grade de células
+	na memória e entrega eventos de teclado roteirizados, ver console_virtual.rs
+*/
+
+
+use std::io::{self,Stdout,Write};
+use std::time::Duration;
+
+use crossterm::terminal;
+use crossterm::event::{self,Event};
+
+
+// Saída para desenhar e entrada de eventos
+pub trait Console: Write {
+	// Largura e altura em caracteres
+	fn tamanho(&self) -> io::Result<(u16,u16)>;
+
+	// Espera o próximo evento
+	fn le_evento(&mut self) -> io::Result<Event>;
+
+	// Existe evento antes do tempo dado ?
+	fn tem_evento(&mut self, espera: Duration) -> io::Result<bool>;
+
+	// Liga ou desliga o raw mode
+	fn modo_bruto(&mut self, ligado: bool) -> io::Result<()>;
+}
+
+
+// Terminal de verdade
+pub struct ConsoleReal {
+	saida: Stdout,
+}
+
+impl ConsoleReal {
+	pub fn new() -> ConsoleReal {
+		ConsoleReal{ saida: io::stdout() }
+	}
+}
+
+impl Default for ConsoleReal {
+	fn default() -> ConsoleReal {
+		ConsoleReal::new()
+	}
+}
+
+impl Write for ConsoleReal {
+	fn write(&mut self, dados: &[u8]) -> io::Result<usize> {
+		self.saida.write(dados)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.saida.flush()
+	}
+}
+
+impl Console for ConsoleReal {
+	fn tamanho(&self) -> io::Result<(u16,u16)> {
+		terminal::size()
+	}
+
+	fn le_evento(&mut self) -> io::Result<Event> {
+		event::read()
+	}
+
+	fn tem_evento(&mut self, espera: Duration) -> io::Result<bool> {
+		event::poll(espera)
+	}
+
+	fn modo_bruto(&mut self, ligado: bool) -> io::Result<()> {
+		match ligado {
+			true => terminal::enable_raw_mode(),
+			false => terminal::disable_raw_mode(),
+		}
+	}
+}
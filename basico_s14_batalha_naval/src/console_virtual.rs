@@ -0,0 +1,300 @@
+/*
+	Console na memória, para os testes
+
+	- Interpreta as sequências ANSI que o crossterm gera: posição do cursor, limpeza,
+	cores, vídeo reverso, salvar e restaurar a posição
+	- Cada célula guarda o caractere, as cores e o vídeo reverso
+	- Os eventos de teclado vêm de uma fila, sem eventos 'le_evento' falha, assim um
+	roteiro incompleto termina o teste em vez de travar
+	- O texto é interpretado no 'flush' ou quando a grade é consultada
+	- A campainha é contada e, se pedido, cada espera por evento fotografa a tela,
+	para conferir os quadros das animações
+*/
+
+
+use std::collections::VecDeque;
+use std::io::{self,Write};
+use std::time::Duration;
+
+use crossterm::style::Color;
+use crossterm::event::{Event,KeyCode,KeyEvent,KeyModifiers};
+
+use crate::console::Console;
+
+
+// Uma posição da grade
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct Celula {
+	pub letra: char,
+	pub frente: Color,
+	pub fundo: Color,
+	pub reverso: bool,
+}
+
+impl Celula {
+	const VAZIA: Celula = Celula{ letra: ' ', frente: Color::Reset, fundo: Color::Reset, reverso: false };
+}
+
+
+pub struct ConsoleVirtual {
+	largura: u16,
+	altura: u16,
+	grade: Vec<Vec<Celula>>,
+	cursor: (u16,u16),				// Coluna e linha
+	salvo: (u16,u16),
+	estilo: Celula,					// Cores e reverso do próximo caractere
+	bruto: bool,
+	pendente: Vec<u8>,				// Escrito e ainda não interpretado
+	eventos: VecDeque<Event>,
+	campainhas: usize,
+	fotografias: Option<Vec<String>>,	// Texto da tela em cada 'tem_evento', se ligado
+}
+
+impl ConsoleVirtual {
+	pub fn new(largura: u16, altura: u16) -> ConsoleVirtual {
+		ConsoleVirtual{
+			largura,
+			altura,
+			grade: vec![vec![Celula::VAZIA; largura as usize]; altura as usize],
+			cursor: (0,0),
+			salvo: (0,0),
+			estilo: Celula::VAZIA,
+			bruto: false,
+			pendente: Vec::new(),
+			eventos: VecDeque::new(),
+			campainhas: 0,
+			fotografias: None,
+		}
+	}
+
+	// Acrescenta teclas ao roteiro, cada caractere é uma tecla
+	pub fn digita(&mut self, texto: &str) {
+		for c in texto.chars() {
+			self.tecla(KeyCode::Char(c));
+		}
+	}
+
+	// Acrescenta uma tecla especial ao roteiro
+	pub fn tecla(&mut self, codigo: KeyCode) {
+		self.envia(Event::Key(KeyEvent::new(codigo, KeyModifiers::NONE)));
+	}
+
+	// Acrescenta um evento qualquer ao roteiro, como Ctrl+tecla ou mudança de tamanho
+	pub fn envia(&mut self, evento: Event) {
+		self.eventos.push_back(evento);
+	}
+
+	// Eventos do roteiro ainda não lidos
+	pub fn eventos_restantes(&self) -> usize {
+		self.eventos.len()
+	}
+
+	// Célula na coluna x, linha y
+	pub fn celula(&mut self, x: u16, y: u16) -> Celula {
+		self.interpreta();
+		self.grade[y as usize][x as usize]
+	}
+
+	// Texto de uma linha, sem os espaços do fim
+	pub fn linha(&mut self, y: u16) -> String {
+		self.interpreta();
+		let texto: String = self.grade[y as usize].iter().map(|c| c.letra).collect();
+		String::from(texto.trim_end())
+	}
+
+	// Texto da tela inteira, sem as linhas vazias do fim
+	pub fn texto(&mut self) -> String {
+		let linhas: Vec<String> = (0..self.altura).map(|y| self.linha(y)).collect();
+		let usadas = linhas.iter().rposition(|l| !l.is_empty()).map_or(0, |i| i+1);
+		linhas[..usadas].join("\n")
+	}
+
+	// Vezes que a campainha tocou
+	pub fn campainhas(&mut self) -> usize {
+		self.interpreta();
+		self.campainhas
+	}
+
+	// Passa a fotografar a tela em cada espera por evento
+	pub fn fotografa_esperas(&mut self) {
+		self.fotografias = Some(Vec::new());
+	}
+
+	// Fotografias tiradas até agora
+	pub fn fotografias(&self) -> &[String] {
+		self.fotografias.as_deref().unwrap_or(&[])
+	}
+
+	// Posição atual do cursor, coluna e linha
+	pub fn cursor(&mut self) -> (u16,u16) {
+		self.interpreta();
+		self.cursor
+	}
+
+
+	// Interpreta tudo o que foi escrito até agora
+	fn interpreta(&mut self) {
+		let pendente = std::mem::take(&mut self.pendente);
+		let texto = String::from_utf8_lossy(&pendente);
+		let mut letras = texto.chars().peekable();
+		while let Some(c) = letras.next() {
+			match c {
+				'\x1b' => match letras.next() {
+					Some('7') => self.salvo = self.cursor,
+					Some('8') => self.cursor = self.salvo,
+					Some('[') => {
+						let mut parametros = String::new();
+						while let Some(&p) = letras.peek() {
+							if p.is_ascii_digit() || p == ';' || p == '?' {
+								parametros.push(p);
+								letras.next();
+							} else {
+								break;
+							}
+						}
+						if let Some(comando) = letras.next() {
+							self.executa(comando, &parametros);
+						}
+					}
+					_ => {}
+				},
+				'\r' => self.cursor.0 = 0,
+				'\x07' => self.campainhas += 1,
+				'\n' => {
+					// Fora do raw mode o terminal também volta ao início da linha
+					if !self.bruto {
+						self.cursor.0 = 0;
+					}
+					self.desce();
+				}
+				c => self.escreve(c),
+			}
+		}
+	}
+
+	// Comando CSI, 'parametros' são os números antes da letra
+	fn executa(&mut self, comando: char, parametros: &str) {
+		let numeros: Vec<u16> = parametros.split(';').map(|n| n.parse().unwrap_or(0)).collect();
+		let n = |i: usize, padrao: u16| numeros.get(i).copied().filter(|&v| v > 0).unwrap_or(padrao);
+		let (x,y) = self.cursor;
+		match comando {
+			'H' => self.cursor = ((n(1,1)-1).min(self.largura-1), (n(0,1)-1).min(self.altura-1)),
+			'A' => self.cursor.1 = y.saturating_sub(n(0,1)),
+			'B' => self.cursor.1 = (y+n(0,1)).min(self.altura-1),
+			'C' => self.cursor.0 = (x+n(0,1)).min(self.largura-1),
+			'D' => self.cursor.0 = x.saturating_sub(n(0,1)),
+			'G' => self.cursor.0 = (n(0,1)-1).min(self.largura-1),
+			'J' => match numeros[0] {
+				2 | 3 => self.limpa(0, self.altura, 0),
+				_ => {
+					self.limpa(y, y+1, x);
+					self.limpa(y+1, self.altura, 0);
+				}
+			},
+			'K' => match numeros[0] {
+				2 => self.limpa(y, y+1, 0),
+				_ => self.limpa(y, y+1, x),
+			},
+			'm' => self.muda_estilo(parametros),
+			_ => {}				// Cursor visível, tela alternativa, ...
+		}
+	}
+
+	// Cores e atributos, como '38;5;14' ou '48;2;0;60;100' ou '7'
+	fn muda_estilo(&mut self, parametros: &str) {
+		let partes: Vec<&str> = parametros.split(';').collect();
+		let mut i = 0;
+		while i < partes.len() {
+			match partes[i] {
+				"" | "0" => {
+					self.estilo = Celula::VAZIA;
+				}
+				"7" => self.estilo.reverso = true,
+				"27" => self.estilo.reverso = false,
+				"39" => self.estilo.frente = Color::Reset,
+				"49" => self.estilo.fundo = Color::Reset,
+				"38" | "48" => {
+					let tamanho = if partes.get(i+1) == Some(&"2") { 4 } else { 2 };
+					let fim = (i+1+tamanho).min(partes.len());
+					let cor = Color::parse_ansi(&partes[i+1..fim].join(";")).unwrap_or(Color::Reset);
+					if partes[i] == "38" {
+						self.estilo.frente = cor;
+					} else {
+						self.estilo.fundo = cor;
+					}
+					i = fim-1;
+				}
+				_ => {}			// Negrito, itálico, ...
+			}
+			i += 1;
+		}
+	}
+
+	// Escreve um caractere no cursor, passando para a próxima linha no fim
+	fn escreve(&mut self, letra: char) {
+		if self.cursor.0 >= self.largura {
+			self.cursor.0 = 0;
+			self.desce();
+		}
+		let (x,y) = self.cursor;
+		self.grade[y as usize][x as usize] = Celula{ letra, ..self.estilo };
+		self.cursor.0 += 1;
+	}
+
+	// Desce uma linha, rolando a tela na última
+	fn desce(&mut self) {
+		if self.cursor.1+1 < self.altura {
+			self.cursor.1 += 1;
+		} else {
+			self.grade.remove(0);
+			self.grade.push(vec![Celula::VAZIA; self.largura as usize]);
+		}
+	}
+
+	// Apaga as linhas de 'y_ini' até antes de 'y_fim', a primeira a partir da coluna 'x_ini'
+	fn limpa(&mut self, y_ini: u16, y_fim: u16, x_ini: u16) {
+		for y in y_ini .. y_fim.min(self.altura) {
+			let inicio = if y == y_ini { x_ini as usize } else { 0 };
+			for celula in self.grade[y as usize].iter_mut().skip(inicio) {
+				*celula = Celula::VAZIA;
+			}
+		}
+	}
+}
+
+impl Write for ConsoleVirtual {
+	fn write(&mut self, dados: &[u8]) -> io::Result<usize> {
+		self.pendente.extend_from_slice(dados);
+		Ok(dados.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.interpreta();
+		Ok(())
+	}
+}
+
+impl Console for ConsoleVirtual {
+	fn tamanho(&self) -> io::Result<(u16,u16)> {
+		Ok((self.largura, self.altura))
+	}
+
+	fn le_evento(&mut self) -> io::Result<Event> {
+		self.interpreta();
+		self.eventos.pop_front().ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "fim do roteiro de teclas"))
+	}
+
+	fn tem_evento(&mut self, _espera: Duration) -> io::Result<bool> {
+		if self.fotografias.is_some() {
+			let texto = self.texto();
+			self.fotografias.get_or_insert_with(Vec::new).push(texto);
+		}
+		Ok(!self.eventos.is_empty())
+	}
+
+	fn modo_bruto(&mut self, ligado: bool) -> io::Result<()> {
+		self.interpreta();
+		self.bruto = ligado;
+		Ok(())
+	}
+}
@@ -0,0 +1,337 @@
+/*
+	Desafio do dia, no menu principal
+
+	- A semente da partida vem da data (UTC), ver 'semente_do_dia': todos que
+	jogam no mesmo dia enfrentam a mesma frota do bot, e os mesmos tiros dele
+	- Regras fixas para os resultados poderem ser comparados: tabuleiro de lado
+	LADO_PADRAO em mar aberto, frota padrão, bot normal, revelação total, sem
+	armas especiais, relógio, dicas nem neblina; a partida não pode ser salva
+	- A pontuação é o número de ataques até afundar a frota do bot, menor é
+	melhor; só a primeira partida de cada jogador no dia conta e a derrota fica
+	sem pontos. Ela é registrada como derrota quando a batalha começa e só o fim
+	dela troca o resultado: sair, abortar ou reiniciar não dá outra chance
+	- Placar local no diretório dos perfis (ver perfil.rs), uma linha por resultado:
+		<dados>/batalha_naval/desafios.txt
+			<data> <jogador> <ataques, ou '-' na derrota>
+	- '--desafios' mostra o placar, '--exporta-desafios <arquivo>' grava os
+	resultados em um arquivo no mesmo formato e '--importa-desafios <arquivo>'
+	junta os de outra máquina, sem repetir jogador no mesmo dia
+*/
+
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime,UNIX_EPOCH};
+
+use crate::{Opcoes,LADO_PADRAO};
+use crate::bot::Dificuldade;
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::perfil;
+use crate::revelacao::Revelacao;
+
+
+const SEGUNDOS_POR_DIA: u64 = 24*60*60;
+
+
+// Resultado de um jogador no desafio de um dia
+#[derive(Debug,Clone,PartialEq)]
+pub struct Resultado {
+	pub data: String,			// AAAA-MM-DD
+	pub jogador: String,
+	pub ataques: Option<u32>,	// None na derrota
+}
+
+
+// Resultados de todos os dias, na ordem em que foram registrados
+#[derive(Debug)]
+pub struct Placar {
+	resultados: Vec<Resultado>,
+}
+
+impl Placar {
+	// Carrega o placar local, vazio se ainda não existe
+	pub fn carrega() -> Result<Placar, String> {
+		let arquivo = arquivo_do_placar()?;
+		match fs::read_to_string(&arquivo) {
+			Ok(texto) => Ok(Placar::de_texto(&texto)),
+			Err(erro) if erro.kind() == std::io::ErrorKind::NotFound => Ok(Placar::de_texto("")),
+			Err(erro) => Err(tr!("Não conseguiu ler os desafios {}: {}", arquivo.display(), erro)),
+		}
+	}
+
+	// Grava o placar local
+	pub fn grava(&self) -> Result<(), String> {
+		let arquivo = arquivo_do_placar()?;
+		if let Some(diretorio) = arquivo.parent() {
+			fs::create_dir_all(diretorio)
+				.map_err(|erro| tr!("Não conseguiu criar {}: {}", diretorio.display(), erro))?;
+		}
+		fs::write(&arquivo, self.para_texto())
+			.map_err(|erro| tr!("Não conseguiu gravar os desafios {}: {}", arquivo.display(), erro))
+	}
+
+	// Linhas mal formadas e resultados repetidos do mesmo jogador no dia são ignorados
+	pub fn de_texto(texto: &str) -> Placar {
+		let mut placar = Placar { resultados: Vec::new() };
+		for linha in texto.lines() {
+			let partes: Vec<&str> = linha.split_whitespace().collect();
+			let [data, jogador, ataques] = partes[..] else {
+				continue;
+			};
+			let ataques = match ataques {
+				"-" => None,
+				numero => match numero.parse() {
+					Ok(numero) => Some(numero),
+					Err(_) => continue,
+				},
+			};
+			if data_valida(data) && perfil::nome_valido(jogador) {
+				placar.registra(Resultado { data: String::from(data), jogador: String::from(jogador), ataques });
+			}
+		}
+		placar
+	}
+
+	pub fn para_texto(&self) -> String {
+		self.resultados.iter()
+			.map(|r| format!("{} {} {}\n", r.data, r.jogador, r.ataques.map_or(String::from("-"), |a| a.to_string())))
+			.collect()
+	}
+
+	// Guarda o resultado se for o primeiro do jogador no dia, diz se guardou
+	pub fn registra(&mut self, resultado: Resultado) -> bool {
+		if self.resultados.iter().any(|r| r.data == resultado.data && r.jogador == resultado.jogador) {
+			return false;
+		}
+		self.resultados.push(resultado);
+		true
+	}
+
+	// Começo de uma partida, que fica como derrota até terminar; diz se é a primeira do jogador no dia
+	pub fn comeca(&mut self, data: &str, jogador: &str) -> bool {
+		self.registra(Resultado { data: String::from(data), jogador: String::from(jogador), ataques: None })
+	}
+
+	// Resultado da partida que começou com 'comeca', no lugar da derrota provisória
+	pub fn substitui(&mut self, resultado: Resultado) {
+		match self.resultados.iter_mut().find(|r| r.data == resultado.data && r.jogador == resultado.jogador) {
+			Some(anterior) => *anterior = resultado,
+			None => self.resultados.push(resultado),
+		}
+	}
+
+	// Junta os resultados de outro placar, diz quantos eram novos
+	pub fn junta(&mut self, outro: Placar) -> usize {
+		outro.resultados.into_iter().filter(|resultado| self.registra(resultado.clone())).count()
+	}
+
+	// Classificação de um dia: menos ataques primeiro, derrotas no fim
+	pub fn linhas_do_dia(&self, data: &str) -> Vec<String> {
+		let mut do_dia: Vec<&Resultado> = self.resultados.iter().filter(|r| r.data == data).collect();
+		do_dia.sort_by_key(|r| r.ataques.unwrap_or(u32::MAX));
+		let mut linhas = vec![tr!("DESAFIO DO DIA {}", data)];
+		for (posicao,resultado) in do_dia.iter().enumerate() {
+			linhas.push(match resultado.ataques {
+				Some(ataques) => tr!("{}. {} {} ataques", format!("{:>2}", posicao+1), format!("{:<20}", resultado.jogador), ataques),
+				None => tr!("    {} não afundou a frota", format!("{:<20}", resultado.jogador)),
+			});
+		}
+		if do_dia.is_empty() {
+			linhas.push(String::from(tr!("Ninguém jogou ainda.")));
+		}
+		linhas
+	}
+
+	// Classificação de todos os dias, do mais recente para o mais antigo
+	pub fn linhas(&self) -> Vec<String> {
+		let mut datas: Vec<&str> = self.resultados.iter().map(|r| r.data.as_str()).collect();
+		datas.sort_by(|a,b| b.cmp(a));
+		datas.dedup();
+		let mut linhas = Vec::new();
+		for data in datas {
+			linhas.extend(self.linhas_do_dia(data));
+			linhas.push(String::new());
+		}
+		if linhas.is_empty() {
+			linhas.push(String::from(tr!("Nenhum desafio registrado.")));
+		}
+		linhas
+	}
+}
+
+
+// Data de hoje em UTC, igual em qualquer fuso para o desafio ser o mesmo
+pub fn hoje() -> String {
+	let segundos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+	data_de_dias((segundos / SEGUNDOS_POR_DIA) as i64)
+}
+
+// Data AAAA-MM-DD a partir dos dias desde 1970-01-01, no calendário gregoriano
+pub fn data_de_dias(dias: i64) -> String {
+	let z = dias + 719468;
+	let era = z.div_euclid(146097);
+	let dia_da_era = z.rem_euclid(146097);
+	let ano_da_era = (dia_da_era - dia_da_era/1460 + dia_da_era/36524 - dia_da_era/146096) / 365;
+	let dia_do_ano = dia_da_era - (365*ano_da_era + ano_da_era/4 - ano_da_era/100);
+	let mes_de_marco = (5*dia_do_ano + 2) / 153;
+	let dia = dia_do_ano - (153*mes_de_marco + 2)/5 + 1;
+	let mes = if mes_de_marco < 10 { mes_de_marco + 3 } else { mes_de_marco - 9 };
+	let ano = ano_da_era + era*400 + if mes <= 2 { 1 } else { 0 };
+	format!("{:04}-{:02}-{:02}", ano, mes, dia)
+}
+
+fn data_valida(data: &str) -> bool {
+	data.len() == 10 && data.char_indices().all(|(i,c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() })
+}
+
+
+// Semente do dia, FNV-1a da data: a mesma em qualquer máquina e versão do Rust
+pub fn semente_do_dia(data: &str) -> u64 {
+	format!("desafio {}", data).bytes()
+		.fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+
+// Opções do desafio de um dia: as regras fixas, o resto como o jogador escolheu
+pub fn opcoes(opcoes: &Opcoes, data: &str) -> Opcoes {
+	Opcoes{
+		lado: LADO_PADRAO,
+		armas_especiais: false,
+		arquivo_frota: None,
+		mapa: None,
+		tempo_jogada: None,
+		tempo_total: None,
+		dificuldade: Dificuldade::NORMAL,
+		neblina: false,
+		revelacao: Revelacao::TOTAL,
+		bot_revelacao: false,
+		semente: Some(semente_do_dia(data)),
+		servidor: None,
+		vagas: None,
+		dicas: 0,
+		desafio: Some(String::from(data)),
+		..opcoes.clone()
+	}
+}
+
+
+// Registra a derrota provisória no começo de uma partida do desafio, diz se é a partida que conta
+pub fn comeca(jogador: &str, data: &str) -> Result<bool, String> {
+	let mut placar = Placar::carrega()?;
+	let conta = placar.comeca(data, jogador);
+	if conta {
+		placar.grava()?;
+	}
+	Ok(conta)
+}
+
+// Registra o fim de uma partida do desafio e mostra a classificação do dia
+// A partida que conta troca a sua derrota provisória; as outras só entram se o dia ainda não tem resultado
+pub fn termina(con: &mut dyn Console, jogador: &str, data: &str, ataques: Option<u32>, conta: bool) -> Result<(), ErroJogo> {
+	let resultado = Resultado { data: String::from(data), jogador: String::from(jogador), ataques };
+	let registrado = Placar::carrega().and_then(|mut placar| {
+		let novo = match conta {
+			true => {
+				placar.substitui(resultado);
+				true
+			}
+			false => placar.registra(resultado),
+		};
+		placar.grava().map(|_| (novo, placar))
+	});
+	match registrado {
+		Ok((novo, placar)) => {
+			if !novo {
+				writeln!(con, "\r{}", tr!("Só a primeira partida do dia conta para o desafio."))?;
+			}
+			for linha in placar.linhas_do_dia(data) {
+				writeln!(con, "\r{}", linha)?;
+			}
+		}
+		Err(erro) => writeln!(con, "\r{}", erro)?,
+	}
+	Ok(())
+}
+
+
+// Grava o placar local no arquivo dado, diz quantos resultados foram
+pub fn exporta(arquivo: &str) -> Result<usize, String> {
+	let placar = Placar::carrega()?;
+	fs::write(arquivo, placar.para_texto())
+		.map_err(|erro| tr!("Não conseguiu gravar os desafios {}: {}", arquivo, erro))?;
+	Ok(placar.resultados.len())
+}
+
+// Junta ao placar local os resultados do arquivo dado, diz quantos eram novos
+pub fn importa(arquivo: &str) -> Result<usize, String> {
+	let texto = fs::read_to_string(arquivo)
+		.map_err(|erro| tr!("Não conseguiu ler os desafios {}: {}", arquivo, erro))?;
+	let mut placar = Placar::carrega()?;
+	let novos = placar.junta(Placar::de_texto(&texto));
+	placar.grava()?;
+	Ok(novos)
+}
+
+
+fn arquivo_do_placar() -> Result<PathBuf, String> {
+	Ok(perfil::diretorio_dados()?.join("desafios.txt"))
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+	use crate::{Jogador,gerador,novo_jogo,sorteia_navios,FLUXO_FROTA_BOT};
+	use crate::testes::{opcoes_de_teste,posicoes};
+
+	#[test]
+	fn desafio_do_dia_com_placar() {
+		assert_eq!(data_de_dias(0), "1970-01-01");
+		assert_eq!(data_de_dias(11016), "2000-02-29");
+		assert_eq!(data_de_dias(20745), "2026-10-19");
+
+		// A mesma frota do bot para opções diferentes no mesmo dia, outra no dia seguinte
+		let frota_do_bot = |opcoes: &Opcoes| {
+			let mut jogo = novo_jogo(opcoes).unwrap();
+			sorteia_navios(&mut jogo, &Jogador::BOT, &mut gerador(opcoes.semente.unwrap(), FLUXO_FROTA_BOT));
+			posicoes(&jogo.navios_bot)
+		};
+		let outras = Opcoes{ armas_especiais: true, dificuldade: Dificuldade::DIFICIL, semente: Some(7), ..opcoes_de_teste(6) };
+		let hoje = opcoes(&opcoes_de_teste(8), "2026-10-19");
+		assert_eq!((hoje.lado, hoje.dicas, hoje.desafio.as_deref()), (10, 0, Some("2026-10-19")));
+		assert_eq!(frota_do_bot(&hoje), frota_do_bot(&opcoes(&outras, "2026-10-19")));
+		assert_ne!(frota_do_bot(&hoje), frota_do_bot(&opcoes(&outras, "2026-10-20")));
+
+		// Só o primeiro resultado de cada jogador no dia, linhas mal formadas ignoradas
+		let mut placar = Placar::de_texto("2026-10-19 ana 40\nlixo\n2026-10-19 bia -\n2026-10-19 ana 30\n2026-1-19 caio 20\n");
+		assert!(placar.registra(Resultado{ data: String::from("2026-10-19"), jogador: String::from("caio"), ataques: Some(35) }));
+		assert!(!placar.registra(Resultado{ data: String::from("2026-10-19"), jogador: String::from("bia"), ataques: Some(20) }));
+		assert_eq!(placar.para_texto(), "2026-10-19 ana 40\n2026-10-19 bia -\n2026-10-19 caio 35\n");
+
+		// Resultados de outra máquina entram sem repetir
+		let novos = placar.junta(Placar::de_texto("2026-10-19 ana 10\n2026-10-20 ana 50\n2026-10-19 davi 38\n"));
+		assert_eq!(novos, 2);
+		assert_eq!(placar.linhas_do_dia("2026-10-19"), vec![
+			"DESAFIO DO DIA 2026-10-19",
+			" 1. caio                 35 ataques",
+			" 2. davi                 38 ataques",
+			" 3. ana                  40 ataques",
+			"    bia                  não afundou a frota",
+		]);
+		assert_eq!(placar.linhas()[0], "DESAFIO DO DIA 2026-10-20");
+		assert_eq!(placar.linhas_do_dia("2026-10-21")[1], "Ninguém jogou ainda.");
+
+		// Partida abandonada fica como derrota, e a de novo com a mesma semente não conta
+		let vitoria = |ataques| Resultado{ data: String::from("2026-10-21"), jogador: String::from("eva"), ataques: Some(ataques) };
+		assert!(placar.comeca("2026-10-21", "eva"));
+		assert!(!placar.comeca("2026-10-21", "eva"));
+		assert!(!placar.registra(vitoria(30)));
+		assert_eq!(placar.linhas_do_dia("2026-10-21")[1], "    eva                  não afundou a frota");
+
+		// Só o fim da partida que começou troca a derrota
+		assert!(placar.comeca("2026-10-21", "fabio"));
+		placar.substitui(Resultado{ jogador: String::from("fabio"), ..vitoria(45) });
+		assert_eq!(placar.linhas_do_dia("2026-10-21")[1], " 1. fabio                45 ataques");
+	}
+}
@@ -0,0 +1,815 @@
+/*
+	Partidas com vários jogadores: todos contra todos e equipes
+
+	- '--jogadores <lista>' escolhe os participantes, na ordem das vezes, separados por vírgula:
+		humano				o jogador do terminal, no máximo um
+		bot[:dificuldade]	bot local, sem dificuldade vale a de '--dificuldade'
+		remoto				adversário no navegador, conectado na porta de '--servidor <porta>'
+	- '--equipes <lista>' dá a equipe de cada participante, na mesma ordem, como 'a,b,a,b';
+	sem ela é todos contra todos
+	- Todos recebem a mesma frota no mesmo terreno, cada um no seu tabuleiro
+	- Na sua vez cada um escolhe em qual inimigo atira; a vez passa para o próximo da
+	lista que ainda tem navios
+	- Os tiros são conhecidos pela equipe inteira de quem atirou: cada célula guarda as
+	equipes que já atiraram nela, um bit por equipe; os bots da equipe também caçam a
+	partir dos acertos dos aliados
+	- Aliados veem as frotas uns dos outros e não atiram entre si
+	- Vence a última equipe com navios; a partida não é salva e não muda os perfis
+	- No terminal aparecem todos os tabuleiros, quantos couberem por linha, o do humano
+	primeiro; sem humano o terminal só assiste, vendo todos os tiros
+	- O navegador recebe os outros tabuleiros em "alvos" (ver rede.rs) e responde
+	'tiro <x> <y> <jogador>'
+	- Só o tiro simples e sem relógio, como na rede
+*/
+
+
+use std::net::TcpListener;
+
+use crossterm::{ExecutableCommand,QueueableCommand};
+use crossterm::cursor;
+use crossterm::event::{Event,KeyCode,KeyEventKind};
+use crossterm::style::{self,ResetColor,SetBackgroundColor,SetForegroundColor,Stylize};
+use crossterm::terminal::{self,Clear};
+use rand::Rng;
+
+use crate::{Jogo,Jogador,Navio,Mapa,Arma,Arsenal,Direcao,Desfecho,Opcoes,AGUA,DESTRUIDO,ILHA,RECIFE};
+use crate::{atinge,eh_obstaculo,gerador,limpa_tela,novo_jogo,pedacos_vivos,sorteia_navios,arruma_navios_humano,avisa_tamanho};
+use crate::{FLUXO_RODADAS,menu,rede,teclas};
+use crate::bot::{Bot,Dificuldade};
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::menu::Pausa;
+use crate::rede::{Fase,Remoto};
+use crate::teclas::Acao;
+use crate::tema::Tema;
+
+
+pub const MAXIMO_JOGADORES: usize = 6;
+
+// Frotas dos participantes, longe dos fluxos das rodadas
+const FLUXO_FROTAS: u64 = 1 << 32;
+
+
+// Quem joga por um participante
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Controle {
+	HUMANO,
+	BOT(Dificuldade),
+	REMOTO,
+}
+
+// Participante como vem da linha de comando
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct Vaga {
+	pub controle: Controle,
+	pub equipe: usize,
+}
+
+
+// Participantes de '--jogadores' e '--equipes', None se a lista não forma uma partida
+// As equipes são numeradas na ordem em que aparecem
+pub fn vagas(jogadores: &str, equipes: Option<&str>, dificuldade: Dificuldade) -> Option<Vec<Vaga>> {
+	let controles = jogadores.split(',').map(|nome| match nome.trim().split_once(':') {
+		None => match nome.trim() {
+			"humano" => Some(Controle::HUMANO),
+			"bot" => Some(Controle::BOT(dificuldade)),
+			"remoto" => Some(Controle::REMOTO),
+			_ => None,
+		},
+		Some(("bot", nivel)) => Dificuldade::por_nome(nivel).map(Controle::BOT),
+		Some(_) => None,
+	}).collect::<Option<Vec<Controle>>>()?;
+
+	let numeros: Vec<usize> = match equipes {
+		None => (0..controles.len()).collect(),
+		Some(texto) => {
+			let mut nomes: Vec<&str> = Vec::new();
+			let mut numeros = Vec::new();
+			for nome in texto.split(',').map(str::trim) {
+				if nome.is_empty() {
+					return None;
+				}
+				match nomes.iter().position(|&n| n == nome) {
+					Some(i) => numeros.push(i),
+					None => {
+						nomes.push(nome);
+						numeros.push(nomes.len()-1);
+					}
+				}
+			}
+			numeros
+		}
+	};
+
+	let humanos = controles.iter().filter(|&&c| c == Controle::HUMANO).count();
+	let total_equipes = numeros.iter().max().map_or(0, |&e| e+1);
+	if !(2..=MAXIMO_JOGADORES).contains(&controles.len()) || numeros.len() != controles.len()
+			|| humanos > 1 || total_equipes < 2 {
+		return None;
+	}
+	Some(controles.into_iter().zip(numeros).map(|(controle,equipe)| Vaga{ controle, equipe }).collect())
+}
+
+
+// Frota inicial de cada participante, sorteada com a semente da partida
+pub fn sorteia_frotas(jogo: &mut Jogo, participantes: usize, semente: u64) -> Vec<Vec<Navio>> {
+	(0..participantes).map(|i| {
+		sorteia_navios(jogo, &Jogador::BOT, &mut gerador(semente, FLUXO_FROTAS + i as u64));
+		jogo.navios_bot.clone()
+	}).collect()
+}
+
+
+// O que um participante sabe de uma célula de um tabuleiro
+#[derive(Debug,Copy,Clone,PartialEq)]
+enum Vista {
+	MAR,
+	NAVIO(usize),
+	ACERTO(usize),
+	AGUA,
+	ILHA,
+	RECIFE,
+}
+
+impl Vista {
+	// Caractere no estado para o navegador, como em rede.rs
+	fn letra(&self, navios: &[Navio]) -> char {
+		match self {
+			Vista::MAR => '~',
+			Vista::NAVIO(n) => navios[*n].letra,
+			Vista::ACERTO(_) => 'X',
+			Vista::AGUA => 'o',
+			Vista::ILHA => '#',
+			Vista::RECIFE => '%',
+		}
+	}
+}
+
+
+// Um jogador da partida, com o seu tabuleiro
+struct Participante {
+	nome: String,
+	controle: Controle,
+	equipe: usize,
+	navios: Vec<Navio>,
+	original: Mapa,			// Terreno e navios, para saber o que cada tiro achou
+	mapa: Mapa,				// Como o original, com DESTRUIDO nas células atingidas
+	tiros: Vec<Vec<u8>>,	// Equipes que já atiraram em cada célula, um bit por equipe
+	bots: Vec<Bot>,			// Estratégia do bot contra cada participante, vazio nos outros
+}
+
+
+// Partida de vários jogadores em andamento
+pub struct Partida {
+	participantes: Vec<Participante>,
+	lado: usize,
+	semente: u64,
+	rodada: u32,
+	visor: Option<usize>,		// Humano do terminal
+	alvo: usize,				// Tabuleiro onde o humano mira
+	mira_x: usize,
+	mira_y: usize,
+	aviso: String,				// Último tiro, na linha de status
+}
+
+impl Partida {
+	// Partida com as frotas já arrumadas, na ordem das vagas
+	pub fn new(jogo: &Jogo, vagas: &[Vaga], nomes: Vec<String>, frotas: Vec<Vec<Navio>>, semente: u64) -> Partida {
+		let lado = jogo.lado();
+		let participantes: Vec<Participante> = vagas.iter().zip(nomes).zip(frotas).map(|((vaga,nome),navios)| {
+			let original = jogo.mapeia(&navios, None);
+			let bots = match vaga.controle {
+				Controle::BOT(dificuldade) => vagas.iter().map(|_| Bot::new(dificuldade, lado)).collect(),
+				_ => Vec::new(),
+			};
+			Participante{ nome, controle: vaga.controle, equipe: vaga.equipe, navios, mapa: original.clone(), original,
+							tiros: vec![vec![0; lado]; lado], bots }
+		}).collect();
+		let visor = vagas.iter().position(|v| v.controle == Controle::HUMANO);
+		let mut partida = Partida{ participantes, lado, semente, rodada: 0, visor, alvo: 0, mira_x: 0, mira_y: 0, aviso: String::new() };
+		if let Some(visor) = visor {
+			partida.alvo = partida.proximo_alvo(visor, visor);
+		}
+		partida
+	}
+
+	// Ainda tem algum pedaço de navio inteiro
+	pub fn vivo(&self, i: usize) -> bool {
+		pedacos_vivos(&self.participantes[i].mapa) > 0
+	}
+
+	fn inimigos(&self, a: usize, b: usize) -> bool {
+		self.participantes[a].equipe != self.participantes[b].equipe
+	}
+
+	// O bot de 'atirador' ainda caça em volta de acertos em 'alvo'
+	pub fn perseguindo(&self, atirador: usize, alvo: usize) -> bool {
+		self.participantes[atirador].bots.get(alvo).is_some_and(|bot| bot.perseguindo())
+	}
+
+	// Equipe vencedora, quando só ela ainda tem navios
+	pub fn vencedora(&self) -> Option<usize> {
+		let mut vivas = (0..self.participantes.len()).filter(|&i| self.vivo(i)).map(|i| self.participantes[i].equipe);
+		let primeira = vivas.next()?;
+		vivas.all(|e| e == primeira).then_some(primeira)
+	}
+
+	// Próximo da lista com navios, depois de 'vez'
+	pub fn proxima_vez(&self, vez: usize) -> usize {
+		let n = self.participantes.len();
+		(1..=n).map(|passo| (vez+passo) % n).find(|&i| self.vivo(i)).unwrap_or(vez)
+	}
+
+	// Próximo inimigo com navios depois de 'atual', para o humano trocar de alvo
+	fn proximo_alvo(&self, atirador: usize, atual: usize) -> usize {
+		let n = self.participantes.len();
+		(1..=n).map(|passo| (atual+passo) % n)
+			.find(|&i| self.inimigos(atirador, i) && self.vivo(i))
+			.unwrap_or(atual)
+	}
+
+	// Tiro permitido: inimigo com navios, dentro do tabuleiro, sem terreno e ainda não tentado pela equipe
+	pub fn valido(&self, atirador: usize, alvo: usize, x: usize, y: usize) -> bool {
+		alvo < self.participantes.len() && self.inimigos(atirador, alvo) && self.vivo(alvo)
+			&& x < self.lado && y < self.lado
+			&& !eh_obstaculo(self.participantes[alvo].original[y][x])
+			&& self.participantes[alvo].tiros[y][x] & (1 << self.participantes[atirador].equipe) == 0
+	}
+
+	// Tabuleiro de 'alvo' como 'equipe' vê: os tiros da equipe e, se for aliado, os navios e todos os tiros levados
+	// Sem equipe é quem só assiste, vendo todos os tiros; no fim todos veem tudo
+	fn visao(&self, alvo: usize, equipe: Option<usize>, fim: bool) -> Vec<Vec<Vista>> {
+		let p = &self.participantes[alvo];
+		let aliado = equipe == Some(p.equipe);
+		(0..self.lado).map(|y| (0..self.lado).map(|x| {
+			let visto = match equipe {
+				Some(e) if !aliado => p.tiros[y][x] & (1 << e) != 0,
+				_ => p.mapa[y][x] == DESTRUIDO,
+			};
+			match p.original[y][x] {
+				ILHA => Vista::ILHA,
+				RECIFE => Vista::RECIFE,
+				AGUA if visto => Vista::AGUA,
+				AGUA => Vista::MAR,
+				n if visto => Vista::ACERTO(n),
+				n if aliado || fim => Vista::NAVIO(n),
+				_ => Vista::MAR,
+			}
+		}).collect()).collect()
+	}
+
+	// Mapa de 'alvo' para o bot de 'equipe': o que a equipe já tentou aparece como destruído
+	fn mapa_da_equipe(&self, alvo: usize, equipe: usize) -> Mapa {
+		let p = &self.participantes[alvo];
+		p.original.iter().zip(&p.tiros).map(|(linha,tiros)| {
+			linha.iter().zip(tiros).map(|(&celula,&t)| if t & (1 << equipe) != 0 { DESTRUIDO } else { celula }).collect()
+		}).collect()
+	}
+
+	// Atira e conta para a equipe, devolve se achou navio
+	pub fn atira(&mut self, atirador: usize, alvo: usize, x: usize, y: usize) -> bool {
+		let equipe = self.participantes[atirador].equipe;
+		let p = &mut self.participantes[alvo];
+		p.tiros[y][x] |= 1 << equipe;
+		atinge(&mut p.mapa, (Arma::TIRO, Direcao::LESTE), (x,y));
+		let acertou = p.original[y][x] != AGUA;
+
+		// Os bots da equipe caçam a partir do acerto e param de caçar em volta do navio que a equipe afundou,
+		// só com os tiros dela: os das outras equipes ela não vê
+		if acertou {
+			let afundado = p.navios.get(p.original[y][x]).map(|navio| navio.celulas())
+				.filter(|celulas| celulas.iter().all(|&(cx,cy)| p.tiros[cy][cx] & (1 << equipe) != 0));
+			for aliado in self.participantes.iter_mut().filter(|a| a.equipe == equipe) {
+				if let Some(bot) = aliado.bots.get_mut(alvo) {
+					bot.registra_acertos(&[(x,y)], None);
+					if let Some(celulas) = &afundado {
+						bot.registra_afundado(celulas);
+					}
+				}
+			}
+		}
+
+		let (nome, nome_alvo) = (self.rotulo(atirador), self.rotulo(alvo));
+		self.aviso = match acertou {
+			true => tr!("{} atirou em {} ({} {}): acertou!", nome, nome_alvo, x, y),
+			false => tr!("{} atirou em {} ({} {}): água", nome, nome_alvo, x, y),
+		};
+		acertou
+	}
+
+	// Alvo e mira de um bot: continua no inimigo onde está caçando, senão no que tem menos navio
+	fn escolhe_bot(&mut self, atirador: usize, rng: &mut impl Rng) -> (usize,usize,usize) {
+		let inimigos: Vec<usize> = (0..self.participantes.len())
+			.filter(|&i| self.inimigos(atirador, i) && self.vivo(i))
+			.collect();
+		let perseguido = inimigos.iter().copied().find(|&i| self.perseguindo(atirador, i));
+		let alvo = perseguido.or_else(|| inimigos.iter().copied().min_by_key(|&i| pedacos_vivos(&self.participantes[i].mapa)))
+			.unwrap_or(atirador);
+		let mapa = self.mapa_da_equipe(alvo, self.participantes[atirador].equipe);
+		let (_,x,y,_) = self.participantes[atirador].bots[alvo].escolhe_ataque(&mapa, &Arsenal::new(false), rng);
+		(alvo,x,y)
+	}
+
+	// Estado em JSON para o participante 'meu' no navegador: o tabuleiro dele em "meu" e os outros em "alvos"
+	pub fn estado(&self, meu: usize, fase: Fase, vez: bool, mensagem: &str) -> String {
+		let fim = matches!(fase, Fase::FIM);
+		let equipe = Some(self.participantes[meu].equipe);
+		let linhas = |i: usize| {
+			let navios = &self.participantes[i].navios;
+			let linhas: Vec<String> = self.visao(i, equipe, fim).iter()
+				.map(|linha| rede::json_texto(&linha.iter().map(|v| v.letra(navios)).collect::<String>()))
+				.collect();
+			format!("[{}]", linhas.join(","))
+		};
+		let alvos: Vec<String> = (0..self.participantes.len()).filter(|&i| i != meu).map(|i| {
+			format!("{{\"jogador\":{},\"nome\":{},\"aliado\":{},\"vivo\":{},\"linhas\":{}}}",
+				i, rede::json_texto(&self.participantes[i].nome), !self.inimigos(meu, i), self.vivo(i), linhas(i))
+		}).collect();
+		format!("{{\"tipo\":\"estado\",\"fase\":\"{}\",\"vez\":{},\"lado\":{},\"meu\":{},\"inimigo\":[],\"alvos\":[{}],\"mensagem\":{}}}",
+			fase.nome(), vez, self.lado, linhas(meu), alvos.join(","), rede::json_texto(mensagem))
+	}
+
+
+	// Tabuleiros por linha da tela e linhas ocupadas por eles
+	fn grade(&self, largura_tela: u16) -> (u16,u16) {
+		let por_linha = ((largura_tela+1) / (2*self.lado as u16 + 3)).max(1);
+		let linhas = (self.participantes.len() as u16).div_ceil(por_linha);
+		(por_linha, linhas*(self.lado as u16 + 3))
+	}
+
+	// Canto superior esquerdo do tabuleiro de cada participante: o do humano primeiro, os outros na ordem
+	fn posicoes(&self, largura_tela: u16) -> Vec<(u16,u16)> {
+		let (por_linha,_) = self.grade(largura_tela);
+		let n = self.participantes.len();
+		let ordem = self.visor.into_iter().chain((0..n).filter(|&i| Some(i) != self.visor));
+		let mut posicoes = vec![(0,0); n];
+		for (k,i) in ordem.enumerate() {
+			let k = k as u16;
+			posicoes[i] = ((k % por_linha) * (2*self.lado as u16 + 3), (k / por_linha) * (self.lado as u16 + 3));
+		}
+		posicoes
+	}
+
+	// Todos os tabuleiros e mais três linhas de status precisam caber no terminal
+	pub fn confere_tamanho(&self, con: &mut dyn Console) -> Result<(), ErroJogo> {
+		let (largura,altura) = con.tamanho()?;
+		let largura_minima = 2*self.lado as u16 + 2;
+		let (por_linha,altura_tabuleiros) = self.grade(largura.max(largura_minima));
+		let largura_usada = por_linha.min(self.participantes.len() as u16) * (largura_minima+1) - 1;
+		if largura < largura_minima || altura < altura_tabuleiros + 3 {
+			return Err(ErroJogo::TAMANHO(largura_usada.max(largura_minima), altura_tabuleiros + 3));
+		}
+		Ok(())
+	}
+
+	// Desenha a tela inteira, 'vez' marca de quem é a vez no título
+	pub fn desenha(&self, con: &mut dyn Console, opcoes: &Opcoes, vez: Option<usize>, fim: bool) -> Result<(), ErroJogo> {
+		limpa_tela(con)?;
+		let (largura_tela,_) = con.tamanho()?;
+		let equipe = self.visor.map(|v| self.participantes[v].equipe);
+		for (i,(x,y)) in self.posicoes(largura_tela).into_iter().enumerate() {
+			self.desenha_tabuleiro(con, i, (x,y), equipe, fim, &opcoes.tema)?;
+			let titulo = self.titulo(i, vez);
+			let titulo = match self.visor.is_some() && i == self.alvo && !fim {
+				true => titulo.reverse(),
+				false => titulo.stylize(),
+			};
+			con
+				.queue( cursor::MoveTo(x,y) )?
+				.queue( style::PrintStyledContent(titulo) )?;
+		}
+
+		// Linhas de status embaixo dos tabuleiros
+		let (_,altura_tabuleiros) = self.grade(largura_tela);
+		con
+			.queue( cursor::MoveTo(0,altura_tabuleiros) )?
+			.queue( cursor::SavePosition )?;
+		if self.visor.is_some() && !fim {
+			let teclas = &opcoes.teclas;
+			con.queue( style::Print(tr!("Alvo com 1-{} ou {}, mire com {}, {} fogo, {} pausa", self.participantes.len(),
+								teclas.tecla(Acao::MUDA), teclas.movimento(), teclas.tecla(Acao::FOGO), teclas.tecla(Acao::PAUSA))) )?;
+		}
+		con
+			.queue( cursor::MoveTo(0,altura_tabuleiros+1) )?
+			.queue( style::Print(&self.aviso) )?;
+		con.flush()?;
+		Ok(())
+	}
+
+	// Número e nome, para as mensagens, pois vários bots têm o mesmo nome
+	fn rotulo(&self, i: usize) -> String {
+		format!("{} {}", i+1, self.participantes[i].nome)
+	}
+
+	// Título do tabuleiro cortado na largura dele: '>' na vez, 'x' sem navios, número, equipe e nome
+	fn titulo(&self, i: usize, vez: Option<usize>) -> String {
+		let p = &self.participantes[i];
+		let marca = match (vez == Some(i), self.vivo(i)) {
+			(true, _) => '>',
+			(false, true) => ' ',
+			(false, false) => 'x',
+		};
+		// Com equipes de mais de um a letra da equipe aparece
+		let equipe = match self.participantes.iter().filter(|o| o.equipe == p.equipe).count() > 1 {
+			true => format!("[{}]", (b'A' + p.equipe as u8) as char),
+			false => String::new(),
+		};
+		format!("{}{}{} {}", marca, i+1, equipe, p.nome).chars().take(2*self.lado + 2).collect()
+	}
+
+	// Moldura e células de um tabuleiro, abaixo da linha do título
+	fn desenha_tabuleiro(&self, con: &mut dyn Console, i: usize, (x_esq,y_titulo): (u16,u16), equipe: Option<usize>, fim: bool, tema: &Tema) -> Result<(), ErroJogo> {
+		let largura = 2*self.lado;
+		let y_sup = y_titulo + 1;
+		let glifos = tema.glifos;
+		let (cor_frente,cor_fundo) = tema.moldura();
+		con
+			.queue( SetForegroundColor(cor_frente) )?
+			.queue( SetBackgroundColor(cor_fundo) )?
+			.queue( cursor::MoveTo(x_esq,y_sup) )?
+			.queue( style::Print(glifos.cantos_sup[0]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_sup[2]) )?;
+		for y in 1 ..= self.lado as u16 {
+			con
+				.queue( cursor::MoveTo(x_esq,y_sup+y) )?
+				.queue( style::Print(glifos.vertical) )?
+				.queue( style::Print(" ".repeat(largura)) )?
+				.queue( style::Print(glifos.vertical) )?;
+		}
+		con
+			.queue( cursor::MoveTo(x_esq,y_sup+self.lado as u16+1) )?
+			.queue( style::Print(glifos.cantos_inf[0]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_inf[2]) )?;
+
+		// O mar é o fundo da moldura, só o resto é desenhado
+		let navios = &self.participantes[i].navios;
+		for (y,linha) in self.visao(i, equipe, fim).iter().enumerate() {
+			for (x,vista) in linha.iter().enumerate() {
+				let posicao = cursor::MoveTo(x_esq+1+2*x as u16, y_sup+1+y as u16);
+				match *vista {
+					Vista::MAR => {}
+					Vista::NAVIO(n) => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.navio(&navios[n])) )?; }
+					Vista::ACERTO(n) => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.acerto(&navios[n])) )?; }
+					Vista::AGUA => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.agua()) )?; }
+					Vista::ILHA => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.ilha()) )?; }
+					Vista::RECIFE => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.recife()) )?; }
+				}
+			}
+		}
+		con.queue( ResetColor )?;
+		Ok(())
+	}
+
+	// Cursor na mira do humano, no tabuleiro do alvo
+	fn posiciona_mira(&self, con: &mut dyn Console) -> Result<(), ErroJogo> {
+		let (x,y) = self.posicoes(con.tamanho()?.0)[self.alvo];
+		con.execute(cursor::MoveTo(x+1+2*self.mira_x as u16, y+2+self.mira_y as u16))?;
+		Ok(())
+	}
+
+
+	// Vez do humano do terminal: troca de alvo, mira e atira; Err(desfecho) se saiu da partida
+	fn vez_do_humano(&mut self, con: &mut dyn Console, opcoes: &Opcoes, vez: usize) -> Result<Result<(usize,usize,usize),Desfecho>, ErroJogo> {
+		if !self.inimigos(vez, self.alvo) || !self.vivo(self.alvo) {
+			self.alvo = self.proximo_alvo(vez, self.alvo);
+		}
+		self.desenha(con, opcoes, Some(vez), false)?;
+		loop {
+			self.posiciona_mira(con)?;
+			let tecla = match con.le_evento()? {
+				Event::Key(tecla) if tecla.kind == KeyEventKind::Press => tecla,
+				Event::Resize(_,_) => {
+					self.confere_tamanho(con)?;
+					self.desenha(con, opcoes, Some(vez), false)?;
+					continue;
+				}
+				_ => continue,
+			};
+			if teclas::aborta(&tecla) {
+				return Ok(Err(Desfecho::ABORTADO));
+			}
+
+			// Número do tabuleiro escolhe o alvo
+			if let KeyCode::Char(digito @ '1'..='9') = tecla.code {
+				let escolhido = digito as usize - '1' as usize;
+				if escolhido < self.participantes.len() && self.inimigos(vez, escolhido) && self.vivo(escolhido) {
+					self.alvo = escolhido;
+					self.desenha(con, opcoes, Some(vez), false)?;
+				}
+				continue;
+			}
+
+			let lado = self.lado;
+			match opcoes.teclas.acao(&tecla) {
+				Some(Acao::MUDA) => {
+					self.alvo = self.proximo_alvo(vez, self.alvo);
+					self.desenha(con, opcoes, Some(vez), false)?;
+				}
+				Some(Acao::CIMA) => self.mira_y = self.mira_y.saturating_sub(1),
+				Some(Acao::BAIXO) => self.mira_y = (self.mira_y+1).min(lado-1),
+				Some(Acao::DIREITA) => self.mira_x = (self.mira_x+1).min(lado-1),
+				Some(Acao::ESQUERDA) => self.mira_x = self.mira_x.saturating_sub(1),
+				Some(Acao::FOGO) if self.valido(vez, self.alvo, self.mira_x, self.mira_y) => {
+					return Ok(Ok((self.alvo, self.mira_x, self.mira_y)));
+				}
+				Some(Acao::FOGO) => {
+					self.aviso = String::from(tr!("Alvo inválido, escolha outra célula"));
+					self.desenha(con, opcoes, Some(vez), false)?;
+				}
+				Some(Acao::PAUSA) => {
+					con.modo_bruto(false)?;
+					match menu::pausa(con, false, &opcoes.teclas)? {
+						Pausa::CONTINUAR | Pausa::SALVAR => {}
+						Pausa::REINICIAR => return Ok(Err(Desfecho::REINICIA)),
+						Pausa::SAIR => return Ok(Err(Desfecho::MENU)),
+						Pausa::ABORTAR => return Ok(Err(Desfecho::ABORTADO)),
+					}
+					con.modo_bruto(true)?;
+					self.desenha(con, opcoes, Some(vez), false)?;
+				}
+				_ => {}
+			}
+		}
+	}
+
+	// Vez do participante no navegador: espera um tiro válido
+	fn vez_do_remoto(&self, con: &mut dyn Console, remoto: &mut Remoto, vez: usize) -> Result<Result<(usize,usize,usize),Desfecho>, ErroJogo> {
+		let mut aviso = match self.aviso.is_empty() {
+			true => String::from(tr!("Sua vez")),
+			false => tr!("{}, sua vez", self.aviso),
+		};
+		loop {
+			if remoto.envia(&self.estado(vez, Fase::BATALHA, true, &aviso)).is_err() {
+				return rede::desconectou(con);
+			}
+			let mensagem = match remoto.espera_mensagem(con)? {
+				Ok(mensagem) => mensagem,
+				Err(desfecho) => return Ok(Err(desfecho)),
+			};
+			let partes: Vec<&str> = mensagem.split_whitespace().collect();
+			let tiro = match partes[..] {
+				["tiro", x, y, alvo] => match (x.parse::<usize>(), y.parse::<usize>(), alvo.parse::<usize>()) {
+					(Ok(x), Ok(y), Ok(alvo)) => Some((alvo,x,y)),
+					_ => None,
+				},
+				_ => None,
+			};
+			if let Some((alvo,x,y)) = tiro {
+				if self.valido(vez, alvo, x, y) {
+					return Ok(Ok((alvo,x,y)));
+				}
+			}
+			aviso = String::from(tr!("Alvo inválido, escolha outra célula"));
+		}
+	}
+
+	// Vezes em sequência até sobrar uma equipe, Err(desfecho) se alguém saiu da partida
+	pub fn executa(&mut self, con: &mut dyn Console, opcoes: &Opcoes, remotos: &mut [Option<Remoto>]) -> Result<Desfecho, ErroJogo> {
+		con.modo_bruto(true)?;
+		let mut vez = 0;
+		let vencedora = loop {
+			let mut rng = gerador(self.semente, FLUXO_RODADAS + self.rodada as u64);
+			let jogada = match (self.participantes[vez].controle, remotos[vez].as_mut()) {
+				(Controle::HUMANO, _) => self.vez_do_humano(con, opcoes, vez)?,
+				(Controle::REMOTO, Some(remoto)) => {
+					self.desenha(con, opcoes, Some(vez), false)?;
+					self.vez_do_remoto(con, remoto, vez)?
+				}
+				_ => Ok(self.escolhe_bot(vez, &mut rng)),
+			};
+			let (alvo,x,y) = match jogada {
+				Ok(jogada) => jogada,
+				Err(desfecho) => {
+					con.modo_bruto(false)?;
+					return Ok(desfecho);
+				}
+			};
+
+			self.atira(vez, alvo, x, y);
+			self.desenha(con, opcoes, Some(vez), false)?;
+			let (x_tela,y_tela) = self.posicoes(con.tamanho()?.0)[alvo];
+			con.execute(cursor::MoveTo(x_tela+1+2*x as u16, y_tela+2+y as u16))?;
+			std::thread::sleep(opcoes.atraso);
+
+			if let Some(equipe) = self.vencedora() {
+				break equipe;
+			}
+
+			// Quem está no navegador vê o tiro, uma desconexão aparece na vez dele
+			vez = self.proxima_vez(vez);
+			for (i,remoto) in remotos.iter_mut().enumerate() {
+				if let Some(remoto) = remoto.as_mut().filter(|_| i != vez) {
+					let _ = remoto.envia(&self.estado(i, Fase::BATALHA, false, &self.aviso));
+				}
+			}
+			self.rodada += 1;
+		};
+
+		// No fim todos veem todas as frotas
+		let nomes: Vec<String> = (0..self.participantes.len()).filter(|&i| self.participantes[i].equipe == vencedora).map(|i| self.rotulo(i)).collect();
+		self.aviso = tr!("VITÓRIA DE {}!!!", nomes.join(" + "));
+		for (i,remoto) in remotos.iter_mut().enumerate() {
+			if let Some(remoto) = remoto.as_mut() {
+				let _ = remoto.envia(&self.estado(i, Fase::FIM, false, &self.aviso));
+			}
+		}
+		self.desenha(con, opcoes, None, true)?;
+		con.modo_bruto(false)?;
+		con.queue( cursor::RestorePosition )?.queue( Clear(terminal::ClearType::FromCursorDown) )?.flush()?;
+		writeln!(con, "\r{}", self.aviso)?;
+		writeln!(con, "\r{}", tr!("Semente da partida: {}", self.semente))?;
+		Ok(Desfecho::CONTINUA)
+	}
+}
+
+
+// Partidas seguidas com os mesmos participantes, os do navegador ficam conectados entre elas
+pub fn joga(con: &mut dyn Console, opcoes: &Opcoes, vagas: &[Vaga]) -> Result<Desfecho, ErroJogo> {
+	// Como na rede, só o tiro simples e sem relógio
+	let opcoes = &Opcoes{ armas_especiais: false, tempo_jogada: None, tempo_total: None, ..opcoes.clone() };
+	let mut remotos: Vec<Option<Remoto>> = vagas.iter().map(|_| None).collect();
+	let mut ouvinte: Option<TcpListener> = None;
+
+	loop {
+		let mut jogo = match novo_jogo(opcoes) {
+			Ok(jogo) => jogo,
+			Err(erro) => {
+				menu::mostra_texto(con, &[erro])?;
+				return Ok(Desfecho::MENU);
+			}
+		};
+		if let Some(desfecho) = avisa_tamanho(con, jogo.lado())? {
+			return Ok(desfecho);
+		}
+
+		// Cada participante do navegador conecta uma vez, na porta de '--servidor'
+		for (i,vaga) in vagas.iter().enumerate() {
+			if vaga.controle != Controle::REMOTO || remotos[i].is_some() {
+				continue;
+			}
+			if ouvinte.is_none() {
+				let porta = opcoes.servidor.unwrap_or_default();
+				match rede::abre(porta) {
+					Ok(aberto) => ouvinte = Some(aberto),
+					Err(erro) => {
+						menu::mostra_texto(con, &[tr!("Não conseguiu abrir a porta {}: {}", porta, erro)])?;
+						return Ok(Desfecho::MENU);
+					}
+				}
+			}
+			if let Some(ouvinte) = ouvinte.as_ref() {
+				match rede::espera_adversario(con, ouvinte)? {
+					Ok(remoto) => remotos[i] = Some(remoto),
+					Err(desfecho) => return Ok(desfecho),
+				}
+			}
+		}
+
+		// Tudo o que é sorteado na partida vem da semente
+		let semente = opcoes.semente.unwrap_or_else(|| rand::thread_rng().gen());
+		let mut frotas = sorteia_frotas(&mut jogo, vagas.len(), semente);
+
+		// Os do navegador já veem a própria frota enquanto o humano arruma a dele
+		let mut temporarios: Vec<Jogo> = frotas.iter().map(|frota| Jogo{ terreno: jogo.terreno.clone(), navios_humano: Vec::new(), navios_bot: frota.clone() }).collect();
+		for (remoto,temporario) in remotos.iter_mut().zip(&temporarios) {
+			if let Some(remoto) = remoto.as_mut() {
+				let _ = remoto.envia_estado(temporario, None, Fase::ARRUMACAO, false, tr!("Sorteie a frota e clique em Pronto"));
+			}
+		}
+
+		// Humano do terminal arruma os navios como na partida contra o bot
+		match vagas.iter().position(|v| v.controle == Controle::HUMANO) {
+			Some(h) => {
+				jogo.navios_humano = frotas[h].clone();
+				match arruma_navios_humano(con, &mut jogo, opcoes, &mut gerador(semente, FLUXO_FROTAS + h as u64))? {
+					Desfecho::CONTINUA => {}
+					Desfecho::REINICIA => continue,
+					desfecho => return Ok(desfecho),
+				}
+				frotas[h] = jogo.navios_humano.clone();
+			}
+			None => {
+				limpa_tela(con)?;
+				con.execute(cursor::SavePosition)?;
+			}
+		}
+
+		// Depois cada um do navegador termina a sua arrumação
+		for (i,remoto) in remotos.iter_mut().enumerate() {
+			if let Some(remoto) = remoto.as_mut() {
+				let mut rng = gerador(semente, FLUXO_FROTAS + (MAXIMO_JOGADORES + i) as u64);
+				if let Err(desfecho) = rede::arrumacao(con, remoto, &mut temporarios[i], &mut rng)? {
+					return Ok(desfecho);
+				}
+				frotas[i] = std::mem::take(&mut temporarios[i].navios_bot);
+			}
+		}
+
+		let nomes: Vec<String> = vagas.iter().zip(&remotos).map(|(vaga,remoto)| match (vaga.controle, remoto) {
+			(Controle::HUMANO, _) => opcoes.jogador.clone(),
+			(Controle::BOT(dificuldade), _) => format!("bot {}", tr!(dificuldade.nome())),
+			(Controle::REMOTO, Some(remoto)) => remoto.nome.clone(),
+			(Controle::REMOTO, None) => String::from("?"),
+		}).collect();
+
+		let mut partida = Partida::new(&jogo, vagas, nomes, frotas, semente);
+		if let Err(erro @ ErroJogo::TAMANHO(..)) = partida.confere_tamanho(con) {
+			menu::mostra_texto(con, &[erro.to_string()])?;
+			return Ok(Desfecho::MENU);
+		}
+		match partida.executa(con, opcoes, &mut remotos)? {
+			Desfecho::CONTINUA => {
+				match menu::pergunta(con, tr!("Jogar novamente? (s/n)"))? {
+					Some(true) => {}
+					Some(false) => return Ok(Desfecho::MENU),
+					None => return Ok(Desfecho::ABORTADO),
+				}
+			}
+			Desfecho::REINICIA => {}
+			desfecho => return Ok(desfecho),
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+	use crate::testes::{jogo_de_teste,partida_de_teste};
+
+	// Linhas do tabuleiro de 'jogador' nos alvos de um estado em JSON
+	fn linhas_do_alvo(estado: &str, jogador: usize) -> String {
+		let inicio = estado.find(&format!("{{\"jogador\":{},", jogador)).unwrap();
+		let linhas = inicio + estado[inicio..].find("\"linhas\":[").unwrap();
+		let fim = linhas + estado[linhas..].find(']').unwrap();
+		estado[linhas+10 .. fim].replace('"', "")
+	}
+
+	#[test]
+	fn vagas_da_linha_de_comando() {
+		use Controle::*;
+		let lugares = vagas("humano,bot:dificil,bot,remoto", Some("a,b,a,b"), Dificuldade::FACIL).unwrap();
+		let controles: Vec<_> = lugares.iter().map(|v| (v.controle, v.equipe)).collect();
+		assert_eq!(controles, [(HUMANO,0), (BOT(Dificuldade::DIFICIL),1), (BOT(Dificuldade::FACIL),0), (REMOTO,1)]);
+		// Sem equipes é todos contra todos
+		let lugares = vagas("bot,bot,bot", None, Dificuldade::FACIL).unwrap();
+		assert_eq!(lugares.iter().map(|v| v.equipe).collect::<Vec<_>>(), [0,1,2]);
+
+		assert!(vagas("bot", None, Dificuldade::FACIL).is_none());
+		assert!(vagas("humano,humano", None, Dificuldade::FACIL).is_none());
+		assert!(vagas("bot,bot", Some("a,a"), Dificuldade::FACIL).is_none());
+		assert!(vagas("bot,bot,bot", Some("a,b"), Dificuldade::FACIL).is_none());
+		assert!(vagas("bot,robo", None, Dificuldade::FACIL).is_none());
+		assert!(vagas("bot:impossivel,bot", None, Dificuldade::FACIL).is_none());
+		assert!(vagas("bot,bot,bot,bot,bot,bot,bot", None, Dificuldade::FACIL).is_none());
+	}
+
+	#[test]
+	fn equipe_ve_os_tiros_dos_aliados() {
+		// j1 e j2 na equipe a, j3 e j4 sozinhos
+		let mut partida = partida_de_teste(6, "bot,bot,bot,bot", Some("a,a,b,c"));
+		assert!(!partida.valido(0, 1, 0, 0), "aliado não é alvo");
+		assert!(partida.valido(0, 2, 0, 0));
+		partida.atira(0, 2, 0, 0);
+
+		// A equipe não repete o tiro, as outras ainda podem
+		assert!(!partida.valido(1, 2, 0, 0));
+		assert!(partida.valido(3, 2, 0, 0));
+
+		let celula = |estado: String| linhas_do_alvo(&estado, 2).chars().next().unwrap();
+		assert!(matches!(celula(partida.estado(1, rede::Fase::BATALHA, false, "")), 'X' | 'o'));
+		assert_eq!(celula(partida.estado(3, rede::Fase::BATALHA, false, "")), '~');
+		// O alvo vê o que levou na própria frota
+		let meu = partida.estado(2, rede::Fase::BATALHA, false, "");
+		assert!(matches!(meu.split("\"meu\":[\"").nth(1).unwrap().chars().next(), Some('X' | 'o')));
+		// Aliados veem a frota um do outro, inimigos não
+		assert!(linhas_do_alvo(&partida.estado(0, rede::Fase::BATALHA, false, ""), 1).contains('P'));
+		assert!(!linhas_do_alvo(&partida.estado(0, rede::Fase::BATALHA, false, ""), 3).contains('P'));
+	}
+
+	#[test]
+	fn equipe_para_de_cacar_o_navio_afundado() {
+		// Mesmas frotas da partida de teste, j1 e j2 na equipe a
+		let mut partida = partida_de_teste(6, "bot,bot,bot", Some("a,a,b"));
+		let frotas = sorteia_frotas(&mut jogo_de_teste(6), 3, 0);
+		let celulas = frotas[2][0].celulas();
+
+		// No primeiro acerto os dois bots da equipe caçam, com o navio afundado param
+		partida.atira(0, 2, celulas[0].0, celulas[0].1);
+		assert!(partida.perseguindo(0, 2) && partida.perseguindo(1, 2));
+		for &(x,y) in celulas[1..].iter() {
+			partida.atira(1, 2, x, y);
+		}
+		assert!(!partida.perseguindo(0, 2) && !partida.perseguindo(1, 2));
+
+		// Todos contra todos: quem não deu todos os tiros no navio não sabe que ele afundou
+		let mut partida = partida_de_teste(6, "bot,bot,bot", None);
+		partida.atira(0, 2, celulas[0].0, celulas[0].1);
+		for &(x,y) in celulas[1..].iter() {
+			partida.atira(1, 2, x, y);
+		}
+		assert!(partida.perseguindo(0, 2) && partida.perseguindo(1, 2));
+	}
+}
@@ -0,0 +1,40 @@
+/*
+	Erros do jogo
+
+	- Falhas do terminal (teclado, tela) chegam do crossterm como 'std::io::Error'
+	- O terminal ficar menor que o tabuleiro durante o jogo também termina a partida
+	- Erros de arquivos (frota, mapa, perfil, partida salva) continuam como String,
+	eles são mostrados no menu e o jogo segue
+	- 'main' restaura o terminal antes de mostrar o erro, ver tela.rs
+*/
+
+
+use std::fmt;
+use std::io;
+
+
+// Erro que interrompe o jogo
+#[derive(Debug)]
+pub enum ErroJogo {
+	TERMINAL(io::Error),	// Falha ao ler o teclado ou escrever na tela
+	TAMANHO(u16,u16),		// Terminal ficou menor que o mínimo, largura e altura necessárias
+}
+
+impl fmt::Display for ErroJogo {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ErroJogo::TERMINAL(erro) => write!(f, "{}", tr!("Erro no terminal: {}", erro)),
+			ErroJogo::TAMANHO(largura,altura) =>
+				write!(f, "{}", tr!("O terminal precisa de pelo menos {} colunas e {} linhas", largura, altura)),
+		}
+	}
+}
+
+impl std::error::Error for ErroJogo {}
+
+// Permite usar '?' nas chamadas do crossterm
+impl From<io::Error> for ErroJogo {
+	fn from(erro: io::Error) -> ErroJogo {
+		ErroJogo::TERMINAL(erro)
+	}
+}
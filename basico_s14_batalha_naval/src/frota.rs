@@ -0,0 +1,202 @@
+/*
+	Definição das frotas
+
+	Uma frota é descrita em texto, um navio após o outro:
+
+		# Linhas iniciadas com '#' são comentários
+		navio <letra> <cor> <nome>
+		<desenho da forma, 'X' é parte do navio, '.' é vazio>
+
+	A forma é desenhada com o navio virado para LESTE, a primeira célula 'X'
+	(lendo da esquerda para a direita, de cima para baixo) é a popa.
+	Cores: azul, magenta, amarelo, vermelho, verde, cinza
+
+	Exemplo em assets/frota_poliominos.txt, também disponível pelo nome 'poliominos'
+*/
+
+
+use std::fs;
+
+use crossterm::style::Color::{self,*};
+
+use crate::{Navio,LADO_MAXIMO};
+
+
+// Frota clássica, navios retos de tamanho 5, 4, 3 e 2
+const FROTA_PADRAO: &str = "
+navio P azul Porta-aviões
+XXXXX
+
+navio C magenta Cruzador
+XXXX
+
+navio D amarelo Destróier
+XXX
+
+navio S vermelho Submarino
+XX
+";
+
+// Outras frotas prontas, escolhidas pelo nome
+pub const PREDEFINIDAS: [(&str,&str);1] = [
+	("poliominos", include_str!("../assets/frota_poliominos.txt")),
+];
+
+
+// Navio como aparece no texto, antes de converter o desenho
+struct Definicao<'a> {
+	letra: char,
+	cor: Color,
+	nome: String,
+	desenho: Vec<&'a str>,
+}
+
+
+// Frota padrão, predefinida com o nome dado ou lida do arquivo indicado
+pub fn carrega(arquivo: Option<&str>) -> Result<Vec<Navio>, String> {
+	match arquivo {
+		None => le_frota(FROTA_PADRAO),
+		Some(nome) => {
+			if let Some((_,texto)) = PREDEFINIDAS.iter().find(|(predefinida,_)| *predefinida == nome) {
+				return le_frota(texto);
+			}
+			let texto = fs::read_to_string(nome)
+				.map_err(|erro| tr!("Não conseguiu ler a frota {}: {}", nome, erro))?;
+			le_frota(&texto).map_err(|erro| tr!("Frota {}: {}", nome, erro))
+		}
+	}
+}
+
+
+// Converte o nome da cor
+fn cor_por_nome(nome: &str) -> Option<Color> {
+	match nome {
+		"azul" => Some(DarkBlue),
+		"magenta" => Some(DarkMagenta),
+		"amarelo" => Some(DarkYellow),
+		"vermelho" => Some(DarkRed),
+		"verde" => Some(DarkGreen),
+		"cinza" => Some(DarkGrey),
+		_ => None,
+	}
+}
+
+
+// Nome da cor, inverso de 'cor_por_nome'
+fn nome_da_cor(cor: Color) -> &'static str {
+	match cor {
+		DarkBlue => "azul",
+		DarkMagenta => "magenta",
+		DarkYellow => "amarelo",
+		DarkRed => "vermelho",
+		DarkGreen => "verde",
+		_ => "cinza",
+	}
+}
+
+
+// Escreve a frota no mesmo formato lido por 'carrega'
+pub fn para_texto(frota: &[Navio]) -> String {
+	let mut texto = String::new();
+	for navio in frota {
+		texto.push_str(&format!("navio {} {} {}\n", navio.letra, nome_da_cor(navio.cor), navio.nome));
+		let min_x = navio.forma.iter().map(|c| c.0).min().unwrap_or(0);
+		let max_x = navio.forma.iter().map(|c| c.0).max().unwrap_or(0);
+		let max_y = navio.forma.iter().map(|c| c.1).max().unwrap_or(0);
+		// A popa é a primeira célula lida, então fica na linha de cima
+		for y in 0 ..= max_y {
+			let linha: String = (min_x ..= max_x)
+				.map(|x| if navio.forma.contains(&(x,y)) { 'X' } else { '.' })
+				.collect();
+			texto.push_str(&linha);
+			texto.push('\n');
+		}
+		texto.push('\n');
+	}
+	texto
+}
+
+
+// Interpreta o texto de uma frota
+pub fn le_frota(texto: &str) -> Result<Vec<Navio>, String> {
+	let mut definicoes: Vec<Definicao> = Vec::new();
+
+	for (numero,linha) in texto.lines().enumerate() {
+		let linha = linha.trim_end();
+		if linha.starts_with('#') {
+			continue;
+		}
+		if let Some(resto) = linha.strip_prefix("navio ") {
+			let mut partes = resto.split_whitespace();
+			let letra = partes.next().and_then(|l| l.chars().next());
+			let cor = partes.next().map(|c| (c, cor_por_nome(c)));
+			let nome = partes.collect::<Vec<&str>>().join(" ");
+			match (letra, cor) {
+				(Some(letra), Some((_,Some(cor)))) if !nome.is_empty() => {
+					definicoes.push(Definicao{ letra, cor, nome, desenho: Vec::new() });
+				}
+				(_, Some((cor,None))) => return Err(tr!("linha {}: cor desconhecida '{}'", numero+1, cor)),
+				_ => return Err(tr!("linha {}: esperado 'navio <letra> <cor> <nome>'", numero+1)),
+			}
+		} else if !linha.is_empty() {
+			match definicoes.last_mut() {
+				Some(definicao) => definicao.desenho.push(linha),
+				None => return Err(tr!("linha {}: desenho antes de 'navio'", numero+1)),
+			}
+		}
+	}
+
+	if definicoes.is_empty() {
+		return Err(String::from(tr!("nenhum navio definido")));
+	}
+
+	let mut frota = Vec::new();
+	for definicao in definicoes {
+		let forma = forma_do_desenho(&definicao.desenho)
+			.map_err(|erro| tr!("navio '{}': {}", definicao.nome, erro))?;
+		frota.push(Navio::new(&definicao.nome, forma, definicao.letra, definicao.cor));
+	}
+	Ok(frota)
+}
+
+
+// Converte o desenho em células relativas à popa, a forma precisa ser conexa
+fn forma_do_desenho(desenho: &[&str]) -> Result<Vec<(i64,i64)>, String> {
+	let mut celulas = Vec::new();
+	for (y,linha) in desenho.iter().enumerate() {
+		for (x,c) in linha.chars().enumerate() {
+			match c {
+				'X' | 'x' => celulas.push((x as i64, y as i64)),
+				'.' | ' ' => {}
+				_ => return Err(tr!("caractere inválido '{}' no desenho", c)),
+			}
+		}
+	}
+
+	if celulas.is_empty() {
+		return Err(String::from(tr!("desenho vazio")));
+	}
+	if desenho.len() > LADO_MAXIMO || desenho.iter().any(|l| l.chars().count() > LADO_MAXIMO) {
+		return Err(String::from(tr!("desenho maior que o tabuleiro")));
+	}
+
+	// Busca em largura a partir da primeira célula para testar se é conexa
+	let mut visitadas = vec![celulas[0]];
+	let mut i = 0;
+	while i < visitadas.len() {
+		let (x,y) = visitadas[i];
+		for vizinha in [(x+1,y),(x-1,y),(x,y+1),(x,y-1)] {
+			if celulas.contains(&vizinha) && !visitadas.contains(&vizinha) {
+				visitadas.push(vizinha);
+			}
+		}
+		i += 1;
+	}
+	if visitadas.len() != celulas.len() {
+		return Err(String::from(tr!("a forma precisa ser conexa")));
+	}
+
+	// Popa na origem
+	let (popa_x,popa_y) = celulas[0];
+	Ok(celulas.iter().map(|&(x,y)| (x-popa_x, y-popa_y)).collect())
+}
@@ -0,0 +1,615 @@
+/*
+	Batalha Naval em tabuleiro hexagonal, no menu principal
+
+	- Cada casa tem seis vizinhas e os navios ficam retos ao longo de um dos três
+	eixos do hexágono, em seis direções: LESTE, NORDESTE, NOROESTE, OESTE,
+	SUDOESTE e SUDESTE; girar passa para a próxima direção, 60 graus
+	- Coordenadas de deslocamento: 'x' coluna e 'y' linha, como no mapa, e as
+	linhas ímpares ficam meia casa para a direita na tela
+	- Para os passos são usadas coordenadas axiais, q = x - (y - y%2)/2 e r = y,
+	em que cada direção soma sempre o mesmo (dq,dr)
+	- Frota padrão de navios retos, mar aberto e o lado das opções; sem armas
+	especiais, relógio, terreno, rede nem partida salva, e o acerto mostra o navio
+	- O bot joga na dificuldade das opções com as vizinhas do hexágono (ver bot.rs);
+	o difícil procura primeiro nas casas de uma das três cores de 'cor', todo
+	navio de três ou mais casas passa por uma delas
+	- Teclas: na arrumação movimento, gira, muda, sorteia e inicia; na batalha
+	movimento e fogo; Esc pausa e '?' mostra a ajuda
+*/
+
+
+use crossterm::QueueableCommand;
+use crossterm::cursor;
+use crossterm::event::{Event,KeyEventKind};
+use crossterm::style::{self,ResetColor,SetBackgroundColor,SetForegroundColor};
+use crossterm::terminal::{self,Clear};
+use rand::Rng;
+
+use crate::{Jogador,Navio,Mapa,Arsenal,Desfecho,Opcoes,AGUA,DESTRUIDO,FLUXO_FROTA_BOT,FLUXO_FROTA_HUMANO,FLUXO_RODADAS};
+use crate::{gerador,limpa_tela,pedacos_vivos,frota,menu,teclas};
+use crate::bot::Bot;
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::menu::Pausa;
+use crate::teclas::Acao;
+
+
+// Tentativas de posição para cada navio antes de sortear a frota de novo
+const TENTATIVAS_NAVIO: usize = 200;
+
+// Ações de cada fase, na ordem da ajuda
+const ACOES_ARRUMACAO: [Acao;10] = [Acao::CIMA, Acao::BAIXO, Acao::ESQUERDA, Acao::DIREITA,
+	Acao::GIRA, Acao::MUDA, Acao::SORTEIA, Acao::INICIA, Acao::PAUSA, Acao::AJUDA];
+const ACOES_BATALHA: [Acao;7] = [Acao::CIMA, Acao::BAIXO, Acao::ESQUERDA, Acao::DIREITA,
+	Acao::FOGO, Acao::PAUSA, Acao::AJUDA];
+
+
+// Direções dos navios no hexágono
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum DirecaoHex {
+	LESTE,
+	NORDESTE,
+	NOROESTE,
+	OESTE,
+	SUDOESTE,
+	SUDESTE,
+}
+
+impl DirecaoHex {
+	pub const TODAS: [DirecaoHex;6] = [DirecaoHex::LESTE, DirecaoHex::NORDESTE, DirecaoHex::NOROESTE,
+		DirecaoHex::OESTE, DirecaoHex::SUDOESTE, DirecaoHex::SUDESTE];
+
+	// Próxima direção no sentido horário, 60 graus
+	pub fn horaria(self) -> DirecaoHex {
+		match self {
+			DirecaoHex::LESTE => DirecaoHex::SUDESTE,
+			DirecaoHex::SUDESTE => DirecaoHex::SUDOESTE,
+			DirecaoHex::SUDOESTE => DirecaoHex::OESTE,
+			DirecaoHex::OESTE => DirecaoHex::NOROESTE,
+			DirecaoHex::NOROESTE => DirecaoHex::NORDESTE,
+			DirecaoHex::NORDESTE => DirecaoHex::LESTE,
+		}
+	}
+
+	// Passo em coordenadas axiais
+	fn passo(self) -> (i64,i64) {
+		match self {
+			DirecaoHex::LESTE => (1,0),
+			DirecaoHex::NORDESTE => (1,-1),
+			DirecaoHex::NOROESTE => (0,-1),
+			DirecaoHex::OESTE => (-1,0),
+			DirecaoHex::SUDOESTE => (-1,1),
+			DirecaoHex::SUDESTE => (0,1),
+		}
+	}
+}
+
+
+// Coordenadas axiais (q,r) da casa (x,y)
+fn axial(x:i64, y:i64) -> (i64,i64) {
+	(x - (y - y.rem_euclid(2))/2, y)
+}
+
+// Casa (x,y) das coordenadas axiais (q,r)
+fn deslocamento(q:i64, r:i64) -> (i64,i64) {
+	(q + (r - r.rem_euclid(2))/2, r)
+}
+
+fn dentro(x:i64, y:i64, lado:usize) -> bool {
+	x >= 0 && y >= 0 && x < lado as i64 && y < lado as i64
+}
+
+
+// Vizinha de (x,y) na direção dada, pode estar fora do tabuleiro
+pub fn vizinha(x:i64, y:i64, direcao:DirecaoHex) -> (i64,i64) {
+	let (q,r) = axial(x, y);
+	let (dq,dr) = direcao.passo();
+	deslocamento(q+dq, r+dr)
+}
+
+// Casas vizinhas de (x,y) dentro do tabuleiro, até seis
+pub fn vizinhas(x:usize, y:usize, lado:usize) -> Vec<(usize,usize)> {
+	DirecaoHex::TODAS.iter()
+		.map(|&direcao| vizinha(x as i64, y as i64, direcao))
+		.filter(|&(x,y)| dentro(x, y, lado))
+		.map(|(x,y)| (x as usize, y as usize))
+		.collect()
+}
+
+// Uma de três cores, vizinhas nunca têm a mesma e três casas em linha têm as três
+pub fn cor(x:usize, y:usize) -> usize {
+	let (q,r) = axial(x as i64, y as i64);
+	(q-r).rem_euclid(3) as usize
+}
+
+
+// Navio reto em uma das seis direções; nome, tamanho, letra, cor e popa vêm do navio
+#[derive(Debug,Clone)]
+pub struct NavioHex {
+	pub navio: Navio,
+	pub direcao: DirecaoHex,
+}
+
+impl NavioHex {
+	pub fn new(navio: &Navio) -> NavioHex {
+		NavioHex{ navio: navio.clone(), direcao: DirecaoHex::LESTE }
+	}
+
+	// Células com a popa em (x,y) e a direção dada, podem estar fora do tabuleiro
+	pub fn celulas_em(&self, x:i64, y:i64, direcao:DirecaoHex) -> Vec<(i64,i64)> {
+		let mut celulas = vec![(x,y)];
+		while celulas.len() < self.navio.tamanho {
+			let &(x,y) = celulas.last().unwrap();
+			celulas.push(vizinha(x, y, direcao));
+		}
+		celulas
+	}
+
+	// Células na posição atual, que sempre é válida
+	pub fn celulas(&self) -> Vec<(usize,usize)> {
+		self.celulas_em(self.navio.popa_x as i64, self.navio.popa_y as i64, self.direcao)
+			.iter()
+			.map(|&(x,y)| (x as usize, y as usize))
+			.collect()
+	}
+}
+
+
+// O navio 'qual' caberia nas células dadas, dentro do tabuleiro e sem sobrepor os outros da frota
+pub fn cabe(navios:&[NavioHex], qual:usize, celulas:&[(i64,i64)], lado:usize) -> bool {
+	celulas.iter().all(|&(x,y)| {
+		dentro(x, y, lado) && !navios.iter().enumerate()
+			.any(|(n,outro)| n != qual && outro.celulas().contains(&(x as usize, y as usize)))
+	})
+}
+
+// Move a popa (dx,dy) e, se pedido, gira; diz se coube
+pub fn move_navio(navios:&mut [NavioHex], qual:usize, dx:i64, dy:i64, gira:bool, lado:usize) -> bool {
+	let navio = &navios[qual];
+	let (x,y) = (navio.navio.popa_x as i64 + dx, navio.navio.popa_y as i64 + dy);
+	let direcao = if gira { navio.direcao.horaria() } else { navio.direcao };
+	if !cabe(navios, qual, &navio.celulas_em(x, y, direcao), lado) {
+		return false;
+	}
+	let navio = &mut navios[qual];
+	(navio.navio.popa_x, navio.navio.popa_y, navio.direcao) = (x as usize, y as usize, direcao);
+	true
+}
+
+// Sorteia posições e direções para a frota inteira
+pub fn sorteia(navios:&mut [NavioHex], lado:usize, rng:&mut impl Rng) {
+	'frota: loop {
+		for qual in 0..navios.len() {
+			// Só os navios já colocados contam
+			let colocado = (0..TENTATIVAS_NAVIO).any(|_| {
+				let (x,y) = (rng.gen_range(0..lado as i64), rng.gen_range(0..lado as i64));
+				let direcao = DirecaoHex::TODAS[rng.gen_range(0..6)];
+				if !cabe(&navios[..qual], qual, &navios[qual].celulas_em(x, y, direcao), lado) {
+					return false;
+				}
+				let navio = &mut navios[qual];
+				(navio.navio.popa_x, navio.navio.popa_y, navio.direcao) = (x as usize, y as usize, direcao);
+				true
+			});
+			if !colocado {
+				continue 'frota;
+			}
+		}
+		return;
+	}
+}
+
+
+// Mapa dos oceanos da frota, no formato do jogo quadrado
+pub fn mapeia(navios:&[NavioHex], lado:usize) -> Mapa {
+	let mut mapa = vec![vec![AGUA; lado]; lado];
+	for (n,navio) in navios.iter().enumerate() {
+		for (x,y) in navio.celulas() {
+			mapa[y][x] = n;
+		}
+	}
+	mapa
+}
+
+// Tiro em (x,y): o navio atingido e se afundou, None na água
+pub fn atira(mapa:&mut Mapa, x:usize, y:usize) -> Option<(usize,bool)> {
+	let celula = mapa[y][x];
+	mapa[y][x] = DESTRUIDO;
+	if celula == AGUA || celula == DESTRUIDO {
+		return None;
+	}
+	Some((celula, !mapa.iter().flatten().any(|&outra| outra == celula)))
+}
+
+
+// Posição da casa (x,y) na tela, as linhas ímpares deslocadas meia casa
+pub fn posicao_na_tela(jogador:&Jogador, x:usize, y:usize, lado:usize) -> cursor::MoveTo {
+	let inicio = match jogador {
+		Jogador::HUMANO => 0,
+		Jogador::BOT => 2*lado+2,
+	};
+	cursor::MoveTo((inicio + 1 + y%2 + 2*x) as u16, 1+y as u16)
+}
+
+
+// Partida hexagonal em andamento
+struct PartidaHex {
+	lado: usize,
+	humano: Vec<NavioHex>,
+	bot: Vec<NavioHex>,
+	mapa_humano: Mapa,			// Frota do humano e tiros do bot
+	mapa_bot: Mapa,
+	mira_x: usize,
+	mira_y: usize,
+	aviso: String,
+}
+
+impl PartidaHex {
+	// Moldura dos dois tabuleiros, navios, tiros e linhas de status
+	fn desenha(&self, con:&mut dyn Console, opcoes:&Opcoes, instrucoes:&str, batalha:bool) -> Result<(), ErroJogo> {
+		let tema = &opcoes.tema;
+		let glifos = tema.glifos;
+		let lado = self.lado;
+		let largura = 2*lado+1;
+		limpa_tela(con)?;
+
+		let (cor_frente,cor_fundo) = tema.moldura();
+		con
+			.queue( SetForegroundColor(cor_frente) )?
+			.queue( SetBackgroundColor(cor_fundo) )?
+			.queue( cursor::MoveTo(0,0) )?
+			.queue( style::Print(glifos.cantos_sup[0]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_sup[1]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_sup[2]) )?;
+		for y in 1 ..= lado as u16 {
+			con
+				.queue( cursor::MoveTo(0,y) )?
+				.queue( style::Print(glifos.vertical) )?
+				.queue( style::Print(" ".repeat(largura)) )?
+				.queue( style::Print(glifos.vertical) )?
+				.queue( style::Print(" ".repeat(largura)) )?
+				.queue( style::Print(glifos.vertical) )?;
+		}
+		con
+			.queue( cursor::MoveTo(0,lado as u16+1) )?
+			.queue( style::Print(glifos.cantos_inf[0]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_inf[1]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_inf[2]) )?;
+
+		// Frota do humano com os tiros do bot, no tabuleiro do bot só os tiros do humano
+		for navio in self.humano.iter() {
+			for (x,y) in navio.celulas() {
+				con.queue( posicao_na_tela(&Jogador::HUMANO, x, y, lado) )?;
+				match self.mapa_humano[y][x] {
+					DESTRUIDO => con.queue( style::PrintStyledContent(tema.acerto(&navio.navio)) )?,
+					_ => con.queue( style::PrintStyledContent(tema.navio(&navio.navio)) )?,
+				};
+			}
+		}
+		let original_humano = mapeia(&self.humano, lado);
+		let original_bot = mapeia(&self.bot, lado);
+		for y in 0..lado {
+			for x in 0..lado {
+				if self.mapa_humano[y][x] == DESTRUIDO && original_humano[y][x] == AGUA {
+					con.queue( posicao_na_tela(&Jogador::HUMANO, x, y, lado) )?.queue( style::PrintStyledContent(tema.agua()) )?;
+				}
+				if self.mapa_bot[y][x] == DESTRUIDO {
+					let desenho = match original_bot[y][x] {
+						AGUA => tema.agua(),
+						n => tema.acerto(&self.bot[n].navio),
+					};
+					con.queue( posicao_na_tela(&Jogador::BOT, x, y, lado) )?.queue( style::PrintStyledContent(desenho) )?;
+				}
+			}
+		}
+		con.queue( ResetColor )?;
+
+		let mira = match batalha {
+			true => posicao_na_tela(&Jogador::BOT, self.mira_x, self.mira_y, lado),
+			false => cursor::MoveTo(0, lado as u16+3),
+		};
+		con
+			.queue( cursor::MoveTo(0, lado as u16+2) )?
+			.queue( style::Print(format!("{:<w$}{}", tr!("Meus Navios"), tr!("Inimigo"), w = largura+1)) )?
+			.queue( cursor::MoveTo(0, lado as u16+3) )?
+			.queue( style::Print(instrucoes) )?
+			.queue( cursor::MoveTo(0, lado as u16+4) )?
+			.queue( style::Print(&self.aviso) )?
+			.queue( mira )?;
+		con.flush()?;
+		Ok(())
+	}
+
+	// Instruções da arrumação, com o navio corrente
+	fn instrucoes_arrumacao(&self, opcoes:&Opcoes, corrente:usize) -> String {
+		let teclas = &opcoes.teclas;
+		tr!("{}: {} move, {} gira, {} muda, {} sorteia, {} inicia", tr!(self.humano[corrente].navio.nome.as_str()),
+			teclas.movimento(), teclas.tecla(Acao::GIRA), teclas.tecla(Acao::MUDA), teclas.tecla(Acao::SORTEIA), teclas.tecla(Acao::INICIA))
+	}
+
+	// Linha de fim de partida, logo abaixo do aviso
+	fn termina(&self, con:&mut dyn Console, texto:&str) -> Result<(), ErroJogo> {
+		con.modo_bruto(false)?;
+		con
+			.queue( cursor::MoveTo(0, self.lado as u16+5) )?
+			.queue( Clear(terminal::ClearType::FromCursorDown) )?
+			.queue( style::Print(texto) )?;
+		writeln!(con)?;
+		Ok(())
+	}
+}
+
+
+// Tamanho do terminal para os dois tabuleiros deslocados e as linhas de status
+fn confere_tamanho(con: &mut dyn Console, lado: usize) -> Result<(), ErroJogo> {
+	let (largura_minima,altura_minima) = (4*lado as u16 + 5, lado as u16 + 8);
+	let (largura,altura) = con.tamanho()?;
+	if largura < largura_minima || altura < altura_minima {
+		return Err(ErroJogo::TAMANHO(largura_minima, altura_minima));
+	}
+	Ok(())
+}
+
+
+// Pausa no meio da partida, None para continuar
+fn pausa(con:&mut dyn Console, opcoes:&Opcoes) -> Result<Option<Desfecho>, ErroJogo> {
+	con.modo_bruto(false)?;
+	let desfecho = match menu::pausa(con, false, &opcoes.teclas)? {
+		Pausa::CONTINUAR | Pausa::SALVAR => None,
+		Pausa::REINICIAR => Some(Desfecho::REINICIA),
+		Pausa::SAIR => Some(Desfecho::MENU),
+		Pausa::ABORTAR => Some(Desfecho::ABORTADO),
+	};
+	con.modo_bruto(true)?;
+	Ok(desfecho)
+}
+
+
+// Partidas hexagonais seguidas contra o bot, até o jogador voltar ao menu
+pub fn joga(con: &mut dyn Console, opcoes: &Opcoes) -> Result<Desfecho, ErroJogo> {
+	let frota = match frota::carrega(None) {
+		Ok(frota) => frota,
+		Err(erro) => {
+			menu::mostra_texto(con, &[erro])?;
+			return Ok(Desfecho::MENU);
+		}
+	};
+	if let Err(erro @ ErroJogo::TAMANHO(..)) = confere_tamanho(con, opcoes.lado) {
+		menu::mostra_texto(con, &[erro.to_string()])?;
+		return Ok(Desfecho::MENU);
+	}
+	loop {
+		let semente = opcoes.semente.unwrap_or_else(|| rand::thread_rng().gen());
+		match partida(con, opcoes, &frota, semente)? {
+			Desfecho::CONTINUA => {
+				writeln!(con, "\r{}", tr!("Semente da partida: {}", semente))?;
+				match menu::pergunta(con, tr!("Jogar novamente? (s/n)"))? {
+					Some(true) => {}
+					Some(false) => return Ok(Desfecho::MENU),
+					None => return Ok(Desfecho::ABORTADO),
+				}
+			}
+			Desfecho::REINICIA => {}
+			desfecho => return Ok(desfecho),
+		}
+	}
+}
+
+
+// Uma partida: arrumação e batalha, CONTINUA quando alguém venceu
+pub fn partida(con: &mut dyn Console, opcoes: &Opcoes, frota: &[Navio], semente: u64) -> Result<Desfecho, ErroJogo> {
+	let lado = opcoes.lado;
+	let mut humano: Vec<NavioHex> = frota.iter().map(NavioHex::new).collect();
+	let mut bot: Vec<NavioHex> = frota.iter().map(NavioHex::new).collect();
+	let mut rng_humano = gerador(semente, FLUXO_FROTA_HUMANO);
+	sorteia(&mut humano, lado, &mut rng_humano);
+	sorteia(&mut bot, lado, &mut gerador(semente, FLUXO_FROTA_BOT));
+	let (mapa_humano,mapa_bot) = (mapeia(&humano, lado), mapeia(&bot, lado));
+	let mut partida = PartidaHex{ lado, humano, bot, mapa_humano, mapa_bot, mira_x: 0, mira_y: 0, aviso: String::new() };
+
+	// Arrumação
+	let mut corrente = 0;
+	partida.desenha(con, opcoes, &partida.instrucoes_arrumacao(opcoes, corrente), false)?;
+	con.modo_bruto(true)?;
+	loop {
+		let tecla = match con.le_evento()? {
+			Event::Key(tecla) if tecla.kind == KeyEventKind::Press => tecla,
+			Event::Resize(_,_) => {
+				confere_tamanho(con, lado)?;
+				partida.desenha(con, opcoes, &partida.instrucoes_arrumacao(opcoes, corrente), false)?;
+				continue;
+			}
+			_ => continue,
+		};
+		if teclas::aborta(&tecla) {
+			con.modo_bruto(false)?;
+			return Ok(Desfecho::ABORTADO);
+		}
+
+		partida.aviso.clear();
+		let coube = match opcoes.teclas.acao(&tecla) {
+			Some(Acao::CIMA) => move_navio(&mut partida.humano, corrente, 0, -1, false, lado),
+			Some(Acao::BAIXO) => move_navio(&mut partida.humano, corrente, 0, 1, false, lado),
+			Some(Acao::ESQUERDA) => move_navio(&mut partida.humano, corrente, -1, 0, false, lado),
+			Some(Acao::DIREITA) => move_navio(&mut partida.humano, corrente, 1, 0, false, lado),
+			Some(Acao::GIRA) => move_navio(&mut partida.humano, corrente, 0, 0, true, lado),
+			Some(Acao::MUDA) => {
+				corrente = (corrente + 1) % partida.humano.len();
+				true
+			}
+			Some(Acao::SORTEIA) => {
+				sorteia(&mut partida.humano, lado, &mut rng_humano);
+				true
+			}
+			Some(Acao::INICIA) => break,
+			Some(Acao::PAUSA) => {
+				if let Some(desfecho) = pausa(con, opcoes)? {
+					return Ok(desfecho);
+				}
+				true
+			}
+			Some(Acao::AJUDA) => {
+				menu::mostra_ajuda(con, &opcoes.teclas.ajuda(&ACOES_ARRUMACAO))?;
+				con.modo_bruto(true)?;
+				true
+			}
+			_ => continue,
+		};
+		if !coube {
+			partida.aviso = String::from(tr!("O navio não cabe nessa posição."));
+		}
+		partida.desenha(con, opcoes, &partida.instrucoes_arrumacao(opcoes, corrente), false)?;
+	}
+
+	// Batalha, o humano começa
+	partida.mapa_humano = mapeia(&partida.humano, lado);
+	let mut adversario = Bot::hexagonal(opcoes.dificuldade, lado);
+	let teclas = &opcoes.teclas;
+	let instrucoes = tr!("Mire com {}, {} atira, {} pausa, {} ajuda",
+		teclas.movimento(), teclas.tecla(Acao::FOGO), teclas.tecla(Acao::PAUSA), teclas.tecla(Acao::AJUDA));
+	let mut rodada: u64 = 0;
+	partida.desenha(con, opcoes, &instrucoes, true)?;
+	loop {
+		let tecla = match con.le_evento()? {
+			Event::Key(tecla) if tecla.kind == KeyEventKind::Press => tecla,
+			Event::Resize(_,_) => {
+				confere_tamanho(con, lado)?;
+				partida.desenha(con, opcoes, &instrucoes, true)?;
+				continue;
+			}
+			_ => continue,
+		};
+		if teclas::aborta(&tecla) {
+			con.modo_bruto(false)?;
+			return Ok(Desfecho::ABORTADO);
+		}
+
+		match opcoes.teclas.acao(&tecla) {
+			Some(Acao::CIMA) => partida.mira_y = partida.mira_y.saturating_sub(1),
+			Some(Acao::BAIXO) => partida.mira_y = (partida.mira_y+1).min(lado-1),
+			Some(Acao::ESQUERDA) => partida.mira_x = partida.mira_x.saturating_sub(1),
+			Some(Acao::DIREITA) => partida.mira_x = (partida.mira_x+1).min(lado-1),
+			Some(Acao::PAUSA) => {
+				if let Some(desfecho) = pausa(con, opcoes)? {
+					return Ok(desfecho);
+				}
+			}
+			Some(Acao::AJUDA) => {
+				menu::mostra_ajuda(con, &opcoes.teclas.ajuda(&ACOES_BATALHA))?;
+				con.modo_bruto(true)?;
+			}
+			Some(Acao::FOGO) => {
+				let (x,y) = (partida.mira_x, partida.mira_y);
+				if partida.mapa_bot[y][x] == DESTRUIDO {
+					partida.aviso = String::from(tr!("Você já atirou aí."));
+					partida.desenha(con, opcoes, &instrucoes, true)?;
+					continue;
+				}
+				partida.aviso = match atira(&mut partida.mapa_bot, x, y) {
+					None => String::from(tr!("Água.")),
+					Some((n,true)) => tr!("Você afundou: {}", tr!(partida.bot[n].navio.nome.as_str())),
+					Some((_,false)) => String::from(tr!("Acerto!")),
+				};
+				partida.desenha(con, opcoes, &instrucoes, true)?;
+				if pedacos_vivos(&partida.mapa_bot) == 0 {
+					partida.termina(con, tr!("VITÓRIA DO HUMANO!!!"))?;
+					return Ok(Desfecho::CONTINUA);
+				}
+				std::thread::sleep(opcoes.atraso);
+
+				// O bot responde com um tiro
+				let mut rng = gerador(semente, FLUXO_RODADAS + rodada);
+				let (_,x,y,_) = adversario.escolhe_ataque(&partida.mapa_humano, &Arsenal::new(false), &mut rng);
+				partida.aviso = match atira(&mut partida.mapa_humano, x, y) {
+					None => tr!("O bot respondeu em {} {}: água", x, y),
+					Some((n,afundou)) => {
+						adversario.registra_acertos(&[(x,y)], None);
+						let nome = tr!(partida.humano[n].navio.nome.as_str());
+						match afundou {
+							true => {
+								adversario.registra_afundado(&partida.humano[n].celulas());
+								tr!("O bot respondeu em {} {}: afundou o seu {}", x, y, nome)
+							}
+							false => tr!("O bot respondeu em {} {}: acertou o seu {}", x, y, nome),
+						}
+					}
+				};
+				rodada += 1;
+				partida.desenha(con, opcoes, &instrucoes, true)?;
+				if pedacos_vivos(&partida.mapa_humano) == 0 {
+					partida.termina(con, tr!("VITÓRIA DO BOT!!!"))?;
+					return Ok(Desfecho::CONTINUA);
+				}
+				continue;
+			}
+			_ => continue,
+		}
+		partida.desenha(con, opcoes, &instrucoes, true)?;
+	}
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+	use crate::bot::Dificuldade;
+
+	#[test]
+	fn hexagono_vizinhas_cores_e_direcoes() {
+		// Linha par e linha ímpar, deslocada meia casa para a direita
+		assert_eq!(vizinhas(0, 0, 6), vec![(1,0), (0,1)]);
+		let mut seis = vizinhas(2, 1, 6);
+		seis.sort();
+		assert_eq!(seis, vec![(1,1), (2,0), (2,2), (3,0), (3,1), (3,2)]);
+
+		// Vizinhas com cores diferentes, três casas em linha com as três cores
+		let frota = frota::carrega(None).unwrap();
+		let mut navio = NavioHex::new(&frota[2]);
+		for y in 0..8 {
+			for x in 0..8 {
+				assert!(vizinhas(x, y, 8).iter().all(|&(vx,vy)| cor(vx, vy) != cor(x, y)));
+				for direcao in DirecaoHex::TODAS {
+					let celulas = navio.celulas_em(x as i64, y as i64, direcao);
+					if celulas.iter().any(|&(x,y)| x < 0 || y < 0) {
+						continue;
+					}
+					let mut cores: Vec<usize> = celulas.iter().map(|&(x,y)| cor(x as usize, y as usize)).collect();
+					cores.sort();
+					assert_eq!(cores, vec![0, 1, 2]);
+				}
+			}
+		}
+
+		// Submarino de três casas para o nordeste, seis giros voltam ao começo
+		assert_eq!(navio.celulas_em(2, 2, DirecaoHex::NORDESTE), vec![(2,2), (2,1), (3,0)]);
+		(navio.navio.popa_x, navio.navio.popa_y) = (2, 2);
+		let mut navios = vec![navio];
+		for _ in 0..6 {
+			assert!(move_navio(&mut navios, 0, 0, 0, true, 6));
+		}
+		assert_eq!(navios[0].direcao, DirecaoHex::LESTE);
+		assert!(!move_navio(&mut navios, 0, 4, 0, false, 6));
+	}
+
+	#[test]
+	fn bot_com_seis_vizinhas() {
+		// Depois de um acerto o bot tenta as seis vizinhas do hexágono
+		let mut mapa = vec![vec![AGUA; 6]; 6];
+		mapa[1][2] = DESTRUIDO;
+		let mut bot = Bot::hexagonal(Dificuldade::NORMAL, 6);
+		bot.registra_acertos(&[(2,1)], None);
+		let mut tiros = Vec::new();
+		let mut rng = gerador(0, FLUXO_RODADAS);
+		for _ in 0..6 {
+			let (_,x,y,_) = bot.escolhe_ataque(&mapa, &Arsenal::new(false), &mut rng);
+			mapa[y][x] = DESTRUIDO;
+			tiros.push((x,y));
+		}
+		tiros.sort();
+		assert_eq!(tiros, vec![(1,1), (2,0), (2,2), (3,0), (3,1), (3,2)]);
+	}
+}
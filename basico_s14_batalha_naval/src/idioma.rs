@@ -0,0 +1,446 @@
+/*
+	Idioma das mensagens
+
+	- O texto em português é a chave, o catálogo 'INGLES' traz a tradução de cada uma
+	- Mensagem sem tradução aparece em português
+	- Marcadores '{}' são trocados pelos argumentos, na ordem, com a macro 'tr!'
+	- O idioma vem de LC_ALL, LC_MESSAGES ou LANG, e pode ser trocado com '--lang pt|en'
+	ou na tela de opções
+	- Nomes gravados em arquivos (ações, armas, cores, seções) não são traduzidos
+*/
+
+
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool,Ordering};
+
+
+// Idiomas disponíveis
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Idioma {
+	PT,
+	EN,
+}
+
+impl Idioma {
+	pub const TODOS: [Idioma;2] = [Idioma::PT, Idioma::EN];
+
+	// Código usado em '--lang'
+	pub fn codigo(&self) -> &'static str {
+		match self {
+			Idioma::PT => "pt",
+			Idioma::EN => "en",
+		}
+	}
+
+	// Nome mostrado no menu, sempre no próprio idioma
+	pub fn nome(&self) -> &'static str {
+		match self {
+			Idioma::PT => "português",
+			Idioma::EN => "English",
+		}
+	}
+
+	// Idioma pelo código, aceita também 'pt_BR', 'en_US.UTF-8' e semelhantes
+	pub fn por_codigo(codigo: &str) -> Option<Idioma> {
+		let codigo = codigo.to_lowercase();
+		Idioma::TODOS.into_iter().find(|idioma| codigo.starts_with(idioma.codigo()))
+	}
+
+	// Idioma do ambiente, português quando não definido
+	pub fn do_ambiente() -> Idioma {
+		let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+			.filter_map(|variavel| env::var(variavel).ok())
+			.find(|valor| !valor.is_empty());
+		match locale.as_deref() {
+			None | Some("C") | Some("POSIX") => Idioma::PT,
+			Some(valor) if valor.starts_with("C.") => Idioma::PT,
+			Some(valor) => Idioma::por_codigo(valor).unwrap_or(Idioma::EN),
+		}
+	}
+}
+
+
+// Idioma em uso, as mensagens são pedidas de todos os módulos
+static INGLES_ATIVO: AtomicBool = AtomicBool::new(false);
+
+// Troca o idioma das mensagens
+pub fn escolhe(idioma: Idioma) {
+	INGLES_ATIVO.store(idioma == Idioma::EN, Ordering::Relaxed);
+}
+
+// Idioma atual das mensagens
+pub fn atual() -> Idioma {
+	match INGLES_ATIVO.load(Ordering::Relaxed) {
+		true => Idioma::EN,
+		false => Idioma::PT,
+	}
+}
+
+
+// Mensagem no idioma atual
+pub fn traduz(texto: &str) -> &str {
+	match atual() {
+		Idioma::PT => texto,
+		Idioma::EN => INGLES.iter().find(|(pt,_)| *pt == texto).map_or(texto, |(_,en)| en),
+	}
+}
+
+// Troca cada '{}' do modelo pelo próximo argumento
+pub fn formata(modelo: &str, argumentos: &[&dyn Display]) -> String {
+	let mut partes = modelo.split("{}");
+	let mut texto = String::from(partes.next().unwrap_or(""));
+	for (i,parte) in partes.enumerate() {
+		if let Some(argumento) = argumentos.get(i) {
+			texto.push_str(&argumento.to_string());
+		}
+		texto.push_str(parte);
+	}
+	texto
+}
+
+
+// Mensagem traduzida, com argumentos vira String
+macro_rules! tr {
+	($texto:expr) => {
+		$crate::idioma::traduz($texto)
+	};
+	($modelo:expr, $($argumento:expr),+ $(,)?) => {
+		$crate::idioma::formata($crate::idioma::traduz($modelo), &[$(&$argumento),+])
+	};
+}
+
+
+// Catálogo em inglês, a primeira coluna é a chave usada no código
+const INGLES: &[(&str,&str)] = &[
+	// Tela do jogo
+	("Batalha Naval", "Battleship"),
+	("Meus Navios", "My Fleet"),
+	("Inimigo", "Enemy"),
+	("Restam:    {}{}", "Left:      {}{}"),		// Mesma largura, alinha com os tabuleiros
+	("Navio: {}", "Ship: {}"),
+	("Mova com {}, {} gira, {} espelha, {} muda, {} inicia, {} pausa, {} ajuda",
+		"Move {}, {} rotate, {} mirror, {} next, {} start, {} pause, {} help"),
+	("Mire com {}, {} fogo{}{}, {} pausa, {} ajuda", "Aim {}, {} fire{}{}, {} pause, {} help"),
+	(", {} {} {} {} armas, {} gira torpedo", ", {} {} {} {} weapons, {} turn torpedo"),
+	("Arma: {}", "Weapon: {}"),
+	("O bot usou: {}", "The bot used: {}"),
+	("Não é possível atirar em ilhas ou recifes.", "You cannot fire at islands or reefs."),
+	("Tempo da jogada esgotado, tiro automático!", "Out of time for this shot, automatic fire!"),
+	("Radar: navio detectado na área!", "Radar: ship detected in the area!"),
+	("Radar: nenhum navio na área.", "Radar: no ships in the area."),
+	("Nenhuma dica restante.", "No hints left."),
+	("Dica: maior chance de navio em {} {}, restam {} dicas", "Hint: best chance of a ship at {} {}, {} hints left"),
+	("Partida salva em {}", "Game saved to {}"),
+	("VITÓRIA DO HUMANO!!!", "THE HUMAN WINS!!!"),
+	("VITÓRIA DO BOT!!!", "THE BOT WINS!!!"),
+	("Semente da partida: {}", "Game seed: {}"),
+	("Reprise em {}", "Replay saved to {}"),
+	("Fim da reprise, tecle algo para voltar", "End of replay, press any key to go back"),
+	("VITÓRIA DO BOT!!! Tempo total esgotado.", "THE BOT WINS!!! Out of total time."),
+	("Jogar novamente? (s/n)", "Play again? (y/n)"),
+	(", {} manobra", ", {} maneuver"),
+	("{}: {} anda, {} gira, {} outro, {} volta", "{}: {} move, {} rotate, {} next, {} back"),
+	("Nenhum navio inteiro para manobrar.", "No undamaged ship to maneuver."),
+	("O navio não cabe nessa posição.", "The ship does not fit there."),
+	("Você manobrou: {}", "You maneuvered: {}"),
+	("O bot manobrou um navio.", "The bot maneuvered a ship."),
+	("Você afundou um navio!", "You sank a ship!"),
+	("Você afundou: {}", "You sank: {}"),
+
+	// Partida em rede, mensagens do terminal e do navegador
+	("Aguardando adversário em {}", "Waiting for an opponent at {}"),
+	("Esc volta ao menu", "Esc returns to the menu"),
+	("Não conseguiu abrir a porta {}: {}", "Could not open port {}: {}"),
+	("Aguardando o adversário arrumar a frota...", "Waiting for the opponent to place their fleet..."),
+	("Sorteie a frota e clique em Pronto", "Shuffle your fleet and click Pronto"),
+	("Vez do adversário...", "Opponent's turn..."),
+	("O adversário atirou em {} {}, sua vez", "The opponent fired at {} {}, your turn"),
+	("Alvo inválido, escolha outra célula", "Invalid target, choose another cell"),
+	("O adversário desconectou.", "The opponent disconnected."),
+	("VITÓRIA DO ADVERSÁRIO!!!", "THE OPPONENT WINS!!!"),
+	("Você venceu!", "You win!"),
+	("Você perdeu.", "You lose."),
+	("Saguão em {}, Ctrl+C termina", "Lobby at {}, Ctrl+C stops"),
+	("Sala {}: {} x {}", "Room {}: {} vs {}"),
+	("Sala {}: vitória de {}", "Room {}: {} wins"),
+	("Sala cheia ou inexistente", "Room is full or does not exist"),
+	("O nome {} já é de outro jogador", "The name {} belongs to another player"),
+	("Termine a partida antes", "Finish the game first"),
+	("Ficha inválida ou expirada", "Invalid or expired token"),
+	("Aguardando outro jogador...", "Waiting for another player..."),
+	("A batalha começou", "The battle has begun"),
+	("Sua vez", "Your turn"),
+	("{} atirou em {} {}: acertou!", "{} fired at {} {}: hit!"),
+	("{} atirou em {} {}: água", "{} fired at {} {}: miss"),
+	("{} caiu, esperando a volta", "{} dropped, waiting for them to return"),
+	("{} voltou", "{} is back"),
+	("{} não voltou, vitória por W.O.", "{} did not return, win by forfeit"),
+
+	// Partidas de vários jogadores
+	("Alvo com 1-{} ou {}, mire com {}, {} fogo, {} pausa", "Target with 1-{} or {}, aim with {}, {} fire, {} pause"),
+	("{} atirou em {} ({} {}): acertou!", "{} fired at {} ({} {}): hit!"),
+	("{} atirou em {} ({} {}): água", "{} fired at {} ({} {}): miss"),
+	("{}, sua vez", "{}, your turn"),
+	("VITÓRIA DE {}!!!", "{} WINS!!!"),
+
+	// Tutorial
+	("Passo 1/7: leve o porta-aviões com {} até a célula destacada", "Step 1/7: move the aircraft carrier with {} to the highlighted cell"),
+	("Passo 2/7: gire o navio com {}", "Step 2/7: rotate the ship with {}"),
+	("Passo 3/7: passe para o próximo navio com {}", "Step 3/7: go to the next ship with {}"),
+	("Passo 4/7: frota pronta, comece a batalha com {}", "Step 4/7: fleet ready, start the battle with {}"),
+	("Passo 5/7: leve a mira com {} até a célula destacada do inimigo", "Step 5/7: aim with {} at the highlighted enemy cell"),
+	("Passo 6/7: atire com {}", "Step 6/7: fire with {}"),
+	("Passo 7/7: acertou! Afunde o navio atirando na célula destacada", "Step 7/7: a hit! Sink the ship by firing at the highlighted cell"),
+	("Submarino afundado, tutorial concluído! Tecle algo para voltar", "Submarine sunk, tutorial complete! Press any key to return"),
+	("Não há espaço para girar aqui, mova o navio antes", "No room to rotate here, move the ship first"),
+	("Mire na célula destacada antes de atirar", "Aim at the highlighted cell before firing"),
+	("Ainda não, siga o passo acima", "Not yet, follow the step above"),
+	("O bot respondeu em {} {}: água", "The bot answered at {} {}: miss"),
+	("O bot respondeu em {} {}: acertou o seu {}", "The bot answered at {} {}: hit your {}"),
+
+	// Quebra-cabeça
+	("Mova com {}, {} marca, '1' água, '2' navio, '0' limpa, {} dica, {} pausa",
+		"Move with {}, {} marks, '1' water, '2' ship, '0' clears, {} hint, {} pause"),
+	("Dica: esta marca está errada", "Hint: this mark is wrong"),
+	("Dica: aqui tem navio", "Hint: there is a ship here"),
+	("Resolvido em {}s, com {} dicas!", "Solved in {}s, with {} hints!"),
+	("A frota não cabe neste tabuleiro sem navios encostados", "The fleet does not fit on this board without ships touching"),
+
+	// Tabuleiro hexagonal
+	("{}: {} move, {} gira, {} muda, {} sorteia, {} inicia", "{}: {} move, {} rotate, {} next, {} shuffle, {} start"),
+	("Mire com {}, {} atira, {} pausa, {} ajuda", "Aim {}, {} fires, {} pause, {} help"),
+	("Água.", "Miss."),
+	("Acerto!", "Hit!"),
+	("Você já atirou aí.", "You already fired there."),
+	("O bot respondeu em {} {}: afundou o seu {}", "The bot answered at {} {}: sank your {}"),
+
+	// Desafio do dia
+	("DESAFIO DO DIA {}", "DAILY CHALLENGE {}"),
+	("{}. {} {} ataques", "{}. {} {} attacks"),
+	("    {} não afundou a frota", "    {} did not sink the fleet"),
+	("Ninguém jogou ainda.", "Nobody has played yet."),
+	("Nenhum desafio registrado.", "No challenge recorded."),
+	("Só a primeira partida do dia conta para o desafio.", "Only the first game of the day counts for the challenge."),
+	("Não conseguiu ler os desafios {}: {}", "Could not read the challenges {}: {}"),
+	("Não conseguiu gravar os desafios {}: {}", "Could not write the challenges {}: {}"),
+	("{} resultados gravados em {}", "{} results written to {}"),
+	("{} resultados novos de {}", "{} new results from {}"),
+
+	("Jogo foi abortado.", "Game aborted."),
+	("Fim do jogo.", "Game over."),
+	("Erro no terminal: {}", "Terminal error: {}"),
+	("O terminal precisa de pelo menos {} colunas e {} linhas", "The terminal needs at least {} columns and {} lines"),
+	("A frota ocupa {} células, máximo de {} neste tabuleiro", "The fleet takes {} cells, at most {} on this board"),
+	("Não há espaço no tabuleiro para o navio '{}'", "There is no room on the board for the ship '{}'"),
+	("Tempo:", "Time:"),
+	("  jogada {}s", "  shot {}s"),
+	("  total {}", "  total {}"),
+	("Opção inválida: {}", "Invalid option: {}"),
+	("Uso: s14_batalha_naval [--lado <6-16>] [--armas] [--frota <nome ou arquivo>] [--mapa <nome ou arquivo>]",
+		"Usage: s14_batalha_naval [--lado <6-16>] [--armas] [--frota <name or file>] [--mapa <name or file>]"),
+
+	// Armas, dificuldades, navios da frota padrão e das frotas prontas
+	("Tiro", "Shot"),
+	("Bomba", "Bomb"),
+	("Torpedo", "Torpedo"),
+	("Radar", "Radar"),
+	("facil", "easy"),
+	("normal", "normal"),
+	("dificil", "hard"),
+	("adaptativo", "adaptive"),
+	("Porta-aviões", "Aircraft carrier"),
+	("Cruzador", "Cruiser"),
+	("Destróier", "Destroyer"),
+	("Submarino", "Submarine"),
+	("Cruzador em L", "L-shaped cruiser"),
+	("Destróier em T", "T-shaped destroyer"),
+	("Base", "Base"),
+
+	// Menus
+	("BATALHA NAVAL   jogador: {}", "BATTLESHIP   player: {}"),
+	("Novo jogo", "New game"),
+	("Dificuldade: {}", "Difficulty: {}"),
+	("Opções", "Options"),
+	("Regras", "Rules"),
+	("Tutorial", "Tutorial"),
+	("Carregar partida", "Load game"),
+	("Quebra-cabeça", "Puzzle"),
+	("Tabuleiro hexagonal", "Hex board"),
+	("Desafio do dia", "Daily challenge"),
+	("Placar", "Scoreboard"),
+	("Sair", "Quit"),
+	("Enter escolhe, {}{} mudam a dificuldade, Esc sai", "Enter selects, {}{} change the difficulty, Esc quits"),
+	("OPÇÕES", "OPTIONS"),
+	("Tabuleiro: {}", "Board: {}"),
+	("Tabuleiro: definido pelo mapa", "Board: set by the map"),
+	("Frota: {}", "Fleet: {}"),
+	("clássica", "classic"),
+	("Mapa: {}", "Map: {}"),
+	("mar aberto", "open sea"),
+	("Armas especiais: {}", "Special weapons: {}"),
+	("sim", "yes"),
+	("não", "no"),
+	("Relógio: {}", "Clock: {}"),
+	("desligado", "off"),
+	("blitz", "blitz"),
+	("personalizado", "custom"),
+	("Tema: {}", "Theme: {}"),
+	("clássico", "classic"),
+	("noturno", "night"),
+	("daltônico", "colour-blind"),
+	("monocromático", "monochrome"),
+	("Glifos: {}", "Glyphs: {}"),
+	("Teclas: {}", "Keys: {}"),
+	("Idioma: {}", "Language: {}"),
+	("Animações: {}", "Animations: {}"),
+	("Som: {}", "Sound: {}"),
+	("Neblina: {}", "Fog: {}"),
+	("Revelação: {}", "Reveal: {}"),
+	("navio afundado", "sunk ship"),
+	("total", "full"),
+	("Voltar", "Back"),
+	("{}{} ou Enter mudam o valor, Esc volta", "{}{} or Enter change the value, Esc goes back"),
+	("REGRAS", "RULES"),
+	("Cada jogador esconde a sua frota no próprio tabuleiro.", "Each player hides their fleet on their own board."),
+	("Na sua vez, mire no tabuleiro inimigo e atire.", "On your turn, aim at the enemy board and fire."),
+	("Vence quem destruir primeiro todos os pedaços dos navios do outro.",
+		"The first to destroy every piece of the other's ships wins."),
+	("Navios não podem ficar sobre ilhas ou recifes e ninguém atira neles.",
+		"Ships cannot sit on islands or reefs and nobody fires at them."),
+	("Bomba atinge 3x3, torpedo segue a linha até um navio, radar detecta navios em 3x3.",
+		"Bomb hits 3x3, torpedo runs along the line up to a ship, radar detects ships in 3x3."),
+	("Se o tempo da jogada acabar, um tiro automático é dado.", "If the time for a shot runs out, an automatic shot is fired."),
+	("Se o tempo total acabar, a partida está perdida.", "If the total time runs out, the game is lost."),
+	("Acertos não dizem o navio e o aviso de afundado não diz qual.", "Hits do not tell the ship and a sinking does not tell which."),
+	("Acertos não dizem o navio, mas o navio afundado é anunciado.", "Hits do not tell the ship, but a sunk ship is announced."),
+	("Neblina: no lugar do tiro, um navio sem dano pode andar uma casa ou girar.",
+		"Fog: instead of firing, an undamaged ship may move one cell or rotate."),
+	("A água já atingida é só histórico, um navio pode ter entrado ali depois.",
+		"Water already hit is only history, a ship may have moved there since."),
+	("Desafio do dia: a mesma frota do bot para todos, conta só a primeira partida e ganha quem usar menos ataques.",
+		"Daily challenge: the same bot fleet for everyone, only the first game counts and the fewest attacks win."),
+	("Esc durante o jogo abre a pausa: continuar, salvar, reiniciar ou sair.",
+		"Esc during the game pauses: continue, save, restart or quit."),
+	("PAUSA", "PAUSE"),
+	("Continuar", "Continue"),
+	("Salvar partida", "Save game"),
+	("Reiniciar", "Restart"),
+	("Sair para o menu", "Quit to menu"),
+	("Enter escolhe, Esc continua", "Enter selects, Esc continues"),
+	("Tecle algo para voltar", "Press any key to go back"),
+	("TECLAS", "KEYS"),
+
+	// Ajuda das teclas
+	("Mover para cima", "Move up"),
+	("Mover para baixo", "Move down"),
+	("Mover para a esquerda", "Move left"),
+	("Mover para a direita", "Move right"),
+	("Girar navio ou torpedo", "Rotate ship or torpedo"),
+	("Espelhar navio", "Mirror ship"),
+	("Mudar de navio", "Next ship"),
+	("Sortear posições da frota", "Shuffle fleet positions"),
+	("Iniciar a batalha", "Start the battle"),
+	("Atirar", "Fire"),
+	("Arma: tiro", "Weapon: shot"),
+	("Arma: bomba", "Weapon: bomb"),
+	("Arma: torpedo", "Weapon: torpedo"),
+	("Arma: radar", "Weapon: radar"),
+	("Mapa de calor", "Heatmap"),
+	("Dica: mira na melhor", "Hint: aim at the best"),
+	("Manobrar um navio", "Maneuver a ship"),
+	("Pausa", "Pause"),
+	("Esta ajuda", "This help"),
+	("Abortar o jogo", "Abort the game"),
+	("espaço", "space"),
+
+	// Placar
+	("PLACAR", "SCOREBOARD"),
+	("Jogador", "Player"),
+	("Jog", "Gms"),
+	("Fácil", "Easy"),
+	("Normal", "Normal"),
+	("Difíc", "Hard"),
+	("Adapt", "Adapt"),
+	("Precis", "Accur"),
+	("Elo", "Elo"),
+	("média de {} ataques por vitória", "{} attacks per win on average"),
+	("Nenhuma partida registrada.", "No games recorded."),
+
+	// Exemplo de treino do ambiente de aprendizado
+	("Treinando a política tabular: {} partidas em lotes de {}", "Training the tabular policy: {} games in batches of {}"),
+	("  {} partidas: média de {} tiros", "  {} games: {} shots on average"),
+	("  {} passos por segundo", "  {} steps per second"),
+	("Avaliação em {} partidas novas, tiros para afundar a frota:", "Evaluation on {} new games, shots to sink the fleet:"),
+	("  tiros aleatórios: {}", "  random shots: {}"),
+	("  política treinada: {}", "  trained policy: {}"),
+
+	// Memória do bot adaptativo
+	("MEMÓRIA DO BOT: {}", "BOT MEMORY: {}"),
+	("Tabuleiro {}x{}, {} partidas lembradas", "Board {}x{}, {} games remembered"),
+	("Navios", "Ships"),
+	("Primeiros tiros", "First shots"),
+	("Nenhuma partida lembrada.", "No games remembered."),
+	("O bot esqueceu as partidas de {}.", "The bot forgot the games of {}."),
+	("O bot não lembrava de nenhuma partida de {}.", "The bot did not remember any game of {}."),
+
+	// Erros de arquivos
+	("Não conseguiu criar {}: {}", "Could not create {}: {}"),
+	("Não conseguiu ler {}: {}", "Could not read {}: {}"),
+	("Não encontrou o diretório do usuário (HOME)", "Could not find the user directory (HOME)"),
+	("Não conseguiu ler o perfil {}: {}", "Could not read the profile {}: {}"),
+	("Não conseguiu gravar o perfil {}: {}", "Could not write the profile {}: {}"),
+	("Não conseguiu ler a memória {}: {}", "Could not read the memory {}: {}"),
+	("Não conseguiu gravar a memória {}: {}", "Could not write the memory {}: {}"),
+	("Não conseguiu apagar a memória {}: {}", "Could not delete the memory {}: {}"),
+	("Nome de perfil inválido: '{}'", "Invalid profile name: '{}'"),
+	("Não conseguiu ler a frota {}: {}", "Could not read the fleet {}: {}"),
+	("Frota {}: {}", "Fleet {}: {}"),
+	("linha {}: cor desconhecida '{}'", "line {}: unknown colour '{}'"),
+	("linha {}: esperado 'navio <letra> <cor> <nome>'", "line {}: expected 'navio <letter> <colour> <name>'"),
+	("linha {}: desenho antes de 'navio'", "line {}: drawing before 'navio'"),
+	("nenhum navio definido", "no ships defined"),
+	("navio '{}': {}", "ship '{}': {}"),
+	("caractere inválido '{}' no desenho", "invalid character '{}' in the drawing"),
+	("desenho vazio", "empty drawing"),
+	("desenho maior que o tabuleiro", "drawing larger than the board"),
+	("a forma precisa ser conexa", "the shape must be connected"),
+	("Não conseguiu ler o mapa {}: {} (predefinidos: {})", "Could not read the map {}: {} (built-in: {})"),
+	("Mapa {}: {}", "Map {}: {}"),
+	("o lado do mapa deve ser entre {} e {}", "the map side must be between {} and {}"),
+	("mais de {} linhas", "more than {} lines"),
+	("linha {} do mapa deve ter {} colunas", "map line {} must have {} columns"),
+	("caractere inválido '{}' na linha {} do mapa", "invalid character '{}' on map line {}"),
+	("mapa vazio", "empty map"),
+	("o mapa deve ter {} linhas", "the map must have {} lines"),
+	("Não conseguiu ler as teclas {}: {}", "Could not read the keys {}: {}"),
+	("Não conseguiu ler as teclas {}: {} (predefinidas: {})", "Could not read the keys {}: {} (built-in: {})"),
+	("Teclas {}: linha {}: {}", "Keys {}: line {}: {}"),
+	("esperado 'ação = teclas'", "expected 'action = keys'"),
+	("conjunto desconhecido '{}'", "unknown set '{}'"),
+	("ação desconhecida '{}'", "unknown action '{}'"),
+	("tecla desconhecida '{}'", "unknown key '{}'"),
+	("nenhuma tecla para '{}'", "no keys for '{}'"),
+	("Teclas {}: '{}' usada em '{}' e '{}'", "Keys {}: '{}' used for '{}' and '{}'"),
+	("Nenhuma partida salva.", "No saved game."),
+	("Não conseguiu salvar a partida em {}: {}", "Could not save the game to {}: {}"),
+	("Não conseguiu ler a partida {}: {}", "Could not read the saved game {}: {}"),
+	("Partida salva {}: {}", "Saved game {}: {}"),
+	("Não conseguiu gravar a reprise em {}: {}", "Could not write the replay to {}: {}"),
+	("Não conseguiu ler a reprise {}: {}", "Could not read the replay {}: {}"),
+	("Reprise {}: {}", "Replay {}: {}"),
+	("jogada inválida '{}'", "invalid move '{}'"),
+	("direção inválida '{}'", "invalid direction '{}'"),
+	("arma inválida '{}'", "invalid weapon '{}'"),
+	("falta '{}'", "missing '{}'"),
+	("valor inválido em '{}'", "invalid value in '{}'"),
+	("número inválido '{}'", "invalid number '{}'"),
+	("esperados {} números em '{}'", "expected {} numbers in '{}'"),
+	("número de navios diferente da frota", "number of ships differs from the fleet"),
+	("navio inválido '{}'", "invalid ship '{}'"),
+	("grade de tiros com tamanho diferente do tabuleiro", "shot grid size differs from the board"),
+	("falta a seção [{}]", "missing section [{}]"),
+	("dificuldade inválida", "invalid difficulty"),
+	("navios em posições inválidas", "ships in invalid positions"),
+	("mira fora do tabuleiro", "aim outside the board"),
+];
@@ -21,9 +21,14 @@
 	- Para endereçar o cursor é usado 'u16'
 
 	- Cada navios é armazenado como:
+		- Forma, as células relativas à popa (ver frota.rs)
 		- Posição x,y da popa
 		- Direção
-		- Tamanho
+		- Espelhado ou não
+	- Ilhas e recifes ficam no mapa dos oceanos (ILHA, RECIFE), valem para os dois
+	tabuleiros e são escolhidos com '--mapa <nome ou arquivo>', ver terreno.rs
+	- A frota padrão tem navios retos de tamanho 5, 4, 3 e 2, outra frota pode
+	ser lida com a opção '--frota <arquivo>', exemplo em assets/frota_poliominos.txt
 	- Para algumas operações é construído o mapa dos oceanos
 	- No mapa 'y' é linha, 'x' é coluna
 
@@ -48,59 +53,157 @@
 				Windows: always
 
 		Mas no Ubuntu não foi necessário mudar flags. Caso necessário seria:
-		stdout.execute( crossterm::event::PushKeyboardEnhancementFlags(crossterm::event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES));
+		con.execute( crossterm::event::PushKeyboardEnhancementFlags(crossterm::event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES));
 		... programa
-		stdout.execute(crossterm::event::PopKeyboardEnhancementFlags);
+		con.execute(crossterm::event::PopKeyboardEnhancementFlags);
 
 		Inclusive no Ubuntu e no Windows
 		println!("{:#?}", crossterm::terminal::supports_keyboard_enhancement());
 		retorna falso.
-*/
 
 
+	Arsenal especial (opção '--armas'):
+
+	- Bomba: atinge uma área 3x3 em torno da mira
+	- Torpedo: percorre a linha ou coluna a partir da mira e para no primeiro navio
+	- Radar: informa se existe algum navio em uma área 3x3, sem destruir nada
+	- Cada arma especial tem usos limitados, mostrados na linha de status
+
+
+	Relógio (opções '--tempo-jogada', '--tempo-total' e '--blitz'):
+
+	- Durante a vez do humano a entrada é lida com 'poll', sem bloquear, para atualizar o relógio
+	- Jogada esgotada: tiro automático em uma posição ainda não tentada
+	- Tempo total esgotado: o humano perde a partida
+	- A pausa para mostrar cada tiro é configurada com '--atraso <ms>'
+	- Antes da pausa cada tiro é animado, desligado com '--sem-animacao'; '--som' toca a campainha
+	do terminal nos acertos, ver animacao.rs
+
+
+	Dificuldade do bot com '--dificuldade', ver bot.rs
+	O bot adaptativo lembra das frotas e dos primeiros tiros do humano, o que aprendeu é mostrado
+	com '--memoria' e apagado com '--esquece-memoria', ver memoria.rs
+	Perfis dos jogadores e placar com '--jogador <nome>' e '--placar', ver perfil.rs
+	Teclas configuráveis com '--teclas <nome ou arquivo>' e ajuda com '?', ver teclas.rs
+	Cores e desenhos com '--tema <nome>' e '--glifos unicode|ascii', ver tema.rs
+	Mensagens em português ou inglês, pelo LANG ou com '--lang pt|en', ver idioma.rs
+	O jogo roda na tela alternativa, restaurada ao sair ou em pânico, ver tela.rs
+	Falhas do terminal viram 'ErroJogo' e são mostradas ao sair, ver erro.rs
+	Tudo o que é sorteado vem de uma semente, fixada com '--seed <n>' e mostrada no fim da
+	partida, assim uma partida pode ser repetida; 'r' sorteia as posições da frota do humano
+	A última partida contra o bot é gravada e vista de novo com '--reprise <arquivo>', ver reprise.rs
+	Desenho e teclado passam por um 'Console', nos testes uma grade na memória, ver console.rs
+	Partida contra um adversário no navegador da rede local com '--servidor <porta>', ver rede.rs
+	Servidor de salas entre navegadores, com fila e espectadores, com '--saguao <porta>', ver saguao.rs
+	Partidas de vários jogadores, todos contra todos ou em equipes, com '--jogadores <lista>'
+	e '--equipes <lista>', misturando humano, bots e navegadores, ver equipes.rs
+	Quebra-cabeça de Batalha Naval solitária (Bimaru) no menu principal, ver quebra_cabeca.rs
+	Tutorial passo a passo da arrumação e dos tiros no menu principal, ver tutorial.rs
+	Mapa de calor com a chance de navio em cada célula do inimigo e dicas limitadas com
+	'--dicas <n>', ver calor.rs
+	Ambiente de aprendizado por reforço sobre as regras da batalha e um exemplo de treino
+	com '--treina <partidas>', ver treino.rs
+	Variante com neblina, em que um navio inteiro pode andar ou girar no lugar do tiro e a
+	água já vista envelhece, com '--neblina', ver neblina.rs
+	Quanto cada acerto revela do navio com '--revelacao classica|afundado|total', também
+	para o bot com '--bot-revelacao', ver revelacao.rs
+	Batalha em tabuleiro hexagonal, com navios nos três eixos e seis direções, no menu
+	principal, ver hexagonal.rs
+	Desafio do dia no menu principal, a mesma frota do bot para todos a partir da data, com
+	placar local dos ataques; '--desafios' mostra o placar, '--exporta-desafios <arquivo>' e
+	'--importa-desafios <arquivo>' levam os resultados para outra máquina, ver desafio.rs
+
+
+	Menus (ver menu.rs):
+
+	- O jogo começa no menu principal, as opções da linha de comando são os valores iniciais
+	- O lado do tabuleiro vai de LADO_MINIMO a LADO_MAXIMO, um mapa com terreno define o próprio lado
+	- Esc pausa o jogo, na batalha a partida pode ser salva e carregada depois, ver salvamento.rs
+*/
 
+// Os nomes das variantes seguem o padrão do projeto: NORTE, SUL, HUMANO, BOT, ...
+#![allow(clippy::upper_case_acronyms)]
+
+
+// Primeiro, para a macro 'tr!' valer nos outros módulos
+#[macro_use]
+mod idioma;
+
+mod animacao;
+mod bot;
+mod calor;
+mod console;
+#[cfg(test)]
+mod console_virtual;
+mod desafio;
+mod equipes;
+mod erro;
+mod frota;
+mod hexagonal;
+mod memoria;
+mod menu;
+mod neblina;
+mod perfil;
+mod quebra_cabeca;
+mod rede;
+mod relogio;
+mod reprise;
+mod revelacao;
+mod saguao;
+mod salvamento;
+mod teclas;
+mod tela;
+mod tema;
+mod terreno;
+mod treino;
+mod tutorial;
+#[cfg(test)]
+mod testes;
 
-use std::io::{stdout,Error,Write};
 use std::time::Duration;
 
-use rand::Rng;
+use bot::{Bot,Dificuldade};
+use console::{Console,ConsoleReal};
+use erro::ErroJogo;
+use idioma::Idioma;
+use memoria::Memoria;
+use menu::Pausa;
+use neblina::{Neblina,Manobra};
+use perfil::Perfil;
+use rede::Remoto;
+use relogio::Relogio;
+use reprise::{Reprise,Jogada};
+use revelacao::Revelacao;
+use tela::Tela;
+use teclas::{Acao,Teclas};
+use tema::Tema;
+
+use rand::{Rng,SeedableRng};
+use rand::rngs::StdRng;
 
 
 use crossterm::{ExecutableCommand,QueueableCommand};
 use crossterm::terminal::{self,Clear};
 use crossterm::cursor;
-use crossterm::style::{self,Stylize,ResetColor,SetForegroundColor,SetBackgroundColor,StyledContent};
-use crossterm::style::Color::{self,*};
-use crossterm::event::{Event,KeyCode,KeyModifiers,KeyEventKind};
+use crossterm::style::{self,ResetColor,SetForegroundColor,SetBackgroundColor,StyledContent};
+use crossterm::style::Color;
+use crossterm::event::{Event,KeyEventKind};
 
 
-const LADO_TABULEIRO: usize = 10;
-const LARGURA_MINIMA: u16 = 1+2*LADO_TABULEIRO as u16+1+2*LADO_TABULEIRO as u16+1;
-const ALTURA_MINIMA: u16 = 1+LADO_TABULEIRO as u16+1+5;
-
-const MAX_TAM_NAVIO:usize = 5;
-const NUM_NAVIOS: usize = 4;
+// Lado do tabuleiro, escolhido na tela de opções
+const LADO_PADRAO: usize = 10;
+const LADO_MINIMO: usize = 6;
+const LADO_MAXIMO: usize = 16;
 
 const AGUA:usize = 999;
 const DESTRUIDO:usize = 888;
+const ILHA:usize = 777;
+const RECIFE:usize = 666;
 
-
-// https://en.wikipedia.org/wiki/List_of_Unicode_characters
-const _BLOCO: char = '\u{2588}';
-
-const BLOCO_DUPLO: &str = "\u{2588}\u{2588}";
-const EXPLOSAO_DUPLO: &str = "\u{0496}\u{0496}";	// Outras opções "\u{1F525}", "##"
-
-const CANTO_SUP_ESQ: char = '\u{250C}';
-const CANTO_SUP_MEIO: char = '\u{2565}';
-const CANTO_SUP_DIR: char = '\u{2510}';
-const CANTO_INF_ESQ: char = '\u{2514}';
-const CANTO_INF_MEIO: char = '\u{2568}';
-const CANTO_INF_DIR: char = '\u{2518}';
-
-const BARRA_HORIZONTAL: char = '\u{2500}';
-const BARRA_VERTICAL: char = '\u{2502}';
-const BARRA_VERTICAL_DUPLA: char = '\u{2551}';
+// Usos de cada arma especial no início da partida
+const USOS_BOMBA: u32 = 1;
+const USOS_TORPEDO: u32 = 2;
+const USOS_RADAR: u32 = 2;
 
 
 // Existe jogador humano e jogador bot
@@ -118,225 +221,321 @@ enum Direcao {
 	OESTE,
 }
 
+impl Direcao {
+	// Próxima direção no sentido horário
+	fn horaria(self) -> Direcao {
+		match self {
+			Direcao::NORTE => Direcao::LESTE,
+			Direcao::SUL => Direcao::OESTE,
+			Direcao::LESTE => Direcao::SUL,
+			Direcao::OESTE => Direcao::NORTE,
+		}
+	}
+
+	// Sorteia uma direção
+	fn aleatoria(rng: &mut impl Rng) -> Direcao {
+		match rng.gen_range(0..4) {
+			0 => Direcao::NORTE,
+			1 => Direcao::SUL,
+			2 => Direcao::LESTE,
+			_ => Direcao::OESTE,
+		}
+	}
+}
+
+// Mapa dos oceanos de um jogador, 'y' é linha, 'x' é coluna, sempre quadrado
+type Mapa = Vec<Vec<usize>>;
+
 // Descrição de um navio
-#[derive(Debug)]
+#[derive(Debug,Clone)]
 struct Navio {
+	nome: String,
+	forma: Vec<(i64,i64)>,		// Células relativas à popa, com o navio virado para LESTE
 	popa_x: usize,
 	popa_y: usize,
 	direcao: Direcao,
-	tamanho: usize,
-	desenho: StyledContent<&'static str>,		// String com o desenho deste navio
+	espelhado: bool,			// Forma refletida antes de girar
+	tamanho: usize,				// Número de células da forma
+	letra: char,
+	cor: Color,
 }
 
 impl Navio {
-	fn new(popa_y:usize,tamanho:usize ) -> Navio {
+	// A primeira célula da forma é a popa, em torno dela o navio gira
+	fn new(nome:&str, forma:Vec<(i64,i64)>, letra:char, cor:Color) -> Navio {
 		Navio{
-			popa_x: 1,
-			popa_y,
+			nome: String::from(nome),
+			tamanho: forma.len(),
+			forma,
+			popa_x: 0,
+			popa_y: 0,
 			direcao: Direcao::LESTE,
-			tamanho,
-			desenho: match tamanho {
-				MAX_TAM_NAVIO => "PP".on_dark_blue(),
-				4 => "CC".on_dark_magenta(),				
-				3 => "DD".on_dark_yellow(),
-				2 => "SS".on_dark_red(),
-				_ => panic!("Tamanho de Navio desconhecido"),
-			},
+			espelhado: false,
+			letra,
+			cor,
 		}
 	}
 
 	// Gira a direção do navio no sentido horário
 	fn gira(&mut self) {
-		match self.direcao {
-			Direcao::NORTE => self.direcao = Direcao::LESTE,
-			Direcao::SUL =>  self.direcao = Direcao::OESTE,
-			Direcao::LESTE =>  self.direcao = Direcao::SUL,
-			Direcao::OESTE =>  self.direcao = Direcao::NORTE,
-		};
+		self.direcao = self.direcao.horaria();
+	}
+
+	// Reflete a forma do navio
+	fn espelha(&mut self) {
+		self.espelhado = !self.espelhado;
+	}
+
+	// Células que o navio ocuparia com a popa em (x,y) e a orientação dada, podem estar fora do tabuleiro
+	fn celulas_em(&self, popa_x:i64, popa_y:i64, direcao:Direcao, espelhado:bool) -> Vec<(i64,i64)> {
+		self.forma.iter().map(|&(dx,dy)| {
+			let dy = if espelhado { -dy } else { dy };
+			let (dx,dy) = match direcao {
+				Direcao::LESTE => (dx,dy),
+				Direcao::SUL => (-dy,dx),
+				Direcao::OESTE => (-dx,-dy),
+				Direcao::NORTE => (dy,-dx),
+			};
+			(popa_x+dx, popa_y+dy)
+		}).collect()
+	}
+
+	// Células ocupadas pelo navio na posição atual, que sempre é válida
+	fn celulas(&self) -> Vec<(usize,usize)> {
+		self.celulas_em(self.popa_x as i64, self.popa_y as i64, self.direcao, self.espelhado)
+			.iter()
+			.map(|&(x,y)| (x as usize, y as usize))
+			.collect()
+	}
+
+}
+
+
+
+// Armas que podem ser usadas em um ataque
+#[derive(Debug,Copy,Clone,PartialEq)]
+enum Arma {
+	TIRO,		// Atinge uma única célula
+	BOMBA,		// Atinge uma área 3x3
+	TORPEDO,	// Percorre linha ou coluna até o primeiro navio
+	RADAR,		// Detecta navios em uma área 3x3, sem destruir
+}
+
+impl Arma {
+	fn nome(&self) -> &'static str {
+		match self {
+			Arma::TIRO => "Tiro",
+			Arma::BOMBA => "Bomba",
+			Arma::TORPEDO => "Torpedo",
+			Arma::RADAR => "Radar",
+		}
+	}
+}
+
+
+// Usos restantes das armas especiais de um jogador
+#[derive(Debug)]
+struct Arsenal {
+	bombas: u32,
+	torpedos: u32,
+	radares: u32,
+}
+
+impl Arsenal {
+	// Sem o arsenal especial somente o tiro simples está disponível
+	fn new(armas_especiais: bool) -> Arsenal {
+		if armas_especiais {
+			Arsenal{ bombas: USOS_BOMBA, torpedos: USOS_TORPEDO, radares: USOS_RADAR }
+		} else {
+			Arsenal{ bombas: 0, torpedos: 0, radares: 0 }
+		}
+	}
+
+	// Quantos usos restam da arma, o tiro simples é ilimitado
+	fn usos(&self, arma: Arma) -> u32 {
+		match arma {
+			Arma::TIRO => u32::MAX,
+			Arma::BOMBA => self.bombas,
+			Arma::TORPEDO => self.torpedos,
+			Arma::RADAR => self.radares,
+		}
 	}
 
-	// Retorna os extremos do navio como tupla (N,S,L,O)
-	fn _extremos(&self) -> (usize,usize,usize,usize) {
-		let tam = self.tamanho;
-		match self.direcao {
-			Direcao::NORTE => (self.popa_y-tam+1,self.popa_y,self.popa_x,self.popa_x),
-			Direcao::SUL => (self.popa_y,self.popa_y+tam-1,self.popa_x,self.popa_x),
-			Direcao::LESTE => (self.popa_y,self.popa_y,self.popa_x+tam-1,self.popa_x),
-			Direcao::OESTE => (self.popa_y,self.popa_y,self.popa_x,self.popa_x-tam+1),
+	// Gasta um uso da arma
+	fn gasta(&mut self, arma: Arma) {
+		match arma {
+			Arma::TIRO => {}
+			Arma::BOMBA => self.bombas -= 1,
+			Arma::TORPEDO => self.torpedos -= 1,
+			Arma::RADAR => self.radares -= 1,
 		}
 	}
 
+	// Texto para a linha de status
+	fn descricao(&self, arma: Arma, direcao_torpedo: Direcao, tema: &Tema) -> String {
+		let seta = match direcao_torpedo {
+			Direcao::NORTE => tema.glifos.setas[0],
+			Direcao::SUL => tema.glifos.setas[1],
+			Direcao::LESTE => tema.glifos.setas[3],
+			Direcao::OESTE => tema.glifos.setas[2],
+		};
+		let mut texto = tr!("Arma: {}", tr!(arma.nome()));
+		if arma == Arma::TORPEDO {
+			texto.push_str(&format!(" {}", seta));
+		}
+		texto.push_str(&format!("   B:{} T:{} R:{}", self.bombas, self.torpedos, self.radares));
+		texto
+	}
 }
 
 
 
 // Estrutura com as informações do jogo
 struct Jogo {
-	navios_humano: [Navio;NUM_NAVIOS],
-	navios_bot: [Navio;NUM_NAVIOS],
+	terreno: Mapa,			// Ilhas e recifes, comuns aos dois tabuleiros
+	navios_humano: Vec<Navio>,
+	navios_bot: Vec<Navio>,
 }
 
 impl Jogo {
-	fn new() -> Jogo {
-		Jogo {
-			navios_humano: [
-				Navio::new(2,MAX_TAM_NAVIO),
-				Navio::new(4,MAX_TAM_NAVIO-1),
-				Navio::new(6,MAX_TAM_NAVIO-2), 
-				Navio::new(8,MAX_TAM_NAVIO-3), 
-			],
-
-			navios_bot: [
-				Navio::new(2,MAX_TAM_NAVIO), 
-				Navio::new(4,MAX_TAM_NAVIO-1), 
-				Navio::new(6,MAX_TAM_NAVIO-2), 
-				Navio::new(8,MAX_TAM_NAVIO-3), 
-			],
+	// Os dois jogadores recebem a mesma frota, nas mesmas posições iniciais sem sobreposição
+	fn new(frota:&[Navio], terreno:Mapa) -> Result<Jogo, String> {
+		let mut jogo = Jogo {
+			terreno,
+			navios_humano: Vec::new(),
+			navios_bot: Vec::new(),
+		};
+		// Deixa espaço para o bot sortear posições sem ficar preso
+		let livres = jogo.terreno.iter().flatten().filter(|&&c| c == AGUA).count();
+		let total: usize = frota.iter().map(|n| n.tamanho).sum();
+		if total > livres/2 {
+			return Err(tr!("A frota ocupa {} células, máximo de {} neste tabuleiro", total, livres/2));
 		}
-	}
-
-	// Cria mapa com navios humano, no mapa 'y' é linha, 'x' é coluna
-	fn mapeia_humano(&self, excecao: Option<usize>) -> [[usize;LADO_TABULEIRO];LADO_TABULEIRO] {
-		let mut mapa = [[AGUA;LADO_TABULEIRO];LADO_TABULEIRO];
-		for n in 0..NUM_NAVIOS {
-			if excecao==None || excecao!=Some(n) {
-				for i in 0 .. self.navios_humano[n].tamanho {
-					let mut x = self.navios_humano[n].popa_x;
-					let mut y = self.navios_humano[n].popa_y;
-					match self.navios_humano[n].direcao {
-						Direcao::NORTE => y -= i,
-						Direcao::SUL => y += i,
-						Direcao::LESTE => x += i,
-						Direcao::OESTE => x -= i,
-					}
-					mapa[y][x] = self.navios_humano[n].tamanho;
-				}
+		// Cada navio é posicionado considerando apenas os anteriores
+		for (n,navio) in frota.iter().enumerate() {
+			jogo.navios_humano.push(navio.clone());
+			if !jogo.posiciona_inicial(n) {
+				return Err(tr!("Não há espaço no tabuleiro para o navio '{}'", navio.nome));
 			}
 		}
-		mapa
+		jogo.navios_bot = jogo.navios_humano.clone();
+		Ok(jogo)
 	}
 
+	// Lado do tabuleiro, o mesmo do terreno
+	fn lado(&self) -> usize {
+		self.terreno.len()
+	}
 
-	// Cria mapa com navios bot, no mapa 'y' é linha, 'x' é coluna
-	fn mapeia_bot(&self, excecao: Option<usize>) -> [[usize;LADO_TABULEIRO];LADO_TABULEIRO] {
-		let mut mapa = [[AGUA;LADO_TABULEIRO];LADO_TABULEIRO];
-		for n in 0..NUM_NAVIOS {
-			if excecao==None || excecao!=Some(n) {
-				for i in 0 .. self.navios_bot[n].tamanho {
-					let mut x = self.navios_bot[n].popa_x;
-					let mut y = self.navios_bot[n].popa_y;
-					match self.navios_bot[n].direcao {
-						Direcao::NORTE => y -= i,
-						Direcao::SUL => y += i,
-						Direcao::LESTE => x += i,
-						Direcao::OESTE => x -= i,
+	// Coloca o navio humano na primeira posição livre, de preferência sem encostar em outro navio
+	fn posiciona_inicial(&mut self, qual:usize) -> bool {
+		for afastado in [true,false] {
+			for x in 1 .. self.lado() {
+				for y in 1 .. self.lado() {
+					self.navios_humano[qual].popa_x = x;
+					self.navios_humano[qual].popa_y = y;
+					if self.pode_colocar_navio(&Jogador::HUMANO,qual)
+						&& (!afastado || !self.encosta_em_navio(qual)) {
+						return true;
 					}
-					mapa[y][x] = self.navios_bot[n].tamanho;
 				}
 			}
 		}
-		mapa
+		false
 	}
 
+	// Algum outro navio humano é vizinho, inclusive na diagonal ?
+	fn encosta_em_navio(&self, qual:usize) -> bool {
+		let outros: Vec<(usize,usize)> = self.navios_humano.iter().enumerate()
+			.filter(|&(n,_)| n != qual)
+			.flat_map(|(_,navio)| navio.celulas())
+			.collect();
+		self.navios_humano[qual].celulas().iter().any(|&(x,y)| {
+			outros.iter().any(|&(ox,oy)| x.abs_diff(ox) <= 1 && y.abs_diff(oy) <= 1)
+		})
+	}
 
-	// Testa se pode colocar um navio, considera extremos do tabuleiro e outros navios
-	fn pode_colocar_navio(&self,jogador:&Jogador,qual:usize) -> bool {
-		let navio;
-		let mapa;
+	// Navios de um jogador, para mudar
+	fn navios_mut(&mut self, jogador:&Jogador) -> &mut Vec<Navio> {
 		match jogador {
-			Jogador::HUMANO => {
-				navio = &self.navios_humano[qual];
-				mapa = self.mapeia_humano(Some(qual));
-			}
-			Jogador::BOT => {
-				navio = &self.navios_bot[qual];
-				mapa = self.mapeia_bot(Some(qual));
-			}
+			Jogador::HUMANO => &mut self.navios_humano,
+			Jogador::BOT => &mut self.navios_bot,
 		}
+	}
 
-		// Tenta marcar navio no mapa e observar conflitos
-		for i in 0 .. navio.tamanho {
-			let mut x = navio.popa_x as i64;
-			let mut y = navio.popa_y as i64;
-			match navio.direcao {
-				Direcao::NORTE => y -= i as i64,
-				Direcao::SUL => y += i as i64,
-				Direcao::LESTE => x += i as i64,
-				Direcao::OESTE => x -= i as i64,
-			}
-			// Testa limites do tabuleiro
-			if y < 0 || y >= LADO_TABULEIRO as i64 || x < 0 || x >= LADO_TABULEIRO as i64 {
-				return false;
-			}
-			// Testa outros navios
-			if mapa[y as usize][x as usize] != AGUA {
-				return false;
+	// Navios de um jogador
+	fn navios(&self, jogador:&Jogador) -> &Vec<Navio> {
+		match jogador {
+			Jogador::HUMANO => &self.navios_humano,
+			Jogador::BOT => &self.navios_bot,
+		}
+	}
+
+	// Cria mapa com o terreno e os navios, cada célula com navio guarda o índice dele
+	fn mapeia(&self, navios:&[Navio], excecao: Option<usize>) -> Mapa {
+		let mut mapa = self.terreno.clone();
+		for (n,navio) in navios.iter().enumerate() {
+			if excecao != Some(n) {
+				for (x,y) in navio.celulas() {
+					mapa[y][x] = n;
+				}
 			}
 		}
-		true
+		mapa
+	}
+
+	// Cria mapa com navios humano, no mapa 'y' é linha, 'x' é coluna
+	fn mapeia_humano(&self, excecao: Option<usize>) -> Mapa {
+		self.mapeia(&self.navios_humano, excecao)
 	}
 
+	// Cria mapa com navios bot, no mapa 'y' é linha, 'x' é coluna
+	fn mapeia_bot(&self, excecao: Option<usize>) -> Mapa {
+		self.mapeia(&self.navios_bot, excecao)
+	}
+
+
+	// Testa se o navio pode ocupar as células, considera extremos do tabuleiro, terreno e outros navios
+	fn cabe_navio(&self,jogador:&Jogador,qual:usize,celulas:&[(i64,i64)]) -> bool {
+		let mapa = self.mapeia(self.navios(jogador), Some(qual));
+		let lado = self.lado() as i64;
+		celulas.iter().all(|&(x,y)| {
+			// Testa limites do tabuleiro, ilhas, recifes e outros navios
+			y >= 0 && y < lado && x >= 0 && x < lado
+				&& mapa[y as usize][x as usize] == AGUA
+		})
+	}
 
-	// Testa se pode girar um navio, considera extremos do tabuleiro e outros navios
+
+	// Testa se pode colocar um navio onde ele está
+	fn pode_colocar_navio(&self,jogador:&Jogador,qual:usize) -> bool {
+		let navio = &self.navios(jogador)[qual];
+		let celulas = navio.celulas_em(navio.popa_x as i64, navio.popa_y as i64, navio.direcao, navio.espelhado);
+		self.cabe_navio(jogador, qual, &celulas)
+	}
+
+
+	// Testa se pode girar um navio
 	fn pode_girar_navio(&self,jogador:&Jogador,qual:usize) -> bool {
-		let navio;
-		let mapa;
-		match jogador {
-			Jogador::HUMANO => {
-				navio = &self.navios_humano[qual];
-				mapa = self.mapeia_humano(Some(qual));
-			}
-			Jogador::BOT => {
-				navio = &self.navios_bot[qual];
-				mapa = self.mapeia_bot(Some(qual));
-			}
-		}
+		let navio = &self.navios(jogador)[qual];
+		let celulas = navio.celulas_em(navio.popa_x as i64, navio.popa_y as i64,
+													navio.direcao.horaria(), navio.espelhado);
+		self.cabe_navio(jogador, qual, &celulas)
+	}
 
-		// Simula giro
-		let nova_direcao = match &navio.direcao {
-			Direcao::NORTE => Direcao::LESTE,
-			Direcao::SUL => Direcao::OESTE,
-			Direcao::LESTE => Direcao::SUL,
-			Direcao::OESTE => Direcao::NORTE,
-		};
 
-		// Tenta marcar navio no mapa e observar conflitos
-		for i in 0 .. navio.tamanho {
-			let mut x = navio.popa_x as i64;
-			let mut y = navio.popa_y as i64;
-			match nova_direcao {
-				Direcao::NORTE => y -= i as i64,
-				Direcao::SUL => y += i as i64,
-				Direcao::LESTE => x += i as i64,
-				Direcao::OESTE => x -= i as i64,
-			}
-			// Testa limites do tabuleiro
-			if y < 0 || y >= LADO_TABULEIRO as i64 || x < 0 || x >= LADO_TABULEIRO as i64 {
-				return false;
-			}
-			// Testa outros navios
-			if mapa[y as usize][x as usize] != AGUA {
-				return false;
-			}
-		}
-		true
+	// Testa se pode espelhar um navio
+	fn pode_espelhar_navio(&self,jogador:&Jogador,qual:usize) -> bool {
+		let navio = &self.navios(jogador)[qual];
+		let celulas = navio.celulas_em(navio.popa_x as i64, navio.popa_y as i64,
+													navio.direcao, !navio.espelhado);
+		self.cabe_navio(jogador, qual, &celulas)
 	}
 
 
-	// Testa se pode mover um navio, considera extremos do tabuleiro e outros navios
+	// Testa se pode mover um navio
 	fn pode_mover_navio(&self,jogador:&Jogador,qual:usize,move_direcao:Direcao) -> bool {
-		let navio;
-		let mapa;
-		match jogador {
-			Jogador::HUMANO => {
-				navio = &self.navios_humano[qual];
-				mapa = self.mapeia_humano(Some(qual));
-			}
-			Jogador::BOT => {
-				navio = &self.navios_bot[qual];
-				mapa = self.mapeia_bot(Some(qual));
-			}
-		}
+		let navio = &self.navios(jogador)[qual];
 		// Simula movimento
 		let mut novo_popa_x = navio.popa_x as i64;
 		let mut novo_popa_y = navio.popa_y as i64;
@@ -346,26 +545,8 @@ impl Jogo {
 			Direcao::LESTE => novo_popa_x += 1,
 			Direcao::OESTE => novo_popa_x -= 1,
 		}
-		// Tenta marcar navio no mapa e observar conflitos
-		for i in 0 .. navio.tamanho {
-			let mut x = novo_popa_x as i64;
-			let mut y = novo_popa_y as i64;
-			match navio.direcao {
-				Direcao::NORTE => y -= i as i64,
-				Direcao::SUL => y += i as i64,
-				Direcao::LESTE => x += i as i64,
-				Direcao::OESTE => x -= i as i64,
-			}
-			// Testa limites do tabuleiro
-			if y < 0 || y >= LADO_TABULEIRO as i64 || x < 0 || x >= LADO_TABULEIRO as i64 {
-				return false;
-			}
-			// Testa outros navios
-			if mapa[y as usize][x as usize] != AGUA {
-				return false;
-			}
-		}
-		true
+		let celulas = navio.celulas_em(novo_popa_x, novo_popa_y, navio.direcao, navio.espelhado);
+		self.cabe_navio(jogador, qual, &celulas)
 	}
 
 
@@ -374,96 +555,122 @@ impl Jogo {
 
 
 // Limpa toda a tela, posiciona cursor no topo à esquerda
-fn limpa_tela() -> Result<bool, Error> {
-	let mut stdout = stdout();
-	stdout
+fn limpa_tela(con:&mut dyn Console) -> Result<(), ErroJogo> {
+	con
 		.queue(cursor::MoveTo(0,0))?
 		.queue(terminal::Clear(terminal::ClearType::All))?;
-	stdout.flush()?;
-	Ok(true)
+	con.flush()?;
+	Ok(())
 }
 
 
-// Terminal tem o tamanho mínimo necessário ?
-fn tem_tamanho_minimo() -> Result<bool, Error> {
-	let (largura,altura) = terminal::size().expect("Falha da biblioteca crossterm");
-	Ok(largura >= LARGURA_MINIMA  &&  altura >= ALTURA_MINIMA)
+// Confere se o terminal tem o tamanho mínimo necessário para o tabuleiro com o lado dado
+fn confere_tamanho(con:&mut dyn Console, lado:usize) -> Result<(), ErroJogo> {
+	let lado = lado as u16;
+	let largura_minima = 1+2*lado+1+2*lado+1;
+	let altura_minima = 1+lado+1+6;
+	let (largura,altura) = con.tamanho()?;
+	if largura < largura_minima  ||  altura < altura_minima {
+		return Err(ErroJogo::TAMANHO(largura_minima, altura_minima));
+	}
+	Ok(())
 }
 
 
-// Desenha moldura com 2 tabuleiros de '10 linhas e 20 colunas' cada um
-fn desenha_moldura(x_esq:u16, y_sup:u16, cor_frente:Color, cor_fundo:Color) -> Result<bool, Error> {
-	let x_meio = x_esq+1+2*10;
-	let x_dir = x_esq+1+2*10+1+2*10;
-	let y_inf = y_sup+10+1;
+// Desenha moldura com 2 tabuleiros de 'lado linhas e 2*lado colunas' cada um, com ilhas e recifes
+fn desenha_moldura(con:&mut dyn Console, x_esq:u16, y_sup:u16, tema:&Tema, terreno:&Mapa) -> Result<(), ErroJogo> {
+	let lado = terreno.len();
+	let largura = 2*lado;
+	let x_meio = x_esq+1+largura as u16;
+	let x_dir = x_esq+1+largura as u16+1+largura as u16;
+	let y_inf = y_sup+lado as u16+1;
 
-	limpa_tela()?;
+	limpa_tela(con)?;
 
 	// Saídas acontecem através dessa variável 
-	let mut stdout = stdout();
 
 	// Muda cor
-	stdout
+	let (cor_frente,cor_fundo) = tema.moldura();
+	let glifos = tema.glifos;
+	con
 		.queue( SetForegroundColor(cor_frente) )?
 		.queue( SetBackgroundColor(cor_fundo) )?;
 
 	for x in x_esq .. x_dir {
 		for y in y_sup .. y_inf {
-			stdout
+			con
 			.queue( cursor::MoveTo(x,y) )?
 			.queue( style::Print(" ") )?;
 		}
 	}
 
 	// Barra horizontal superior
-	stdout
+	con
 		.queue( cursor::MoveTo(x_esq,y_sup) )?
-		.queue( style::Print(CANTO_SUP_ESQ) )?
-		.queue( style::Print(String::from(BARRA_HORIZONTAL).repeat(20)) )?
-		.queue( style::Print(CANTO_SUP_MEIO) )?
-		.queue( style::Print(String::from(BARRA_HORIZONTAL).repeat(20)) )?
-		.queue( style::Print(CANTO_SUP_DIR) )?;
+		.queue( style::Print(glifos.cantos_sup[0]) )?
+		.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+		.queue( style::Print(glifos.cantos_sup[1]) )?
+		.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+		.queue( style::Print(glifos.cantos_sup[2]) )?;
 
 	// Barra horizontal inferior
-	stdout
+	con
 		.queue( cursor::MoveTo(x_esq,y_inf) )?
-		.queue( style::Print(CANTO_INF_ESQ) )?
-		.queue( style::Print(String::from(BARRA_HORIZONTAL).repeat(20)) )?
-		.queue( style::Print(CANTO_INF_MEIO) )?
-		.queue( style::Print(String::from(BARRA_HORIZONTAL).repeat(20)) )?
-		.queue( style::Print(CANTO_INF_DIR) )?;
+		.queue( style::Print(glifos.cantos_inf[0]) )?
+		.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+		.queue( style::Print(glifos.cantos_inf[1]) )?
+		.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+		.queue( style::Print(glifos.cantos_inf[2]) )?;
 
 	// Barras verticais
-	for y in y_sup+1..=y_sup+1+9 {
-		stdout
+	for y in y_sup+1 .. y_inf {
+		con
 			.queue( cursor::MoveTo(x_esq,y) )?
-			.queue( style::Print(BARRA_VERTICAL) )?;
+			.queue( style::Print(glifos.vertical) )?;
 	}
-	for y in y_sup+1..=y_sup+1+9 {
-		stdout
+	for y in y_sup+1 .. y_inf {
+		con
 			.queue( cursor::MoveTo(x_meio,y) )?
-			.queue( style::Print(BARRA_VERTICAL_DUPLA) )?;
+			.queue( style::Print(glifos.vertical_dupla) )?;
 	}
-	for y in y_sup+1..=y_sup+1+9 {
-		stdout
+	for y in y_sup+1 .. y_inf {
+		con
 			.queue( cursor::MoveTo(x_dir,y) )?
-			.queue( style::Print(BARRA_VERTICAL) )?;
+			.queue( style::Print(glifos.vertical) )?;
+	}
+
+	// Terreno aparece igual nos dois tabuleiros
+	for (y,linha) in terreno.iter().enumerate() {
+		for (x,&celula) in linha.iter().enumerate() {
+			let desenho = match celula {
+				ILHA => tema.ilha(),
+				RECIFE => tema.recife(),
+				_ => continue,
+			};
+			con
+				.queue( cursor::MoveTo(x_esq+x_para_tela_humano(x),y_sup+y_para_tela_humano(y)) )?
+				.queue( style::PrintStyledContent(desenho) )?
+				.queue( cursor::MoveTo(x_esq+x_para_tela_bot(x,lado),y_sup+y_para_tela_bot(y)) )?
+				.queue( style::PrintStyledContent(desenho) )?;
+		}
 	}
 
 	// Volta cor padrão
-		stdout
+		con
 			.queue( ResetColor )?
 			.queue( cursor::MoveTo(0,y_inf+1) )?;
 
 	// Executa o lote de comandos
-	stdout.flush()?;
+	con.flush()?;
 
-	println!("+11223344556677889900+11223344556677889900+");
-	println!("      Meus Navios           Inimigo          ");
+	// Régua com o último dígito de cada coluna
+	let regua: String = (1..=lado).map(|x| format!("{0}{0}", x%10)).collect();
+	writeln!(con, "+{}+{}+", regua, regua)?;
+	writeln!(con, " {:^largura$} {:^largura$}", tr!("Meus Navios"), tr!("Inimigo"))?;
 	// Define linha das mensagens
-	stdout.execute(cursor::SavePosition)?;
+	con.execute(cursor::SavePosition)?;
 
-	Ok(true)
+	Ok(())
 }
 
 
@@ -479,8 +686,8 @@ fn y_para_tela_humano(y:usize) -> u16 {
 }
 
 // Converte posição x do tabuleiro para posição na tela bot (são 2 caracteres)
-fn x_para_tela_bot(x:usize) -> u16 {
-	x as u16*2+1 + 1+20
+fn x_para_tela_bot(x:usize, lado:usize) -> u16 {
+	x as u16*2+1 + 1+2*lado as u16
 }
 
 // Converte posição y do tabuleiro para posição na tela bot
@@ -491,179 +698,198 @@ fn y_para_tela_bot(y:usize) -> u16 {
 
 
 // Desdesenha um navio com anotações especiais
-fn desdesenha_navio(navio: &Navio) -> Result<bool, Error> {
-	let mut stdout = stdout();
-	let desenho = BLOCO_DUPLO.cyan();
+fn desdesenha_navio(con:&mut dyn Console, navio: &Navio, tema: &Tema) -> Result<(), ErroJogo> {
+	let desenho = tema.mar();
 
 	// Desdesenha cada pedaço do navio
-	for i in 0 .. navio.tamanho {
-		let mut x = navio.popa_x;
-		let mut y = navio.popa_y;
-		match navio.direcao {
-			Direcao::NORTE => y -= i,
-			Direcao::SUL => y += i,
-			Direcao::LESTE => x += i,
-			Direcao::OESTE => x -= i,
-		}
-		stdout
+	for (x,y) in navio.celulas() {
+		con
 			.queue( cursor::MoveTo(x_para_tela_humano(x),y_para_tela_humano(y)) )?
 			.queue( style::PrintStyledContent(desenho) )?;
 	}
 
-	stdout.flush()?;
-	Ok(true)
+	con.flush()?;
+	Ok(())
 }
 
 
 // Desenha um navio com anotações especiais
-fn desenha_navio(navio: &Navio, cursor:bool) -> Result<bool, Error> {
-	let mut stdout = stdout();
+fn desenha_navio(con:&mut dyn Console, navio: &Navio, tema: &Tema, cursor:bool) -> Result<(), ErroJogo> {
 
 	// Desenha cada pedaço do navio
-	for i in 0 .. navio.tamanho {
-		let mut x = navio.popa_x;
-		let mut y = navio.popa_y;
-		match navio.direcao {
-			Direcao::NORTE => y -= i,
-			Direcao::SUL => y += i,
-			Direcao::LESTE => x += i,
-			Direcao::OESTE => x -= i,
-		}
-		stdout
+	for (x,y) in navio.celulas() {
+		con
 			.queue( cursor::MoveTo(x_para_tela_humano(x),y_para_tela_humano(y)) )?
-			.queue( style::PrintStyledContent(navio.desenho) )?;
+			.queue( style::PrintStyledContent(tema.navio(navio)) )?;
 	}
 
 	if cursor {
-		stdout.queue(cursor::MoveTo(x_para_tela_humano(navio.popa_x),
+		con.queue(cursor::MoveTo(x_para_tela_humano(navio.popa_x),
 											y_para_tela_humano(navio.popa_y)) )?;
 	} else {
-		stdout.queue(cursor::RestorePosition)?;
+		con.queue(cursor::RestorePosition)?;
 	}
 
-	stdout.flush()?;
-	Ok(true)
+	con.flush()?;
+	Ok(())
 }
 
 
 
-// Arruma os navios do bot de forma aleatória
-fn arruma_navios_bot(jogo: &mut Jogo) {
-	for n in 0 .. NUM_NAVIOS {
+// Arruma os navios do jogador em posições sorteadas
+fn sorteia_navios(jogo: &mut Jogo, jogador: &Jogador, rng: &mut impl Rng) {
+	for n in 0 .. jogo.navios(jogador).len() {
 		let mut colocado = false;
 		while !colocado {
-			let nd = rand::thread_rng().gen_range(0..4);
-			let nova_direcao = match nd {
-				0 => Direcao::NORTE,
-				1 => Direcao::SUL,
-				2 => Direcao::LESTE,
-				3 => Direcao::OESTE,
-				_ => Direcao::NORTE,
-			};
+			let nova_direcao = Direcao::aleatoria(rng);
+			let novo_espelhado = rng.gen_bool(0.5);
 
-			let novo_popa_x = rand::thread_rng().gen_range(0..LADO_TABULEIRO);
-			let novo_popa_y = rand::thread_rng().gen_range(0..LADO_TABULEIRO);
+			let novo_popa_x = rng.gen_range(0..jogo.lado());
+			let novo_popa_y = rng.gen_range(0..jogo.lado());
 
 			// Tenta
-			let velha_direcao = jogo.navios_bot[n].direcao;
-			let velho_popa_x = jogo.navios_bot[n].popa_x;
-			let velho_popa_y = jogo.navios_bot[n].popa_y;
-			jogo.navios_bot[n].direcao = nova_direcao;
-			jogo.navios_bot[n].popa_x = novo_popa_x;
-			jogo.navios_bot[n].popa_y = novo_popa_y;
-
-			if jogo.pode_colocar_navio(&Jogador::BOT,n) {
+			let velho = jogo.navios(jogador)[n].clone();
+			let navio = &mut jogo.navios_mut(jogador)[n];
+			navio.direcao = nova_direcao;
+			navio.espelhado = novo_espelhado;
+			navio.popa_x = novo_popa_x;
+			navio.popa_y = novo_popa_y;
+
+			if jogo.pode_colocar_navio(jogador,n) {
 				colocado = true;
-			} else {		
+			} else {
 				// Desfaz
-				jogo.navios_bot[n].direcao = velha_direcao;
-				jogo.navios_bot[n].popa_x = velho_popa_x;
-				jogo.navios_bot[n].popa_y = velho_popa_y;
+				jogo.navios_mut(jogador)[n] = velho;
 			}
 		}
 	}
 }
 
 
+// Gerador de números aleatórios de um fluxo da partida, sempre o mesmo para a mesma semente
+fn gerador(semente: u64, fluxo: u64) -> StdRng {
+	StdRng::seed_from_u64(semente ^ fluxo.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+// Fluxos derivados da semente: a frota de cada jogador e cada rodada da batalha (a partir de FLUXO_RODADAS)
+const FLUXO_FROTA_BOT: u64 = 0;
+const FLUXO_FROTA_HUMANO: u64 = 1;
+const FLUXO_RODADAS: u64 = 2;
+
 
-// Deixa humano arrumar a posição dos seus navios
-fn arruma_navios_humano(jogo: &mut Jogo) -> Result<bool, Error> {
-	println!("Mova com as teclas {}{}{}{}, 'g' p/girar, 'm' p/mudar, 'i' p/iniciar batalha",
-											 '\u{2190}', '\u{2191}', '\u{2192}', '\u{2193}');
 
-	let mut corrente = 0;
-	stdout().execute(cursor::MoveTo(x_para_tela_humano(jogo.navios_humano[corrente].popa_x),
+// Desenha a tela onde o humano arruma os seus navios, cursor no navio corrente
+fn desenha_arrumacao(con:&mut dyn Console, jogo: &Jogo, opcoes: &Opcoes, corrente: usize) -> Result<(), ErroJogo> {
+	desenha_moldura(con, 0,0,&opcoes.tema,&jogo.terreno)?;
+	for navio in jogo.navios_humano.iter() {
+		desenha_navio(con, navio,&opcoes.tema,false)?;
+	}
+
+	let teclas = &opcoes.teclas;
+	writeln!(con, "{}", tr!("Mova com {}, {} gira, {} espelha, {} muda, {} inicia, {} pausa, {} ajuda",
+		teclas.movimento(), teclas.tecla(Acao::GIRA), teclas.tecla(Acao::ESPELHA), teclas.tecla(Acao::MUDA),
+		teclas.tecla(Acao::INICIA), teclas.tecla(Acao::PAUSA), teclas.tecla(Acao::AJUDA)))?;
+	escreve_status(con, 1, &tr!("Navio: {}", tr!(jogo.navios_humano[corrente].nome.as_str())))?;
+	con.execute(cursor::MoveTo(x_para_tela_humano(jogo.navios_humano[corrente].popa_x),
 											y_para_tela_humano(jogo.navios_humano[corrente].popa_y) ) )?;
+	Ok(())
+}
 
-	terminal::enable_raw_mode()?;
+
+// Deixa humano arrumar a posição dos seus navios
+fn arruma_navios_humano(con:&mut dyn Console, jogo: &mut Jogo, opcoes: &Opcoes, rng: &mut impl Rng) -> Result<Desfecho, ErroJogo> {
+	let mut corrente = 0;
+	desenha_arrumacao(con, jogo, opcoes, corrente)?;
+
+	con.modo_bruto(true)?;
 
 	loop {
-		let evento = crossterm::event::read()?;
+		let evento = con.le_evento()?;
 		match evento {
 //			Event::Key(key_event) => {
 			Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
 //				match (key_event.code, key_event.modifiers) {
 	
-				match (key_event.code,key_event.modifiers) {
-					(KeyCode::Char(_x),m) if m == KeyModifiers::CONTROL => {
-						terminal::disable_raw_mode()?;
-						return Ok(false);
-					}
+				if teclas::aborta(&key_event) {
+					con.modo_bruto(false)?;
+					return Ok(Desfecho::ABORTADO);
+				}
 
-					(KeyCode::Char(x), _) => {
-						match x {
-							'g'|'G' => {
-								if jogo.pode_girar_navio(&Jogador::HUMANO,corrente) {
-									desdesenha_navio(&jogo.navios_humano[corrente])?;
-									jogo.navios_humano[corrente].gira();
-									desenha_navio(&jogo.navios_humano[corrente], true)?;
-								}
-							}
-							'm'|'M' => {
-								corrente = (corrente+1) % NUM_NAVIOS;
-								stdout()
-									.execute(
-									cursor::MoveTo(x_para_tela_humano(jogo.navios_humano[corrente].popa_x),
-														  y_para_tela_humano(jogo.navios_humano[corrente].popa_y) ))?;
-							}
-							'i'|'I' => {
-								terminal::disable_raw_mode()?;
-								break;	
-							}
-							_ => {}
+				match opcoes.teclas.acao(&key_event) {
+					Some(Acao::PAUSA) => {
+						con.modo_bruto(false)?;
+						match menu::pausa(con, false, &opcoes.teclas)? {
+							Pausa::CONTINUAR | Pausa::SALVAR => {}
+							Pausa::REINICIAR => return Ok(Desfecho::REINICIA),
+							Pausa::SAIR => return Ok(Desfecho::MENU),
+							Pausa::ABORTAR => return Ok(Desfecho::ABORTADO),
 						}
+						desenha_arrumacao(con, jogo, opcoes, corrente)?;
+						con.modo_bruto(true)?;
 					}
 
-					(KeyCode::Up, _) => {
-						if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::NORTE) {
-							desdesenha_navio(&jogo.navios_humano[corrente])?;
-							jogo.navios_humano[corrente].popa_y -= 1;
-							desenha_navio(&jogo.navios_humano[corrente], true)?;
-						}
+					Some(Acao::AJUDA) => {
+						menu::mostra_ajuda(con, &opcoes.teclas.ajuda(&Acao::ARRUMACAO))?;
+						con.modo_bruto(false)?;
+						desenha_arrumacao(con, jogo, opcoes, corrente)?;
+						con.modo_bruto(true)?;
 					}
 
-					(KeyCode::Down, _) => {
-						if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::SUL) {
-							desdesenha_navio(&jogo.navios_humano[corrente])?;
-							jogo.navios_humano[corrente].popa_y += 1;
-							desenha_navio(&jogo.navios_humano[corrente], true)?;
-						}
+					Some(Acao::SORTEIA) => {
+						sorteia_navios(jogo, &Jogador::HUMANO, rng);
+						con.modo_bruto(false)?;
+						desenha_arrumacao(con, jogo, opcoes, corrente)?;
+						con.modo_bruto(true)?;
 					}
 
-					(KeyCode::Right, _) => {
-						if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::LESTE) {
-							desdesenha_navio(&jogo.navios_humano[corrente])?;
-							jogo.navios_humano[corrente].popa_x += 1;
-							desenha_navio(&jogo.navios_humano[corrente], true)?;
-						}
-					}	
-
-					(KeyCode::Left, _) => {
-						if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::OESTE) {
-							desdesenha_navio(&jogo.navios_humano[corrente])?;
-							jogo.navios_humano[corrente].popa_x -= 1;
-							desenha_navio(&jogo.navios_humano[corrente], true)?;
-						}	
+					Some(Acao::GIRA) if jogo.pode_girar_navio(&Jogador::HUMANO,corrente) => {
+						desdesenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema)?;
+						jogo.navios_humano[corrente].gira();
+						desenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema, true)?;
+					}
+
+					Some(Acao::ESPELHA) if jogo.pode_espelhar_navio(&Jogador::HUMANO,corrente) => {
+						desdesenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema)?;
+						jogo.navios_humano[corrente].espelha();
+						desenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema, true)?;
+					}
+
+					Some(Acao::MUDA) => {
+						corrente = (corrente+1) % jogo.navios_humano.len();
+						escreve_status(con, 1, &tr!("Navio: {}", tr!(jogo.navios_humano[corrente].nome.as_str())))?;
+						con
+							.execute(
+							cursor::MoveTo(x_para_tela_humano(jogo.navios_humano[corrente].popa_x),
+												  y_para_tela_humano(jogo.navios_humano[corrente].popa_y) ))?;
+					}
+
+					Some(Acao::INICIA) => {
+						con.modo_bruto(false)?;
+						break;
+					}
+
+					Some(Acao::CIMA) if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::NORTE) => {
+						desdesenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema)?;
+						jogo.navios_humano[corrente].popa_y -= 1;
+						desenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema, true)?;
+					}
+
+					Some(Acao::BAIXO) if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::SUL) => {
+						desdesenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema)?;
+						jogo.navios_humano[corrente].popa_y += 1;
+						desenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema, true)?;
+					}
+
+					Some(Acao::DIREITA) if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::LESTE) => {
+						desdesenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema)?;
+						jogo.navios_humano[corrente].popa_x += 1;
+						desenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema, true)?;
+					}
+
+					Some(Acao::ESQUERDA) if jogo.pode_mover_navio(&Jogador::HUMANO,corrente,Direcao::OESTE) => {
+						desdesenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema)?;
+						jogo.navios_humano[corrente].popa_x -= 1;
+						desenha_navio(con, &jogo.navios_humano[corrente], &opcoes.tema, true)?;
 					}
 
 					_ => {
@@ -676,88 +902,567 @@ fn arruma_navios_humano(jogo: &mut Jogo) -> Result<bool, Error> {
 			Event::FocusLost => {},
 			Event::Mouse(_mouse_event) => {},
 			Event::Paste(_s) => {},
-			Event::Resize(_colunas,_linhas) => {
-				if !tem_tamanho_minimo().expect("Erro na biblioteca crossterm") {
-					terminal::disable_raw_mode()?;
-					panic!("Terminal não tem o tamanho mínimo!");
+			Event::Resize(_colunas,_linhas) => confere_tamanho(con, jogo.lado())?,
+		}
+	}
+
+	Ok(Desfecho::CONTINUA)
+}
+
+
+// Escreve uma mensagem nas linhas abaixo do placar (1: arsenal, 2: avisos)
+fn escreve_status(con:&mut dyn Console, linha:u16, texto:&str) -> Result<(), ErroJogo> {
+	con
+		.queue( cursor::RestorePosition )?
+		.queue( cursor::MoveDown(linha) )?
+		.queue( Clear(terminal::ClearType::CurrentLine) )?
+		.queue( style::Print(texto) )?;
+	con.flush()?;
+	Ok(())
+}
+
+
+// Células atingidas por um ataque, no mapa 'y' é linha, 'x' é coluna
+fn celulas_do_ataque(mapa:&Mapa, arma:Arma, mira_x:usize, mira_y:usize, direcao:Direcao) -> Vec<(usize,usize)> {
+	let lado = mapa.len();
+	let mut celulas = Vec::new();
+	match arma {
+		Arma::TIRO => celulas.push((mira_x,mira_y)),
+		Arma::BOMBA | Arma::RADAR => {
+			for y in mira_y.saturating_sub(1) ..= (mira_y+1).min(lado-1) {
+				for x in mira_x.saturating_sub(1) ..= (mira_x+1).min(lado-1) {
+					celulas.push((x,y));
 				}
-			},
+			}
+		}
+		Arma::TORPEDO => {
+			// Percorre a partir da mira até a borda, para no primeiro navio inteiro
+			let mut x = mira_x as i64;
+			let mut y = mira_y as i64;
+			while y >= 0 && y < lado as i64 && x >= 0 && x < lado as i64 {
+				// Ilhas e recifes bloqueiam o torpedo
+				if eh_obstaculo(mapa[y as usize][x as usize]) {
+					break;
+				}
+				celulas.push((x as usize,y as usize));
+				if tem_navio_inteiro(mapa[y as usize][x as usize]) {
+					break;
+				}
+				match direcao {
+					Direcao::NORTE => y -= 1,
+					Direcao::SUL => y += 1,
+					Direcao::LESTE => x += 1,
+					Direcao::OESTE => x -= 1,
+				}
+			}
 		}
 	}
+	celulas
+}
 
-	Ok(true)
+
+// Célula do mapa contém pedaço de navio ainda não destruído ?
+fn tem_navio_inteiro(celula:usize) -> bool {
+	!matches!(celula, AGUA | DESTRUIDO | ILHA | RECIFE)
 }
 
 
-// Realiza a batalha naval
-fn executa_batalha(jogo: &mut Jogo) -> Result<bool, Error> {
-	let mut stdout = stdout();
+// Célula do mapa é ilha ou recife ?
+fn eh_obstaculo(celula:usize) -> bool {
+	celula == ILHA  ||  celula == RECIFE
+}
 
-	// Mapas
-	let mut mapa_humano = jogo.mapeia_humano(None);
-	let mut mapa_bot = jogo.mapeia_bot(None);
 
-	// Quantos pedaços vivos (não destruídos) ainda existem
-	let mut vivos_humano = 5+4+3+2;
-	let mut vivos_bot = 5+4+3+2;
-	
-	// Mira do humano
-	let mut mira_x_humano = 0;
-	let mut mira_y_humano = 0;
-									
-	// Mira do bot
-	let mut mira_x_bot;
-	let mut mira_y_bot;
+// Célula do mapa pode receber um tiro, não foi tentada e não é terreno ?
+fn alvo_valido(celula:usize) -> bool {
+	celula != DESTRUIDO  &&  !eh_obstaculo(celula)
+}
+
+
+// Radar: existe algum pedaço de navio não destruído na área 3x3 ?
+fn radar_detecta(mapa:&Mapa, mira_x:usize, mira_y:usize) -> bool {
+	celulas_do_ataque(mapa, Arma::RADAR, mira_x, mira_y, Direcao::LESTE)
+		.iter()
+		.any(|&(x,y)| tem_navio_inteiro(mapa[y][x]))
+}
+
+
+// Resultado de um ataque
+struct Impacto {
+	atingidas: usize,				// Células que ainda não tinham recebido tiro
+	acertos: Vec<(usize,usize)>,	// Células onde um pedaço de navio foi destruído
+	navios: Vec<usize>,				// Navio de cada acerto, na mesma ordem
+	agua: Vec<(usize,usize)>,		// Células de tiro na água
+	afundados: Vec<usize>,			// Navios que perderam o último pedaço neste ataque
+}
+
+impl Impacto {
+	// Cada acerto com o navio atingido
+	fn acertos_por_navio(&self) -> Vec<(usize,usize,usize)> {
+		self.acertos.iter().zip(self.navios.iter()).map(|(&(x,y),&navio)| (x,y,navio)).collect()
+	}
+}
+
+
+// Desenho de uma célula atingida, 'celula' é o conteúdo do mapa antes do ataque
+fn desenho_explosao(celula:usize, navios:&[Navio], tema:&Tema) -> StyledContent<&'static str> {
+	match celula {
+		AGUA => tema.agua(),
+		n => tema.acerto(&navios[n]),
+	}
+}
+
+
+// Posição na tela de uma célula do tabuleiro do jogador
+fn posicao_na_tela(jogador:&Jogador, x:usize, y:usize, lado:usize) -> cursor::MoveTo {
+	match jogador {
+		Jogador::HUMANO => cursor::MoveTo(x_para_tela_humano(x), y_para_tela_humano(y)),
+		Jogador::BOT => cursor::MoveTo(x_para_tela_bot(x,lado), y_para_tela_bot(y)),
+	}
+}
+
+
+// Aplica um ataque ao mapa do 'alvo' e desenha o resultado no tabuleiro dele
+fn realiza_ataque(con:&mut dyn Console, mapa:&mut Mapa, navios:&[Navio], alvo:&Jogador, ataque:(Arma,Direcao), mira:(usize,usize), tema:&Tema) -> Result<Impacto, ErroJogo> {
+	let mut impacto = Impacto{ atingidas: 0, acertos: Vec::new(), navios: Vec::new(), agua: Vec::new(), afundados: Vec::new() };
+
+	for (x,y,celula) in atinge(mapa, ataque, mira) {
+		if celula == AGUA {
+			impacto.agua.push((x,y));
+		} else {
+			impacto.acertos.push((x,y));
+			impacto.navios.push(celula);
+			if !impacto.afundados.contains(&celula) && !mapa.iter().flatten().any(|&c| c == celula) {
+				impacto.afundados.push(celula);
+			}
+		}
+		impacto.atingidas += 1;
 
-	// Gerador de números aleatórios
-	let mut rng = rand::thread_rng();
+		con
+			.queue( posicao_na_tela(alvo, x, y, mapa.len()) )?
+			.queue( style::PrintStyledContent(desenho_explosao(celula, navios, tema)) )?;
+	}
+
+	con.flush()?;
+	Ok(impacto)
+}
+
+
+// Marca no mapa as células atingidas por um ataque, sem desenhar nada
+// Devolve cada célula atingida com o que havia nela antes
+fn atinge(mapa:&mut Mapa, ataque:(Arma,Direcao), mira:(usize,usize)) -> Vec<(usize,usize,usize)> {
+	let (arma,direcao) = ataque;
+	let (mira_x,mira_y) = mira;
+	let mut atingidas = Vec::new();
+
+	for (x,y) in celulas_do_ataque(mapa, arma, mira_x, mira_y, direcao) {
+		if matches!(mapa[y][x], DESTRUIDO | ILHA | RECIFE) {
+			continue;
+		}
+		atingidas.push((x, y, mapa[y][x]));
+		mapa[y][x] = DESTRUIDO;
+	}
+	atingidas
+}
+
+
+// Quantos pedaços de navio ainda não foram destruídos no mapa
+fn pedacos_vivos(mapa:&Mapa) -> usize {
+	mapa.iter().flatten().filter(|&&celula| tem_navio_inteiro(celula)).count()
+}
+
+
+// Atualiza placar com os pedaços restantes de cada jogador
+fn mostra_placar(con:&mut dyn Console, vivos_humano:usize, vivos_bot:usize, lado:usize) -> Result<(), ErroJogo> {
+	con
+		.queue( cursor::RestorePosition )?
+		.queue( Clear(terminal::ClearType::CurrentLine) )?
+		.queue( style::Print(tr!("Restam:    {}{}", format!("{:<largura$}", vivos_humano, largura=2*lado), vivos_bot)) )?
+		.flush()?;
+	Ok(())
+}
+
+
+// Anuncia o vencedor na linha de mensagens, apagando as linhas de status, e a semente para repetir a partida
+fn anuncia_vencedor(con:&mut dyn Console, texto:&str, semente:u64) -> Result<(), ErroJogo> {
+	con.modo_bruto(false)?;
+	con
+		.queue( Clear(terminal::ClearType::CurrentLine) )?
+		.queue( Clear(terminal::ClearType::FromCursorDown) )?
+		.flush()?;
+	writeln!(con, "\r{}     ", texto)?;
+	writeln!(con, "\r{}", tr!("Semente da partida: {}", semente))?;
+	Ok(())
+}
+
+
+// Registra o fim da partida no perfil do humano e na memória do bot adaptativo
+fn registra_resultado(con:&mut dyn Console, jogo:&Jogo, opcoes:&Opcoes, venceu:bool, batalha:&Batalha) -> Result<(), ErroJogo> {
+	let gravado = Perfil::carrega(&opcoes.jogador).and_then(|mut perfil| {
+		perfil.registra_partida(opcoes.dificuldade, venceu, batalha.ataques, batalha.tiros, batalha.acertos);
+		perfil.grava()
+	});
+	// Com neblina os navios manobraram, a frota do fim não é a que o humano arrumou
+	let lembrado = match batalha.neblina {
+		Some(_) => Ok(()),
+		None => Memoria::carrega(&opcoes.jogador).and_then(|mut memoria| {
+			let frota = jogo.navios_humano.iter().flat_map(|navio| navio.celulas()).collect();
+			memoria.registra(jogo.lado(), frota, &batalha.primeiros_tiros);
+			memoria.grava()
+		}),
+	};
+	for erro in [gravado, lembrado].into_iter().filter_map(Result::err) {
+		writeln!(con, "\r{}", erro)?;
+	}
+	Ok(())
+}
+
+
+
+// Registra o fim da partida em rede nos perfis dos dois jogadores, só o Elo muda
+fn registra_resultado_rede(con:&mut dyn Console, opcoes:&Opcoes, adversario:&str, venceu:bool) -> Result<(), ErroJogo> {
+	let gravado = match venceu {
+		true => perfil::registra_confronto(&opcoes.jogador, adversario),
+		false => perfil::registra_confronto(adversario, &opcoes.jogador),
+	};
+	if let Err(erro) = gravado {
+		writeln!(con, "\r{}", erro)?;
+	}
+	Ok(())
+}
+
+
+// Fim da partida: anuncia o vencedor, grava a reprise, avisa o adversário na rede e registra nos perfis
+// A reprise de uma partida só anuncia o vencedor
+fn termina_partida(con:&mut dyn Console, jogo:&Jogo, opcoes:&Opcoes, batalha:&Batalha, remoto:Option<&mut Remoto>, venceu:bool, texto:&str) -> Result<(), ErroJogo> {
+	anuncia_vencedor(con, texto, batalha.semente)?;
+	if batalha.reprise.mostrando() {
+		return Ok(());
+	}
+	match batalha.reprise.grava() {
+		Some(Ok(arquivo)) => writeln!(con, "\r{}", tr!("Reprise em {}", arquivo.display()))?,
+		Some(Err(erro)) => writeln!(con, "\r{}", erro)?,
+		None => {}
+	}
+	match remoto {
+		None => {
+			registra_resultado(con, jogo, opcoes, venceu, batalha)?;
+			match &opcoes.desafio {
+				Some(data) => desafio::termina(con, &opcoes.jogador, data, Some(batalha.ataques).filter(|_| venceu), batalha.conta_no_desafio),
+				None => Ok(()),
+			}
+		}
+		Some(remoto) => {
+			let mensagem = if venceu { tr!("Você perdeu.") } else { tr!("Você venceu!") };
+			// Adversário que já saiu não impede o registro
+			let _ = remoto.envia_estado(jogo, Some(batalha), rede::Fase::FIM, false, mensagem);
+			registra_resultado_rede(con, opcoes, &remoto.nome, venceu)
+		}
+	}
+}
+
+
+
+// Como terminou uma fase do jogo (arrumação ou batalha)
+enum Desfecho {
+	CONTINUA,		// Fase completa, o jogo segue
+	ABORTADO,		// Ctrl+tecla, termina o programa
+	REINICIA,		// Nova partida com as mesmas opções
+	MENU,			// Volta ao menu principal
+}
+
+
+// Estado da batalha, tudo o que é preciso para gravar a partida e continuar depois
+struct Batalha {
+	mapa_humano: Mapa,			// Navios humanos e tiros do bot
+	mapa_bot: Mapa,				// Navios do bot e tiros do humano
+	mira_x: usize,				// Mira do humano
+	mira_y: usize,
+	arma: Arma,					// Arma escolhida pelo humano
+	direcao_torpedo: Direcao,
+	arsenal_humano: Arsenal,	// Usos restantes das armas especiais
+	arsenal_bot: Arsenal,
+	bot: Bot,					// Estratégia de tiro do bot
+	ataques: u32,				// Estatísticas do humano para o perfil
+	tiros: u32,
+	acertos: u32,
+	relogio: Relogio,			// Limites de tempo do humano
+	semente: u64,				// Semente dos números aleatórios da partida
+	rodada: u32,				// Rodadas completas, cada uma tem o seu gerador, ver 'gerador'
+	dicas: u32,					// Dicas que o humano ainda pode usar, ver calor.rs
+	primeiros_tiros: Vec<(usize,usize)>,	// Miras do começo da partida, lembradas pelo bot adaptativo
+	neblina: Option<Neblina>,	// Água vista por cada lado na variante com neblina
+	reprise: Reprise,			// Jogadas do humano gravadas ou mostradas, ver reprise.rs
+	conta_no_desafio: bool,		// Partida que registrou a derrota provisória do desafio do dia
+}
+
+impl Batalha {
+	// Batalha no início, com os navios já arrumados
+	fn new(jogo: &Jogo, opcoes: &Opcoes, semente: u64) -> Batalha {
+		Batalha {
+			mapa_humano: jogo.mapeia_humano(None),
+			mapa_bot: jogo.mapeia_bot(None),
+			mira_x: 0,
+			mira_y: 0,
+			arma: Arma::TIRO,
+			direcao_torpedo: Direcao::LESTE,
+			arsenal_humano: Arsenal::new(opcoes.armas_especiais),
+			arsenal_bot: Arsenal::new(opcoes.armas_especiais),
+			bot: Bot::new(opcoes.dificuldade, jogo.lado()),
+			ataques: 0,
+			tiros: 0,
+			acertos: 0,
+			relogio: Relogio::new(opcoes.tempo_jogada, opcoes.tempo_total),
+			semente,
+			rodada: 0,
+			// Com neblina os navios do bot andam, o cálculo das dicas erraria
+			dicas: if opcoes.neblina { 0 } else { opcoes.dicas },
+			primeiros_tiros: Vec::new(),
+			neblina: opcoes.neblina.then(|| Neblina::new(jogo.lado())),
+			reprise: Reprise::DESLIGADA,
+			conta_no_desafio: false,
+		}
+	}
+}
+
+
+// Desenha a tela da batalha do zero, depois da pausa ou de carregar uma partida
+fn redesenha_batalha(con:&mut dyn Console, jogo: &Jogo, batalha: &Batalha, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+	let lado = jogo.lado();
+
+	desenha_moldura(con, 0,0,&opcoes.tema,&jogo.terreno)?;
+	for navio in jogo.navios_humano.iter() {
+		desenha_navio(con, navio,&opcoes.tema,false)?;
+	}
+
+	// Tiros já dados nos dois tabuleiros, no inimigo só o que a regra revela
+	let vistos_bot = opcoes.revelacao.navios_vistos(&jogo.navios_bot, &batalha.mapa_bot);
+	let originais = [
+		(Jogador::HUMANO, &batalha.mapa_humano, jogo.mapeia_humano(None), &jogo.navios_humano),
+		(Jogador::BOT, &batalha.mapa_bot, jogo.mapeia_bot(None), &vistos_bot),
+	];
+	for (jogador, mapa, original, navios) in originais.iter() {
+		for (y,linha) in mapa.iter().enumerate() {
+			for (x,&celula) in linha.iter().enumerate() {
+				if celula == DESTRUIDO {
+					con
+						.queue( posicao_na_tela(jogador, x, y, lado) )?
+						.queue( style::PrintStyledContent(desenho_explosao(original[y][x], navios, &opcoes.tema)) )?;
+				}
+			}
+		}
+	}
+	if let Some(neblina) = &batalha.neblina {
+		neblina.desenha(con, &batalha.mapa_humano, &opcoes.tema)?;
+	}
+	con.queue(cursor::RestorePosition)?.flush()?;
+
+	// O placar ocupa a linha das instruções depois do primeiro tiro
+	mostra_instrucoes_batalha(con, opcoes)?;
+	let houve_tiros = batalha.mapa_humano.iter().chain(batalha.mapa_bot.iter()).flatten().any(|&c| c == DESTRUIDO);
+	if houve_tiros {
+		mostra_placar(con, pedacos_vivos(&batalha.mapa_humano), pedacos_vivos(&batalha.mapa_bot), lado)?;
+	}
+	if opcoes.armas_especiais {
+		escreve_status(con, 1, &batalha.arsenal_humano.descricao(batalha.arma,batalha.direcao_torpedo,&opcoes.tema))?;
+	}
+	Ok(())
+}
+
+
+// Teclas da batalha, na linha de mensagens
+fn mostra_instrucoes_batalha(con:&mut dyn Console, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+	let teclas = &opcoes.teclas;
+	let armas = match opcoes.armas_especiais {
+		true => tr!(", {} {} {} {} armas, {} gira torpedo", teclas.tecla(Acao::TIRO), teclas.tecla(Acao::BOMBA),
+							teclas.tecla(Acao::TORPEDO), teclas.tecla(Acao::RADAR), teclas.tecla(Acao::GIRA)),
+		false => String::new(),
+	};
+	let manobra = match opcoes.neblina {
+		true => tr!(", {} manobra", teclas.tecla(Acao::MANOBRA)),
+		false => String::new(),
+	};
+	writeln!(con, "{}", tr!("Mire com {}, {} fogo{}{}, {} pausa, {} ajuda", teclas.movimento(), teclas.tecla(Acao::FOGO),
+		armas, manobra, teclas.tecla(Acao::PAUSA), teclas.tecla(Acao::AJUDA)))?;
+	Ok(())
+}
+
+
+// Realiza a batalha naval, a partir do estado em que ela está
+// Com 'remoto' o adversário no navegador joga no lugar do bot
+fn executa_batalha(con:&mut dyn Console, jogo: &mut Jogo, opcoes: &Opcoes, batalha: &mut Batalha, mut remoto: Option<&mut Remoto>) -> Result<Desfecho, ErroJogo> {
+	let lado = jogo.lado();
 
 	// Teclado em modo 'raw'
-	terminal::enable_raw_mode()?;
+	con.modo_bruto(true)?;
+
+	if opcoes.armas_especiais {
+		escreve_status(con, 1, &batalha.arsenal_humano.descricao(batalha.arma,batalha.direcao_torpedo,&opcoes.tema))?;
+	}
+
+	// Partida contra o bot é gravada a partir daqui, para a reprise
+	if remoto.is_none() && !batalha.reprise.mostrando() {
+		batalha.reprise = Reprise::inicia(jogo, batalha, opcoes);
+	}
+
+	// Mapa de calor ligado, só contra o bot
+	let mut calor = false;
+	let original_bot = jogo.mapeia_bot(None);
 
 	// Cada loop é uma rodada, humanos começam
 	loop {
+		// A rodada sorteia sempre igual, mesmo numa partida carregada
+		let mut rng = gerador(batalha.semente, FLUXO_RODADAS + batalha.rodada as u64);
+
+		// O mapa muda com os tiros da rodada anterior
+		if calor {
+			calor::desenha(con, &calor::chances(&batalha.mapa_bot, &original_bot, &jogo.navios_bot), &opcoes.tema)?;
+		}
 
 		// TIRO DO HUMANO
-		loop {
-			stdout.execute(cursor::MoveTo(x_para_tela_bot(mira_x_humano),
-													y_para_tela_bot(mira_y_humano) ) )?;
+		batalha.relogio.inicia_vez();
+		let mut texto_relogio = String::new();
+		let mut manobrou = false;
+		let tiro_automatico = loop {
+			// Na reprise o ataque vem da gravação
+			if batalha.reprise.mostrando() {
+				match reprise::proxima_jogada(con, batalha, opcoes)? {
+					Ok(automatico) => break automatico,
+					Err(desfecho) => {
+						con.modo_bruto(false)?;
+						return Ok(desfecho);
+					}
+				}
+			}
 
-			let evento = crossterm::event::read()?;
+			// Atualiza o relógio quando muda o segundo mostrado
+			if batalha.relogio.ativo() {
+				if batalha.relogio.total_esgotado() {
+					termina_partida(con, jogo, opcoes, batalha, remoto.as_deref_mut(), false, tr!("VITÓRIA DO BOT!!! Tempo total esgotado."))?;
+					return Ok(Desfecho::CONTINUA);
+				}
+				if batalha.relogio.jogada_esgotada() {
+					break true;
+				}
+				let texto = batalha.relogio.descricao();
+				if texto != texto_relogio {
+					escreve_status(con, 3, &texto)?;
+					texto_relogio = texto;
+				}
+			}
+
+			con.execute(cursor::MoveTo(x_para_tela_bot(batalha.mira_x,lado),
+													y_para_tela_bot(batalha.mira_y) ) )?;
+
+			// Espera um pouco por uma tecla, para o relógio continuar andando
+			if !con.tem_evento(Duration::from_millis(100))? {
+				continue;
+			}
+			let evento = con.le_evento()?;
 			match evento {
 				Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-//					match (key_event.code, key_event.modifiers) {
-			
-				
-//				Event::Key(key_event) => {
-					match (key_event.code,key_event.modifiers) {
-						(KeyCode::Char(_x),m) if m == KeyModifiers::CONTROL => {
-							terminal::disable_raw_mode()?;
-							return Ok(false);
+					if teclas::aborta(&key_event) {
+						con.modo_bruto(false)?;
+						return Ok(Desfecho::ABORTADO);
+					}
+					match opcoes.teclas.acao(&key_event) {
+						Some(Acao::PAUSA) => {
+							// Relógio parado durante a pausa
+							batalha.relogio.termina_vez();
+							con.modo_bruto(false)?;
+							// Partida em rede, com neblina ou do desafio do dia não é salva
+							let pode_salvar = remoto.is_none() && batalha.neblina.is_none() && opcoes.desafio.is_none();
+							let aviso = match menu::pausa(con, pode_salvar, &opcoes.teclas)? {
+								Pausa::CONTINUAR => None,
+								Pausa::SALVAR => match salvamento::grava(jogo, batalha, opcoes) {
+									Ok(arquivo) => Some(tr!("Partida salva em {}", arquivo.display())),
+									Err(erro) => Some(erro),
+								},
+								Pausa::REINICIAR => return Ok(Desfecho::REINICIA),
+								Pausa::SAIR => return Ok(Desfecho::MENU),
+								Pausa::ABORTAR => return Ok(Desfecho::ABORTADO),
+							};
+							redesenha_batalha(con, jogo, batalha, opcoes)?;
+							if calor {
+								calor::desenha(con, &calor::chances(&batalha.mapa_bot, &original_bot, &jogo.navios_bot), &opcoes.tema)?;
+							}
+							if let Some(aviso) = aviso {
+								escreve_status(con, 2, &aviso)?;
+							}
+							texto_relogio.clear();
+							con.modo_bruto(true)?;
+							batalha.relogio.inicia_vez();
 						}
-						(KeyCode::Char(x), _) => {
-							match x {
-								'f'|'F' => break,
-								_ => {}
+						Some(Acao::AJUDA) => {
+							// Relógio parado enquanto a ajuda aparece
+							batalha.relogio.termina_vez();
+							menu::mostra_ajuda(con, &opcoes.teclas.ajuda(&Acao::BATALHA))?;
+							con.modo_bruto(false)?;
+							redesenha_batalha(con, jogo, batalha, opcoes)?;
+							if calor {
+								calor::desenha(con, &calor::chances(&batalha.mapa_bot, &original_bot, &jogo.navios_bot), &opcoes.tema)?;
+							}
+							texto_relogio.clear();
+							con.modo_bruto(true)?;
+							batalha.relogio.inicia_vez();
+						}
+						Some(Acao::FOGO) if eh_obstaculo(batalha.mapa_bot[batalha.mira_y][batalha.mira_x]) => {
+							escreve_status(con, 2, tr!("Não é possível atirar em ilhas ou recifes."))?;
+						}
+						Some(Acao::FOGO) => break false,
+						Some(Acao::MANOBRA) if batalha.neblina.is_some() => {
+							match neblina::manobra_humano(con, jogo, batalha, opcoes)? {
+								Manobra::FEITA => {
+									manobrou = true;
+									break false;
+								}
+								Manobra::CANCELADA => {}
+								Manobra::ABORTADA => {
+									con.modo_bruto(false)?;
+									return Ok(Desfecho::ABORTADO);
+								}
 							}
 						}
-						(KeyCode::Up, _) =>
-							if mira_y_humano > 0 {
-								mira_y_humano -= 1;
+						// Com neblina o mapa de calor usaria as posições atuais dos navios, no desafio seria ajuda
+						Some(Acao::CALOR) if remoto.is_none() && batalha.neblina.is_none() && opcoes.desafio.is_none() => {
+							calor = !calor;
+							let chances = calor::chances(&batalha.mapa_bot, &original_bot, &jogo.navios_bot);
+							match calor {
+								true => calor::desenha(con, &chances, &opcoes.tema)?,
+								false => calor::apaga(con, &chances, &opcoes.tema)?,
 							}
-						(KeyCode::Down, _) => 
-							if mira_y_humano < LADO_TABULEIRO-1 {
-								mira_y_humano += 1;
+						}
+						Some(Acao::DICA) if batalha.dicas == 0 || batalha.neblina.is_some() => {
+							escreve_status(con, 2, tr!("Nenhuma dica restante."))?;
+						}
+						Some(Acao::DICA) => {
+							let chances = calor::chances(&batalha.mapa_bot, &original_bot, &jogo.navios_bot);
+							if let Some((x,y)) = calor::melhor(&chances) {
+								(batalha.mira_x, batalha.mira_y) = (x,y);
+								batalha.dicas -= 1;
+								escreve_status(con, 2, &tr!("Dica: maior chance de navio em {} {}, restam {} dicas", x, y, batalha.dicas))?;
 							}
-						(KeyCode::Right, _) =>
-							if mira_x_humano < LADO_TABULEIRO-1 {
-								mira_x_humano += 1;
+						}
+						Some(acao @ (Acao::TIRO | Acao::BOMBA | Acao::TORPEDO | Acao::RADAR)) if opcoes.armas_especiais => {
+							let arma = match acao {
+								Acao::TIRO => Arma::TIRO,
+								Acao::BOMBA => Arma::BOMBA,
+								Acao::TORPEDO => Arma::TORPEDO,
+								_ => Arma::RADAR,
+							};
+							if batalha.arsenal_humano.usos(arma) > 0 {
+								batalha.arma = arma;
 							}
-						(KeyCode::Left, _) => {
-							if mira_x_humano > 0 {
-								mira_x_humano -= 1;
-							}	
+							escreve_status(con, 1, &batalha.arsenal_humano.descricao(batalha.arma,batalha.direcao_torpedo,&opcoes.tema))?;
 						}
+						Some(Acao::GIRA) if batalha.arma == Arma::TORPEDO => {
+							batalha.direcao_torpedo = batalha.direcao_torpedo.horaria();
+							escreve_status(con, 1, &batalha.arsenal_humano.descricao(batalha.arma,batalha.direcao_torpedo,&opcoes.tema))?;
+						}
+						Some(Acao::CIMA) => batalha.mira_y = batalha.mira_y.saturating_sub(1),
+						Some(Acao::BAIXO) => batalha.mira_y = (batalha.mira_y+1).min(lado-1),
+						Some(Acao::DIREITA) => batalha.mira_x = (batalha.mira_x+1).min(lado-1),
+						Some(Acao::ESQUERDA) => batalha.mira_x = batalha.mira_x.saturating_sub(1),
 						_ => {}
 					}
 				}
@@ -766,196 +1471,639 @@ fn executa_batalha(jogo: &mut Jogo) -> Result<bool, Error> {
 				Event::FocusLost => {}
 				Event::Mouse(_mouse_event) => {}
 				Event::Paste(_s) => {}
-				Event::Resize(_colunas,_linhas) => {
-					if !tem_tamanho_minimo().expect("Erro na biblioteca crossterm") {
-						terminal::disable_raw_mode()?;
-						panic!("Terminal não tem o tamanho mínimo!");
-					}
-				}
+				Event::Resize(_colunas,_linhas) => confere_tamanho(con, lado)?,
 			}
-		}
-		// Tiro foi dado na mira do humano
-		match mapa_bot[mira_y_humano][mira_x_humano] {
-			AGUA => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_cyan()) )?;
+		};
+
+		batalha.relogio.termina_vez();
+
+		// Quem manobrou não atira nesta rodada
+		if !manobrou {
+			batalha.reprise.anota(match tiro_automatico {
+				true => Jogada::AUTOMATICO,
+				false => Jogada::ATAQUE(batalha.arma, batalha.direcao_torpedo, batalha.mira_x, batalha.mira_y),
+			});
+
+			// Sem tempo, tiro simples em uma posição ainda não tentada
+			if tiro_automatico {
+				(batalha.mira_x, batalha.mira_y) = bot::posicao_aleatoria(&batalha.mapa_bot, &mut rng);
+				batalha.arma = Arma::TIRO;
+				escreve_status(con, 2, tr!("Tempo da jogada esgotado, tiro automático!"))?;
 			}
-			DESTRUIDO => {
+
+			// Ataque foi feito na mira do humano
+			batalha.arsenal_humano.gasta(batalha.arma);
+			if batalha.primeiros_tiros.len() < memoria::PRIMEIROS_TIROS {
+				batalha.primeiros_tiros.push((batalha.mira_x, batalha.mira_y));
 			}
-			2 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_red()) )?;
-				mapa_bot[mira_y_humano][mira_x_humano] = DESTRUIDO;
-				vivos_bot -= 1;
+			if batalha.arma == Arma::RADAR {
+				if radar_detecta(&batalha.mapa_bot, batalha.mira_x, batalha.mira_y) {
+					escreve_status(con, 2, tr!("Radar: navio detectado na área!"))?;
+				} else {
+					escreve_status(con, 2, tr!("Radar: nenhum navio na área."))?;
+				}
+			} else {
+				// Antes do ataque o navio que afunda agora ainda não foi revelado, depois sim
+				let vistos = opcoes.revelacao.navios_vistos(&jogo.navios_bot, &batalha.mapa_bot);
+				let impacto = realiza_ataque(con, &mut batalha.mapa_bot, &vistos, &Jogador::BOT,
+												(batalha.arma, batalha.direcao_torpedo), (batalha.mira_x, batalha.mira_y), &opcoes.tema)?;
+				let vistos = opcoes.revelacao.navios_vistos(&jogo.navios_bot, &batalha.mapa_bot);
+				animacao::anima(con, &Jogador::BOT, &impacto, &vistos, lado, opcoes)?;
+				let aviso = opcoes.revelacao.aviso_afundados(&impacto, &jogo.navios_bot);
+				if !aviso.is_empty() {
+					escreve_status(con, 2, &aviso)?;
+				}
+				if let Some(neblina) = batalha.neblina.as_mut() {
+					neblina.guarda_agua(&Jogador::BOT, &mut batalha.mapa_bot, &impacto, batalha.rodada);
+				}
+				batalha.ataques += 1;
+				batalha.tiros += impacto.atingidas as u32;
+				batalha.acertos += impacto.acertos.len() as u32;
 			}
-			3 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_yellow()) )?;
-				mapa_bot[mira_y_humano][mira_x_humano] = DESTRUIDO;
-				vivos_bot -= 1;
+			if opcoes.armas_especiais {
+				// Arma especial esgotada volta para o tiro simples
+				if batalha.arsenal_humano.usos(batalha.arma) == 0 {
+					batalha.arma = Arma::TIRO;
+				}
+				escreve_status(con, 1, &batalha.arsenal_humano.descricao(batalha.arma,batalha.direcao_torpedo,&opcoes.tema))?;
 			}
-			4 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_magenta()) )?;
-				mapa_bot[mira_y_humano][mira_x_humano] = DESTRUIDO;
-				vivos_bot -= 1;
+
+			// Atualiza placar
+			let vivos_bot = pedacos_vivos(&batalha.mapa_bot);
+			mostra_placar(con, pedacos_vivos(&batalha.mapa_humano), vivos_bot, lado)?;
+			std::thread::sleep(opcoes.atraso);
+
+			//	Terminou ?
+			if vivos_bot == 0 {
+				termina_partida(con, jogo, opcoes, batalha, remoto.as_deref_mut(), true, tr!("VITÓRIA DO HUMANO!!!"))?;
+				return Ok(Desfecho::CONTINUA);
 			}
-			5 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_blue()) )?;
-				mapa_bot[mira_y_humano][mira_x_humano] = DESTRUIDO;
-				vivos_bot -= 1;
-			}			
-			_ => panic!("Tamanho de Navio desconhecido"),
 		}
-		// Atualiza placar
-		stdout.queue(cursor::RestorePosition).expect("Erro na biblioteca crossterm");
-		stdout.queue(Clear(terminal::ClearType::CurrentLine)).expect("Erro na biblioteca crossterm");
-		stdout.queue( style::Print(format!("Restam:    {}                  {}", vivos_humano,vivos_bot)) )?;
-		stdout.flush()?;
-		std::thread::sleep(Duration::from_secs(1));
 
-		//	Terminou ?
-		if vivos_bot == 0 {
-			terminal::disable_raw_mode()?;
-			stdout
-				.execute(Clear(terminal::ClearType::CurrentLine))
-				.expect("Erro na biblioteca crossterm");
-			println!("\rVITÓRIA DO HUMANO!!!     ");
-			return Ok(true);
-		}
-
-		// TIRO DO BOT
-
-		// Não deixa o bot atirar em posição já tentada
-		loop {
-			mira_x_bot = rng.gen::<usize>() % LADO_TABULEIRO;
-			mira_y_bot = rng.gen::<usize>() % LADO_TABULEIRO;
-			if mapa_humano[mira_y_bot][mira_x_bot] != DESTRUIDO {
-				break;
-			}
+		// Com neblina o bot pode manobrar um navio no lugar do tiro
+		if remoto.is_none() && neblina::manobra_bot(jogo, batalha, &mut rng) {
+			escreve_status(con, 2, tr!("O bot manobrou um navio."))?;
+			std::thread::sleep(opcoes.atraso);
+			batalha.rodada += 1;
+			continue;
 		}
 
-		stdout.execute(cursor::MoveTo(x_para_tela_humano(mira_x_bot),
+		// TIRO DO BOT, ou do adversário na rede; com neblina o bot só vê a água recente
+		let (arma_bot, mira_x_bot, mira_y_bot, direcao_bot) = match remoto.as_deref_mut() {
+			None => match &batalha.neblina {
+				Some(neblina) => batalha.bot.escolhe_ataque(&neblina.vista_do_bot(&batalha.mapa_humano, batalha.rodada), &batalha.arsenal_bot, &mut rng),
+				None => batalha.bot.escolhe_ataque(&batalha.mapa_humano, &batalha.arsenal_bot, &mut rng),
+			},
+			Some(remoto) => {
+				escreve_status(con, 2, tr!("Vez do adversário..."))?;
+				let aviso = tr!("O adversário atirou em {} {}, sua vez", batalha.mira_x, batalha.mira_y);
+				match rede::espera_tiro(con, remoto, jogo, batalha, &aviso)? {
+					Ok((x,y)) => (Arma::TIRO, x, y, Direcao::LESTE),
+					Err(desfecho) => {
+						con.modo_bruto(false)?;
+						return Ok(desfecho);
+					}
+				}
+			}
+		};
+		batalha.arsenal_bot.gasta(arma_bot);
+
+		con.execute(cursor::MoveTo(x_para_tela_humano(mira_x_bot),
 												y_para_tela_humano(mira_y_bot) ) )?;
 
-		// Tiro foi dado na mira do bot
-		match mapa_humano[mira_y_bot][mira_x_bot] {
-			AGUA => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_cyan()) )?;
-				mapa_humano[mira_y_bot][mira_x_bot] = DESTRUIDO;
+		// Ataque foi feito na mira do bot
+		if arma_bot == Arma::RADAR {
+			if radar_detecta(&batalha.mapa_humano, mira_x_bot, mira_y_bot) {
+				batalha.bot.registra_radar(mira_x_bot,mira_y_bot);
 			}
-			DESTRUIDO => {
-			}
-			2 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_red()) )?;
-				mapa_humano[mira_y_bot][mira_x_bot] = DESTRUIDO;
-				vivos_humano -= 1;
-			}
-			3 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_yellow()) )?;
-				mapa_humano[mira_y_bot][mira_x_bot] = DESTRUIDO;
-				vivos_humano -= 1;
-			}
-			4 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_magenta()) )?;
-				mapa_humano[mira_y_bot][mira_x_bot] = DESTRUIDO;
-				vivos_humano -= 1;
-			}
-			5 => {
-				stdout.execute( style::PrintStyledContent(EXPLOSAO_DUPLO.on_dark_blue()) )?;
-				mapa_humano[mira_y_bot][mira_x_bot] = DESTRUIDO;
-				vivos_humano -= 1;
-			}			
-			_ => {
-				println!("@@@@@@@ {} {} {}", mira_y_bot, mira_x_bot, mapa_humano[mira_y_bot][mira_x_bot]);
-				panic!("Tamanho de Navio desconhecido");
+		} else {
+			let impacto = realiza_ataque(con, &mut batalha.mapa_humano, &jogo.navios_humano, &Jogador::HUMANO,
+											(arma_bot, direcao_bot), (mira_x_bot, mira_y_bot), &opcoes.tema)?;
+			animacao::anima(con, &Jogador::HUMANO, &impacto, &jogo.navios_humano, lado, opcoes)?;
+			if let Some(neblina) = batalha.neblina.as_mut() {
+				neblina.guarda_agua(&Jogador::HUMANO, &mut batalha.mapa_humano, &impacto, batalha.rodada);
 			}
+			opcoes.revelacao.informa_bot(&mut batalha.bot, &impacto, &jogo.navios_humano, opcoes.bot_revelacao);
 		}
+		if arma_bot != Arma::TIRO {
+			escreve_status(con, 2, &tr!("O bot usou: {}", tr!(arma_bot.nome())))?;
+		}
+
 		// Volta cursor para humano ver onde o bot atirou
-		stdout.execute(cursor::MoveTo(x_para_tela_humano(mira_x_bot),
+		con.execute(cursor::MoveTo(x_para_tela_humano(mira_x_bot),
 												y_para_tela_humano(mira_y_bot) ) )?;
-		std::thread::sleep(Duration::from_secs(1));
+		std::thread::sleep(opcoes.atraso);
 
 		// Atualiza placar
-		stdout
-			.queue(cursor::RestorePosition).expect("Erro na biblioteca crossterm")
-			.queue(Clear(terminal::ClearType::CurrentLine)).expect("Erro na biblioteca crossterm")
-			.queue( style::Print(format!("Restam:    {}                  {}", vivos_humano,vivos_bot)) )?
-			.flush()?;
+		let vivos_humano = pedacos_vivos(&batalha.mapa_humano);
+		mostra_placar(con, vivos_humano, pedacos_vivos(&batalha.mapa_bot), lado)?;
 
 		//	Terminou ?
 		if vivos_humano == 0 {
-			terminal::disable_raw_mode()?;
-			stdout.execute(Clear(terminal::ClearType::CurrentLine)).expect("Erro na biblioteca crossterm");
-			println!("\rVITÓRIA DO BOT!!!     ");
-			return Ok(true);
+			let texto = if remoto.is_some() { tr!("VITÓRIA DO ADVERSÁRIO!!!") } else { tr!("VITÓRIA DO BOT!!!") };
+			termina_partida(con, jogo, opcoes, batalha, remoto.as_deref_mut(), false, texto)?;
+			return Ok(Desfecho::CONTINUA);
 		}
+
+		// Adversário vê o resultado do seu tiro, uma desconexão aparece na próxima vez dele
+		if let Some(remoto) = remoto.as_deref_mut() {
+			let _ = remoto.envia_estado(jogo, Some(batalha), rede::Fase::BATALHA, false, tr!("Vez do adversário..."));
+		}
+		batalha.rodada += 1;
 	}
 }
 
 
 
-// Estrutura para realizar o drop e normalizar o teclado em caso de pânico
-struct Limpeza;
-impl Drop for Limpeza {
-	fn drop(&mut self) {
-		terminal::disable_raw_mode().expect("Falha ao sair do raw mode");
-    }
+// Opções da partida, vêm da linha de comando e podem ser mudadas no menu
+#[derive(Clone)]
+struct Opcoes {
+	lado: usize,					// Lado do tabuleiro em mar aberto, um mapa define o próprio lado
+	armas_especiais: bool,			// Bomba, torpedo e radar
+	arquivo_frota: Option<String>,	// Frota predefinida ou lida de arquivo no lugar da padrão
+	mapa: Option<String>,			// Mapa predefinido ou arquivo com ilhas e recifes
+	tempo_jogada: Option<Duration>,	// Limite para cada tiro do humano
+	tempo_total: Option<Duration>,	// Tempo do humano para a partida inteira
+	atraso: Duration,				// Pausa para mostrar cada tiro
+	dificuldade: Dificuldade,		// Estratégia de tiro do bot
+	tema: Tema,						// Cores e desenhos dos tabuleiros
+	teclas: Teclas,					// Teclas de cada ação
+	jogador: String,				// Perfil onde o resultado é registrado
+	placar: bool,					// Só mostra o placar dos perfis
+	memoria: bool,					// Só mostra o que o bot adaptativo aprendeu sobre o jogador
+	esquece_memoria: bool,			// Só apaga o que o bot adaptativo aprendeu sobre o jogador
+	treina: Option<u64>,			// Só treina a política de exemplo do ambiente de aprendizado
+	animacao: bool,					// Anima respingos, explosões e navios afundando
+	som: bool,						// Campainha do terminal nos acertos
+	neblina: bool,					// Navios manobram no lugar do tiro e a água vista envelhece
+	revelacao: Revelacao,			// Quanto cada acerto revela do navio atingido
+	bot_revelacao: bool,			// A regra de revelação vale também para o bot
+	semente: Option<u64>,			// Semente fixa para repetir partidas, sorteada se não dada
+	servidor: Option<u16>,			// Porta onde o adversário no navegador se conecta, no lugar do bot
+	saguao: Option<u16>,			// Porta do saguão, só o servidor, sem terminal
+	vagas: Option<Vec<equipes::Vaga>>,	// Participantes da partida de vários jogadores, na ordem das vezes
+	dicas: u32,						// Dicas de mira em cada partida
+	desafio: Option<String>,		// Data do desafio do dia em jogo, ver desafio.rs
+	desafios: bool,					// Só mostra o placar dos desafios do dia
+	exporta_desafios: Option<String>,	// Só grava os resultados dos desafios neste arquivo
+	importa_desafios: Option<String>,	// Só junta os resultados dos desafios deste arquivo
+	reprise: Option<String>,		// Só mostra a reprise gravada neste arquivo
 }
 
-fn main() {
-	let _limpeza = Limpeza;
-	let mut stdout = stdout();
+impl Opcoes {
+	fn da_linha_de_comando() -> Opcoes {
+		// O idioma vale antes das outras opções, que podem mostrar erros
+		let lang = std::env::args().skip_while(|arg| arg != "--lang").nth(1);
+		idioma::escolhe(lang.as_deref().and_then(Idioma::por_codigo).unwrap_or_else(Idioma::do_ambiente));
+
+		let mut opcoes = Opcoes{
+			lado: LADO_PADRAO,
+			armas_especiais: false,
+			arquivo_frota: None,
+			mapa: None,
+			tempo_jogada: None,
+			tempo_total: None,
+			atraso: Duration::from_secs(1),
+			dificuldade: Dificuldade::FACIL,
+			tema: Tema::padrao(),
+			teclas: Teclas::padrao().unwrap_or_else(|erro| {
+				println!("{}", erro);
+				std::process::exit(1);
+			}),
+			jogador: perfil::nome_padrao(),
+			placar: false,
+			memoria: false,
+			esquece_memoria: false,
+			treina: None,
+			animacao: true,
+			som: false,
+			neblina: false,
+			revelacao: Revelacao::TOTAL,
+			bot_revelacao: false,
+			semente: None,
+			servidor: None,
+			saguao: None,
+			vagas: None,
+			dicas: calor::DICAS_PADRAO,
+			desafio: None,
+			desafios: false,
+			exporta_desafios: None,
+			importa_desafios: None,
+			reprise: None,
+		};
+		let mut jogadores: Option<String> = None;
+		let mut equipes: Option<String> = None;
+		let mut args = std::env::args().skip(1);
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--lado" => {
+					opcoes.lado = Opcoes::numero(args.next(), &arg) as usize;
+					if !(LADO_MINIMO ..= LADO_MAXIMO).contains(&opcoes.lado) {
+						Opcoes::uso(&arg);
+					}
+				}
+				"--armas" => opcoes.armas_especiais = true,
+				"--frota" => opcoes.arquivo_frota = Some(args.next().unwrap_or_else(|| Opcoes::uso(&arg))),
+				"--mapa" => opcoes.mapa = Some(args.next().unwrap_or_else(|| Opcoes::uso(&arg))),
+				"--tempo-jogada" => opcoes.tempo_jogada = Some(Duration::from_secs(Opcoes::numero(args.next(), &arg))),
+				"--tempo-total" => opcoes.tempo_total = Some(Duration::from_secs(Opcoes::numero(args.next(), &arg))),
+				"--atraso" => opcoes.atraso = Duration::from_millis(Opcoes::numero(args.next(), &arg)),
+				"--dificuldade" => {
+					opcoes.dificuldade = args.next().as_deref().and_then(Dificuldade::por_nome)
+											.unwrap_or_else(|| Opcoes::uso(&arg));
+				}
+				"--jogador" => {
+					opcoes.jogador = args.next().filter(|nome| perfil::nome_valido(nome))
+											.unwrap_or_else(|| Opcoes::uso(&arg));
+				}
+				"--teclas" => {
+					let nome = args.next().unwrap_or_else(|| Opcoes::uso(&arg));
+					opcoes.teclas = Teclas::carrega(&nome).unwrap_or_else(|erro| {
+						println!("{}", erro);
+						std::process::exit(1);
+					});
+				}
+				"--tema" => {
+					opcoes.tema.paleta = args.next().as_deref().and_then(Tema::paleta)
+											.unwrap_or_else(|| Opcoes::uso(&arg));
+				}
+				"--glifos" => {
+					opcoes.tema.glifos = args.next().as_deref().and_then(Tema::glifos)
+											.unwrap_or_else(|| Opcoes::uso(&arg));
+				}
+				"--lang" => {
+					let idioma = args.next().as_deref().and_then(Idioma::por_codigo)
+									.unwrap_or_else(|| Opcoes::uso(&arg));
+					idioma::escolhe(idioma);
+				}
+				"--placar" => opcoes.placar = true,
+				"--memoria" => opcoes.memoria = true,
+				"--esquece-memoria" => opcoes.esquece_memoria = true,
+				"--treina" => opcoes.treina = Some(Opcoes::numero(args.next(), &arg)),
+				"--sem-animacao" => opcoes.animacao = false,
+				"--som" => opcoes.som = true,
+				"--neblina" => opcoes.neblina = true,
+				"--revelacao" => {
+					opcoes.revelacao = args.next().as_deref().and_then(Revelacao::por_nome)
+											.unwrap_or_else(|| Opcoes::uso(&arg));
+				}
+				"--bot-revelacao" => opcoes.bot_revelacao = true,
+				"--seed" => opcoes.semente = Some(Opcoes::numero(args.next(), &arg)),
+				"--reprise" => opcoes.reprise = Some(args.next().unwrap_or_else(|| Opcoes::uso(&arg))),
+				"--servidor" => {
+					let porta = Opcoes::numero(args.next(), &arg);
+					opcoes.servidor = Some(u16::try_from(porta).unwrap_or_else(|_| Opcoes::uso(&arg)));
+				}
+				"--saguao" => {
+					let porta = Opcoes::numero(args.next(), &arg);
+					opcoes.saguao = Some(u16::try_from(porta).unwrap_or_else(|_| Opcoes::uso(&arg)));
+				}
+				"--jogadores" => jogadores = Some(args.next().unwrap_or_else(|| Opcoes::uso(&arg))),
+				"--equipes" => equipes = Some(args.next().unwrap_or_else(|| Opcoes::uso(&arg))),
+				"--dicas" => opcoes.dicas = Opcoes::numero(args.next(), &arg) as u32,
+				"--desafios" => opcoes.desafios = true,
+				"--exporta-desafios" => opcoes.exporta_desafios = Some(args.next().unwrap_or_else(|| Opcoes::uso(&arg))),
+				"--importa-desafios" => opcoes.importa_desafios = Some(args.next().unwrap_or_else(|| Opcoes::uso(&arg))),
+				"--blitz" => {
+					opcoes.tempo_jogada = Some(Duration::from_secs(5));
+					opcoes.tempo_total = Some(Duration::from_secs(120));
+					opcoes.atraso = Duration::from_millis(300);
+				}
+				_ => Opcoes::uso(&arg),
+			}
+		}
+
+		// As equipes valem para a lista de jogadores, dadas em qualquer ordem
+		match (jogadores, equipes) {
+			(Some(jogadores), equipes) => {
+				let vagas = equipes::vagas(&jogadores, equipes.as_deref(), opcoes.dificuldade)
+								.unwrap_or_else(|| Opcoes::uso("--jogadores"));
+				// Quem está no navegador conecta na porta do servidor
+				if opcoes.servidor.is_none() && vagas.iter().any(|v| v.controle == equipes::Controle::REMOTO) {
+					Opcoes::uso("--servidor");
+				}
+				opcoes.vagas = Some(vagas);
+			}
+			(None, Some(_)) => Opcoes::uso("--equipes"),
+			(None, None) => {}
+		}
+		opcoes
+	}
 
-	println!("Batalha Naval");
+	// Valor numérico de uma opção
+	fn numero(valor: Option<String>, arg: &str) -> u64 {
+		match valor.map(|v| v.parse()) {
+			Some(Ok(numero)) => numero,
+			_ => Opcoes::uso(arg),
+		}
+	}
 
-	// Cria um novo jogo com posições iniciais dos navios
-	let mut jogo = Jogo::new();
+	// Mostra como usar e termina o programa
+	fn uso(arg: &str) -> ! {
+		println!("{}", tr!("Opção inválida: {}", arg));
+		println!("{}", tr!("Uso: s14_batalha_naval [--lado <6-16>] [--armas] [--frota <nome ou arquivo>] [--mapa <nome ou arquivo>]"));
+		println!("                       [--tempo-jogada <s>] [--tempo-total <s>] [--blitz] [--atraso <ms>] [--sem-animacao] [--som]");
+		println!("                       [--dificuldade facil|normal|dificil|adaptativo] [--jogador <nome>] [--placar]");
+		println!("                       [--revelacao classica|afundado|total] [--bot-revelacao]");
+		println!("                       [--teclas setas|wasd|hjkl|<arquivo>]");
+		println!("                       [--tema classico|noturno|daltonico|monocromatico] [--glifos unicode|ascii]");
+		println!("                       [--lang pt|en] [--seed <n>] [--reprise <arquivo>] [--servidor <porta>] [--saguao <porta>]");
+		println!("                       [--jogadores humano,bot[:dificuldade],remoto,...] [--equipes a,b,...]");
+		println!("                       [--dicas <n>] [--memoria] [--esquece-memoria] [--treina <partidas>] [--neblina]");
+		println!("                       [--desafios] [--exporta-desafios <arquivo>] [--importa-desafios <arquivo>]");
+		std::process::exit(1);
+	}
+}
 
-	// Testa se o terminal tem o tamanho mínimo necessário	
-	if !tem_tamanho_minimo().expect("Erro na biblioteca crossterm") {
-		println!("Terminal não tem o tamanho mínimo!");
-		return;
+
+
+// Cria um novo jogo com as opções atuais, com as posições iniciais dos navios
+fn novo_jogo(opcoes: &Opcoes) -> Result<Jogo, String> {
+	let frota = frota::carrega(opcoes.arquivo_frota.as_deref())?;
+	let terreno = terreno::carrega(opcoes.mapa.as_deref(), opcoes.lado)?;
+	Jogo::new(&frota, terreno)
+}
+
+
+// Antes da partida o terminal pequeno só é avisado e o jogo volta ao menu
+fn avisa_tamanho(con:&mut dyn Console, lado:usize) -> Result<Option<Desfecho>, ErroJogo> {
+	match confere_tamanho(con, lado) {
+		Err(erro @ ErroJogo::TAMANHO(..)) => {
+			menu::mostra_texto(con, &[erro.to_string()])?;
+			Ok(Some(Desfecho::MENU))
+		}
+		resultado => resultado.map(|_| None),
+	}
+}
+
+
+// Joga partidas seguidas com as mesmas opções, começando pela partida carregada se houver
+// Com '--servidor' as partidas novas são contra o adversário no navegador, a partida carregada é contra o bot
+// Com '--jogadores' as partidas novas são de vários jogadores, ver equipes.rs
+fn joga(con:&mut dyn Console, opcoes: &Opcoes, carregada: Option<(Jogo,Batalha)>) -> Result<Desfecho, ErroJogo> {
+	// Com '--jogadores' as partidas novas têm vários participantes
+	if let (Some(vagas), None) = (&opcoes.vagas, &carregada) {
+		return equipes::joga(con, opcoes, vagas);
+	}
+
+	let em_rede = opcoes.servidor.filter(|_| carregada.is_none());
+	let mut remoto: Option<Remoto> = None;
+
+	// Na rede só existe o tiro simples e não há relógio, dicas nem neblina
+	let opcoes_rede;
+	let opcoes = match em_rede {
+		Some(_) => {
+			opcoes_rede = Opcoes{ armas_especiais: false, tempo_jogada: None, tempo_total: None, dicas: 0, neblina: false, ..opcoes.clone() };
+			&opcoes_rede
+		}
+		None => opcoes,
+	};
+
+	let mut carregada = carregada;
+	loop {
+		let (mut jogo, mut batalha) = match carregada.take() {
+			Some((jogo,batalha)) => {
+				if let Some(desfecho) = avisa_tamanho(con, jogo.lado())? {
+					return Ok(desfecho);
+				}
+				redesenha_batalha(con, &jogo, &batalha, opcoes)?;
+				(jogo,batalha)
+			}
+			None => {
+				let mut jogo = match novo_jogo(opcoes) {
+					Ok(jogo) => jogo,
+					Err(erro) => {
+						menu::mostra_texto(con, &[erro])?;
+						return Ok(Desfecho::MENU);
+					}
+				};
+
+				// Testa se o terminal tem o tamanho mínimo necessário
+				if let Some(desfecho) = avisa_tamanho(con, jogo.lado())? {
+					return Ok(desfecho);
+				}
+
+				// Adversário no navegador, a mesma conexão vale para as partidas seguintes
+				if let (Some(porta), None) = (em_rede, &remoto) {
+					match rede::conecta(con, porta)? {
+						Ok(conectado) => remoto = Some(conectado),
+						Err(desfecho) => return Ok(desfecho),
+					}
+				}
+
+				// Tudo o que é sorteado na partida vem da semente
+				let semente = opcoes.semente.unwrap_or_else(|| rand::thread_rng().gen());
+
+				// Arruma navios do bot, na rede é a frota inicial do adversário
+				let mut rng_bot = gerador(semente, FLUXO_FROTA_BOT);
+				match memoria_adaptativa(opcoes, remoto.is_none()).and_then(|m| m.frequencia_tiros(jogo.lado())) {
+					Some(tiros) => memoria::arruma_longe_dos_tiros(&mut jogo, &tiros, &mut rng_bot),
+					None => sorteia_navios(&mut jogo, &Jogador::BOT, &mut rng_bot),
+				}
+				if let Some(remoto) = remoto.as_mut() {
+					// Sem conexão a falha aparece quando a arrumação do adversário for esperada
+					let _ = remoto.envia_estado(&jogo, None, rede::Fase::ARRUMACAO, false, tr!("Sorteie a frota e clique em Pronto"));
+				}
+
+				// Permite que o humano arrume os seus navios
+				match arruma_navios_humano(con, &mut jogo, opcoes, &mut gerador(semente, FLUXO_FROTA_HUMANO))? {
+					Desfecho::CONTINUA => {}
+					Desfecho::REINICIA => continue,
+					desfecho => return Ok(desfecho),
+				}
+
+				// O adversário termina a arrumação enquanto o humano espera
+				if let Some(remoto) = remoto.as_mut() {
+					if let Err(desfecho) = rede::arrumacao(con, remoto, &mut jogo, &mut rng_bot)? {
+						return Ok(desfecho);
+					}
+				}
+
+				// Passa para a fase de tiros (batalha)
+				let mut batalha = Batalha::new(&jogo, opcoes, semente);
+
+				// No desafio do dia a primeira partida fica como derrota até terminar, sair ou reiniciar não dá outra chance
+				// Sem o placar a partida segue, mas o humano fica sabendo que ela não conta
+				let mut aviso = None;
+				if let Some(data) = &opcoes.desafio {
+					match desafio::comeca(&opcoes.jogador, data) {
+						Ok(conta) => batalha.conta_no_desafio = conta,
+						Err(erro) => aviso = Some(erro),
+					}
+				}
+				con
+					.queue(cursor::RestorePosition)?
+					.queue(Clear(terminal::ClearType::FromCursorDown))?
+					.flush()?;
+				mostra_instrucoes_batalha(con, opcoes)?;
+				if let Some(erro) = aviso {
+					escreve_status(con, 2, &erro)?;
+				}
+				(jogo,batalha)
+			}
+		};
+
+		// O bot adaptativo procura onde o humano costuma pôr navios
+		if let Some(frota) = memoria_adaptativa(opcoes, remoto.is_none()).and_then(|m| m.frequencia_frota(jogo.lado())) {
+			batalha.bot.lembra(frota);
+		}
+
+		match executa_batalha(con, &mut jogo, opcoes, &mut batalha, remoto.as_mut())? {
+			Desfecho::CONTINUA => {
+				match menu::pergunta(con, tr!("Jogar novamente? (s/n)"))? {
+					Some(true) => {}
+					Some(false) => return Ok(Desfecho::MENU),
+					None => return Ok(Desfecho::ABORTADO),
+				}
+			}
+			Desfecho::REINICIA => {}
+			desfecho => return Ok(desfecho),
+		}
+	}
+}
+
+
+
+// Memória do jogador, só para o bot adaptativo; sem conseguir ler, o bot joga como o difícil
+fn memoria_adaptativa(opcoes: &Opcoes, contra_bot: bool) -> Option<Memoria> {
+	match opcoes.dificuldade == Dificuldade::ADAPTATIVO && contra_bot {
+		true => Memoria::carrega(&opcoes.jogador).ok(),
+		false => None,
 	}
+}
 
-	// Desenha moldura do jogo
-	desenha_moldura(0,0,Black, Cyan)
-		.expect("Erro na biblioteca crossterm");
 
-	// Desenha navios humano
-	for n in jogo.navios_humano.iter() {
-		desenha_navio(n,false).expect("Erro na biblioteca crossterm");
+
+// Menu principal e partidas até o humano sair
+fn executa(con:&mut dyn Console, opcoes: &mut Opcoes) -> Result<Desfecho, ErroJogo> {
+	loop {
+		let carregada = match menu::principal(con, opcoes)? {
+			menu::Principal::JOGAR => None,
+			menu::Principal::CARREGAR => match salvamento::carrega(opcoes) {
+				Ok(partida) => Some(partida),
+				Err(erro) => {
+					menu::mostra_texto(con, &[erro])?;
+					continue;
+				}
+			},
+			menu::Principal::TUTORIAL => {
+				if let Desfecho::ABORTADO = tutorial::executa(con, opcoes)? {
+					return Ok(Desfecho::ABORTADO);
+				}
+				continue;
+			}
+			menu::Principal::ENIGMA => {
+				if let Desfecho::ABORTADO = quebra_cabeca::joga(con, opcoes)? {
+					return Ok(Desfecho::ABORTADO);
+				}
+				continue;
+			}
+			menu::Principal::HEXAGONAL => {
+				if let Desfecho::ABORTADO = hexagonal::joga(con, opcoes)? {
+					return Ok(Desfecho::ABORTADO);
+				}
+				continue;
+			}
+			menu::Principal::DESAFIO => {
+				if let Desfecho::ABORTADO = joga(con, &desafio::opcoes(opcoes, &desafio::hoje()), None)? {
+					return Ok(Desfecho::ABORTADO);
+				}
+				continue;
+			}
+			menu::Principal::SAIR => return Ok(Desfecho::CONTINUA),
+		};
+
+		if let Desfecho::ABORTADO = joga(con, opcoes, carregada)? {
+			return Ok(Desfecho::ABORTADO);
+		}
 	}
+}
+
 
-	// Permite que o humano arrume os seus navios
-	if !arruma_navios_humano(&mut jogo).expect("Erro na biblioteca crossterm") {
-		stdout.queue(cursor::RestorePosition).expect("Erro na biblioteca crossterm");
-		println!("\nJogo foi abortado.\n");
+fn main() {
+	let mut opcoes = Opcoes::da_linha_de_comando();
+
+	println!("{}", tr!("Batalha Naval"));
+
+	// Placar dos perfis gravados
+	if opcoes.placar {
+		match perfil::linhas_placar() {
+			Ok(linhas) => linhas.iter().for_each(|linha| println!("{}", linha)),
+			Err(erro) => println!("{}", erro),
+		}
 		return;
 	}
 
-	// Arruma navios do bot
-	arruma_navios_bot(&mut jogo);
+	// Placar dos desafios do dia e troca de resultados com outras máquinas
+	if opcoes.desafios {
+		match desafio::Placar::carrega() {
+			Ok(placar) => placar.linhas().iter().for_each(|linha| println!("{}", linha)),
+			Err(erro) => println!("{}", erro),
+		}
+		return;
+	}
+	if let Some(arquivo) = &opcoes.exporta_desafios {
+		match desafio::exporta(arquivo) {
+			Ok(total) => println!("{}", tr!("{} resultados gravados em {}", total, arquivo)),
+			Err(erro) => println!("{}", erro),
+		}
+		return;
+	}
+	if let Some(arquivo) = &opcoes.importa_desafios {
+		match desafio::importa(arquivo) {
+			Ok(novos) => println!("{}", tr!("{} resultados novos de {}", novos, arquivo)),
+			Err(erro) => println!("{}", erro),
+		}
+		return;
+	}
 
-	// Passa para a fase de tiros (batalha)
-	stdout.execute(cursor::RestorePosition).expect("Erro na biblioteca crossterm");
-	stdout.execute(Clear(terminal::ClearType::CurrentLine)).expect("Erro na biblioteca crossterm");
-	println!("Mova a mira as teclas {}{}{}{}, 'f' p/fogo", '\u{2190}', '\u{2191}', '\u{2192}', '\u{2193}');
-	if executa_batalha(&mut jogo).expect("Erro na biblioteca crossterm") {
-		stdout.execute(cursor::RestorePosition).expect("Erro na biblioteca crossterm");
-		println!("\nFim do jogo.\n");
-	} else {
-		stdout.execute(cursor::RestorePosition).expect("Erro na biblioteca crossterm");
-		println!("\nJogo foi abortado.\n");
+	// O que o bot adaptativo aprendeu sobre o jogador
+	if opcoes.memoria {
+		match Memoria::carrega(&opcoes.jogador) {
+			Ok(memoria) => memoria.linhas().iter().for_each(|linha| println!("{}", linha)),
+			Err(erro) => println!("{}", erro),
+		}
+		return;
+	}
+	if opcoes.esquece_memoria {
+		match memoria::esquece(&opcoes.jogador) {
+			Ok(true) => println!("{}", tr!("O bot esqueceu as partidas de {}.", opcoes.jogador)),
+			Ok(false) => println!("{}", tr!("O bot não lembrava de nenhuma partida de {}.", opcoes.jogador)),
+			Err(erro) => println!("{}", erro),
+		}
+		return;
 	}
 
-	// Mapa final, para depuração
-	println!("Mapa Humano");
-	let zzz = jogo.mapeia_humano(None);
-	for i in 0..10 {
-		println!("{:?}", zzz[i]);
+	// Exemplo de treino no ambiente de aprendizado, sem terminal
+	if let Some(episodios) = opcoes.treina {
+		let semente = opcoes.semente.unwrap_or_else(|| rand::thread_rng().gen());
+		if let Err(erro) = treino::treina(&opcoes, episodios, semente, &mut |linha| println!("{}", linha)) {
+			println!("{}", erro);
+			std::process::exit(1);
+		}
+		return;
 	}
-	println!("Mapa Bot");
-	let zzz = jogo.mapeia_bot(None);
-	for i in 0..10 {
-		println!("{:?}", zzz[i]);
+
+	// Saguão sem terminal, até ser interrompido
+	if let Some(porta) = opcoes.saguao {
+		if let Err(erro) = saguao::executa(porta, &opcoes) {
+			eprintln!("{}", erro);
+			std::process::exit(1);
+		}
+		return;
 	}
 
+	// O terminal volta ao normal quando 'tela' sai de escopo, antes das mensagens finais
+	let resultado = Tela::abre().and_then(|_tela| match opcoes.reprise.clone() {
+		Some(arquivo) => reprise::executa(&mut ConsoleReal::new(), &opcoes, &arquivo),
+		None => executa(&mut ConsoleReal::new(), &mut opcoes),
+	});
+	match resultado {
+		Ok(Desfecho::ABORTADO) => println!("{}\n", tr!("Jogo foi abortado.")),
+		Ok(_) => println!("{}\n", tr!("Fim do jogo.")),
+		Err(erro) => {
+			eprintln!("{}", erro);
+			std::process::exit(1);
+		}
+	}
 }
-
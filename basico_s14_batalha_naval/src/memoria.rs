@@ -0,0 +1,301 @@
+/*
+	Memória do bot adaptativo
+
+	No fim de cada partida contra o bot, em qualquer dificuldade, ficam gravados
+	para o perfil do humano a arrumação final da sua frota e os seus primeiros
+	tiros, em
+		<dados>/batalha_naval/memoria/<nome>.txt
+	no mesmo diretório dos perfis (ver perfil.rs), uma linha por registro e só
+	as últimas MAXIMO_PARTIDAS partidas:
+
+		frota <lado> x,y x,y ...		células ocupadas pela frota do humano
+		tiros <lado> x,y x,y ...		primeiros PRIMEIROS_TIROS tiros, na ordem
+
+	O bot adaptativo procura primeiro onde o humano costuma pôr navios (ver
+	bot.rs) e arruma a própria frota longe de onde ele costuma atirar primeiro,
+	escolhendo entre CANDIDATAS arrumações sorteadas. Só contam as partidas
+	com o mesmo lado de tabuleiro. Partidas com '--neblina' não são gravadas,
+	porque no fim delas os navios do humano já manobraram.
+
+	'--memoria' mostra o que foi aprendido sobre o jogador e '--esquece-memoria' apaga.
+*/
+
+
+use std::fs;
+use std::path::PathBuf;
+
+use rand::Rng;
+
+use crate::{Jogo,Jogador,sorteia_navios};
+use crate::perfil;
+
+
+// Partidas guardadas por jogador, as mais antigas são esquecidas
+const MAXIMO_PARTIDAS: usize = 20;
+
+// Tiros do começo da partida que são lembrados
+pub const PRIMEIROS_TIROS: usize = 10;
+
+// Arrumações sorteadas para o bot escolher a menos visada
+const CANDIDATAS: usize = 40;
+
+
+// Um registro lembrado: lado do tabuleiro e células
+type Registro = (usize, Vec<(usize,usize)>);
+
+
+#[derive(Debug)]
+pub struct Memoria {
+	pub nome: String,
+	frotas: Vec<Registro>,		// Da mais antiga para a mais recente
+	tiros: Vec<Registro>,
+}
+
+impl Memoria {
+	// Carrega a memória do jogador, vazia se ainda não existe
+	pub fn carrega(nome: &str) -> Result<Memoria, String> {
+		let arquivo = arquivo_da_memoria(nome)?;
+		match fs::read_to_string(&arquivo) {
+			Ok(texto) => Ok(Memoria::de_texto(nome, &texto)),
+			Err(erro) if erro.kind() == std::io::ErrorKind::NotFound => Ok(Memoria::de_texto(nome, "")),
+			Err(erro) => Err(tr!("Não conseguiu ler a memória {}: {}", arquivo.display(), erro)),
+		}
+	}
+
+	// Grava a memória no disco
+	pub fn grava(&self) -> Result<(), String> {
+		let arquivo = arquivo_da_memoria(&self.nome)?;
+		if let Some(diretorio) = arquivo.parent() {
+			fs::create_dir_all(diretorio)
+				.map_err(|erro| tr!("Não conseguiu criar {}: {}", diretorio.display(), erro))?;
+		}
+		fs::write(&arquivo, self.para_texto())
+			.map_err(|erro| tr!("Não conseguiu gravar a memória {}: {}", arquivo.display(), erro))
+	}
+
+	// Linhas mal formadas são ignoradas
+	pub fn de_texto(nome: &str, texto: &str) -> Memoria {
+		let mut memoria = Memoria { nome: String::from(nome), frotas: Vec::new(), tiros: Vec::new() };
+		for linha in texto.lines() {
+			let mut partes = linha.splitn(3, ' ');
+			let (Some(tipo), Some(Ok(lado))) = (partes.next(), partes.next().map(str::parse::<usize>)) else {
+				continue;
+			};
+			let Some(celulas) = posicoes_de_texto(partes.next().unwrap_or("")) else {
+				continue;
+			};
+			if celulas.iter().any(|&(x,y)| x >= lado || y >= lado) {
+				continue;
+			}
+			match tipo {
+				"frota" => memoria.frotas.push((lado, celulas)),
+				"tiros" => memoria.tiros.push((lado, celulas)),
+				_ => {}
+			}
+		}
+		memoria
+	}
+
+	pub fn para_texto(&self) -> String {
+		let mut texto = String::new();
+		for (tipo,registros) in [("frota",&self.frotas), ("tiros",&self.tiros)] {
+			for (lado,celulas) in registros.iter() {
+				texto.push_str(&format!("{} {} {}\n", tipo, lado, texto_das_posicoes(celulas)));
+			}
+		}
+		texto
+	}
+
+	// Lembra mais uma partida, esquecendo as mais antigas
+	pub fn registra(&mut self, lado: usize, frota: Vec<(usize,usize)>, tiros: &[(usize,usize)]) {
+		self.frotas.push((lado, frota));
+		if !tiros.is_empty() {
+			self.tiros.push((lado, tiros.iter().take(PRIMEIROS_TIROS).copied().collect()));
+		}
+		for registros in [&mut self.frotas, &mut self.tiros] {
+			let excesso = registros.len().saturating_sub(MAXIMO_PARTIDAS);
+			registros.drain(0..excesso);
+		}
+	}
+
+	// Fração das partidas com navio do humano em cada célula, None sem partidas nesse lado
+	pub fn frequencia_frota(&self, lado: usize) -> Option<Vec<Vec<f64>>> {
+		frequencia(&self.frotas, lado)
+	}
+
+	// Fração das partidas em que o humano atirou cedo em cada célula
+	pub fn frequencia_tiros(&self, lado: usize) -> Option<Vec<Vec<f64>>> {
+		frequencia(&self.tiros, lado)
+	}
+
+	// Linhas para mostrar com '--memoria', em décimos como o mapa de calor
+	pub fn linhas(&self) -> Vec<String> {
+		let mut linhas = vec![tr!("MEMÓRIA DO BOT: {}", self.nome), String::new()];
+		let mut lados: Vec<usize> = self.frotas.iter().chain(self.tiros.iter()).map(|(lado,_)| *lado).collect();
+		lados.sort();
+		lados.dedup();
+		for lado in lados {
+			let partidas = self.frotas.iter().filter(|(l,_)| *l == lado).count();
+			linhas.push(tr!("Tabuleiro {}x{}, {} partidas lembradas", lado, lado, partidas));
+			let vazia = vec![vec![0.0; lado]; lado];
+			let frota = self.frequencia_frota(lado).unwrap_or_else(|| vazia.clone());
+			let tiros = self.frequencia_tiros(lado).unwrap_or(vazia);
+			linhas.push(format!("{:<w$}   {}", tr!("Navios"), tr!("Primeiros tiros"), w = 2*lado));
+			for y in 0..lado {
+				linhas.push(format!("{}   {}", linha_em_decimos(&frota[y]), linha_em_decimos(&tiros[y])));
+			}
+			linhas.push(String::new());
+		}
+		if self.frotas.is_empty() && self.tiros.is_empty() {
+			linhas.push(String::from(tr!("Nenhuma partida lembrada.")));
+		}
+		linhas
+	}
+}
+
+
+// Apaga a memória do jogador, diz se havia alguma
+pub fn esquece(nome: &str) -> Result<bool, String> {
+	let arquivo = arquivo_da_memoria(nome)?;
+	match fs::remove_file(&arquivo) {
+		Ok(()) => Ok(true),
+		Err(erro) if erro.kind() == std::io::ErrorKind::NotFound => Ok(false),
+		Err(erro) => Err(tr!("Não conseguiu apagar a memória {}: {}", arquivo.display(), erro)),
+	}
+}
+
+
+// Sorteia várias arrumações para a frota do bot e fica com a que tem menos células nos primeiros tiros do humano
+pub fn arruma_longe_dos_tiros(jogo: &mut Jogo, tiros: &[Vec<f64>], rng: &mut impl Rng) {
+	let mut melhor: Option<(f64, Vec<crate::Navio>)> = None;
+	for _ in 0..CANDIDATAS {
+		sorteia_navios(jogo, &Jogador::BOT, rng);
+		let risco: f64 = jogo.navios(&Jogador::BOT).iter()
+			.flat_map(|navio| navio.celulas())
+			.map(|(x,y)| tiros[y][x])
+			.sum();
+		if melhor.as_ref().is_none_or(|(menor,_)| risco < *menor) {
+			melhor = Some((risco, jogo.navios(&Jogador::BOT).clone()));
+		}
+	}
+	if let Some((_,navios)) = melhor {
+		*jogo.navios_mut(&Jogador::BOT) = navios;
+	}
+}
+
+
+// Posições 'x,y' separadas por espaço, como na memória e na partida salva
+pub fn texto_das_posicoes(posicoes: &[(usize,usize)]) -> String {
+	posicoes.iter().map(|(x,y)| format!("{},{}", x, y)).collect::<Vec<_>>().join(" ")
+}
+
+pub fn posicoes_de_texto(texto: &str) -> Option<Vec<(usize,usize)>> {
+	texto.split_whitespace()
+		.map(|par| {
+			let (x,y) = par.split_once(',')?;
+			Some((x.parse().ok()?, y.parse().ok()?))
+		})
+		.collect()
+}
+
+
+// Em que fração dos registros de um lado cada célula aparece
+fn frequencia(registros: &[Registro], lado: usize) -> Option<Vec<Vec<f64>>> {
+	let do_lado: Vec<&Vec<(usize,usize)>> = registros.iter().filter(|(l,_)| *l == lado).map(|(_,c)| c).collect();
+	if do_lado.is_empty() {
+		return None;
+	}
+	let mut contagem = vec![vec![0.0; lado]; lado];
+	for celulas in do_lado.iter() {
+		for &(x,y) in celulas.iter() {
+			contagem[y][x] = 1.0_f64.min(contagem[y][x] + 1.0 / do_lado.len() as f64);
+		}
+	}
+	Some(contagem)
+}
+
+
+// Um dígito de décimos por célula, '.' onde nunca aconteceu
+fn linha_em_decimos(linha: &[f64]) -> String {
+	linha.iter()
+		.map(|&f| match f > 0.0 {
+			true => format!("{} ", ((f*10.0).round() as u32).min(9)),
+			false => String::from(". "),
+		})
+		.collect()
+}
+
+
+fn arquivo_da_memoria(nome: &str) -> Result<PathBuf, String> {
+	if !perfil::nome_valido(nome) {
+		return Err(tr!("Nome de perfil inválido: '{}'", nome));
+	}
+	Ok(perfil::diretorio_dados()?.join("memoria").join(format!("{}.txt", nome)))
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+	use crate::{Arma,Arsenal,Direcao,atinge,gerador,FLUXO_FROTA_BOT,FLUXO_RODADAS};
+	use crate::bot::{Bot,Dificuldade};
+	use crate::testes::jogo_de_teste;
+
+	#[test]
+	fn memoria_lembra_frotas_e_tiros() {
+		let mut memoria = Memoria::de_texto("ana", "frota 6 0,0 1,0\ntiros 6 2,2 3,3\nlixo\nfrota 6 9,9\n");
+		memoria.registra(6, vec![(0,0), (0,1)], &[(2,2)]);
+		memoria.registra(8, vec![(7,7)], &[]);
+		assert_eq!(memoria.para_texto(), "frota 6 0,0 1,0\nfrota 6 0,0 0,1\nfrota 8 7,7\ntiros 6 2,2 3,3\ntiros 6 2,2\n");
+
+		// Frações por lado de tabuleiro, nada para um lado nunca jogado
+		let frota = memoria.frequencia_frota(6).unwrap();
+		assert_eq!((frota[0][0], frota[0][1], frota[1][0], frota[5][5]), (1.0, 0.5, 0.5, 0.0));
+		assert_eq!(memoria.frequencia_tiros(6).unwrap()[3][3], 0.5);
+		assert!(memoria.frequencia_tiros(8).is_none());
+		assert!(memoria.frequencia_frota(10).is_none());
+		assert_eq!(memoria.linhas()[4], "9 5 . . . .    . . . . . . ");
+
+		// Só as partidas mais recentes ficam
+		for _ in 0..30 {
+			memoria.registra(6, vec![(5,5)], &[(1,1)]);
+		}
+		assert_eq!(memoria.frequencia_frota(6).unwrap()[0][0], 0.0);
+	}
+
+	#[test]
+	fn bot_adaptativo_procura_onde_o_humano_costuma_por_navios() {
+		let jogo = jogo_de_teste(10);
+		let mut mapa = jogo.mapeia_bot(None);
+		let mut frequencias = vec![vec![0.0; 10]; 10];
+		(frequencias[7][2], frequencias[8][2], frequencias[0][0]) = (0.9, 0.9, 0.2);
+
+		// As casas mais lembradas primeiro, abaixo do limiar volta ao xadrez
+		let mut bot = Bot::new(Dificuldade::ADAPTATIVO, 10);
+		bot.lembra(frequencias);
+		let mut rng = gerador(0, FLUXO_RODADAS);
+		let mut tiros = Vec::new();
+		for _ in 0..3 {
+			let (arma,x,y,_) = bot.escolhe_ataque(&mapa, &Arsenal::new(false), &mut rng);
+			assert_eq!(arma, Arma::TIRO);
+			atinge(&mut mapa, (Arma::TIRO, Direcao::LESTE), (x,y));
+			tiros.push((x,y));
+		}
+		tiros[..2].sort();
+		assert_eq!(tiros[..2], [(2,7), (2,8)]);
+		assert_eq!((tiros[2].0 + tiros[2].1) % 2, 0);
+
+		// A frota do bot fica longe de onde o humano atira primeiro
+		let mut tiros_humano = vec![vec![0.0; 10]; 10];
+		for linha in tiros_humano.iter_mut().take(5) {
+			linha.iter_mut().for_each(|f| *f = 1.0);
+		}
+		let mut sorteado = jogo_de_teste(10);
+		sorteia_navios(&mut sorteado, &Jogador::BOT, &mut gerador(0, FLUXO_FROTA_BOT));
+		let mut adaptado = jogo_de_teste(10);
+		arruma_longe_dos_tiros(&mut adaptado, &tiros_humano, &mut gerador(0, FLUXO_FROTA_BOT));
+		let acima = |jogo: &Jogo| jogo.navios_bot.iter().flat_map(|navio| navio.celulas()).filter(|&(_,y)| y < 5).count();
+		assert!(acima(&adaptado) < acima(&sorteado));
+		assert!((0..adaptado.navios_bot.len()).all(|n| adaptado.pode_colocar_navio(&Jogador::BOT, n)));
+	}
+}
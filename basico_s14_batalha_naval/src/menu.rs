@@ -0,0 +1,397 @@
+/*
+	Menus em tela cheia
+
+	- Menu principal: novo jogo, dificuldade, opções, regras, tutorial, carregar, quebra-cabeça, placar e sair
+	- Opções: lado do tabuleiro, frota, mapa, armas especiais, relógio, tema, glifos, teclas, idioma,
+	animações e som
+	- Pausa, com Esc durante o jogo: continuar, salvar, reiniciar e sair
+	- Pergunta de sim ou não, como o "jogar novamente" no fim da partida
+	- Ajuda com as teclas, por cima do tabuleiro
+
+	Nos menus as teclas de movimento para cima e para baixo escolhem o item, Enter
+	confirma, as teclas para os lados mudam o valor e Esc volta. Ctrl+C e Ctrl+Q abortam.
+*/
+
+
+use std::time::Duration;
+
+use crossterm::QueueableCommand;
+use crossterm::terminal::{Clear,ClearType};
+use crossterm::cursor;
+use crossterm::style::{self,Stylize};
+use crossterm::event::{Event,KeyCode,KeyEventKind};
+
+use crate::{Opcoes,LADO_MINIMO,LADO_MAXIMO,limpa_tela,frota,perfil,terreno};
+use crate::bot::Dificuldade;
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::idioma::{self,Idioma};
+use crate::revelacao::Revelacao;
+use crate::teclas::{self,Acao,Teclas};
+use crate::tema::{self,Tema};
+
+
+// Relógios prontos: nome, limite por jogada (s), tempo total (s), pausa entre tiros (ms)
+const RELOGIOS: [(&str,Option<u64>,Option<u64>,u64);3] = [
+	("desligado", None, None, 1000),
+	("normal", Some(30), Some(600), 1000),
+	("blitz", Some(5), Some(120), 300),
+];
+
+
+// O que fazer depois do menu principal
+pub enum Principal {
+	JOGAR,
+	CARREGAR,
+	TUTORIAL,
+	ENIGMA,
+	HEXAGONAL,
+	DESAFIO,
+	SAIR,
+}
+
+// Itens do menu de pausa
+#[derive(Copy,Clone)]
+pub enum Pausa {
+	CONTINUAR,
+	SALVAR,
+	REINICIAR,
+	SAIR,
+	ABORTAR,
+}
+
+// Tecla que encerrou a espera em um menu
+enum Escolha {
+	ITEM(usize),		// Enter no item
+	MUDA(usize,bool),	// Seta para a esquerda (false) ou direita (true) no item
+	VOLTA,				// Esc
+	ABORTA,				// Ctrl+C ou Ctrl+Q
+}
+
+
+// Mostra os itens e espera uma escolha, as setas para cima e para baixo mudam o selecionado
+fn escolhe(con:&mut dyn Console, titulo:&str, itens:&[String], selecionado:&mut usize, rodape:&str, teclas:&Teclas) -> Result<Escolha, ErroJogo> {
+	limpa_tela(con)?;
+	con.modo_bruto(true)?;
+
+	let escolha = loop {
+		con
+			.queue( cursor::MoveTo(2,1) )?
+			.queue( style::PrintStyledContent(titulo.bold()) )?;
+		for (i,item) in itens.iter().enumerate() {
+			con
+				.queue( cursor::MoveTo(2,3+i as u16) )?
+				.queue( Clear(ClearType::CurrentLine) )?;
+			if i == *selecionado {
+				con.queue( style::PrintStyledContent(format!("> {} ", item).reverse()) )?;
+			} else {
+				con.queue( style::Print(format!("  {} ", item)) )?;
+			}
+		}
+		con
+			.queue( cursor::MoveTo(2,4+itens.len() as u16) )?
+			.queue( style::Print(rodape) )?
+			.queue( cursor::MoveTo(2,3+*selecionado as u16) )?
+			.flush()?;
+
+		match con.le_evento()? {
+			Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+				if teclas::aborta(&key_event) {
+					break Escolha::ABORTA;
+				}
+				match (key_event.code, teclas.acao(&key_event)) {
+					(KeyCode::Esc, _) => break Escolha::VOLTA,
+					(KeyCode::Enter, _) => break Escolha::ITEM(*selecionado),
+					(_, Some(Acao::ESQUERDA)) => break Escolha::MUDA(*selecionado,false),
+					(_, Some(Acao::DIREITA)) => break Escolha::MUDA(*selecionado,true),
+					(_, Some(Acao::CIMA)) => *selecionado = (*selecionado+itens.len()-1) % itens.len(),
+					(_, Some(Acao::BAIXO)) => *selecionado = (*selecionado+1) % itens.len(),
+					_ => {}
+				}
+			}
+			_ => {}
+		}
+	};
+
+	con.modo_bruto(false)?;
+	Ok(escolha)
+}
+
+
+// Espera qualquer tecla
+pub fn espera_tecla(con:&mut dyn Console) -> Result<(), ErroJogo> {
+	con.modo_bruto(true)?;
+	loop {
+		if let Event::Key(key_event) = con.le_evento()? {
+			if key_event.kind == KeyEventKind::Press {
+				break;
+			}
+		}
+	}
+	con.modo_bruto(false)?;
+	Ok(())
+}
+
+
+// Mostra um texto em tela cheia até o humano teclar algo
+pub fn mostra_texto(con:&mut dyn Console, linhas:&[String]) -> Result<(), ErroJogo> {
+	limpa_tela(con)?;
+	for linha in linhas {
+		writeln!(con, "{}", linha)?;
+	}
+	writeln!(con, "\n{}", tr!("Tecle algo para voltar"))?;
+	espera_tecla(con)
+}
+
+
+// Mostra a ajuda em uma caixa por cima da tela, até o humano teclar algo
+pub fn mostra_ajuda(con:&mut dyn Console, linhas:&[String]) -> Result<(), ErroJogo> {
+	let largura = linhas.iter().map(|l| l.chars().count()).max().unwrap_or(0) + 4;
+	let caixa: Vec<String> = [String::from(tr!("TECLAS")), String::new()].iter()
+		.chain(linhas.iter())
+		.chain([String::new(), String::from(tr!("Tecle algo para voltar"))].iter())
+		.map(|linha| format!("  {:<largura$}", linha, largura=largura-2))
+		.collect();
+
+	for (y,linha) in caixa.iter().enumerate() {
+		con
+			.queue( cursor::MoveTo(2,1+y as u16) )?
+			.queue( style::PrintStyledContent(linha.as_str().black().on_white()) )?;
+	}
+	con.flush()?;
+	espera_tecla(con)
+}
+
+
+// Faz uma pergunta na posição do cursor, 's', 'y' ou Enter é sim, 'n' ou Esc é não, None se abortou
+pub fn pergunta(con:&mut dyn Console, texto:&str) -> Result<Option<bool>, ErroJogo> {
+	write!(con, "\r{} ", texto)?;
+	con.flush()?;
+	con.modo_bruto(true)?;
+	let resposta = loop {
+		match con.le_evento()? {
+			Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+				if teclas::aborta(&key_event) {
+					break None;
+				}
+				match key_event.code {
+					KeyCode::Char('s'|'S'|'y'|'Y') | KeyCode::Enter => break Some(true),
+					KeyCode::Char('n'|'N') | KeyCode::Esc => break Some(false),
+					_ => {}
+				}
+			}
+			_ => {}
+		}
+	};
+	con.modo_bruto(false)?;
+	writeln!(con)?;
+	Ok(resposta)
+}
+
+
+// Valor antes ou depois do atual na lista, dando a volta; fora da lista vai para o primeiro
+fn vizinho<T:PartialEq+Clone>(lista:&[T], atual:&T, avanca:bool) -> T {
+	let n = lista.len();
+	let i = match (lista.iter().position(|v| v == atual), avanca) {
+		(None, _) => 0,
+		(Some(i), true) => (i+1) % n,
+		(Some(i), false) => (i+n-1) % n,
+	};
+	lista[i].clone()
+}
+
+
+// Menu principal, volta quando o humano quer jogar ou sair
+pub fn principal(con:&mut dyn Console, opcoes:&mut Opcoes) -> Result<Principal, ErroJogo> {
+	let mut selecionado = 0;
+	loop {
+		let itens = [
+			String::from(tr!("Novo jogo")),
+			tr!("Dificuldade: {}", tr!(opcoes.dificuldade.nome())),
+			String::from(tr!("Opções")),
+			String::from(tr!("Regras")),
+			String::from(tr!("Tutorial")),
+			String::from(tr!("Carregar partida")),
+			String::from(tr!("Quebra-cabeça")),
+			String::from(tr!("Tabuleiro hexagonal")),
+			String::from(tr!("Desafio do dia")),
+			String::from(tr!("Placar")),
+			String::from(tr!("Sair")),
+		];
+		let titulo = tr!("BATALHA NAVAL   jogador: {}", opcoes.jogador);
+		let rodape = tr!("Enter escolhe, {}{} mudam a dificuldade, Esc sai", opcoes.tema.glifos.setas[2], opcoes.tema.glifos.setas[3]);
+		match escolhe(con, &titulo, &itens, &mut selecionado, &rodape, &opcoes.teclas)? {
+			Escolha::ITEM(0) => return Ok(Principal::JOGAR),
+			Escolha::ITEM(1) => opcoes.dificuldade = vizinho(&Dificuldade::TODAS, &opcoes.dificuldade, true),
+			Escolha::MUDA(1,avanca) => opcoes.dificuldade = vizinho(&Dificuldade::TODAS, &opcoes.dificuldade, avanca),
+			Escolha::ITEM(2) => {
+				if !tela_opcoes(con, opcoes)? {
+					return Ok(Principal::SAIR);
+				}
+			}
+			Escolha::ITEM(3) => { mostra_texto(con, &regras(opcoes))?; }
+			Escolha::ITEM(4) => return Ok(Principal::TUTORIAL),
+			Escolha::ITEM(5) => return Ok(Principal::CARREGAR),
+			Escolha::ITEM(6) => return Ok(Principal::ENIGMA),
+			Escolha::ITEM(7) => return Ok(Principal::HEXAGONAL),
+			Escolha::ITEM(8) => return Ok(Principal::DESAFIO),
+			Escolha::ITEM(9) => {
+				let linhas = perfil::linhas_placar().unwrap_or_else(|erro| vec![erro]);
+				mostra_texto(con, &linhas)?;
+			}
+			Escolha::ITEM(_) | Escolha::VOLTA | Escolha::ABORTA => return Ok(Principal::SAIR),
+			Escolha::MUDA(_,_) => {}
+		}
+	}
+}
+
+
+// Tela de opções, muda as opções da próxima partida, falso se abortou
+fn tela_opcoes(con:&mut dyn Console, opcoes:&mut Opcoes) -> Result<bool, ErroJogo> {
+	let lados: Vec<usize> = (LADO_MINIMO ..= LADO_MAXIMO).collect();
+
+	// Frotas e mapas predefinidos, mais o arquivo dado na linha de comando
+	let mut frotas: Vec<Option<String>> = vec![None];
+	frotas.extend(frota::PREDEFINIDAS.iter().map(|(nome,_)| Some(String::from(*nome))));
+	if !frotas.contains(&opcoes.arquivo_frota) {
+		frotas.push(opcoes.arquivo_frota.clone());
+	}
+	let mut mapas: Vec<Option<String>> = vec![None];
+	mapas.extend(terreno::PREDEFINIDOS.iter().map(|(nome,_)| Some(String::from(*nome))));
+	if !mapas.contains(&opcoes.mapa) {
+		mapas.push(opcoes.mapa.clone());
+	}
+	let mut conjuntos: Vec<String> = Teclas::PREDEFINIDAS.iter().map(|nome| String::from(*nome)).collect();
+	if !conjuntos.contains(&opcoes.teclas.nome) {
+		conjuntos.push(opcoes.teclas.nome.clone());
+	}
+	let inicial = opcoes.teclas.clone();
+	let relogios: Vec<&str> = RELOGIOS.iter().map(|r| r.0).collect();
+	let paletas: Vec<&str> = tema::PALETAS.iter().map(|p| p.nome).collect();
+	let glifos: Vec<&str> = tema::GLIFOS.iter().map(|g| g.nome).collect();
+
+	let mut selecionado = 0;
+	loop {
+		let segundos = |tempo: Option<Duration>| tempo.map(|t| t.as_secs());
+		let relogio = RELOGIOS.iter()
+			.find(|r| r.1 == segundos(opcoes.tempo_jogada) && r.2 == segundos(opcoes.tempo_total))
+			.map_or("personalizado", |r| r.0);
+
+		let itens = [
+			match opcoes.mapa {
+				None => tr!("Tabuleiro: {}", format!("{0}x{0}", opcoes.lado)),
+				Some(_) => String::from(tr!("Tabuleiro: definido pelo mapa")),
+			},
+			tr!("Frota: {}", opcoes.arquivo_frota.as_deref().unwrap_or(tr!("clássica"))),
+			tr!("Mapa: {}", opcoes.mapa.as_deref().unwrap_or(tr!("mar aberto"))),
+			tr!("Armas especiais: {}", if opcoes.armas_especiais { tr!("sim") } else { tr!("não") }),
+			tr!("Relógio: {}", tr!(relogio)),
+			tr!("Tema: {}", tr!(opcoes.tema.paleta.nome)),
+			tr!("Glifos: {}", opcoes.tema.glifos.nome),
+			tr!("Teclas: {}", opcoes.teclas.nome),
+			tr!("Idioma: {}", idioma::atual().nome()),
+			tr!("Animações: {}", if opcoes.animacao { tr!("sim") } else { tr!("não") }),
+			tr!("Som: {}", if opcoes.som { tr!("sim") } else { tr!("não") }),
+			tr!("Neblina: {}", if opcoes.neblina { tr!("sim") } else { tr!("não") }),
+			tr!("Revelação: {}", tr!(opcoes.revelacao.descricao())),
+			String::from(tr!("Voltar")),
+		];
+		let rodape = tr!("{}{} ou Enter mudam o valor, Esc volta", opcoes.tema.glifos.setas[2], opcoes.tema.glifos.setas[3]);
+		let (item,avanca) = match escolhe(con, tr!("OPÇÕES"), &itens, &mut selecionado, &rodape, &opcoes.teclas)? {
+			Escolha::ITEM(item) => (item,true),
+			Escolha::MUDA(item,avanca) => (item,avanca),
+			Escolha::VOLTA => return Ok(true),
+			Escolha::ABORTA => return Ok(false),
+		};
+		match item {
+			0 => opcoes.lado = vizinho(&lados, &opcoes.lado, avanca),
+			1 => opcoes.arquivo_frota = vizinho(&frotas, &opcoes.arquivo_frota, avanca),
+			2 => opcoes.mapa = vizinho(&mapas, &opcoes.mapa, avanca),
+			3 => opcoes.armas_especiais = !opcoes.armas_especiais,
+			4 => {
+				let nome = vizinho(&relogios, &relogio, avanca);
+				if let Some(&(_,jogada,total,atraso)) = RELOGIOS.iter().find(|r| r.0 == nome) {
+					opcoes.tempo_jogada = jogada.map(Duration::from_secs);
+					opcoes.tempo_total = total.map(Duration::from_secs);
+					opcoes.atraso = Duration::from_millis(atraso);
+				}
+			}
+			5 => {
+				let nome = vizinho(&paletas, &opcoes.tema.paleta.nome, avanca);
+				opcoes.tema.paleta = Tema::paleta(nome).unwrap_or(opcoes.tema.paleta);
+			}
+			6 => {
+				let nome = vizinho(&glifos, &opcoes.tema.glifos.nome, avanca);
+				opcoes.tema.glifos = Tema::glifos(nome).unwrap_or(opcoes.tema.glifos);
+			}
+			7 => {
+				// O arquivo de teclas foi lido no início, só volta a ele
+				let nome = vizinho(&conjuntos, &opcoes.teclas.nome, avanca);
+				opcoes.teclas = Teclas::predefinida(&nome).unwrap_or_else(|| inicial.clone());
+			}
+			8 => idioma::escolhe(vizinho(&Idioma::TODOS, &idioma::atual(), avanca)),
+			9 => opcoes.animacao = !opcoes.animacao,
+			10 => opcoes.som = !opcoes.som,
+			11 => opcoes.neblina = !opcoes.neblina,
+			12 => opcoes.revelacao = vizinho(&Revelacao::TODAS, &opcoes.revelacao, avanca),
+			_ => return Ok(true),
+		}
+	}
+}
+
+
+// Texto das regras, de acordo com as opções atuais
+fn regras(opcoes:&Opcoes) -> Vec<String> {
+	let mut linhas = vec![
+		String::from(tr!("REGRAS")),
+		String::new(),
+		String::from(tr!("Cada jogador esconde a sua frota no próprio tabuleiro.")),
+		String::from(tr!("Na sua vez, mire no tabuleiro inimigo e atire.")),
+		String::from(tr!("Vence quem destruir primeiro todos os pedaços dos navios do outro.")),
+		String::from(tr!("Navios não podem ficar sobre ilhas ou recifes e ninguém atira neles.")),
+	];
+	if opcoes.armas_especiais {
+		linhas.push(String::from(tr!("Bomba atinge 3x3, torpedo segue a linha até um navio, radar detecta navios em 3x3.")));
+	}
+	if opcoes.tempo_jogada.is_some() {
+		linhas.push(String::from(tr!("Se o tempo da jogada acabar, um tiro automático é dado.")));
+	}
+	if opcoes.tempo_total.is_some() {
+		linhas.push(String::from(tr!("Se o tempo total acabar, a partida está perdida.")));
+	}
+	match opcoes.revelacao {
+		Revelacao::CLASSICA => linhas.push(String::from(tr!("Acertos não dizem o navio e o aviso de afundado não diz qual."))),
+		Revelacao::AFUNDADO => linhas.push(String::from(tr!("Acertos não dizem o navio, mas o navio afundado é anunciado."))),
+		Revelacao::TOTAL => {}
+	}
+	if opcoes.neblina {
+		linhas.push(String::from(tr!("Neblina: no lugar do tiro, um navio sem dano pode andar uma casa ou girar.")));
+		linhas.push(String::from(tr!("A água já atingida é só histórico, um navio pode ter entrado ali depois.")));
+	}
+	linhas.push(String::from(tr!("Desafio do dia: a mesma frota do bot para todos, conta só a primeira partida e ganha quem usar menos ataques.")));
+	linhas.push(String::new());
+	linhas.push(String::from(tr!("Esc durante o jogo abre a pausa: continuar, salvar, reiniciar ou sair.")));
+	linhas
+}
+
+
+// Menu de pausa, salvar só faz sentido durante a batalha
+pub fn pausa(con:&mut dyn Console, pode_salvar:bool, teclas:&Teclas) -> Result<Pausa, ErroJogo> {
+	let mut itens = vec![(String::from(tr!("Continuar")), Pausa::CONTINUAR)];
+	if pode_salvar {
+		itens.push((String::from(tr!("Salvar partida")), Pausa::SALVAR));
+	}
+	itens.push((String::from(tr!("Reiniciar")), Pausa::REINICIAR));
+	itens.push((String::from(tr!("Sair para o menu")), Pausa::SAIR));
+
+	let nomes: Vec<String> = itens.iter().map(|(nome,_)| nome.clone()).collect();
+	let mut selecionado = 0;
+	loop {
+		match escolhe(con, tr!("PAUSA"), &nomes, &mut selecionado, tr!("Enter escolhe, Esc continua"), teclas)? {
+			Escolha::ITEM(item) => return Ok(itens[item].1),
+			Escolha::VOLTA => return Ok(Pausa::CONTINUAR),
+			Escolha::ABORTA => return Ok(Pausa::ABORTAR),
+			Escolha::MUDA(_,_) => {}
+		}
+	}
+}
@@ -0,0 +1,264 @@
+/*
+	Variante com neblina e navios que se movem ('--neblina')
+
+	Na sua vez, em vez de atirar, cada jogador pode manobrar um navio sem dano:
+	andar uma casa ou girar, se couber ('pode_mover_navio' e 'pode_girar_navio').
+	Navio atingido fica parado, então os acertos continuam valendo; já os tiros
+	na água viram história, um navio inimigo pode ter entrado ali depois.
+
+	- A grade de acompanhamento continua mostrando a água de cada tiro, sem
+	atualizar, e as células com água antiga podem receber um novo tiro
+	- No mapa da batalha a água atingida volta a ser AGUA e a rodada do tiro fica
+	guardada aqui, do lado de quem atirou
+	- O bot só confia na água vista há até VALIDADE_AGUA rodadas e, se não
+	sobrar alvo, esquece toda a água
+	- O bot manobra, com CHANCE_MANOBRA_BOT, um navio inteiro encostado na
+	última mira do humano
+	- Só contra o bot, e a partida com neblina não é salva
+*/
+
+
+use std::time::Duration;
+
+use crossterm::{QueueableCommand,style};
+use crossterm::event::{Event,KeyEventKind};
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::{Jogo,Jogador,Batalha,Opcoes,Mapa,Impacto,Direcao,AGUA,DESTRUIDO,alvo_valido,posicao_na_tela,
+	desenha_navio,escreve_status,confere_tamanho,redesenha_batalha};
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::teclas::{self,Acao};
+use crate::tema::Tema;
+
+
+// Rodadas em que o bot confia num tiro na água
+const VALIDADE_AGUA: u32 = 8;
+
+// Chance do bot manobrar um navio ameaçado
+const CHANCE_MANOBRA_BOT: f64 = 0.5;
+
+
+// Tiros na água de cada lado, com a rodada em que foram dados
+pub struct Neblina {
+	agua_do_humano: Vec<Vec<Option<u32>>>,		// No tabuleiro do bot
+	agua_do_bot: Vec<Vec<Option<u32>>>,			// No tabuleiro do humano
+}
+
+// Como terminou a escolha da manobra do humano
+pub enum Manobra {
+	FEITA,
+	CANCELADA,
+	ABORTADA,
+}
+
+impl Neblina {
+	pub fn new(lado: usize) -> Neblina {
+		Neblina { agua_do_humano: vec![vec![None; lado]; lado], agua_do_bot: vec![vec![None; lado]; lado] }
+	}
+
+	// Depois de um ataque no tabuleiro de 'alvo' a água atingida volta ao mapa e fica na lembrança de quem atirou
+	pub fn guarda_agua(&mut self, alvo: &Jogador, mapa: &mut Mapa, impacto: &Impacto, rodada: u32) {
+		let agua = match alvo {
+			Jogador::BOT => &mut self.agua_do_humano,
+			Jogador::HUMANO => &mut self.agua_do_bot,
+		};
+		for &(x,y) in impacto.agua.iter() {
+			mapa[y][x] = AGUA;
+			agua[y][x] = Some(rodada);
+		}
+		for &(x,y) in impacto.acertos.iter() {
+			agua[y][x] = None;
+		}
+	}
+
+	// O que o bot sabe do tabuleiro do humano: acertos e a água recente como DESTRUIDO
+	pub fn vista_do_bot(&self, mapa: &Mapa, rodada: u32) -> Mapa {
+		let mut vista = mapa.clone();
+		for (y,linha) in self.agua_do_bot.iter().enumerate() {
+			for (x,&tiro) in linha.iter().enumerate() {
+				if tiro.is_some_and(|tiro| rodada.saturating_sub(tiro) <= VALIDADE_AGUA) {
+					vista[y][x] = DESTRUIDO;
+				}
+			}
+		}
+		match vista.iter().flatten().any(|&celula| alvo_valido(celula)) {
+			true => vista,
+			false => mapa.clone(),
+		}
+	}
+
+	// Redesenha a água dos dois lados; no tabuleiro do humano, só onde não há navio dele
+	pub fn desenha(&self, con: &mut dyn Console, mapa_humano: &Mapa, tema: &Tema) -> Result<(), ErroJogo> {
+		let lado = mapa_humano.len();
+		for (jogador,agua) in [(Jogador::HUMANO, &self.agua_do_bot), (Jogador::BOT, &self.agua_do_humano)] {
+			for y in 0..lado {
+				for x in 0..lado {
+					let coberta = matches!(jogador, Jogador::HUMANO) && mapa_humano[y][x] != AGUA;
+					if agua[y][x].is_some() && !coberta {
+						con
+							.queue( posicao_na_tela(&jogador, x, y, lado) )?
+							.queue( style::PrintStyledContent(tema.agua()) )?;
+					}
+				}
+			}
+		}
+		con.flush()?;
+		Ok(())
+	}
+}
+
+
+// Navios sem nenhum pedaço atingido
+fn navios_inteiros(mapa: &Mapa, jogo: &Jogo, jogador: &Jogador) -> Vec<usize> {
+	(0..jogo.navios(jogador).len())
+		.filter(|&n| jogo.navios(jogador)[n].celulas().iter().all(|&(x,y)| mapa[y][x] == n))
+		.collect()
+}
+
+
+// Anda uma casa na direção dada ou, sem direção, gira; o mapa acompanha
+fn aplica(jogo: &mut Jogo, mapa: &mut Mapa, jogador: &Jogador, qual: usize, direcao: Option<Direcao>) {
+	for (x,y) in jogo.navios(jogador)[qual].celulas() {
+		mapa[y][x] = AGUA;
+	}
+	let navio = &mut jogo.navios_mut(jogador)[qual];
+	match direcao {
+		Some(Direcao::NORTE) => navio.popa_y -= 1,
+		Some(Direcao::SUL) => navio.popa_y += 1,
+		Some(Direcao::LESTE) => navio.popa_x += 1,
+		Some(Direcao::OESTE) => navio.popa_x -= 1,
+		None => navio.gira(),
+	}
+	for (x,y) in navio.celulas() {
+		mapa[y][x] = qual;
+	}
+}
+
+
+// O humano escolhe um navio inteiro e o move uma casa ou gira, no lugar do tiro
+// O relógio da vez continua andando: esgotado, a manobra é cancelada e a batalha cuida do tempo
+pub fn manobra_humano(con: &mut dyn Console, jogo: &mut Jogo, batalha: &mut Batalha, opcoes: &Opcoes) -> Result<Manobra, ErroJogo> {
+	let inteiros = navios_inteiros(&batalha.mapa_humano, jogo, &Jogador::HUMANO);
+	if inteiros.is_empty() {
+		escreve_status(con, 2, tr!("Nenhum navio inteiro para manobrar."))?;
+		return Ok(Manobra::CANCELADA);
+	}
+	let tema = &opcoes.tema;
+	let teclas = &opcoes.teclas;
+	let lado = jogo.lado();
+	let destaca = |con: &mut dyn Console, jogo: &Jogo, qual: usize| -> Result<(), ErroJogo> {
+		for (x,y) in jogo.navios_humano[qual].celulas() {
+			con.queue( posicao_na_tela(&Jogador::HUMANO, x, y, lado) )?.queue( style::PrintStyledContent(tema.destaque()) )?;
+		}
+		escreve_status(con, 2, &tr!("{}: {} anda, {} gira, {} outro, {} volta", tr!(jogo.navios_humano[qual].nome.as_str()),
+			teclas.movimento(), teclas.tecla(Acao::GIRA), teclas.tecla(Acao::MUDA), teclas.tecla(Acao::PAUSA)))
+	};
+
+	let mut corrente = 0;
+	let mut texto_relogio = String::new();
+	destaca(con, jogo, inteiros[corrente])?;
+	loop {
+		let qual = inteiros[corrente];
+
+		// Atualiza o relógio quando muda o segundo mostrado, como na mira
+		if batalha.relogio.ativo() {
+			if batalha.relogio.jogada_esgotada() || batalha.relogio.total_esgotado() {
+				desenha_navio(con, &jogo.navios_humano[qual], tema, false)?;
+				return Ok(Manobra::CANCELADA);
+			}
+			let texto = batalha.relogio.descricao();
+			if texto != texto_relogio {
+				escreve_status(con, 3, &texto)?;
+				texto_relogio = texto;
+			}
+		}
+
+		// Espera um pouco por uma tecla, para o relógio continuar andando
+		if !con.tem_evento(Duration::from_millis(100))? {
+			continue;
+		}
+		let tecla = match con.le_evento()? {
+			Event::Key(tecla) if tecla.kind == KeyEventKind::Press => tecla,
+			Event::Resize(_colunas,_linhas) => {
+				confere_tamanho(con, lado)?;
+				redesenha_batalha(con, jogo, batalha, opcoes)?;
+				destaca(con, jogo, qual)?;
+				texto_relogio.clear();
+				continue;
+			}
+			_ => continue,
+		};
+		if teclas::aborta(&tecla) {
+			return Ok(Manobra::ABORTADA);
+		}
+		let movimento = match opcoes.teclas.acao(&tecla) {
+			Some(Acao::CIMA) => Some(Some(Direcao::NORTE)),
+			Some(Acao::BAIXO) => Some(Some(Direcao::SUL)),
+			Some(Acao::ESQUERDA) => Some(Some(Direcao::OESTE)),
+			Some(Acao::DIREITA) => Some(Some(Direcao::LESTE)),
+			Some(Acao::GIRA) => Some(None),
+			Some(acao @ (Acao::MUDA | Acao::PAUSA)) => {
+				desenha_navio(con, &jogo.navios_humano[qual], tema, false)?;
+				if acao == Acao::PAUSA {
+					escreve_status(con, 2, "")?;
+					return Ok(Manobra::CANCELADA);
+				}
+				corrente = (corrente + 1) % inteiros.len();
+				destaca(con, jogo, inteiros[corrente])?;
+				None
+			}
+			_ => None,
+		};
+		let Some(direcao) = movimento else {
+			continue;
+		};
+		let cabe = match direcao {
+			Some(direcao) => jogo.pode_mover_navio(&Jogador::HUMANO, qual, direcao),
+			None => jogo.pode_girar_navio(&Jogador::HUMANO, qual),
+		};
+		if !cabe {
+			escreve_status(con, 2, tr!("O navio não cabe nessa posição."))?;
+			continue;
+		}
+
+		// Onde o navio estava aparece de novo a água dos tiros do bot
+		for (x,y) in jogo.navios_humano[qual].celulas() {
+			let desenho = match batalha.neblina.as_ref().is_some_and(|neblina| neblina.agua_do_bot[y][x].is_some()) {
+				true => tema.agua(),
+				false => tema.mar(),
+			};
+			con.queue( posicao_na_tela(&Jogador::HUMANO, x, y, lado) )?.queue( style::PrintStyledContent(desenho) )?;
+		}
+		aplica(jogo, &mut batalha.mapa_humano, &Jogador::HUMANO, qual, direcao);
+		desenha_navio(con, &jogo.navios_humano[qual], tema, false)?;
+		escreve_status(con, 2, &tr!("Você manobrou: {}", tr!(jogo.navios_humano[qual].nome.as_str())))?;
+		return Ok(Manobra::FEITA);
+	}
+}
+
+
+// O bot manobra um navio inteiro encostado na última mira do humano, às vezes; diz se manobrou
+pub fn manobra_bot(jogo: &mut Jogo, batalha: &mut Batalha, rng: &mut impl Rng) -> bool {
+	if batalha.neblina.is_none() {
+		return false;
+	}
+	let (mira_x,mira_y) = (batalha.mira_x as i64, batalha.mira_y as i64);
+	let ameacados: Vec<usize> = navios_inteiros(&batalha.mapa_bot, jogo, &Jogador::BOT).into_iter()
+		.filter(|&n| jogo.navios_bot[n].celulas().iter().any(|&(x,y)| (x as i64 - mira_x).abs() <= 1 && (y as i64 - mira_y).abs() <= 1))
+		.collect();
+	if ameacados.is_empty() || !rng.gen_bool(CHANCE_MANOBRA_BOT) {
+		return false;
+	}
+	for qual in ameacados {
+		let mut direcoes = [Direcao::NORTE, Direcao::SUL, Direcao::LESTE, Direcao::OESTE];
+		direcoes.shuffle(rng);
+		if let Some(&direcao) = direcoes.iter().find(|&&direcao| jogo.pode_mover_navio(&Jogador::BOT, qual, direcao)) {
+			aplica(jogo, &mut batalha.mapa_bot, &Jogador::BOT, qual, Some(direcao));
+			return true;
+		}
+	}
+	false
+}
+
@@ -0,0 +1,338 @@
+/*
+	Perfis dos jogadores e placar
+
+	Cada perfil é um arquivo texto 'chave=valor' em
+		$XDG_DATA_HOME/batalha_naval/perfis/<nome>.txt
+	ou, sem XDG_DATA_HOME, em
+		~/.local/share/batalha_naval/perfis/<nome>.txt
+
+	São guardados partidas, vitórias e derrotas contra cada dificuldade do bot,
+	células atingidas e acertos (precisão), tiros nas vitórias e o rating Elo
+	das partidas em rede.
+
+	Na rede o nome vem do navegador, então o perfil precisa ser dele: o navegador que
+	pede um nome ainda sem perfil recebe uma ficha secreta, guardada no perfil, e só
+	quem manda a mesma ficha usa o nome de novo. Perfil criado no terminal não é usado
+	do navegador. Quem não tem nome joga como VISITANTE, sem Elo.
+*/
+
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use rand::Rng;
+
+use crate::bot::Dificuldade;
+
+
+const ELO_INICIAL: f64 = 1500.0;
+const ELO_K: f64 = 32.0;
+
+// Nome de quem joga na rede sem perfil, não tem Elo
+pub const VISITANTE: &str = "visitante";
+
+
+#[derive(Debug)]
+pub struct Perfil {
+	pub nome: String,
+	pub partidas: u32,
+	resultados: BTreeMap<String,(u32,u32)>,		// Dificuldade -> (vitórias, derrotas)
+	tiros: u32,				// Células atingidas pelo jogador
+	acertos: u32,			// Pedaços de navio destruídos
+	tiros_vitorias: u32,	// Soma dos ataques feitos nas partidas vencidas
+	pub elo: f64,
+	partidas_rede: u32,
+	ficha: Option<String>,	// Segredo do navegador dono do perfil, ver 'autentica'
+}
+
+impl Perfil {
+	fn new(nome: &str) -> Perfil {
+		Perfil {
+			nome: String::from(nome),
+			partidas: 0,
+			resultados: BTreeMap::new(),
+			tiros: 0,
+			acertos: 0,
+			tiros_vitorias: 0,
+			elo: ELO_INICIAL,
+			partidas_rede: 0,
+			ficha: None,
+		}
+	}
+
+	// Lê o perfil do disco, ou cria um novo se ainda não existe
+	pub fn carrega(nome: &str) -> Result<Perfil, String> {
+		let arquivo = arquivo_do_perfil(nome)?;
+		match fs::read_to_string(&arquivo) {
+			Ok(texto) => Ok(Perfil::de_texto(nome, &texto)),
+			Err(erro) if erro.kind() == std::io::ErrorKind::NotFound => Ok(Perfil::new(nome)),
+			Err(erro) => Err(tr!("Não conseguiu ler o perfil {}: {}", arquivo.display(), erro)),
+		}
+	}
+
+	// Grava o perfil no disco
+	pub fn grava(&self) -> Result<(), String> {
+		let arquivo = arquivo_do_perfil(&self.nome)?;
+		if let Some(diretorio) = arquivo.parent() {
+			fs::create_dir_all(diretorio)
+				.map_err(|erro| tr!("Não conseguiu criar {}: {}", diretorio.display(), erro))?;
+		}
+		fs::write(&arquivo, self.para_texto())
+			.map_err(|erro| tr!("Não conseguiu gravar o perfil {}: {}", arquivo.display(), erro))
+	}
+
+	fn de_texto(nome: &str, texto: &str) -> Perfil {
+		let mut perfil = Perfil::new(nome);
+		for linha in texto.lines() {
+			let Some((chave,valor)) = linha.split_once('=') else {
+				continue;
+			};
+			let numero = valor.trim().parse::<u32>().unwrap_or(0);
+			match chave.trim() {
+				"partidas" => perfil.partidas = numero,
+				"tiros" => perfil.tiros = numero,
+				"acertos" => perfil.acertos = numero,
+				"tiros_vitorias" => perfil.tiros_vitorias = numero,
+				"partidas_rede" => perfil.partidas_rede = numero,
+				"elo" => perfil.elo = valor.trim().parse().unwrap_or(ELO_INICIAL),
+				"ficha" => perfil.ficha = Some(String::from(valor.trim())),
+				chave => {
+					if let Some(dificuldade) = chave.strip_prefix("vitorias_") {
+						perfil.resultados.entry(String::from(dificuldade)).or_default().0 = numero;
+					} else if let Some(dificuldade) = chave.strip_prefix("derrotas_") {
+						perfil.resultados.entry(String::from(dificuldade)).or_default().1 = numero;
+					}
+				}
+			}
+		}
+		perfil
+	}
+
+	fn para_texto(&self) -> String {
+		let mut texto = format!("partidas={}\ntiros={}\nacertos={}\ntiros_vitorias={}\nelo={:.1}\npartidas_rede={}\n",
+			self.partidas, self.tiros, self.acertos, self.tiros_vitorias, self.elo, self.partidas_rede);
+		for (dificuldade,(vitorias,derrotas)) in self.resultados.iter() {
+			texto.push_str(&format!("vitorias_{}={}\nderrotas_{}={}\n", dificuldade, vitorias, dificuldade, derrotas));
+		}
+		if let Some(ficha) = &self.ficha {
+			texto.push_str(&format!("ficha={}\n", ficha));
+		}
+		texto
+	}
+
+	// Soma o resultado de uma partida contra o bot
+	pub fn registra_partida(&mut self, dificuldade: Dificuldade, venceu: bool, ataques: u32, tiros: u32, acertos: u32) {
+		self.partidas += 1;
+		self.tiros += tiros;
+		self.acertos += acertos;
+		let resultado = self.resultados.entry(String::from(dificuldade.nome())).or_default();
+		if venceu {
+			resultado.0 += 1;
+			self.tiros_vitorias += ataques;
+		} else {
+			resultado.1 += 1;
+		}
+	}
+
+	// Atualiza o Elo depois de uma partida em rede
+	pub fn registra_partida_rede(&mut self, elo_adversario: f64, venceu: bool) {
+		let esperado = 1.0 / (1.0 + 10f64.powf((elo_adversario - self.elo) / 400.0));
+		let obtido = if venceu { 1.0 } else { 0.0 };
+		self.elo += ELO_K * (obtido - esperado);
+		self.partidas_rede += 1;
+	}
+
+	// Confere a ficha de um navegador que quer usar o perfil, 'novo' se o perfil ainda não foi gravado
+	// O perfil novo fica com a 'nova' ficha, devolvida para o navegador guardar; Ok(None) com a ficha certa
+	fn confere_ficha(&mut self, novo: bool, ficha: Option<&str>, nova: String) -> Result<Option<String>, String> {
+		if novo {
+			self.ficha = Some(nova.clone());
+			return Ok(Some(nova));
+		}
+		match (self.ficha.as_deref(), ficha) {
+			(Some(gravada), Some(ficha)) if gravada == ficha => Ok(None),
+			_ => Err(tr!("O nome {} já é de outro jogador", self.nome)),
+		}
+	}
+
+	pub fn vitorias(&self) -> u32 {
+		self.resultados.values().map(|r| r.0).sum()
+	}
+
+	// Percentual de células atingidas que tinham navio
+	pub fn precisao(&self) -> f64 {
+		if self.tiros == 0 { 0.0 } else { 100.0 * self.acertos as f64 / self.tiros as f64 }
+	}
+
+	// Média de ataques para vencer
+	pub fn media_tiros_vitoria(&self) -> Option<f64> {
+		match self.vitorias() {
+			0 => None,
+			v => Some(self.tiros_vitorias as f64 / v as f64),
+		}
+	}
+
+	// Vitórias e derrotas contra uma dificuldade
+	pub fn resultado(&self, dificuldade: Dificuldade) -> (u32,u32) {
+		self.resultados.get(dificuldade.nome()).copied().unwrap_or((0,0))
+	}
+}
+
+
+// Atualiza o Elo dos dois perfis de uma partida em rede, o mesmo perfil dos dois lados ou um visitante não conta
+pub fn registra_confronto(vencedor: &str, perdedor: &str) -> Result<(), String> {
+	if vencedor == perdedor || vencedor == VISITANTE || perdedor == VISITANTE {
+		return Ok(());
+	}
+	let mut ganhou = Perfil::carrega(vencedor)?;
+	let mut perdeu = Perfil::carrega(perdedor)?;
+	let (elo_ganhou, elo_perdeu) = (ganhou.elo, perdeu.elo);
+	ganhou.registra_partida_rede(elo_perdeu, true);
+	perdeu.registra_partida_rede(elo_ganhou, false);
+	ganhou.grava()?;
+	perdeu.grava()
+}
+
+
+// Perfil pedido pelo navegador com a ficha que ele guardou, ver 'confere_ficha'
+// Devolve a ficha nova quando o perfil foi criado agora
+pub fn autentica(nome: &str, ficha: Option<&str>) -> Result<Option<String>, String> {
+	if nome == VISITANTE {
+		return Err(tr!("O nome {} já é de outro jogador", nome));
+	}
+	let novo = !arquivo_do_perfil(nome)?.exists();
+	let mut perfil = Perfil::carrega(nome)?;
+	let nova = perfil.confere_ficha(novo, ficha, format!("{:016x}", rand::thread_rng().gen::<u64>()))?;
+	if nova.is_some() {
+		perfil.grava()?;
+	}
+	Ok(nova)
+}
+
+
+// Nome de perfil vira nome de arquivo, só aceita letras, números, '-' e '_'
+pub fn nome_valido(nome: &str) -> bool {
+	!nome.is_empty()
+		&& nome.chars().count() <= 20
+		&& nome.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+
+// Perfil padrão: usuário do sistema operacional
+pub fn nome_padrao() -> String {
+	std::env::var("USER")
+		.or_else(|_| std::env::var("USERNAME"))
+		.ok()
+		.filter(|nome| nome_valido(nome))
+		.unwrap_or_else(|| String::from("jogador"))
+}
+
+
+// Diretório dos dados do jogo: perfis, partida salva
+pub fn diretorio_dados() -> Result<PathBuf, String> {
+	let base = match std::env::var_os("XDG_DATA_HOME") {
+		Some(dados) if !dados.is_empty() => PathBuf::from(dados),
+		_ => match std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+			Some(casa) => PathBuf::from(casa).join(".local").join("share"),
+			None => return Err(String::from(tr!("Não encontrou o diretório do usuário (HOME)"))),
+		},
+	};
+	Ok(base.join("batalha_naval"))
+}
+
+
+fn diretorio_perfis() -> Result<PathBuf, String> {
+	Ok(diretorio_dados()?.join("perfis"))
+}
+
+
+fn arquivo_do_perfil(nome: &str) -> Result<PathBuf, String> {
+	if !nome_valido(nome) {
+		return Err(tr!("Nome de perfil inválido: '{}'", nome));
+	}
+	Ok(diretorio_perfis()?.join(format!("{}.txt", nome)))
+}
+
+
+// Todos os perfis gravados, ordenados por vitórias e depois por Elo
+pub fn todos() -> Result<Vec<Perfil>, String> {
+	let diretorio = diretorio_perfis()?;
+	let mut perfis = Vec::new();
+	let entradas = match fs::read_dir(&diretorio) {
+		Ok(entradas) => entradas,
+		Err(erro) if erro.kind() == std::io::ErrorKind::NotFound => return Ok(perfis),
+		Err(erro) => return Err(tr!("Não conseguiu ler {}: {}", diretorio.display(), erro)),
+	};
+	for entrada in entradas.flatten() {
+		let caminho = entrada.path();
+		if caminho.extension().and_then(|e| e.to_str()) != Some("txt") {
+			continue;
+		}
+		if let Some(nome) = caminho.file_stem().and_then(|n| n.to_str()) {
+			if let Ok(texto) = fs::read_to_string(&caminho) {
+				perfis.push(Perfil::de_texto(nome, &texto));
+			}
+		}
+	}
+	perfis.sort_by(|a,b| b.vitorias().cmp(&a.vitorias()).then(b.elo.total_cmp(&a.elo)));
+	Ok(perfis)
+}
+
+
+// Linhas do placar, prontas para mostrar
+pub fn linhas_placar() -> Result<Vec<String>, String> {
+	let perfis = todos()?;
+	let mut linhas = vec![
+		String::from(tr!("PLACAR")),
+		String::new(),
+		format!("{:<20} {:>4} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6}", tr!("Jogador"), tr!("Jog"), tr!("Fácil"), tr!("Normal"),
+			tr!("Difíc"), tr!("Adapt"), tr!("Precis"), tr!("Elo")),
+	];
+	for perfil in perfis.iter() {
+		let resultados: Vec<String> = Dificuldade::TODAS.iter()
+			.map(|&d| { let (v,p) = perfil.resultado(d); format!("{}/{}", v, p) })
+			.collect();
+		linhas.push(format!("{:<20} {:>4} {:>6} {:>6} {:>6} {:>6} {:>5.0}% {:>6.0}",
+			perfil.nome, perfil.partidas, resultados[0], resultados[1], resultados[2], resultados[3],
+			perfil.precisao(), perfil.elo));
+		if let Some(media) = perfil.media_tiros_vitoria() {
+			linhas.push(format!("{:<20} {}", "", tr!("média de {} ataques por vitória", format!("{:.1}", media))));
+		}
+	}
+	if perfis.is_empty() {
+		linhas.push(String::from(tr!("Nenhuma partida registrada.")));
+	}
+	Ok(linhas)
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+
+	#[test]
+	fn perfil_da_rede_so_com_a_ficha() {
+		// O primeiro navegador fica com o perfil novo
+		let mut ana = Perfil::new("ana");
+		assert_eq!(ana.confere_ficha(true, None, String::from("abc")), Ok(Some(String::from("abc"))));
+		let ana = Perfil::de_texto("ana", &ana.para_texto());
+		assert_eq!(ana.ficha.as_deref(), Some("abc"));
+
+		// Depois só com a mesma ficha
+		let mut ana = ana;
+		assert_eq!(ana.confere_ficha(false, Some("abc"), String::from("xyz")), Ok(None));
+		assert!(ana.confere_ficha(false, Some("xyz"), String::from("xyz")).is_err());
+		assert!(ana.confere_ficha(false, None, String::from("xyz")).is_err());
+
+		// Perfil do terminal não tem ficha
+		assert!(Perfil::new("dono").confere_ficha(false, Some(""), String::from("xyz")).is_err());
+	}
+
+	#[test]
+	fn visitante_nao_muda_elo() {
+		assert!(autentica(VISITANTE, None).is_err());
+		assert_eq!(registra_confronto(VISITANTE, "ana"), Ok(()));
+		assert_eq!(registra_confronto("ana", VISITANTE), Ok(()));
+	}
+}
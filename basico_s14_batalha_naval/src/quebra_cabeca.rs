@@ -0,0 +1,585 @@
+/*
+	Quebra-cabeça: Batalha Naval solitária (Bimaru)
+
+	- A frota das opções é escondida no mapa das opções, em posições sorteadas com a
+	mesma validação da arrumação e sem navios encostados, nem na diagonal
+	- Ao lado de cada linha e embaixo de cada coluna aparece quantas células de navio
+	ela tem; algumas células já vêm reveladas, o terreno também aparece
+	- O jogador marca cada célula como navio ou água, está resolvido quando as células
+	marcadas como navio são exatamente as da frota
+	- O resolvedor procura, por tentativa e erro com poda, as arrumações da frota que
+	batem com os números e as células reveladas; navios iguais são colocados em ordem
+	para a mesma arrumação não aparecer trocada
+	- O gerador revela células sorteadas até a solução ser única
+	- A dica aponta uma marca errada ou revela uma célula de navio ainda não marcada
+	- Teclas: movimento, fogo alterna navio, água e vazio, '1' água, '2' navio, '0' limpa,
+	ajuda dá uma dica, Esc pausa
+*/
+
+
+use std::time::Instant;
+
+use crossterm::{ExecutableCommand,QueueableCommand};
+use crossterm::cursor;
+use crossterm::event::{Event,KeyCode,KeyEventKind};
+use crossterm::style::{self,ResetColor,SetBackgroundColor,SetForegroundColor};
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::{Jogo,Jogador,Navio,Mapa,Direcao,Desfecho,Opcoes,ILHA,RECIFE};
+use crate::{eh_obstaculo,gerador,limpa_tela,frota,terreno,menu,teclas};
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::menu::Pausa;
+use crate::teclas::Acao;
+
+
+// Tentativas de posição para cada navio e de frota inteira antes de desistir
+const TENTATIVAS_NAVIO: usize = 200;
+const TENTATIVAS_FROTA: usize = 100;
+
+// Sorteios do quebra-cabeça, longe dos fluxos da partida
+const FLUXO_QUEBRA_CABECA: u64 = 1 << 33;
+
+
+// Células com navio (true) ou água (false)
+pub type Grade = Vec<Vec<bool>>;
+
+// Marca do jogador em uma célula
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Marca {
+	VAZIA,
+	AGUA,
+	NAVIO,
+}
+
+
+// O que o jogador recebe: terreno, frota, contagens por linha e coluna e células reveladas
+pub struct Enigma {
+	pub terreno: Mapa,
+	pub frota: Vec<Navio>,
+	pub linhas: Vec<usize>,
+	pub colunas: Vec<usize>,
+	pub reveladas: Vec<(usize,usize,bool)>,		// x, y e se tem navio
+}
+
+impl Enigma {
+	// Enigma sem células reveladas, com as contagens da frota escondida
+	pub fn new(terreno: Mapa, frota: Vec<Navio>, solucao: &Grade) -> Enigma {
+		let linhas = solucao.iter().map(|linha| linha.iter().filter(|&&c| c).count()).collect();
+		let colunas = (0..solucao.len()).map(|x| solucao.iter().filter(|linha| linha[x]).count()).collect();
+		Enigma{ terreno, frota, linhas, colunas, reveladas: Vec::new() }
+	}
+
+	fn lado(&self) -> usize {
+		self.terreno.len()
+	}
+
+	// Célula revelada, com navio ou não
+	pub fn revelada(&self, x: usize, y: usize) -> Option<bool> {
+		self.reveladas.iter().find(|&&(rx,ry,_)| (rx,ry) == (x,y)).map(|&(_,_,navio)| navio)
+	}
+}
+
+
+// Esconde a frota em posições sorteadas, sem navios encostados; None se não coube
+pub fn esconde_frota(frota: &[Navio], terreno: &Mapa, rng: &mut impl Rng) -> Option<Vec<Navio>> {
+	let lado = terreno.len();
+	'frota: for _ in 0 .. TENTATIVAS_FROTA {
+		let mut jogo = Jogo{ terreno: terreno.clone(), navios_humano: Vec::new(), navios_bot: Vec::new() };
+		for navio in frota {
+			jogo.navios_humano.push(navio.clone());
+			let n = jogo.navios_humano.len()-1;
+			let colocado = (0 .. TENTATIVAS_NAVIO).any(|_| {
+				let navio = &mut jogo.navios_humano[n];
+				navio.direcao = Direcao::aleatoria(rng);
+				navio.espelhado = rng.gen_bool(0.5);
+				navio.popa_x = rng.gen_range(0..lado);
+				navio.popa_y = rng.gen_range(0..lado);
+				jogo.pode_colocar_navio(&Jogador::HUMANO, n) && !jogo.encosta_em_navio(n)
+			});
+			if !colocado {
+				continue 'frota;
+			}
+		}
+		return Some(jogo.navios_humano);
+	}
+	None
+}
+
+
+// Grade com as células dos navios
+pub fn grade_da_frota(navios: &[Navio], lado: usize) -> Grade {
+	let mut grade = vec![vec![false; lado]; lado];
+	for (x,y) in navios.iter().flat_map(|navio| navio.celulas()) {
+		grade[y][x] = true;
+	}
+	grade
+}
+
+
+// Novo quebra-cabeça com solução única e a solução
+pub fn gera(frota: &[Navio], terreno: &Mapa, rng: &mut impl Rng) -> Result<(Enigma,Grade), String> {
+	let navios = esconde_frota(frota, terreno, rng)
+		.ok_or_else(|| String::from(tr!("A frota não cabe neste tabuleiro sem navios encostados")))?;
+	let solucao = grade_da_frota(&navios, terreno.len());
+	let mut enigma = Enigma::new(terreno.clone(), frota.to_vec(), &solucao);
+
+	// Revela células em ordem sorteada até sobrar uma solução só
+	let mut celulas: Vec<(usize,usize)> = (0..terreno.len())
+		.flat_map(|y| (0..terreno.len()).map(move |x| (x,y)))
+		.filter(|&(x,y)| !eh_obstaculo(terreno[y][x]))
+		.collect();
+	celulas.shuffle(rng);
+	for (x,y) in celulas {
+		if resolve(&enigma, 2).len() <= 1 {
+			break;
+		}
+		enigma.reveladas.push((x, y, solucao[y][x]));
+	}
+	Ok((enigma,solucao))
+}
+
+
+// Até 'limite' soluções diferentes do enigma
+pub fn resolve(enigma: &Enigma, limite: usize) -> Vec<Grade> {
+	let lado = enigma.lado();
+
+	// Maiores primeiro, eles têm menos lugares e podam mais cedo
+	let mut frota = enigma.frota.clone();
+	frota.sort_by_key(|navio| std::cmp::Reverse(navio.tamanho));
+
+	// Lugares possíveis de cada navio sozinho, validados como na arrumação
+	let lugares: Vec<Vec<Vec<(usize,usize)>>> = frota.iter().map(|navio| {
+		let mut jogo = Jogo{ terreno: enigma.terreno.clone(), navios_humano: vec![navio.clone()], navios_bot: Vec::new() };
+		let mut lugares = Vec::new();
+		for y in 0..lado {
+			for x in 0..lado {
+				for direcao in [Direcao::LESTE, Direcao::SUL, Direcao::OESTE, Direcao::NORTE] {
+					for espelhado in [false, true] {
+						let navio = &mut jogo.navios_humano[0];
+						(navio.popa_x, navio.popa_y, navio.direcao, navio.espelhado) = (x, y, direcao, espelhado);
+						if !jogo.pode_colocar_navio(&Jogador::HUMANO, 0) {
+							continue;
+						}
+						let mut celulas = jogo.navios_humano[0].celulas();
+						celulas.sort();
+						let cabe = celulas.iter().all(|&(cx,cy)| {
+							enigma.revelada(cx, cy) != Some(false)
+								&& celulas.iter().filter(|c| c.1 == cy).count() <= enigma.linhas[cy]
+								&& celulas.iter().filter(|c| c.0 == cx).count() <= enigma.colunas[cx]
+						});
+						if cabe && !lugares.contains(&celulas) {
+							lugares.push(celulas);
+						}
+					}
+				}
+			}
+		}
+		lugares.sort();
+		lugares
+	}).collect();
+
+	// Navio igual ao anterior só vai em lugar depois do dele
+	let repetido: Vec<bool> = (0..frota.len()).map(|k| k > 0 && lugares[k] == lugares[k-1]).collect();
+
+	let mut busca = Busca{
+		enigma,
+		lugares: &lugares,
+		repetido: &repetido,
+		escolhido: vec![0; frota.len()],
+		ocupado: vec![vec![false; lado]; lado],
+		encostado: vec![vec![0; lado]; lado],
+		linhas: vec![0; lado],
+		colunas: vec![0; lado],
+		solucoes: Vec::new(),
+		limite,
+	};
+	busca.tenta(0);
+	busca.solucoes
+}
+
+
+// Estado da busca de soluções, cada navio colocado marca as vizinhas como encostadas
+struct Busca<'a> {
+	enigma: &'a Enigma,
+	lugares: &'a [Vec<Vec<(usize,usize)>>],
+	repetido: &'a [bool],
+	escolhido: Vec<usize>,			// Lugar escolhido para cada navio já colocado
+	ocupado: Grade,
+	encostado: Vec<Vec<u16>>,		// Navios colocados na célula ou em volta dela
+	linhas: Vec<usize>,
+	colunas: Vec<usize>,
+	solucoes: Vec<Grade>,
+	limite: usize,
+}
+
+impl Busca<'_> {
+	// Coloca o navio 'k' em cada lugar possível e segue para o próximo
+	fn tenta(&mut self, k: usize) {
+		if self.solucoes.len() >= self.limite {
+			return;
+		}
+		if k == self.lugares.len() {
+			let confere = self.linhas == self.enigma.linhas && self.colunas == self.enigma.colunas
+				&& self.enigma.reveladas.iter().all(|&(x,y,navio)| self.ocupado[y][x] == navio);
+			if confere && !self.solucoes.contains(&self.ocupado) {
+				self.solucoes.push(self.ocupado.clone());
+			}
+			return;
+		}
+
+		let inicio = if self.repetido[k] { self.escolhido[k-1] + 1 } else { 0 };
+		for l in inicio .. self.lugares[k].len() {
+			let celulas = &self.lugares[k][l];
+			if !self.cabe(celulas) {
+				continue;
+			}
+			self.escolhido[k] = l;
+			self.marca(celulas, true);
+			self.tenta(k+1);
+			self.marca(celulas, false);
+		}
+	}
+
+	// Sem encostar em outro navio, sem passar das contagens e sem deixar navio revelado encostado
+	fn cabe(&self, celulas: &[(usize,usize)]) -> bool {
+		if celulas.iter().any(|&(x,y)| self.encostado[y][x] > 0) {
+			return false;
+		}
+		let mut linhas = self.linhas.clone();
+		let mut colunas = self.colunas.clone();
+		for &(x,y) in celulas {
+			linhas[y] += 1;
+			colunas[x] += 1;
+			if linhas[y] > self.enigma.linhas[y] || colunas[x] > self.enigma.colunas[x] {
+				return false;
+			}
+		}
+		// Uma célula de navio revelada em volta deste navio não poderia ter outro navio
+		!self.enigma.reveladas.iter().any(|&(rx,ry,navio)| {
+			navio && !celulas.contains(&(rx,ry))
+				&& celulas.iter().any(|&(x,y)| x.abs_diff(rx) <= 1 && y.abs_diff(ry) <= 1)
+		})
+	}
+
+	// Coloca (true) ou retira (false) um navio
+	fn marca(&mut self, celulas: &[(usize,usize)], coloca: bool) {
+		let lado = self.ocupado.len();
+		for &(x,y) in celulas {
+			self.ocupado[y][x] = coloca;
+			if coloca {
+				self.linhas[y] += 1;
+				self.colunas[x] += 1;
+			} else {
+				self.linhas[y] -= 1;
+				self.colunas[x] -= 1;
+			}
+			for vy in y.saturating_sub(1) ..= (y+1).min(lado-1) {
+				for vx in x.saturating_sub(1) ..= (x+1).min(lado-1) {
+					if coloca {
+						self.encostado[vy][vx] += 1;
+					} else {
+						self.encostado[vy][vx] -= 1;
+					}
+				}
+			}
+		}
+	}
+}
+
+
+// Uma marca errada, ou senão uma célula de navio ainda não marcada
+pub fn dica(solucao: &Grade, marcas: &[Vec<Marca>]) -> Option<(usize,usize)> {
+	let celulas = || (0..solucao.len()).flat_map(|y| (0..solucao.len()).map(move |x| (x,y)));
+	celulas().find(|&(x,y)| matches!((marcas[y][x], solucao[y][x]), (Marca::NAVIO,false) | (Marca::AGUA,true)))
+		.or_else(|| celulas().find(|&(x,y)| solucao[y][x] && marcas[y][x] == Marca::VAZIA))
+}
+
+// Resolvido quando as marcas de navio são exatamente a solução, o resto pode ficar vazio
+pub fn resolvido(solucao: &Grade, marcas: &[Vec<Marca>]) -> bool {
+	solucao.iter().zip(marcas).all(|(linha,marcas)| linha.iter().zip(marcas).all(|(&navio,&marca)| navio == (marca == Marca::NAVIO)))
+}
+
+
+// Partida do quebra-cabeça em andamento
+struct Tabuleiro<'a> {
+	enigma: &'a Enigma,
+	marcas: Vec<Vec<Marca>>,
+	mira_x: usize,
+	mira_y: usize,
+	dicas: u32,
+	aviso: String,
+}
+
+impl Tabuleiro<'_> {
+	// Moldura, células, contagens e linhas de status
+	fn desenha(&self, con: &mut dyn Console, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+		let tema = &opcoes.tema;
+		let glifos = tema.glifos;
+		let lado = self.enigma.lado();
+		let largura = 2*lado;
+		limpa_tela(con)?;
+
+		let (cor_frente,cor_fundo) = tema.moldura();
+		con
+			.queue( SetForegroundColor(cor_frente) )?
+			.queue( SetBackgroundColor(cor_fundo) )?
+			.queue( cursor::MoveTo(0,0) )?
+			.queue( style::Print(glifos.cantos_sup[0]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_sup[2]) )?;
+		for y in 1 ..= lado as u16 {
+			con
+				.queue( cursor::MoveTo(0,y) )?
+				.queue( style::Print(glifos.vertical) )?
+				.queue( style::Print(" ".repeat(largura)) )?
+				.queue( style::Print(glifos.vertical) )?;
+		}
+		con
+			.queue( cursor::MoveTo(0,lado as u16+1) )?
+			.queue( style::Print(glifos.cantos_inf[0]) )?
+			.queue( style::Print(String::from(glifos.horizontal).repeat(largura)) )?
+			.queue( style::Print(glifos.cantos_inf[2]) )?;
+
+		for y in 0..lado {
+			for x in 0..lado {
+				let posicao = cursor::MoveTo(1+2*x as u16, 1+y as u16);
+				match (self.enigma.terreno[y][x], self.marcas[y][x]) {
+					(ILHA, _) => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.ilha()) )?; }
+					(RECIFE, _) => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.recife()) )?; }
+					(_, Marca::AGUA) => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.agua()) )?; }
+					(_, Marca::NAVIO) => { con.queue(posicao)?.queue( style::PrintStyledContent(tema.marcado()) )?; }
+					(_, Marca::VAZIA) => {}
+				}
+			}
+		}
+		con.queue( ResetColor )?;
+
+		// Contagens: à direita de cada linha e embaixo de cada coluna
+		for (y,total) in self.enigma.linhas.iter().enumerate() {
+			con
+				.queue( cursor::MoveTo(2*lado as u16+3, 1+y as u16) )?
+				.queue( style::Print(total) )?;
+		}
+		let colunas: String = self.enigma.colunas.iter().map(|total| format!("{:>2}", total)).collect();
+		con
+			.queue( cursor::MoveTo(0, lado as u16+2) )?
+			.queue( style::Print(format!(" {}", colunas)) )?;
+
+		let teclas = &opcoes.teclas;
+		con
+			.queue( cursor::MoveTo(0, lado as u16+4) )?
+			.queue( style::Print(tr!("Mova com {}, {} marca, '1' água, '2' navio, '0' limpa, {} dica, {} pausa",
+				teclas.movimento(), teclas.tecla(Acao::FOGO), teclas.tecla(Acao::AJUDA), teclas.tecla(Acao::PAUSA))) )?
+			.queue( cursor::MoveTo(0, lado as u16+5) )?
+			.queue( style::Print(&self.aviso) )?
+			.queue( cursor::MoveTo(1+2*self.mira_x as u16, 1+self.mira_y as u16) )?;
+		con.flush()?;
+		Ok(())
+	}
+
+	// Muda a marca da mira, terreno e células reveladas não mudam
+	fn marca(&mut self, marca: Option<Marca>) {
+		let (x,y) = (self.mira_x, self.mira_y);
+		if eh_obstaculo(self.enigma.terreno[y][x]) || self.enigma.revelada(x, y).is_some() {
+			return;
+		}
+		self.marcas[y][x] = marca.unwrap_or(match self.marcas[y][x] {
+			Marca::VAZIA => Marca::NAVIO,
+			Marca::NAVIO => Marca::AGUA,
+			Marca::AGUA => Marca::VAZIA,
+		});
+	}
+}
+
+
+// Tamanho do terminal para o tabuleiro, as contagens e as linhas de status
+fn confere_tamanho(con: &mut dyn Console, lado: usize) -> Result<(), ErroJogo> {
+	let (largura_minima,altura_minima) = (2*lado as u16 + 6, lado as u16 + 7);
+	let (largura,altura) = con.tamanho()?;
+	if largura < largura_minima || altura < altura_minima {
+		return Err(ErroJogo::TAMANHO(largura_minima, altura_minima));
+	}
+	Ok(())
+}
+
+
+// Quebra-cabeças seguidos com a frota e o mapa das opções, até o jogador voltar ao menu
+pub fn joga(con: &mut dyn Console, opcoes: &Opcoes) -> Result<Desfecho, ErroJogo> {
+	loop {
+		let semente = opcoes.semente.unwrap_or_else(|| rand::thread_rng().gen());
+		let gerado = frota::carrega(opcoes.arquivo_frota.as_deref()).and_then(|frota| {
+			let terreno = terreno::carrega(opcoes.mapa.as_deref(), opcoes.lado)?;
+			gera(&frota, &terreno, &mut gerador(semente, FLUXO_QUEBRA_CABECA))
+		});
+		let (enigma,solucao) = match gerado {
+			Ok(gerado) => gerado,
+			Err(erro) => {
+				menu::mostra_texto(con, &[erro])?;
+				return Ok(Desfecho::MENU);
+			}
+		};
+		if let Err(erro @ ErroJogo::TAMANHO(..)) = confere_tamanho(con, enigma.lado()) {
+			menu::mostra_texto(con, &[erro.to_string()])?;
+			return Ok(Desfecho::MENU);
+		}
+
+		match resolve_na_tela(con, opcoes, &enigma, &solucao)? {
+			Desfecho::CONTINUA => {
+				writeln!(con, "\r{}", tr!("Semente da partida: {}", semente))?;
+				match menu::pergunta(con, tr!("Jogar novamente? (s/n)"))? {
+					Some(true) => {}
+					Some(false) => return Ok(Desfecho::MENU),
+					None => return Ok(Desfecho::ABORTADO),
+				}
+			}
+			Desfecho::REINICIA => {}
+			desfecho => return Ok(desfecho),
+		}
+	}
+}
+
+
+// O jogador marca as células até resolver, CONTINUA quando resolveu
+pub fn resolve_na_tela(con: &mut dyn Console, opcoes: &Opcoes, enigma: &Enigma, solucao: &Grade) -> Result<Desfecho, ErroJogo> {
+	let lado = enigma.lado();
+	let mut marcas = vec![vec![Marca::VAZIA; lado]; lado];
+	for &(x,y,navio) in &enigma.reveladas {
+		marcas[y][x] = if navio { Marca::NAVIO } else { Marca::AGUA };
+	}
+	let mut tabuleiro = Tabuleiro{ enigma, marcas, mira_x: 0, mira_y: 0, dicas: 0, aviso: String::new() };
+	let inicio = Instant::now();
+
+	tabuleiro.desenha(con, opcoes)?;
+	con.modo_bruto(true)?;
+	loop {
+		let tecla = match con.le_evento()? {
+			Event::Key(tecla) if tecla.kind == KeyEventKind::Press => tecla,
+			Event::Resize(_,_) => {
+				confere_tamanho(con, lado)?;
+				tabuleiro.desenha(con, opcoes)?;
+				continue;
+			}
+			_ => continue,
+		};
+		if teclas::aborta(&tecla) {
+			con.modo_bruto(false)?;
+			return Ok(Desfecho::ABORTADO);
+		}
+
+		tabuleiro.aviso.clear();
+		match (tecla.code, opcoes.teclas.acao(&tecla)) {
+			(_, Some(Acao::CIMA)) => tabuleiro.mira_y = tabuleiro.mira_y.saturating_sub(1),
+			(_, Some(Acao::BAIXO)) => tabuleiro.mira_y = (tabuleiro.mira_y+1).min(lado-1),
+			(_, Some(Acao::DIREITA)) => tabuleiro.mira_x = (tabuleiro.mira_x+1).min(lado-1),
+			(_, Some(Acao::ESQUERDA)) => tabuleiro.mira_x = tabuleiro.mira_x.saturating_sub(1),
+			(_, Some(Acao::FOGO)) => tabuleiro.marca(None),
+			(_, Some(Acao::AJUDA)) => {
+				tabuleiro.dicas += 1;
+				tabuleiro.aviso = match dica(solucao, &tabuleiro.marcas) {
+					Some((x,y)) if tabuleiro.marcas[y][x] != Marca::VAZIA => {
+						(tabuleiro.mira_x, tabuleiro.mira_y) = (x,y);
+						String::from(tr!("Dica: esta marca está errada"))
+					}
+					Some((x,y)) => {
+						(tabuleiro.mira_x, tabuleiro.mira_y) = (x,y);
+						String::from(tr!("Dica: aqui tem navio"))
+					}
+					None => String::new(),
+				};
+			}
+			(_, Some(Acao::PAUSA)) => {
+				con.modo_bruto(false)?;
+				match menu::pausa(con, false, &opcoes.teclas)? {
+					Pausa::CONTINUAR | Pausa::SALVAR => {}
+					Pausa::REINICIAR => return Ok(Desfecho::REINICIA),
+					Pausa::SAIR => return Ok(Desfecho::MENU),
+					Pausa::ABORTAR => return Ok(Desfecho::ABORTADO),
+				}
+				con.modo_bruto(true)?;
+			}
+			(KeyCode::Char('1'), _) => tabuleiro.marca(Some(Marca::AGUA)),
+			(KeyCode::Char('2'), _) => tabuleiro.marca(Some(Marca::NAVIO)),
+			(KeyCode::Char('0'), _) => tabuleiro.marca(Some(Marca::VAZIA)),
+			_ => continue,
+		}
+
+		if resolvido(solucao, &tabuleiro.marcas) {
+			tabuleiro.aviso = tr!("Resolvido em {}s, com {} dicas!", inicio.elapsed().as_secs(), tabuleiro.dicas);
+			tabuleiro.desenha(con, opcoes)?;
+			con.modo_bruto(false)?;
+			con.execute( cursor::MoveTo(0, lado as u16+6) )?;
+			return Ok(Desfecho::CONTINUA);
+		}
+		tabuleiro.desenha(con, opcoes)?;
+	}
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+	use crate::testes::enigma_ambiguo;
+
+	#[test]
+	fn resolvedor_conta_as_solucoes() {
+		let mut enigma = enigma_ambiguo();
+		assert_eq!(enigma.linhas, [1,1,1,1,0,0]);
+		assert_eq!(enigma.colunas, [2,0,2,0,0,0]);
+		// Os dois submarinos trocados são a mesma solução
+		assert_eq!(resolve(&enigma, 10).len(), 2);
+
+		// Uma célula revelada decide
+		enigma.reveladas.push((0, 0, true));
+		let solucoes = resolve(&enigma, 10);
+		assert_eq!(solucoes.len(), 1);
+		assert!(solucoes[0][1][0] && solucoes[0][3][2]);
+
+		// Água revelada em uma célula que precisa de navio não tem solução
+		enigma.reveladas.push((0, 1, false));
+		assert!(resolve(&enigma, 10).is_empty());
+	}
+
+	#[test]
+	fn quebra_cabeca_gerado_tem_solucao_unica() {
+		let frota = frota::carrega(None).unwrap();
+		for semente in 0..3 {
+			let (enigma,solucao) = gera(&frota, &terreno::mar_aberto(10), &mut gerador(semente, 0)).unwrap();
+			assert_eq!(resolve(&enigma, 2), std::slice::from_ref(&solucao));
+			assert_eq!(enigma.linhas.iter().sum::<usize>(), 14);
+			assert!(enigma.reveladas.iter().all(|&(x,y,navio)| solucao[y][x] == navio));
+			// Navios não se encostam, nem na diagonal: cada célula de navio tem no máximo duas vizinhas de navio
+			for y in 0..10 {
+				for x in 0..10 {
+					if solucao[y][x] {
+						let diagonais = [(-1,-1), (-1,1), (1,-1), (1,1)].iter()
+							.filter(|&&(dx,dy)| solucao.get((y as i64+dy) as usize).and_then(|l| l.get((x as i64+dx) as usize)) == Some(&true))
+							.count();
+						assert_eq!(diagonais, 0);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn dica_aponta_marca_errada_e_depois_navio() {
+		use Marca::*;
+		let mut enigma = enigma_ambiguo();
+		enigma.reveladas.push((0, 0, true));
+		let solucao = resolve(&enigma, 1).pop().unwrap();
+		let mut marcas = vec![vec![VAZIA; 6]; 6];
+		marcas[0][0] = NAVIO;
+		marcas[5][5] = NAVIO;
+		assert_eq!(dica(&solucao, &marcas), Some((5,5)));
+		marcas[5][5] = AGUA;
+		assert_eq!(dica(&solucao, &marcas), Some((0,1)));
+		assert!(!resolvido(&solucao, &marcas));
+		for (x,y) in [(0,1), (2,2), (2,3)] {
+			marcas[y][x] = NAVIO;
+		}
+		assert!(resolvido(&solucao, &marcas));
+		assert_eq!(dica(&solucao, &marcas), None);
+	}
+}
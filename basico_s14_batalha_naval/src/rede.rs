@@ -0,0 +1,657 @@
+/*
+	Partida em rede: um humano no terminal e outro no navegador
+
+	- Com '--servidor <porta>' o "Novo jogo" espera um adversário no navegador, que toma
+	o lugar do bot; o endereço para abrir no navegador aparece no terminal
+	- HTTP e WebSocket sobre 'std::net', sem dependências e sem internet:
+		GET /		página assets/navegador.html, com HTML, CSS e JS embutidos
+		GET /ws		WebSocket da partida
+	- O motor é o mesmo do terminal: a frota do adversário é sorteada com a semente da
+	partida e pode ser sorteada de novo até ele dizer que está pronto
+	- Mensagens do navegador, em texto:
+		nome <nome> [ficha]	perfil do adversário, para o Elo; o perfil novo responde
+						{"tipo":"perfil", "nome":"...", "ficha":"..."}, ver perfil.rs
+		sorteia			sorteia de novo a frota, durante a arrumação
+		pronto			frota arrumada
+		tiro <x> <y>	ataque, na vez do adversário
+	- Mensagens para o navegador, em JSON:
+		{"tipo":"estado", "fase":"espera|arrumacao|batalha|fim", "vez":bool, "lado":n,
+		 "meu":[...], "inimigo":[...], "mensagem":"..."}
+		cada tabuleiro é uma lista de linhas com um caractere por célula:
+		'~' mar, letra do navio, 'X' acerto, 'o' água, '#' ilha, '%' recife
+		na partida de vários jogadores (ver equipes.rs) "inimigo" vem vazio e os outros
+		tabuleiros vêm em "alvos":[{"jogador":n, "nome":"...", "aliado":bool, "vivo":bool,
+		"linhas":[...]}], o tiro é 'tiro <x> <y> <jogador>'
+	- Quadro do navegador sem máscara, acima de 64 KiB, ou de controle acima de 125 bytes, fecha a conexão
+	- Na rede só existe o tiro simples e não há relógio; a partida não pode ser salva
+	e o resultado muda o Elo dos dois perfis (ver perfil.rs)
+	- Só um adversário por vez, recarregar a página no meio da partida perde a conexão;
+	salas, espectadores e volta depois de uma queda ficam no saguão, ver saguao.rs
+*/
+
+
+use std::io::{self,ErrorKind,Read,Write};
+use std::net::{TcpListener,TcpStream,UdpSocket};
+use std::time::{Duration,Instant};
+
+use crossterm::event::{Event,KeyCode,KeyEventKind};
+use rand::Rng;
+
+use crate::{Jogo,Jogador,Batalha,Desfecho,Mapa,Navio,AGUA,DESTRUIDO,ILHA,RECIFE};
+use crate::{alvo_valido,escreve_status,limpa_tela,sorteia_navios,menu,perfil,teclas};
+use crate::console::Console;
+use crate::erro::ErroJogo;
+
+
+const PAGINA: &str = include_str!("../assets/navegador.html");
+
+// Constante do protocolo WebSocket (RFC 6455) para a resposta do aperto de mão
+const GUID_WEBSOCKET: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// Espera máxima por uma mensagem antes de olhar o teclado de novo
+const ESPERA: Duration = Duration::from_millis(100);
+
+// Maior quadro aceito do navegador, as mensagens do jogo têm poucos bytes
+const MAIOR_QUADRO: usize = 64 * 1024;
+
+// Maior quadro de controle (close, ping, pong), pela RFC 6455
+const MAIOR_CONTROLE: usize = 125;
+
+// Maior cabeçalho HTTP aceito
+const MAIOR_CABECALHO: usize = 16 * 1024;
+
+
+// Fase da partida, como o navegador mostra os tabuleiros
+pub enum Fase {
+	ESPERA,				// Sala do saguão com um jogador só
+	ARRUMACAO,
+	BATALHA,
+	FIM,
+}
+
+impl Fase {
+	// Nome no JSON
+	pub fn nome(&self) -> &'static str {
+		match self {
+			Fase::ESPERA => "espera",
+			Fase::ARRUMACAO => "arrumacao",
+			Fase::BATALHA => "batalha",
+			Fase::FIM => "fim",
+		}
+	}
+}
+
+
+// Adversário conectado pelo WebSocket
+pub struct Remoto {
+	fluxo: TcpStream,
+	entrada: Vec<u8>,			// Bytes recebidos que ainda não formam um quadro
+	pub nome: String,			// Perfil do adversário
+}
+
+impl Remoto {
+	// Envia um texto em um quadro WebSocket, do servidor vai sem máscara
+	pub fn envia(&mut self, texto: &str) -> io::Result<()> {
+		let dados = texto.as_bytes();
+		let mut quadro = vec![0x81];
+		match dados.len() {
+			n if n < 126 => quadro.push(n as u8),
+			n if n <= u16::MAX as usize => {
+				quadro.push(126);
+				quadro.extend_from_slice(&(n as u16).to_be_bytes());
+			}
+			n => {
+				quadro.push(127);
+				quadro.extend_from_slice(&(n as u64).to_be_bytes());
+			}
+		}
+		quadro.extend_from_slice(dados);
+		self.fluxo.write_all(&quadro)
+	}
+
+	// Próxima mensagem de texto, None se nada chegou no tempo de espera
+	pub fn recebe(&mut self) -> io::Result<Option<String>> {
+		loop {
+			let proximo = match quadro(&self.entrada) {
+				Ok(proximo) => proximo,
+				Err(erro) => {
+					// Fecha com 1002, erro de protocolo, no quadro sem máscara, ou com 1009, mensagem grande demais
+					let codigo: u16 = if self.entrada[1] & 0x80 == 0 { 1002 } else { 1009 };
+					let [alto, baixo] = codigo.to_be_bytes();
+					let _ = self.fluxo.write_all(&[0x88, 2, alto, baixo]);
+					return Err(erro);
+				}
+			};
+			if let Some((opcode,conteudo,tamanho)) = proximo {
+				self.entrada.drain(..tamanho);
+				match opcode {
+					0x1 => return Ok(Some(String::from_utf8_lossy(&conteudo).into_owned())),
+					0x8 => return Err(io::Error::new(ErrorKind::ConnectionAborted, "fechou")),
+					0x9 => {
+						// Ping é respondido com pong, com o mesmo conteúdo, que 'quadro' limita a 125 bytes
+						let mut pong = vec![0x8A, conteudo.len() as u8];
+						pong.extend_from_slice(&conteudo);
+						self.fluxo.write_all(&pong)?;
+					}
+					_ => {}			// Pong, binário e continuação são ignorados
+				}
+				continue;
+			}
+			let mut bloco = [0u8;4096];
+			match self.fluxo.read(&mut bloco) {
+				Ok(0) => return Err(io::Error::new(ErrorKind::ConnectionAborted, "fechou")),
+				Ok(n) => self.entrada.extend_from_slice(&bloco[..n]),
+				Err(erro) if matches!(erro.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Ok(None),
+				Err(erro) => return Err(erro),
+			}
+		}
+	}
+
+	// Espera a próxima mensagem, atendendo o teclado do terminal; 'nome' é tratada aqui mesmo
+	// Err(desfecho) se o humano abortou ou o adversário desconectou
+	pub fn espera_mensagem(&mut self, con: &mut dyn Console) -> Result<Result<String,Desfecho>, ErroJogo> {
+		loop {
+			if con.tem_evento(Duration::ZERO)? {
+				if let Event::Key(tecla) = con.le_evento()? {
+					if tecla.kind == KeyEventKind::Press && teclas::aborta(&tecla) {
+						return Ok(Err(Desfecho::ABORTADO));
+					}
+				}
+				continue;
+			}
+			match self.recebe() {
+				Ok(Some(mensagem)) => {
+					let partes: Vec<&str> = mensagem.split_whitespace().collect();
+					let escolhido = match partes[..] {
+						["nome", nome] => self.escolhe_nome(nome, None),
+						["nome", nome, ficha] => self.escolhe_nome(nome, Some(ficha)),
+						_ => return Ok(Ok(mensagem)),
+					};
+					if escolhido.is_err() {
+						return desconectou(con);
+					}
+				}
+				Ok(None) => {}
+				Err(_) => return desconectou(con),
+			}
+		}
+	}
+
+	// Usa o perfil pedido pelo navegador se ele é seu, senão continua visitante e recebe o erro
+	pub fn escolhe_nome(&mut self, nome: &str, ficha: Option<&str>) -> io::Result<()> {
+		match perfil::autentica(nome, ficha) {
+			Ok(nova) => {
+				self.nome = String::from(nome);
+				match nova {
+					Some(ficha) => self.envia(&format!("{{\"tipo\":\"perfil\",\"nome\":{},\"ficha\":{}}}", json_texto(nome), json_texto(&ficha))),
+					None => Ok(()),
+				}
+			}
+			Err(erro) => self.envia(&format!("{{\"tipo\":\"erro\",\"mensagem\":{}}}", json_texto(&erro))),
+		}
+	}
+
+	// Envia o que o adversário vê: a frota dele e só os tiros dele no tabuleiro do humano
+	pub fn envia_estado(&mut self, jogo: &Jogo, batalha: Option<&Batalha>, fase: Fase, vez: bool, mensagem: &str) -> io::Result<()> {
+		self.envia(&estado(jogo, batalha, &Jogador::BOT, false, fase, vez, mensagem))
+	}
+}
+
+
+// Estado da partida em JSON, visto pelo jogador 'meu': a frota dele inteira e do outro só os tiros
+// O espectador também não vê a frota de 'meu', no fim todos veem tudo
+pub fn estado(jogo: &Jogo, batalha: Option<&Batalha>, meu: &Jogador, espectador: bool, fase: Fase, vez: bool, mensagem: &str) -> String {
+	let fim = matches!(fase, Fase::FIM);
+	let outro = match meu {
+		Jogador::HUMANO => Jogador::BOT,
+		Jogador::BOT => Jogador::HUMANO,
+	};
+	let tabuleiro = |jogador: &Jogador, mostra_navios: bool| {
+		let tiros = batalha.map(|b| match jogador {
+			Jogador::HUMANO => &b.mapa_humano,
+			Jogador::BOT => &b.mapa_bot,
+		});
+		let navios = jogo.navios(jogador);
+		let linhas = linhas(navios, &jogo.mapeia(navios, None), tiros, mostra_navios);
+		format!("[{}]", linhas.iter().map(|l| json_texto(l)).collect::<Vec<_>>().join(","))
+	};
+	format!("{{\"tipo\":\"estado\",\"fase\":\"{}\",\"vez\":{},\"lado\":{},\"meu\":{},\"inimigo\":{},\"mensagem\":{}}}",
+		fase.nome(), vez, jogo.lado(), tabuleiro(meu, fim || !espectador), tabuleiro(&outro, fim), json_texto(mensagem))
+}
+
+
+
+// Abre a porta e espera um adversário, Err(desfecho) se não foi possível ou o humano desistiu
+pub fn conecta(con: &mut dyn Console, porta: u16) -> Result<Result<Remoto,Desfecho>, ErroJogo> {
+	match abre(porta) {
+		Ok(ouvinte) => espera_adversario(con, &ouvinte),
+		Err(erro) => {
+			menu::mostra_texto(con, &[tr!("Não conseguiu abrir a porta {}: {}", porta, erro)])?;
+			Ok(Err(Desfecho::MENU))
+		}
+	}
+}
+
+
+// Abre a porta do servidor em todas as interfaces, para a rede local
+pub fn abre(porta: u16) -> io::Result<TcpListener> {
+	let ouvinte = TcpListener::bind(("0.0.0.0", porta))?;
+	ouvinte.set_nonblocking(true)?;
+	Ok(ouvinte)
+}
+
+
+// Endereço para abrir no navegador, com o IP da rede local quando houver
+pub fn endereco(ouvinte: &TcpListener) -> String {
+	let porta = ouvinte.local_addr().map_or(0, |a| a.port());
+	// Conectar UDP não envia nada, só escolhe a interface de saída
+	let ip = UdpSocket::bind("0.0.0.0:0")
+		.and_then(|socket| { socket.connect("192.0.2.1:80")?; socket.local_addr() })
+		.map_or_else(|_| String::from("localhost"), |a| a.ip().to_string());
+	format!("http://{}:{}/", ip, porta)
+}
+
+
+// Atende o navegador até um adversário abrir o WebSocket, Esc volta ao menu
+pub fn espera_adversario(con: &mut dyn Console, ouvinte: &TcpListener) -> Result<Result<Remoto,Desfecho>, ErroJogo> {
+	limpa_tela(con)?;
+	writeln!(con, "{}", tr!("Aguardando adversário em {}", endereco(ouvinte)))?;
+	writeln!(con, "{}", tr!("Esc volta ao menu"))?;
+	con.modo_bruto(true)?;
+
+	let resultado = loop {
+		if con.tem_evento(ESPERA)? {
+			if let Event::Key(tecla) = con.le_evento()? {
+				if tecla.kind == KeyEventKind::Press && teclas::aborta(&tecla) {
+					break Err(Desfecho::ABORTADO);
+				}
+				if tecla.kind == KeyEventKind::Press && tecla.code == KeyCode::Esc {
+					break Err(Desfecho::MENU);
+				}
+			}
+			continue;
+		}
+		match ouvinte.accept() {
+			// Um pedido com problema não interrompe a espera
+			Ok((fluxo,_)) => {
+				if let Ok(Some(remoto)) = atende(fluxo) {
+					break Ok(remoto);
+				}
+			}
+			Err(erro) if erro.kind() == ErrorKind::WouldBlock => {}
+			Err(erro) => return Err(erro.into()),
+		}
+	};
+
+	con.modo_bruto(false)?;
+	Ok(resultado)
+}
+
+
+// Adversário arruma a frota, já sorteada, até mandar 'pronto'
+pub fn arrumacao(con: &mut dyn Console, remoto: &mut Remoto, jogo: &mut Jogo, rng: &mut impl Rng) -> Result<Result<(),Desfecho>, ErroJogo> {
+	escreve_status(con, 2, tr!("Aguardando o adversário arrumar a frota..."))?;
+	con.modo_bruto(true)?;
+	let resultado = loop {
+		let mensagem = match remoto.espera_mensagem(con)? {
+			Ok(mensagem) => mensagem,
+			Err(desfecho) => break Err(desfecho),
+		};
+		match mensagem.as_str() {
+			"pronto" => break Ok(()),
+			"sorteia" => sorteia_navios(jogo, &Jogador::BOT, rng),
+			_ => {}
+		}
+		if remoto.envia_estado(jogo, None, Fase::ARRUMACAO, false, tr!("Sorteie a frota e clique em Pronto")).is_err() {
+			break desconectou(con)?;
+		}
+	};
+	con.modo_bruto(false)?;
+	Ok(resultado)
+}
+
+
+// Vez do adversário: espera um tiro em uma célula ainda não atingida do humano
+pub fn espera_tiro(con: &mut dyn Console, remoto: &mut Remoto, jogo: &Jogo, batalha: &Batalha, aviso: &str) -> Result<Result<(usize,usize),Desfecho>, ErroJogo> {
+	let mut aviso = aviso;
+	loop {
+		if remoto.envia_estado(jogo, Some(batalha), Fase::BATALHA, true, aviso).is_err() {
+			return desconectou(con);
+		}
+		let mensagem = match remoto.espera_mensagem(con)? {
+			Ok(mensagem) => mensagem,
+			Err(desfecho) => return Ok(Err(desfecho)),
+		};
+		let partes: Vec<&str> = mensagem.split_whitespace().collect();
+		let alvo = match partes[..] {
+			["tiro", x, y] => x.parse::<usize>().ok().zip(y.parse::<usize>().ok()),
+			_ => None,
+		};
+		if let Some((x,y)) = alvo {
+			if x < jogo.lado() && y < jogo.lado() && alvo_valido(batalha.mapa_humano[y][x]) {
+				return Ok(Ok((x,y)));
+			}
+		}
+		aviso = tr!("Alvo inválido, escolha outra célula");
+	}
+}
+
+
+// Avisa que o adversário saiu e volta ao menu
+pub fn desconectou<T>(con: &mut dyn Console) -> Result<Result<T,Desfecho>, ErroJogo> {
+	con.modo_bruto(false)?;
+	menu::mostra_texto(con, &[String::from(tr!("O adversário desconectou."))])?;
+	Ok(Err(Desfecho::MENU))
+}
+
+
+// Responde um pedido HTTP: a página, o WebSocket ou 404
+pub fn atende(mut fluxo: TcpStream) -> io::Result<Option<Remoto>> {
+	fluxo.set_nonblocking(false)?;
+	fluxo.set_read_timeout(Some(Duration::from_secs(2)))?;
+	let pedido = le_cabecalho(&mut fluxo)?;
+	let (resposta, websocket) = resposta(&pedido);
+	fluxo.write_all(&resposta)?;
+	fluxo.flush()?;
+	match websocket {
+		true => {
+			fluxo.set_read_timeout(Some(ESPERA))?;
+			Ok(Some(Remoto{ fluxo, entrada: Vec::new(), nome: String::from(perfil::VISITANTE) }))
+		}
+		false => Ok(None),
+	}
+}
+
+
+// Lê o cabeçalho HTTP até a linha vazia, byte a byte para não consumir nada depois dele
+fn le_cabecalho(fluxo: &mut TcpStream) -> io::Result<String> {
+	let mut dados = Vec::new();
+	let mut byte = [0u8;1];
+	while !dados.ends_with(b"\r\n\r\n") {
+		if fluxo.read(&mut byte)? == 0 {
+			break;
+		}
+		dados.push(byte[0]);
+		if dados.len() > MAIOR_CABECALHO {
+			return Err(io::Error::new(ErrorKind::InvalidData, "cabeçalho grande demais"));
+		}
+	}
+	Ok(String::from_utf8_lossy(&dados).into_owned())
+}
+
+
+// Resposta a um cabeçalho HTTP e se ela abre o WebSocket
+fn resposta(pedido: &str) -> (Vec<u8>, bool) {
+	let mut linhas = pedido.lines();
+	let caminho = linhas.next().and_then(|linha| linha.split_whitespace().nth(1)).unwrap_or("");
+	let chave = linhas.filter_map(|linha| linha.split_once(':'))
+		.find(|(nome,_)| nome.trim().eq_ignore_ascii_case("sec-websocket-key"))
+		.map(|(_,valor)| String::from(valor.trim()));
+
+	match (caminho, chave) {
+		("/ws", Some(chave)) => (format!("HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+											Sec-WebSocket-Accept: {}\r\n\r\n", aceite(&chave)).into_bytes(), true),
+		("/" | "/index.html", _) => (http("200 OK", "text/html; charset=utf-8", PAGINA), false),
+		_ => (http("404 Not Found", "text/plain; charset=utf-8", "404"), false),
+	}
+}
+
+
+// Pedido HTTP atendido aos poucos, sem esperar a conexão: o saguão segue com as salas
+pub struct Pedido {
+	fluxo: TcpStream,
+	entrada: Vec<u8>,						// Cabeçalho recebido até agora
+	resposta: Option<(Vec<u8>,bool)>,		// O que falta enviar e se abre o WebSocket
+	inicio: Instant,
+}
+
+// Como ficou um pedido depois de avançar
+pub enum Andamento {
+	ESPERA(Pedido),			// Falta cabeçalho ou resposta
+	WEBSOCKET(Remoto),
+	FIM,					// Página enviada, 404 ou conexão com problema
+}
+
+impl Pedido {
+	pub fn new(fluxo: TcpStream) -> io::Result<Pedido> {
+		fluxo.set_nonblocking(true)?;
+		Ok(Pedido{ fluxo, entrada: Vec::new(), resposta: None, inicio: Instant::now() })
+	}
+
+	// Lê e escreve o que a conexão permite agora; depois do 'prazo' o pedido é abandonado
+	pub fn avanca(mut self, prazo: Duration) -> Andamento {
+		match self.transfere() {
+			Ok(true) => match self.resposta {
+				// O que chegou depois do cabeçalho já é do WebSocket
+				Some((_, true)) => Andamento::WEBSOCKET(Remoto{ fluxo: self.fluxo, entrada: self.entrada, nome: String::from(perfil::VISITANTE) }),
+				_ => Andamento::FIM,
+			},
+			Ok(false) if self.inicio.elapsed() < prazo => Andamento::ESPERA(self),
+			_ => Andamento::FIM,
+		}
+	}
+
+	// Ok(true) quando a resposta saiu inteira, Ok(false) se a conexão ainda não tem o resto
+	fn transfere(&mut self) -> io::Result<bool> {
+		while self.resposta.is_none() {
+			if let Some(fim) = self.entrada.windows(4).position(|janela| janela == b"\r\n\r\n") {
+				let pedido: Vec<u8> = self.entrada.drain(..fim+4).collect();
+				self.resposta = Some(resposta(&String::from_utf8_lossy(&pedido)));
+				break;
+			}
+			if self.entrada.len() > MAIOR_CABECALHO {
+				return Err(io::Error::new(ErrorKind::InvalidData, "cabeçalho grande demais"));
+			}
+			let mut bloco = [0u8;4096];
+			match self.fluxo.read(&mut bloco) {
+				Ok(0) => return Err(io::Error::new(ErrorKind::ConnectionAborted, "fechou")),
+				Ok(n) => self.entrada.extend_from_slice(&bloco[..n]),
+				Err(erro) if erro.kind() == ErrorKind::WouldBlock => return Ok(false),
+				Err(erro) => return Err(erro),
+			}
+		}
+
+		let Some((saida, _)) = self.resposta.as_mut() else {
+			return Ok(false);
+		};
+		while !saida.is_empty() {
+			match self.fluxo.write(saida) {
+				Ok(0) => return Err(io::Error::new(ErrorKind::WriteZero, "não enviou")),
+				Ok(n) => {
+					saida.drain(..n);
+				}
+				Err(erro) if erro.kind() == ErrorKind::WouldBlock => return Ok(false),
+				Err(erro) => return Err(erro),
+			}
+		}
+		Ok(true)
+	}
+}
+
+
+// Resposta HTTP completa, a conexão fecha depois dela
+fn http(estado: &str, tipo: &str, corpo: &str) -> Vec<u8> {
+	format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+		estado, tipo, corpo.len(), corpo).into_bytes()
+}
+
+
+// Cabeçalho de um quadro WebSocket: opcode, tamanho declarado, máscara (None sem máscara) e início do conteúdo
+fn cabecalho(dados: &[u8]) -> Option<(u8,u64,Option<[u8;4]>,usize)> {
+	let opcode = dados.first()? & 0x0F;
+	let segundo = *dados.get(1)?;
+	let (tamanho, mut inicio) = match segundo & 0x7F {
+		126 => (u16::from_be_bytes(dados.get(2..4)?.try_into().ok()?) as u64, 4),
+		127 => (u64::from_be_bytes(dados.get(2..10)?.try_into().ok()?), 10),
+		n => (n as u64, 2),
+	};
+	let mut mascara = None;
+	if segundo & 0x80 != 0 {
+		mascara = Some(dados.get(inicio..inicio+4)?.try_into().ok()?);
+		inicio += 4;
+	}
+	Some((opcode, tamanho, mascara, inicio))
+}
+
+
+// Quadro WebSocket completo no início dos dados: opcode, conteúdo sem máscara e tamanho total
+// None enquanto o quadro não chegou inteiro; quadro maior que o limite é erro, sem esperar o conteúdo
+// O navegador sempre mascara, quadro sem máscara também é erro (RFC 6455, seção 5.1)
+pub fn quadro(dados: &[u8]) -> io::Result<Option<(u8,Vec<u8>,usize)>> {
+	let Some((opcode, tamanho, mascara, inicio)) = cabecalho(dados) else {
+		return Ok(None);
+	};
+	let Some(mascara) = mascara else {
+		return Err(io::Error::new(ErrorKind::InvalidData, "quadro sem máscara"));
+	};
+	// Close, ping e pong têm bit 0x8 no opcode
+	let limite = if opcode & 0x8 != 0 { MAIOR_CONTROLE } else { MAIOR_QUADRO };
+	if tamanho > limite as u64 {
+		return Err(io::Error::new(ErrorKind::InvalidData, "quadro grande demais"));
+	}
+	let tamanho = tamanho as usize;
+	let Some(conteudo) = dados.get(inicio..inicio+tamanho) else {
+		return Ok(None);
+	};
+	let conteudo = conteudo.iter().enumerate().map(|(i,b)| b ^ mascara[i%4]).collect();
+	Ok(Some((opcode, conteudo, inicio+tamanho)))
+}
+
+
+// Resposta do aperto de mão: base64 do SHA-1 da chave do navegador com o GUID do protocolo
+pub fn aceite(chave: &str) -> String {
+	base64(&sha1(format!("{}{}", chave, GUID_WEBSOCKET).as_bytes()))
+}
+
+
+// SHA-1 (RFC 3174), só para o aperto de mão, não serve para segurança
+fn sha1(dados: &[u8]) -> [u8;20] {
+	let mut h: [u32;5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+	let mut mensagem = dados.to_vec();
+	mensagem.push(0x80);
+	while mensagem.len() % 64 != 56 {
+		mensagem.push(0);
+	}
+	mensagem.extend_from_slice(&(dados.len() as u64 * 8).to_be_bytes());
+
+	for bloco in mensagem.chunks(64) {
+		let mut w = [0u32;80];
+		for (i,palavra) in bloco.chunks(4).enumerate() {
+			w[i] = u32::from_be_bytes([palavra[0], palavra[1], palavra[2], palavra[3]]);
+		}
+		for i in 16..80 {
+			w[i] = (w[i-3] ^ w[i-8] ^ w[i-14] ^ w[i-16]).rotate_left(1);
+		}
+		let [mut a, mut b, mut c, mut d, mut e] = h;
+		for (i,&palavra) in w.iter().enumerate() {
+			let (f,k) = match i {
+				0..=19 => ((b & c) | (!b & d), 0x5A827999),
+				20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+				40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+				_ => (b ^ c ^ d, 0xCA62C1D6),
+			};
+			let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(palavra);
+			(e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+		}
+		for (parcial,valor) in h.iter_mut().zip([a,b,c,d,e]) {
+			*parcial = parcial.wrapping_add(valor);
+		}
+	}
+
+	let mut resumo = [0u8;20];
+	for (i,valor) in h.iter().enumerate() {
+		resumo[4*i..4*i+4].copy_from_slice(&valor.to_be_bytes());
+	}
+	resumo
+}
+
+
+fn base64(dados: &[u8]) -> String {
+	const ALFABETO: &[u8;64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+	let mut texto = String::new();
+	for grupo in dados.chunks(3) {
+		let byte = |i: usize| *grupo.get(i).unwrap_or(&0) as u32;
+		let n = byte(0) << 16 | byte(1) << 8 | byte(2);
+		for i in 0..4 {
+			match i <= grupo.len() {
+				true => texto.push(ALFABETO[(n >> (18 - 6*i) & 63) as usize] as char),
+				false => texto.push('='),
+			}
+		}
+	}
+	texto
+}
+
+
+// Linhas de um tabuleiro para o navegador, navios inteiros só aparecem com 'mostra_navios'
+fn linhas(navios: &[Navio], original: &Mapa, tiros: Option<&Mapa>, mostra_navios: bool) -> Vec<String> {
+	original.iter().enumerate().map(|(y,linha)| {
+		linha.iter().enumerate().map(|(x,&celula)| {
+			let atingida = tiros.is_some_and(|tiros| tiros[y][x] == DESTRUIDO);
+			match celula {
+				ILHA => '#',
+				RECIFE => '%',
+				AGUA if atingida => 'o',
+				AGUA => '~',
+				_ if atingida => 'X',
+				n if mostra_navios => navios[n].letra,
+				_ => '~',
+			}
+		}).collect()
+	}).collect()
+}
+
+
+// Texto entre aspas, com os escapes do JSON
+pub fn json_texto(texto: &str) -> String {
+	let mut json = String::from("\"");
+	for c in texto.chars() {
+		match c {
+			'"' => json.push_str("\\\""),
+			'\\' => json.push_str("\\\\"),
+			c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+			c => json.push(c),
+		}
+	}
+	json.push('"');
+	json
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+
+	#[test]
+	fn aperto_de_mao_da_rfc() {
+		// Exemplo da RFC 6455, seção 1.3
+		assert_eq!(aceite("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+	}
+
+	#[test]
+	fn quadros_mascarados_e_longos() {
+		// Exemplo da RFC 6455, seção 5.7: "Hello" mascarado
+		let hello = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+		assert_eq!(quadro(&hello).unwrap(), Some((1, b"Hello".to_vec(), 11)));
+		assert_eq!(quadro(&hello[..8]).unwrap(), None);
+
+		// Tamanho em 16 bits, com máscara zero
+		let texto = "x".repeat(300);
+		let mut longo = vec![0x81, 0x80 | 126, 0x01, 0x2c, 0, 0, 0, 0];
+		longo.extend_from_slice(texto.as_bytes());
+		assert_eq!(quadro(&longo).unwrap(), Some((1, texto.into_bytes(), 308)));
+
+		// Acima do limite é recusado só pelo cabeçalho, sem esperar o conteúdo
+		let enorme = [0x81, 0xFF, 0, 0, 0, 1, 0, 0, 0, 0, 1, 2, 3, 4];
+		assert!(quadro(&enorme).is_err());
+		assert!(quadro(&[0x81, 0xFF, 0, 0, 0, 0, 0, 1, 0, 1, 1, 2, 3, 4]).is_err());
+		assert_eq!(quadro(&[0x81, 0xFF, 0, 0, 0, 0, 0, 1, 0, 0, 1, 2, 3, 4]).unwrap(), None);
+
+		// Ping com mais de 125 bytes também
+		assert!(quadro(&[0x89, 0x80 | 126, 0, 126, 1, 2, 3, 4]).is_err());
+		assert_eq!(quadro(&[0x89, 0x80 | 125, 1, 2, 3, 4]).unwrap(), None);
+
+		// Quadro do navegador sem máscara, mesmo completo
+		assert!(quadro(b"\x81\x05Hello").is_err());
+	}
+}
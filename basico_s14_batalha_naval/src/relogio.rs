@@ -0,0 +1,76 @@
+/*
+	Relógio do humano
+
+	- Limite por jogada: esgotado, o jogo dá um tiro automático
+	- Tempo total da partida, como no xadrez: esgotado, o humano perde
+	- Só corre durante a vez do humano, o bot não tem relógio
+*/
+
+
+use std::time::{Duration,Instant};
+
+
+pub struct Relogio {
+	por_jogada: Option<Duration>,
+	restante_total: Option<Duration>,
+	inicio_vez: Instant,
+}
+
+impl Relogio {
+	pub fn new(por_jogada: Option<Duration>, total: Option<Duration>) -> Relogio {
+		Relogio {
+			por_jogada,
+			restante_total: total,
+			inicio_vez: Instant::now(),
+		}
+	}
+
+	// Existe algum limite de tempo ?
+	pub fn ativo(&self) -> bool {
+		self.por_jogada.is_some() || self.restante_total.is_some()
+	}
+
+	// Começa a contar a vez do humano
+	pub fn inicia_vez(&mut self) {
+		self.inicio_vez = Instant::now();
+	}
+
+	// Desconta a vez do tempo total
+	pub fn termina_vez(&mut self) {
+		let decorrido = self.inicio_vez.elapsed();
+		if let Some(total) = self.restante_total {
+			self.restante_total = Some(total.saturating_sub(decorrido));
+		}
+	}
+
+	// Tempo total que sobrou, descontadas as vezes já terminadas
+	pub fn restante_total(&self) -> Option<Duration> {
+		self.restante_total
+	}
+
+	// Tempo da jogada atual acabou ?
+	pub fn jogada_esgotada(&self) -> bool {
+		matches!(self.por_jogada, Some(limite) if self.inicio_vez.elapsed() >= limite)
+	}
+
+	// Tempo total da partida acabou ?
+	pub fn total_esgotado(&self) -> bool {
+		matches!(self.restante_total, Some(total) if self.inicio_vez.elapsed() >= total)
+	}
+
+	// Texto para a linha de status, arredondado para cima em segundos
+	pub fn descricao(&self) -> String {
+		let decorrido = self.inicio_vez.elapsed();
+		let segundos = |restante: Duration| (restante.as_millis() as u64).div_ceil(1000);
+
+		let mut texto = String::from(tr!("Tempo:"));
+		if let Some(limite) = self.por_jogada {
+			texto.push_str(&tr!("  jogada {}s", segundos(limite.saturating_sub(decorrido))));
+		}
+		if let Some(total) = self.restante_total {
+			let s = segundos(total.saturating_sub(decorrido));
+			texto.push_str(&tr!("  total {}", format!("{}:{:02}", s/60, s%60)));
+		}
+		texto
+	}
+}
@@ -0,0 +1,197 @@
+/*
+	Reprise de uma partida contra o bot
+
+	A última partida terminada fica gravada em <dados>/batalha_naval/reprise.txt, no
+	diretório dos perfis (ver perfil.rs), e é vista de novo com '--reprise <arquivo>':
+
+	- O começo é a partida no início da batalha, no formato de salvamento.rs, com a semente
+	- A seção [jogadas] traz o ataque do humano em cada rodada, uma linha 'arma x y direção',
+	ou 'automatico' quando a jogada esgotou e o tiro foi sorteado
+	- Os sorteios da rodada e o bot vêm da semente, então repetem o que aconteceu
+	- A reprise vai sozinha, um ataque por atraso; Esc volta e Ctrl+C aborta
+	- Sem relógio: termina com o fim da partida ou quando acabam as jogadas
+
+	Como na partida salva, a variante com neblina não é gravada; nem a partida em rede
+	e a contra o bot adaptativo, que joga diferente quando a memória muda (ver memoria.rs).
+	A reprise não conta nos perfis nem no desafio do dia.
+*/
+
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use crossterm::event::{Event,KeyEventKind};
+
+use crate::{Jogo,Batalha,Opcoes,Arma,Direcao,Desfecho};
+use crate::{avisa_tamanho,escreve_status,executa_batalha,menu,perfil,redesenha_batalha,salvamento,teclas};
+use crate::bot::Dificuldade;
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::relogio::Relogio;
+use crate::teclas::Acao;
+
+
+// Decisão do humano em uma rodada
+#[derive(Debug,Copy,Clone)]
+pub enum Jogada {
+	ATAQUE(Arma,Direcao,usize,usize),	// Arma, direção do torpedo e mira
+	AUTOMATICO,							// Tiro sorteado quando a jogada esgota
+}
+
+
+// Reprise da batalha em andamento
+pub enum Reprise {
+	DESLIGADA,
+	GRAVANDO{ inicio: String, jogadas: Vec<Jogada> },	// Partida no começo e jogadas até agora
+	MOSTRANDO(VecDeque<Jogada>),						// Jogadas que faltam mostrar
+}
+
+impl Reprise {
+	// Começa a gravar a batalha no estado atual, se ela pode ser repetida
+	pub fn inicia(jogo:&Jogo, batalha:&Batalha, opcoes:&Opcoes) -> Reprise {
+		match batalha.neblina.is_none() && opcoes.dificuldade != Dificuldade::ADAPTATIVO {
+			true => Reprise::GRAVANDO{ inicio: salvamento::para_texto(jogo, batalha, opcoes), jogadas: Vec::new() },
+			false => Reprise::DESLIGADA,
+		}
+	}
+
+	// Batalha é a reprise de outra ?
+	pub fn mostrando(&self) -> bool {
+		matches!(self, Reprise::MOSTRANDO(_))
+	}
+
+	// Guarda a jogada do humano
+	pub fn anota(&mut self, jogada:Jogada) {
+		if let Reprise::GRAVANDO{ jogadas, .. } = self {
+			jogadas.push(jogada);
+		}
+	}
+
+	// Texto do arquivo, só quando gravando
+	pub fn para_texto(&self) -> Option<String> {
+		let Reprise::GRAVANDO{ inicio, jogadas } = self else {
+			return None;
+		};
+		let mut texto = inicio.replacen("partida salva", "reprise", 1);
+		texto.push_str("[jogadas]\n");
+		for jogada in jogadas.iter() {
+			match jogada {
+				Jogada::ATAQUE(arma, direcao, x, y) => texto.push_str(&format!("{} {} {} {}\n",
+					arma.nome().to_lowercase(), x, y, salvamento::nome_da_direcao(*direcao))),
+				Jogada::AUTOMATICO => texto.push_str("automatico\n"),
+			}
+		}
+		Some(texto)
+	}
+
+	// Grava o arquivo da reprise no fim da partida, None se ela não foi gravada
+	pub fn grava(&self) -> Option<Result<PathBuf, String>> {
+		let texto = self.para_texto()?;
+		Some(arquivo_da_reprise().and_then(|arquivo| {
+			if let Some(diretorio) = arquivo.parent() {
+				fs::create_dir_all(diretorio)
+					.map_err(|erro| tr!("Não conseguiu criar {}: {}", diretorio.display(), erro))?;
+			}
+			fs::write(&arquivo, texto)
+				.map_err(|erro| tr!("Não conseguiu gravar a reprise em {}: {}", arquivo.display(), erro))?;
+			Ok(arquivo)
+		}))
+	}
+}
+
+
+fn arquivo_da_reprise() -> Result<PathBuf, String> {
+	Ok(perfil::diretorio_dados()?.join("reprise.txt"))
+}
+
+
+// Uma jogada gravada
+fn jogada_de_texto(linha:&str) -> Result<Jogada, String> {
+	let partes: Vec<&str> = linha.split_whitespace().collect();
+	match partes[..] {
+		["automatico"] => Ok(Jogada::AUTOMATICO),
+		[arma, x, y, direcao] => {
+			let x = x.parse().map_err(|_| tr!("jogada inválida '{}'", linha))?;
+			let y = y.parse().map_err(|_| tr!("jogada inválida '{}'", linha))?;
+			Ok(Jogada::ATAQUE(salvamento::arma_por_nome(arma)?, salvamento::direcao_por_nome(direcao)?, x, y))
+		}
+		_ => Err(tr!("jogada inválida '{}'", linha)),
+	}
+}
+
+
+// Partida do começo da reprise e as jogadas, as opções dela passam a valer e o relógio não
+pub fn de_texto(texto:&str, opcoes:&mut Opcoes) -> Result<(Jogo,Batalha), String> {
+	let (jogo, mut batalha) = salvamento::de_texto(texto, opcoes)?;
+	let jogadas = salvamento::secoes(texto).get("jogadas")
+		.ok_or_else(|| tr!("falta a seção [{}]", "jogadas"))?
+		.iter()
+		.map(|linha| jogada_de_texto(linha))
+		.collect::<Result<VecDeque<_>,_>>()?;
+	if jogadas.iter().any(|jogada| matches!(jogada, Jogada::ATAQUE(_,_,x,y) if *x >= jogo.lado() || *y >= jogo.lado())) {
+		return Err(String::from(tr!("mira fora do tabuleiro")));
+	}
+	opcoes.tempo_jogada = None;
+	opcoes.tempo_total = None;
+	batalha.relogio = Relogio::new(None, None);
+	batalha.reprise = Reprise::MOSTRANDO(jogadas);
+	Ok((jogo,batalha))
+}
+
+
+// Próximo ataque da reprise, depois do atraso; Ok(true) quando foi o tiro automático
+// Esc volta ao menu, Ctrl+C aborta, qualquer outra tecla adianta o ataque
+pub fn proxima_jogada(con:&mut dyn Console, batalha:&mut Batalha, opcoes:&Opcoes) -> Result<Result<bool,Desfecho>, ErroJogo> {
+	if con.tem_evento(opcoes.atraso)? {
+		if let Event::Key(tecla) = con.le_evento()? {
+			if tecla.kind == KeyEventKind::Press && teclas::aborta(&tecla) {
+				return Ok(Err(Desfecho::ABORTADO));
+			}
+			if tecla.kind == KeyEventKind::Press && opcoes.teclas.acao(&tecla) == Some(Acao::PAUSA) {
+				return Ok(Err(Desfecho::MENU));
+			}
+		}
+	}
+
+	let Reprise::MOSTRANDO(jogadas) = &mut batalha.reprise else {
+		return Ok(Err(Desfecho::CONTINUA));
+	};
+	match jogadas.pop_front() {
+		Some(Jogada::ATAQUE(arma, direcao, x, y)) => {
+			(batalha.arma, batalha.direcao_torpedo, batalha.mira_x, batalha.mira_y) = (arma, direcao, x, y);
+			Ok(Ok(false))
+		}
+		Some(Jogada::AUTOMATICO) => Ok(Ok(true)),
+		None => Ok(Err(Desfecho::CONTINUA)),
+	}
+}
+
+
+// Mostra a reprise gravada no arquivo até o fim ou até o humano sair
+pub fn executa(con:&mut dyn Console, opcoes:&Opcoes, arquivo:&str) -> Result<Desfecho, ErroJogo> {
+	let mut opcoes = opcoes.clone();
+	let lido = fs::read_to_string(arquivo)
+		.map_err(|erro| tr!("Não conseguiu ler a reprise {}: {}", arquivo, erro))
+		.and_then(|texto| de_texto(&texto, &mut opcoes).map_err(|erro| tr!("Reprise {}: {}", arquivo, erro)));
+	let (mut jogo, mut batalha) = match lido {
+		Ok(partida) => partida,
+		Err(erro) => {
+			menu::mostra_texto(con, &[erro])?;
+			return Ok(Desfecho::MENU);
+		}
+	};
+
+	if let Some(desfecho) = avisa_tamanho(con, jogo.lado())? {
+		return Ok(desfecho);
+	}
+	redesenha_batalha(con, &jogo, &batalha, &opcoes)?;
+	match executa_batalha(con, &mut jogo, &opcoes, &mut batalha, None)? {
+		Desfecho::CONTINUA => {
+			escreve_status(con, 3, tr!("Fim da reprise, tecle algo para voltar"))?;
+			menu::espera_tecla(con)?;
+			Ok(Desfecho::MENU)
+		}
+		desfecho => Ok(desfecho),
+	}
+}
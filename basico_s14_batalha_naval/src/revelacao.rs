@@ -0,0 +1,110 @@
+/*
+	Quanto cada tiro revela do navio atingido ('--revelacao')
+
+	- Clássica: só água ou acerto; quando um navio afunda o aviso não diz qual
+	- Afundado: os acertos não dizem o navio, mas o navio afundado é anunciado
+	pelo nome e ganha a sua cor no tabuleiro inimigo
+	- Total: cada acerto já aparece na cor do navio, como sempre foi
+
+	A regra vale para o tabuleiro inimigo visto pelo humano, inclusive nas
+	animações e ao redesenhar. O bot só sabe onde acertou, como sempre jogou,
+	a não ser com '--bot-revelacao', quando a regra vale também para ele:
+
+	- Clássica: só onde acertou, como o humano
+	- Afundado: também as células de cada navio afundado, que um jogador deduz
+	pelo nome, e para de procurar em volta delas
+	- Total: também qual navio cada acerto atingiu, e segue a linha de dois
+	acertos do mesmo navio
+
+	O próprio tabuleiro é sempre mostrado por inteiro. As partidas de vários
+	jogadores (ver equipes.rs) continuam com a revelação total.
+*/
+
+
+use crate::{Mapa,Navio,Impacto};
+use crate::bot::Bot;
+use crate::tema;
+
+
+// Regra do que um tiro revela
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Revelacao {
+	CLASSICA,
+	AFUNDADO,
+	TOTAL,
+}
+
+impl Revelacao {
+	pub const TODAS: [Revelacao;3] = [Revelacao::CLASSICA, Revelacao::AFUNDADO, Revelacao::TOTAL];
+
+	// Nome usado na linha de comando e na partida salva
+	pub fn nome(&self) -> &'static str {
+		match self {
+			Revelacao::CLASSICA => "classica",
+			Revelacao::AFUNDADO => "afundado",
+			Revelacao::TOTAL => "total",
+		}
+	}
+
+	pub fn por_nome(nome: &str) -> Option<Revelacao> {
+		Revelacao::TODAS.into_iter().find(|r| r.nome() == nome)
+	}
+
+	// Nome na tela de opções
+	pub fn descricao(&self) -> &'static str {
+		match self {
+			Revelacao::CLASSICA => "clássica",
+			Revelacao::AFUNDADO => "navio afundado",
+			Revelacao::TOTAL => "total",
+		}
+	}
+
+	// Navios do inimigo como o atirador os vê no 'mapa' de tiros: os que a regra não revela perdem a cor
+	pub fn navios_vistos(&self, navios: &[Navio], mapa: &Mapa) -> Vec<Navio> {
+		navios.iter().enumerate().map(|(n,navio)| {
+			let afundado = !mapa.iter().flatten().any(|&celula| celula == n);
+			let revelado = match self {
+				Revelacao::CLASSICA => false,
+				Revelacao::AFUNDADO => afundado,
+				Revelacao::TOTAL => true,
+			};
+			match revelado {
+				true => navio.clone(),
+				false => Navio{ cor: tema::COR_ANONIMA, ..navio.clone() },
+			}
+		}).collect()
+	}
+
+	// Aviso de navios afundados pelo humano, vazio se nenhum afundou
+	pub fn aviso_afundados(&self, impacto: &Impacto, navios: &[Navio]) -> String {
+		if impacto.afundados.is_empty() {
+			return String::new();
+		}
+		match self {
+			Revelacao::CLASSICA => String::from(tr!("Você afundou um navio!")),
+			_ => {
+				let nomes: Vec<&str> = impacto.afundados.iter().map(|&n| tr!(navios[n].nome.as_str())).collect();
+				tr!("Você afundou: {}", nomes.join(", "))
+			}
+		}
+	}
+
+	// Conta ao bot o resultado do seu ataque, só o que a regra revela
+	// Sem 'usa_regra' o bot fica só com os acertos, como na revelação clássica
+	pub fn informa_bot(&self, bot: &mut Bot, impacto: &Impacto, navios: &[Navio], usa_regra: bool) {
+		let regra = match usa_regra {
+			true => *self,
+			false => Revelacao::CLASSICA,
+		};
+		let atingidos = match regra {
+			Revelacao::TOTAL => Some(impacto.navios.as_slice()),
+			_ => None,
+		};
+		bot.registra_acertos(&impacto.acertos, atingidos);
+		if regra != Revelacao::CLASSICA {
+			for &n in impacto.afundados.iter() {
+				bot.registra_afundado(&navios[n].celulas());
+			}
+		}
+	}
+}
@@ -0,0 +1,533 @@
+/*
+	Saguão: servidor de partidas entre navegadores, sem terminal
+
+	- '--saguao <porta>' roda só o servidor, até ser interrompido com Ctrl+C
+	- A mesma página e o mesmo WebSocket de rede.rs, com mais mensagens do navegador:
+		salas				lista das salas
+		cria				cria uma sala e espera o outro jogador
+		entra <sala>		entra em uma sala que espera jogador
+		fila				entra na sala que espera há mais tempo, ou cria uma
+		assiste <sala>		espectador, só vê os tiros até a partida acabar
+		volta <ficha>		volta ao lugar depois de uma queda de conexão
+	- Mensagens para o navegador, além do estado da partida:
+		{"tipo":"salas", "salas":[{"id":n, "jogadores":[...], "fase":"..."}]}
+		{"tipo":"ficha", "ficha":"...", "sala":n}		o navegador guarda para voltar
+		{"tipo":"erro", "mensagem":"..."}
+	- O estado das partidas fica no servidor: quem cai tem JANELA_VOLTA para voltar com a
+	ficha, depois disso perde a partida, ou a sala some se ainda esperava jogador
+	- As opções da linha de comando valem para todas as salas: lado, frota, mapa e semente
+	- Os pedidos HTTP chegam e são respondidos aos poucos, sem esperar a conexão: um
+	navegador lento não para as salas e tem PRAZO_PEDIDO para terminar o pedido
+	- Só o tiro simples, como em rede.rs; o resultado muda o Elo dos dois perfis, se
+	nenhum é visitante (ver perfil.rs)
+*/
+
+
+use std::collections::BTreeMap;
+use std::io::{self,ErrorKind};
+use std::net::TcpListener;
+use std::time::{Duration,Instant};
+
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::{Opcoes,Jogo,Jogador,Batalha,Mapa,Arma,Direcao,AGUA};
+use crate::{alvo_valido,atinge,gerador,novo_jogo,pedacos_vivos,perfil,sorteia_navios};
+use crate::{FLUXO_FROTA_BOT,FLUXO_FROTA_HUMANO};
+use crate::rede::{self,Andamento,Fase,Pedido,Remoto};
+
+
+// Tempo para voltar ao lugar depois de uma queda de conexão
+const JANELA_VOLTA: Duration = Duration::from_secs(60);
+
+// Pausa entre as voltas do servidor
+const INTERVALO: Duration = Duration::from_millis(20);
+
+// Tempo para um pedido HTTP chegar e a resposta sair, depois a conexão é fechada
+const PRAZO_PEDIDO: Duration = Duration::from_secs(5);
+
+
+// O que uma conexão está fazendo
+#[derive(Copy,Clone)]
+enum Papel {
+	SAGUAO,					// Vendo a lista de salas
+	JOGADOR(u32,usize),		// Sala e lugar, o lugar 0 usa a frota 'HUMANO' e o 1 a 'BOT'
+	ESPECTADOR(u32),
+}
+
+struct Conexao {
+	remoto: Remoto,
+	papel: Papel,
+}
+
+
+// Lugar de um jogador na sala, continua dele durante uma queda de conexão
+struct Lugar {
+	nome: String,
+	ficha: String,				// Segredo para voltar ao lugar
+	conexao: Option<u64>,
+	caiu: Option<Instant>,		// Quando a conexão caiu
+}
+
+
+struct Sala {
+	jogo: Jogo,
+	batalha: Option<Batalha>,	// Começa quando os dois estão prontos
+	semente: u64,
+	geradores: [StdRng;2],		// Sorteio da frota de cada lugar
+	lugares: [Option<Lugar>;2],
+	prontos: [bool;2],
+	vez: usize,
+	vencedor: Option<usize>,
+	aviso: String,				// Última jogada, aparece para todos
+	espectadores: Vec<u64>,
+}
+
+impl Sala {
+	fn fase(&self) -> Fase {
+		if self.vencedor.is_some() {
+			Fase::FIM
+		} else if self.batalha.is_some() {
+			Fase::BATALHA
+		} else if self.lugares[1].is_none() {
+			Fase::ESPERA
+		} else {
+			Fase::ARRUMACAO
+		}
+	}
+
+	fn nome(&self, lugar: usize) -> &str {
+		self.lugares[lugar].as_ref().map_or("", |l| &l.nome)
+	}
+
+	// Mapa com a frota do lugar e os tiros que ela levou
+	fn mapa_mut(&mut self, lugar: usize) -> Option<&mut Mapa> {
+		self.batalha.as_mut().map(|b| if lugar == 0 { &mut b.mapa_humano } else { &mut b.mapa_bot })
+	}
+}
+
+
+// Frota de cada lugar
+fn jogador(lugar: usize) -> Jogador {
+	if lugar == 0 { Jogador::HUMANO } else { Jogador::BOT }
+}
+
+
+pub struct Saguao {
+	opcoes: Opcoes,
+	conexoes: BTreeMap<u64,Conexao>,
+	pedidos: Vec<Pedido>,				// Pedidos HTTP ainda chegando, sem parar as salas
+	salas: BTreeMap<u32,Sala>,			// Em ordem de criação, a fila escolhe a mais antiga
+	proxima_conexao: u64,
+	proxima_sala: u32,
+	pub janela_volta: Duration,
+}
+
+impl Saguao {
+	// Na rede só existe o tiro simples e não há relógio
+	pub fn new(opcoes: &Opcoes) -> Saguao {
+		Saguao{
+			opcoes: Opcoes{ armas_especiais: false, tempo_jogada: None, tempo_total: None, ..opcoes.clone() },
+			conexoes: BTreeMap::new(),
+			pedidos: Vec::new(),
+			salas: BTreeMap::new(),
+			proxima_conexao: 0,
+			proxima_sala: 1,
+			janela_volta: JANELA_VOLTA,
+		}
+	}
+
+	// Uma volta do servidor: novas conexões, mensagens recebidas e quedas sem volta
+	pub fn atende(&mut self, ouvinte: &TcpListener) -> io::Result<()> {
+		loop {
+			match ouvinte.accept() {
+				// Um pedido com problema não interrompe o servidor
+				Ok((fluxo,_)) => {
+					if let Ok(pedido) = Pedido::new(fluxo) {
+						self.pedidos.push(pedido);
+					}
+				}
+				Err(erro) if erro.kind() == ErrorKind::WouldBlock => break,
+				Err(erro) => return Err(erro),
+			}
+		}
+
+		// Cada pedido avança o que a sua conexão permite, um lento não atrasa os outros
+		for pedido in std::mem::take(&mut self.pedidos) {
+			match pedido.avanca(PRAZO_PEDIDO) {
+				Andamento::ESPERA(pedido) => self.pedidos.push(pedido),
+				Andamento::WEBSOCKET(remoto) => {
+					let id = self.proxima_conexao;
+					self.proxima_conexao += 1;
+					self.conexoes.insert(id, Conexao{ remoto, papel: Papel::SAGUAO });
+					self.envia_salas(Some(id));
+				}
+				Andamento::FIM => {}
+			}
+		}
+
+		let ids: Vec<u64> = self.conexoes.keys().copied().collect();
+		for id in ids {
+			while let Some(conexao) = self.conexoes.get_mut(&id) {
+				match conexao.remoto.recebe() {
+					Ok(Some(mensagem)) => self.trata(id, &mensagem),
+					Ok(None) => break,
+					Err(_) => {
+						self.caiu(id);
+						break;
+					}
+				}
+			}
+		}
+
+		self.fecha_janelas();
+		Ok(())
+	}
+
+
+	// Uma mensagem do navegador
+	fn trata(&mut self, id: u64, mensagem: &str) {
+		let partes: Vec<&str> = mensagem.split_whitespace().collect();
+		let papel = self.conexoes[&id].papel;
+		match (papel, &partes[..]) {
+			(_, ["nome", nome, ficha @ ..]) if ficha.len() <= 1 => {
+				if let Some(conexao) = self.conexoes.get_mut(&id) {
+					let _ = conexao.remoto.escolhe_nome(nome, ficha.first().copied());
+				}
+			}
+			(Papel::JOGADOR(sala,lugar), ["sorteia"]) => self.sorteia(sala, lugar),
+			(Papel::JOGADOR(sala,lugar), ["pronto"]) => self.pronto(sala, lugar),
+			(Papel::JOGADOR(sala,lugar), ["tiro", x, y]) => {
+				if let (Ok(x), Ok(y)) = (x.parse(), y.parse()) {
+					self.tiro(sala, lugar, x, y);
+				}
+			}
+			(_, ["salas"]) => self.envia_salas(Some(id)),
+			(_, ["cria"]) if self.sai(id) => {
+				match self.cria_sala() {
+					Ok(sala) => self.senta(id, sala, 0),
+					Err(erro) => self.envia_erro(id, &erro),
+				}
+			}
+			(_, ["entra", sala]) if self.sai(id) => {
+				match sala.parse().ok().filter(|s| self.salas.get(s).is_some_and(|s| matches!(s.fase(), Fase::ESPERA))) {
+					Some(sala) => self.senta(id, sala, 1),
+					None => self.envia_erro(id, tr!("Sala cheia ou inexistente")),
+				}
+			}
+			(_, ["fila"]) if self.sai(id) => {
+				let esperando = self.salas.iter().find(|(_,s)| matches!(s.fase(), Fase::ESPERA)).map(|(&n,_)| n);
+				match esperando.map_or_else(|| self.cria_sala().map(|n| (n,0)), |n| Ok((n,1))) {
+					Ok((sala,lugar)) => self.senta(id, sala, lugar),
+					Err(erro) => self.envia_erro(id, &erro),
+				}
+			}
+			(_, ["assiste", sala]) if self.sai(id) => {
+				match sala.parse().ok().filter(|s| self.salas.contains_key(s)) {
+					Some(sala) => {
+						if let (Some(s), Some(conexao)) = (self.salas.get_mut(&sala), self.conexoes.get_mut(&id)) {
+							s.espectadores.push(id);
+							conexao.papel = Papel::ESPECTADOR(sala);
+						}
+						self.atualiza(sala);
+					}
+					None => self.envia_erro(id, tr!("Sala cheia ou inexistente")),
+				}
+			}
+			(_, ["volta", ficha]) if self.sai(id) => self.volta(id, ficha),
+			(_, ["cria" | "entra" | "fila" | "assiste" | "volta", ..]) => self.envia_erro(id, tr!("Termine a partida antes")),
+			_ => {}
+		}
+	}
+
+
+	// Deixa a sala para o saguão, não vale no meio de uma partida
+	fn sai(&mut self, id: u64) -> bool {
+		let Some(conexao) = self.conexoes.get_mut(&id) else { return false };
+		match conexao.papel {
+			Papel::SAGUAO => return true,
+			Papel::ESPECTADOR(sala) => {
+				if let Some(s) = self.salas.get_mut(&sala) {
+					s.espectadores.retain(|&e| e != id);
+				}
+			}
+			Papel::JOGADOR(sala,lugar) => match self.salas.get_mut(&sala) {
+				Some(s) if matches!(s.fase(), Fase::FIM) => {
+					if let Some(l) = s.lugares[lugar].as_mut() {
+						l.conexao = None;
+					}
+				}
+				Some(_) => return false,
+				None => {}
+			},
+		}
+		conexao.papel = Papel::SAGUAO;
+		self.limpa_salas();
+		true
+	}
+
+
+	// Nova sala, com a frota de cada lugar já sorteada
+	fn cria_sala(&mut self) -> Result<u32, String> {
+		let mut jogo = novo_jogo(&self.opcoes)?;
+		let semente = self.opcoes.semente.unwrap_or_else(|| rand::thread_rng().gen());
+		let mut geradores = [gerador(semente, FLUXO_FROTA_HUMANO), gerador(semente, FLUXO_FROTA_BOT)];
+		for (lugar,rng) in geradores.iter_mut().enumerate() {
+			sorteia_navios(&mut jogo, &jogador(lugar), rng);
+		}
+		let id = self.proxima_sala;
+		self.proxima_sala += 1;
+		self.salas.insert(id, Sala{
+			jogo,
+			batalha: None,
+			semente,
+			geradores,
+			lugares: [None, None],
+			prontos: [false, false],
+			vez: 0,
+			vencedor: None,
+			aviso: String::new(),
+			espectadores: Vec::new(),
+		});
+		Ok(id)
+	}
+
+
+	// Coloca a conexão em um lugar livre da sala e entrega a ficha para voltar
+	fn senta(&mut self, id: u64, sala: u32, lugar: usize) {
+		let (Some(s), Some(conexao)) = (self.salas.get_mut(&sala), self.conexoes.get_mut(&id)) else { return };
+		let ficha = format!("{:016x}", rand::thread_rng().gen::<u64>());
+		s.lugares[lugar] = Some(Lugar{ nome: conexao.remoto.nome.clone(), ficha: ficha.clone(), conexao: Some(id), caiu: None });
+		conexao.papel = Papel::JOGADOR(sala, lugar);
+		let _ = conexao.remoto.envia(&format!("{{\"tipo\":\"ficha\",\"ficha\":\"{}\",\"sala\":{}}}", ficha, sala));
+		if lugar == 1 {
+			println!("{}", tr!("Sala {}: {} x {}", sala, s.nome(0), s.nome(1)));
+		}
+		self.atualiza(sala);
+		self.envia_salas(None);
+	}
+
+
+	// Volta ao lugar com a ficha, uma conexão antiga no mesmo lugar perde o lugar
+	fn volta(&mut self, id: u64, ficha: &str) {
+		let achado = self.salas.iter().find_map(|(&sala,s)| {
+			(0..2).find(|&l| s.lugares[l].as_ref().is_some_and(|l| l.ficha == ficha)).map(|l| (sala,l))
+		});
+		let Some((sala,lugar)) = achado else {
+			return self.envia_erro(id, tr!("Ficha inválida ou expirada"));
+		};
+		let s = self.salas.get_mut(&sala).unwrap();
+		let Some(l) = s.lugares[lugar].as_mut() else { return };
+		let antiga = l.conexao.replace(id);
+		l.caiu = None;
+		s.aviso = tr!("{} voltou", l.nome);
+		if let Some(antiga) = antiga.and_then(|a| self.conexoes.get_mut(&a)) {
+			antiga.papel = Papel::SAGUAO;
+		}
+		if let Some(conexao) = self.conexoes.get_mut(&id) {
+			conexao.papel = Papel::JOGADOR(sala, lugar);
+			let _ = conexao.remoto.envia(&format!("{{\"tipo\":\"ficha\",\"ficha\":\"{}\",\"sala\":{}}}", ficha, sala));
+		}
+		self.atualiza(sala);
+	}
+
+
+	// Conexão fechada: o jogador guarda o lugar durante a janela de volta
+	fn caiu(&mut self, id: u64) {
+		let Some(conexao) = self.conexoes.remove(&id) else { return };
+		match conexao.papel {
+			Papel::SAGUAO => {}
+			Papel::ESPECTADOR(sala) => {
+				if let Some(s) = self.salas.get_mut(&sala) {
+					s.espectadores.retain(|&e| e != id);
+				}
+			}
+			Papel::JOGADOR(sala,lugar) => {
+				if let Some(s) = self.salas.get_mut(&sala) {
+					let em_jogo = !matches!(s.fase(), Fase::FIM);
+					if let Some(l) = s.lugares[lugar].as_mut() {
+						l.conexao = None;
+						if em_jogo {
+							l.caiu = Some(Instant::now());
+							s.aviso = tr!("{} caiu, esperando a volta", l.nome);
+						}
+					}
+				}
+				self.atualiza(sala);
+			}
+		}
+		self.limpa_salas();
+	}
+
+
+	// Quem não voltou a tempo perde a partida, a sala esperando jogador some
+	fn fecha_janelas(&mut self) {
+		let janela = self.janela_volta;
+		let vencidos: Vec<(u32,usize)> = self.salas.iter().flat_map(|(&sala,s)| {
+			(0..2).filter(move |&l| s.lugares[l].as_ref().and_then(|l| l.caiu).is_some_and(|t| t.elapsed() >= janela))
+				.map(move |l| (sala,l))
+		}).collect();
+
+		for (sala,lugar) in vencidos {
+			let Some(s) = self.salas.get_mut(&sala) else { continue };
+			if matches!(s.fase(), Fase::ESPERA) {
+				self.salas.remove(&sala);
+				continue;
+			}
+			if let Some(l) = s.lugares[lugar].as_mut() {
+				l.caiu = None;
+			}
+			if s.vencedor.is_none() {
+				s.aviso = tr!("{} não voltou, vitória por W.O.", s.nome(lugar));
+				self.termina(sala, 1-lugar);
+			}
+		}
+		self.limpa_salas();
+	}
+
+
+	// Sorteia de novo a frota do lugar, só antes de ficar pronto
+	fn sorteia(&mut self, sala: u32, lugar: usize) {
+		let Some(s) = self.salas.get_mut(&sala) else { return };
+		if s.batalha.is_none() && !s.prontos[lugar] {
+			sorteia_navios(&mut s.jogo, &jogador(lugar), &mut s.geradores[lugar]);
+			self.atualiza(sala);
+		}
+	}
+
+
+	// Frota arrumada, a batalha começa quando os dois lugares estão prontos
+	fn pronto(&mut self, sala: u32, lugar: usize) {
+		let Some(s) = self.salas.get_mut(&sala) else { return };
+		if !matches!(s.fase(), Fase::ARRUMACAO) {
+			return;
+		}
+		s.prontos[lugar] = true;
+		if s.prontos == [true,true] {
+			s.batalha = Some(Batalha::new(&s.jogo, &self.opcoes, s.semente));
+			s.vez = 0;
+			s.aviso = String::from(tr!("A batalha começou"));
+		}
+		self.atualiza(sala);
+		self.envia_salas(None);
+	}
+
+
+	// Tiro do lugar na sua vez, em uma célula do outro ainda não atingida
+	fn tiro(&mut self, sala: u32, lugar: usize, x: usize, y: usize) {
+		let Some(s) = self.salas.get_mut(&sala) else { return };
+		let lado = s.jogo.lado();
+		let nome = String::from(s.nome(lugar));
+		if !matches!(s.fase(), Fase::BATALHA) || s.vez != lugar || x >= lado || y >= lado {
+			return;
+		}
+		let Some(mapa) = s.mapa_mut(1-lugar) else { return };
+		if !alvo_valido(mapa[y][x]) {
+			return;
+		}
+		let acertou = atinge(mapa, (Arma::TIRO, Direcao::LESTE), (x,y)).iter().any(|&(_,_,celula)| celula != AGUA);
+		let afundou = pedacos_vivos(mapa) == 0;
+		s.aviso = match acertou {
+			true => tr!("{} atirou em {} {}: acertou!", nome, x, y),
+			false => tr!("{} atirou em {} {}: água", nome, x, y),
+		};
+		if afundou {
+			self.termina(sala, lugar);
+		} else {
+			s.vez = 1-lugar;
+			self.atualiza(sala);
+		}
+	}
+
+
+	// Fim da partida, registra o Elo dos dois perfis
+	fn termina(&mut self, sala: u32, vencedor: usize) {
+		let Some(s) = self.salas.get_mut(&sala) else { return };
+		s.vencedor = Some(vencedor);
+		println!("{}", tr!("Sala {}: vitória de {}", sala, s.nome(vencedor)));
+		if let Err(erro) = perfil::registra_confronto(s.nome(vencedor), s.nome(1-vencedor)) {
+			println!("{}", erro);
+		}
+		self.atualiza(sala);
+		self.envia_salas(None);
+	}
+
+
+	// Envia o estado da sala para os jogadores conectados e os espectadores
+	fn atualiza(&mut self, sala: u32) {
+		let Some(s) = self.salas.get(&sala) else { return };
+		let batalha = s.batalha.as_ref();
+		let mut envios = Vec::new();
+		for lugar in 0..2 {
+			let Some(conexao) = s.lugares[lugar].as_ref().and_then(|l| l.conexao) else { continue };
+			let vez = matches!(s.fase(), Fase::BATALHA) && s.vez == lugar;
+			let mensagem = match s.fase() {
+				Fase::ESPERA => String::from(tr!("Aguardando outro jogador...")),
+				Fase::ARRUMACAO if s.prontos[lugar] => String::from(tr!("Aguardando o adversário arrumar a frota...")),
+				Fase::ARRUMACAO => String::from(tr!("Sorteie a frota e clique em Pronto")),
+				Fase::BATALHA if vez => format!("{} {}", s.aviso, tr!("Sua vez")),
+				Fase::BATALHA => format!("{} {}", s.aviso, tr!("Vez do adversário...")),
+				Fase::FIM if s.vencedor == Some(lugar) => format!("{} {}", s.aviso, tr!("Você venceu!")),
+				Fase::FIM => format!("{} {}", s.aviso, tr!("Você perdeu.")),
+			};
+			envios.push((conexao, rede::estado(&s.jogo, batalha, &jogador(lugar), false, s.fase(), vez, mensagem.trim())));
+		}
+		let placar = tr!("Sala {}: {} x {}", sala, s.nome(0), s.nome(1));
+		let espectador = rede::estado(&s.jogo, batalha, &Jogador::HUMANO, true, s.fase(), false, &format!("{} {}", placar, s.aviso));
+		envios.extend(s.espectadores.iter().map(|&e| (e, espectador.clone())));
+
+		for (id,texto) in envios {
+			// Uma falha aparece como queda na próxima leitura
+			if let Some(conexao) = self.conexoes.get_mut(&id) {
+				let _ = conexao.remoto.envia(&texto);
+			}
+		}
+	}
+
+
+	// Lista das salas para uma conexão, ou para todas que estão no saguão
+	fn envia_salas(&mut self, destino: Option<u64>) {
+		let salas: Vec<String> = self.salas.iter().map(|(id,s)| {
+			let jogadores: Vec<String> = s.lugares.iter().flatten().map(|l| rede::json_texto(&l.nome)).collect();
+			format!("{{\"id\":{},\"jogadores\":[{}],\"fase\":\"{}\"}}", id, jogadores.join(","), s.fase().nome())
+		}).collect();
+		let texto = format!("{{\"tipo\":\"salas\",\"salas\":[{}]}}", salas.join(","));
+		for (id,conexao) in self.conexoes.iter_mut() {
+			if destino.map_or(matches!(conexao.papel, Papel::SAGUAO), |d| d == *id) {
+				let _ = conexao.remoto.envia(&texto);
+			}
+		}
+	}
+
+
+	fn envia_erro(&mut self, id: u64, mensagem: &str) {
+		if let Some(conexao) = self.conexoes.get_mut(&id) {
+			let _ = conexao.remoto.envia(&format!("{{\"tipo\":\"erro\",\"mensagem\":{}}}", rede::json_texto(mensagem)));
+		}
+	}
+
+
+	// Salas terminadas sem ninguém conectado somem
+	fn limpa_salas(&mut self) {
+		let antes = self.salas.len();
+		self.salas.retain(|_,s| {
+			let conectados = s.lugares.iter().flatten().any(|l| l.conexao.is_some() || l.caiu.is_some());
+			conectados || !s.espectadores.is_empty()
+		});
+		if self.salas.len() != antes {
+			self.envia_salas(None);
+		}
+	}
+}
+
+
+// Roda o saguão até o programa ser interrompido
+pub fn executa(porta: u16, opcoes: &Opcoes) -> io::Result<()> {
+	let ouvinte = rede::abre(porta)?;
+	println!("{}", tr!("Saguão em {}, Ctrl+C termina", rede::endereco(&ouvinte)));
+	let mut saguao = Saguao::new(opcoes);
+	loop {
+		saguao.atende(&ouvinte)?;
+		std::thread::sleep(INTERVALO);
+	}
+}
@@ -0,0 +1,323 @@
+/*
+	Partida salva
+
+	Uma única partida fica gravada em <dados>/batalha_naval/partida_salva.txt,
+	no mesmo diretório dos perfis (ver perfil.rs), em texto dividido em seções:
+
+		[opcoes]			chave=valor das opções da partida
+		[frota]				a frota, no formato de frota.rs
+		[terreno]			o terreno, no formato de terreno.rs
+		[navios_humano]		uma linha 'x y direção espelhado' por navio
+		[navios_bot]
+		[tiros_humano]		grade com 'X' nas células do humano já atingidas
+		[tiros_bot]			grade com 'X' nas células do bot já atingidas
+		[estado]			chave=valor da mira, armas, estatísticas, relógio, semente, rodada, dicas
+							e primeiros tiros do humano, lembrados pelo bot adaptativo (ver memoria.rs)
+
+	Com a semente e a rodada a partida carregada sorteia o mesmo que a original.
+	A variante com neblina (ver neblina.rs) não é salva, a partida carregada é clássica.
+	Ao carregar, o bot recomeça a sua estratégia: esquece as vizinhas de acertos
+	que ainda ia tentar e a área detectada pelo radar.
+	O mesmo formato é o começo da reprise de uma partida, ver reprise.rs.
+*/
+
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::{Jogo,Jogador,Batalha,Opcoes,Mapa,Arma,Arsenal,Direcao,Navio,DESTRUIDO};
+use crate::memoria;
+use crate::{frota,perfil,terreno};
+use crate::bot::Dificuldade;
+use crate::relogio::Relogio;
+use crate::revelacao::Revelacao;
+
+
+fn arquivo_salvo() -> Result<PathBuf, String> {
+	Ok(perfil::diretorio_dados()?.join("partida_salva.txt"))
+}
+
+
+// Grava a partida, substituindo a que estava salva
+pub fn grava(jogo:&Jogo, batalha:&Batalha, opcoes:&Opcoes) -> Result<PathBuf, String> {
+	let arquivo = arquivo_salvo()?;
+	if let Some(diretorio) = arquivo.parent() {
+		fs::create_dir_all(diretorio)
+			.map_err(|erro| tr!("Não conseguiu criar {}: {}", diretorio.display(), erro))?;
+	}
+	fs::write(&arquivo, para_texto(jogo, batalha, opcoes))
+		.map_err(|erro| tr!("Não conseguiu salvar a partida em {}: {}", arquivo.display(), erro))?;
+	Ok(arquivo)
+}
+
+
+// Lê a partida salva, as opções dela passam a valer
+pub fn carrega(opcoes:&mut Opcoes) -> Result<(Jogo,Batalha), String> {
+	let arquivo = arquivo_salvo()?;
+	let texto = match fs::read_to_string(&arquivo) {
+		Ok(texto) => texto,
+		Err(erro) if erro.kind() == std::io::ErrorKind::NotFound => return Err(String::from(tr!("Nenhuma partida salva."))),
+		Err(erro) => return Err(tr!("Não conseguiu ler a partida {}: {}", arquivo.display(), erro)),
+	};
+	de_texto(&texto, opcoes).map_err(|erro| tr!("Partida salva {}: {}", arquivo.display(), erro))
+}
+
+
+pub fn nome_da_direcao(direcao:Direcao) -> &'static str {
+	match direcao {
+		Direcao::NORTE => "norte",
+		Direcao::SUL => "sul",
+		Direcao::LESTE => "leste",
+		Direcao::OESTE => "oeste",
+	}
+}
+
+
+pub fn direcao_por_nome(nome:&str) -> Result<Direcao, String> {
+	match nome {
+		"norte" => Ok(Direcao::NORTE),
+		"sul" => Ok(Direcao::SUL),
+		"leste" => Ok(Direcao::LESTE),
+		"oeste" => Ok(Direcao::OESTE),
+		_ => Err(tr!("direção inválida '{}'", nome)),
+	}
+}
+
+
+pub fn arma_por_nome(nome:&str) -> Result<Arma, String> {
+	[Arma::TIRO, Arma::BOMBA, Arma::TORPEDO, Arma::RADAR].into_iter()
+		.find(|arma| arma.nome().to_lowercase() == nome)
+		.ok_or_else(|| tr!("arma inválida '{}'", nome))
+}
+
+
+pub fn para_texto(jogo:&Jogo, batalha:&Batalha, opcoes:&Opcoes) -> String {
+	let mut texto = String::from("# Batalha Naval, partida salva\n");
+
+	texto.push_str("[opcoes]\n");
+	texto.push_str(&format!("lado={}\n", opcoes.lado));
+	texto.push_str(&format!("dificuldade={}\n", opcoes.dificuldade.nome()));
+	texto.push_str(&format!("revelacao={}\n", opcoes.revelacao.nome()));
+	texto.push_str(&format!("bot_revelacao={}\n", opcoes.bot_revelacao));
+	texto.push_str(&format!("armas={}\n", opcoes.armas_especiais));
+	texto.push_str(&format!("atraso={}\n", opcoes.atraso.as_millis()));
+	if let Some(tempo) = opcoes.tempo_jogada {
+		texto.push_str(&format!("tempo_jogada={}\n", tempo.as_secs()));
+	}
+	if let Some(tempo) = opcoes.tempo_total {
+		texto.push_str(&format!("tempo_total={}\n", tempo.as_secs()));
+	}
+	if let Some(arquivo) = &opcoes.arquivo_frota {
+		texto.push_str(&format!("frota={}\n", arquivo));
+	}
+	if let Some(mapa) = &opcoes.mapa {
+		texto.push_str(&format!("mapa={}\n", mapa));
+	}
+
+	texto.push_str("[frota]\n");
+	texto.push_str(&frota::para_texto(&jogo.navios_humano));
+	texto.push_str("[terreno]\n");
+	texto.push_str(&terreno::para_texto(&jogo.terreno));
+
+	for (secao,navios) in [("navios_humano",&jogo.navios_humano), ("navios_bot",&jogo.navios_bot)] {
+		texto.push_str(&format!("[{}]\n", secao));
+		for navio in navios.iter() {
+			texto.push_str(&format!("{} {} {} {}\n", navio.popa_x, navio.popa_y,
+										nome_da_direcao(navio.direcao), navio.espelhado));
+		}
+	}
+
+	for (secao,mapa) in [("tiros_humano",&batalha.mapa_humano), ("tiros_bot",&batalha.mapa_bot)] {
+		texto.push_str(&format!("[{}]\n", secao));
+		for linha in mapa.iter() {
+			let linha: String = linha.iter().map(|&c| if c == DESTRUIDO { 'X' } else { '.' }).collect();
+			texto.push_str(&linha);
+			texto.push('\n');
+		}
+	}
+
+	let arsenal = |a:&Arsenal| format!("{} {} {}", a.bombas, a.torpedos, a.radares);
+	texto.push_str("[estado]\n");
+	texto.push_str(&format!("mira={} {}\n", batalha.mira_x, batalha.mira_y));
+	texto.push_str(&format!("arma={}\n", batalha.arma.nome().to_lowercase()));
+	texto.push_str(&format!("torpedo={}\n", nome_da_direcao(batalha.direcao_torpedo)));
+	texto.push_str(&format!("arsenal_humano={}\n", arsenal(&batalha.arsenal_humano)));
+	texto.push_str(&format!("arsenal_bot={}\n", arsenal(&batalha.arsenal_bot)));
+	texto.push_str(&format!("ataques={}\ntiros={}\nacertos={}\n", batalha.ataques, batalha.tiros, batalha.acertos));
+	if let Some(restante) = batalha.relogio.restante_total() {
+		texto.push_str(&format!("restante={}\n", restante.as_millis()));
+	}
+	texto.push_str(&format!("semente={}\nrodada={}\n", batalha.semente, batalha.rodada));
+	texto.push_str(&format!("dicas={}\n", batalha.dicas));
+	texto.push_str(&format!("primeiros_tiros={}\n", memoria::texto_das_posicoes(&batalha.primeiros_tiros)));
+	texto
+}
+
+
+// Separa as seções, sem linhas vazias e comentários
+pub fn secoes(texto:&str) -> BTreeMap<&str, Vec<&str>> {
+	let mut secoes: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+	let mut atual = "";
+	for linha in texto.lines() {
+		let linha = linha.trim_end();
+		if linha.is_empty() || linha.starts_with('#') {
+			continue;
+		}
+		match linha.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+			Some(nome) => atual = nome,
+			None => secoes.entry(atual).or_default().push(linha),
+		}
+	}
+	secoes
+}
+
+
+// Pares chave=valor de uma seção
+fn valores<'a>(linhas:&[&'a str]) -> BTreeMap<&'a str, &'a str> {
+	linhas.iter().filter_map(|linha| linha.split_once('=')).collect()
+}
+
+
+// Valor obrigatório de uma chave
+fn valor<T:FromStr>(valores:&BTreeMap<&str,&str>, chave:&str) -> Result<T, String> {
+	valores.get(chave)
+		.ok_or_else(|| tr!("falta '{}'", chave))?
+		.parse()
+		.map_err(|_| tr!("valor inválido em '{}'", chave))
+}
+
+
+// Valor opcional de uma chave
+fn talvez<T:FromStr>(valores:&BTreeMap<&str,&str>, chave:&str) -> Result<Option<T>, String> {
+	match valores.contains_key(chave) {
+		true => valor(valores, chave).map(Some),
+		false => Ok(None),
+	}
+}
+
+
+// Vários números separados por espaço
+fn numeros<T:FromStr>(texto:&str, quantos:usize) -> Result<Vec<T>, String> {
+	let numeros: Vec<T> = texto.split_whitespace()
+		.map(|n| n.parse().map_err(|_| tr!("número inválido '{}'", n)))
+		.collect::<Result<_,_>>()?;
+	if numeros.len() != quantos {
+		return Err(tr!("esperados {} números em '{}'", quantos, texto));
+	}
+	Ok(numeros)
+}
+
+
+// Coloca os navios nas posições gravadas
+fn posiciona(navios:&mut [Navio], linhas:&[&str]) -> Result<(), String> {
+	if linhas.len() != navios.len() {
+		return Err(String::from(tr!("número de navios diferente da frota")));
+	}
+	for (navio,linha) in navios.iter_mut().zip(linhas) {
+		let partes: Vec<&str> = linha.split_whitespace().collect();
+		let [x,y,direcao,espelhado] = partes[..] else {
+			return Err(tr!("navio inválido '{}'", linha));
+		};
+		navio.popa_x = x.parse().map_err(|_| tr!("navio inválido '{}'", linha))?;
+		navio.popa_y = y.parse().map_err(|_| tr!("navio inválido '{}'", linha))?;
+		navio.direcao = direcao_por_nome(direcao)?;
+		navio.espelhado = espelhado.parse().map_err(|_| tr!("navio inválido '{}'", linha))?;
+	}
+	Ok(())
+}
+
+
+// Marca no mapa as células já atingidas
+fn aplica_tiros(mapa:&mut Mapa, linhas:&[&str]) -> Result<(), String> {
+	if linhas.len() != mapa.len() || linhas.iter().any(|l| l.chars().count() != mapa.len()) {
+		return Err(String::from(tr!("grade de tiros com tamanho diferente do tabuleiro")));
+	}
+	for (y,linha) in linhas.iter().enumerate() {
+		for (x,c) in linha.chars().enumerate() {
+			if c == 'X' {
+				mapa[y][x] = DESTRUIDO;
+			}
+		}
+	}
+	Ok(())
+}
+
+
+pub fn de_texto(texto:&str, opcoes:&mut Opcoes) -> Result<(Jogo,Batalha), String> {
+	let secoes = secoes(texto);
+	let secao = |nome:&str| secoes.get(nome).ok_or_else(|| tr!("falta a seção [{}]", nome));
+
+	// Opções só mudam depois que tudo foi lido
+	let salvas = valores(secao("opcoes")?);
+	let dificuldade = Dificuldade::por_nome(&valor::<String>(&salvas, "dificuldade")?)
+		.ok_or_else(|| String::from(tr!("dificuldade inválida")))?;
+	// Partidas salvas antes da regra de revelação mostravam tudo
+	let revelacao = match talvez::<String>(&salvas, "revelacao")? {
+		Some(nome) => Revelacao::por_nome(&nome).ok_or_else(|| tr!("valor inválido em '{}'", "revelacao"))?,
+		None => Revelacao::TOTAL,
+	};
+	let bot_revelacao = talvez(&salvas, "bot_revelacao")?.unwrap_or(false);
+	let tempo_jogada = talvez(&salvas, "tempo_jogada")?.map(Duration::from_secs);
+	let tempo_total = talvez(&salvas, "tempo_total")?.map(Duration::from_secs);
+
+	let frota = frota::le_frota(&secao("frota")?.join("\n"))?;
+	let terreno = terreno::le_terreno(&secao("terreno")?.join("\n"))?;
+	let mut jogo = Jogo { terreno, navios_humano: frota.clone(), navios_bot: frota };
+	posiciona(&mut jogo.navios_humano, secao("navios_humano")?)?;
+	posiciona(&mut jogo.navios_bot, secao("navios_bot")?)?;
+	for jogador in [Jogador::HUMANO, Jogador::BOT] {
+		if (0 .. jogo.navios(&jogador).len()).any(|n| !jogo.pode_colocar_navio(&jogador, n)) {
+			return Err(String::from(tr!("navios em posições inválidas")));
+		}
+	}
+
+	opcoes.lado = valor(&salvas, "lado")?;
+	opcoes.dificuldade = dificuldade;
+	opcoes.revelacao = revelacao;
+	opcoes.bot_revelacao = bot_revelacao;
+	opcoes.armas_especiais = valor(&salvas, "armas")?;
+	opcoes.atraso = Duration::from_millis(valor(&salvas, "atraso")?);
+	opcoes.tempo_jogada = tempo_jogada;
+	opcoes.tempo_total = tempo_total;
+	opcoes.arquivo_frota = talvez(&salvas, "frota")?;
+	opcoes.mapa = talvez(&salvas, "mapa")?;
+	opcoes.neblina = false;
+
+	// Partidas salvas antes da semente recebem uma nova
+	let estado = valores(secao("estado")?);
+	let semente = talvez(&estado, "semente")?.unwrap_or_else(|| rand::thread_rng().gen());
+	let mut batalha = Batalha::new(&jogo, opcoes, semente);
+	batalha.rodada = talvez(&estado, "rodada")?.unwrap_or(0);
+	batalha.dicas = talvez(&estado, "dicas")?.unwrap_or(opcoes.dicas);
+	if let Some(tiros) = estado.get("primeiros_tiros") {
+		batalha.primeiros_tiros = memoria::posicoes_de_texto(tiros)
+			.filter(|tiros| tiros.iter().all(|&(x,y)| x < jogo.lado() && y < jogo.lado()))
+			.ok_or_else(|| tr!("valor inválido em '{}'", "primeiros_tiros"))?;
+	}
+	aplica_tiros(&mut batalha.mapa_humano, secao("tiros_humano")?)?;
+	aplica_tiros(&mut batalha.mapa_bot, secao("tiros_bot")?)?;
+
+	let mira: Vec<usize> = numeros(&valor::<String>(&estado, "mira")?, 2)?;
+	if mira.iter().any(|&m| m >= jogo.lado()) {
+		return Err(String::from(tr!("mira fora do tabuleiro")));
+	}
+	(batalha.mira_x, batalha.mira_y) = (mira[0], mira[1]);
+	batalha.arma = arma_por_nome(&valor::<String>(&estado, "arma")?)?;
+	batalha.direcao_torpedo = direcao_por_nome(&valor::<String>(&estado, "torpedo")?)?;
+	for (chave,arsenal) in [("arsenal_humano",&mut batalha.arsenal_humano), ("arsenal_bot",&mut batalha.arsenal_bot)] {
+		let usos: Vec<u32> = numeros(&valor::<String>(&estado, chave)?, 3)?;
+		(arsenal.bombas, arsenal.torpedos, arsenal.radares) = (usos[0], usos[1], usos[2]);
+	}
+	batalha.ataques = valor(&estado, "ataques")?;
+	batalha.tiros = valor(&estado, "tiros")?;
+	batalha.acertos = valor(&estado, "acertos")?;
+	let restante = talvez(&estado, "restante")?.map(Duration::from_millis);
+	batalha.relogio = Relogio::new(opcoes.tempo_jogada, restante);
+
+	Ok((jogo,batalha))
+}
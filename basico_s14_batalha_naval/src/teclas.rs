@@ -0,0 +1,333 @@
+/*
+	Teclas do jogo
+
+	Cada ação do jogo tem uma ou mais teclas. Há três conjuntos prontos, escolhidos
+	com '--teclas <nome>' ou na tela de opções, todos mantêm também as setas:
+
+		setas	setas movem, 'g' gira, 'e' espelha, 'm' muda, 'r' sorteia, 'i' inicia, 'f' ou espaço atira,
+				'c' mapa de calor, 'p' dica, 'n' manobra (só com '--neblina')
+		wasd	como o 'setas', 'w' 'a' 's' 'd' movem
+		hjkl	como o 'setas', 'h' 'j' 'k' 'l' movem, como no vi
+
+	As teclas podem ser trocadas no arquivo de configuração
+		$XDG_CONFIG_HOME/batalha_naval/teclas.txt
+	ou, sem XDG_CONFIG_HOME, em
+		~/.config/batalha_naval/teclas.txt
+	ou em outro arquivo dado com '--teclas <arquivo>':
+
+		# Linhas iniciadas com '#' são comentários
+		predefinido = hjkl		# conjunto de partida, 'setas' se omitido
+		fogo = f espaco			# ação = teclas separadas por espaço
+
+	Ações: cima baixo esquerda direita gira espelha muda sorteia inicia fogo tiro
+	bomba torpedo radar calor dica manobra pausa ajuda
+	Teclas: um caractere, seta_cima seta_baixo seta_esquerda seta_direita espaco enter tab esc
+
+	Ctrl+C e Ctrl+Q sempre abortam o jogo e não podem ser trocadas.
+*/
+
+
+use std::fs;
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode,KeyEvent,KeyModifiers};
+
+
+// Ações do jogo que têm teclas
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Acao {
+	CIMA,
+	BAIXO,
+	ESQUERDA,
+	DIREITA,
+	GIRA,
+	ESPELHA,
+	MUDA,
+	SORTEIA,
+	INICIA,
+	FOGO,
+	TIRO,
+	BOMBA,
+	TORPEDO,
+	RADAR,
+	CALOR,
+	DICA,
+	MANOBRA,
+	PAUSA,
+	AJUDA,
+}
+
+impl Acao {
+	const TODAS: [Acao;19] = [Acao::CIMA, Acao::BAIXO, Acao::ESQUERDA, Acao::DIREITA, Acao::GIRA, Acao::ESPELHA,
+		Acao::MUDA, Acao::SORTEIA, Acao::INICIA, Acao::FOGO, Acao::TIRO, Acao::BOMBA, Acao::TORPEDO, Acao::RADAR,
+		Acao::CALOR, Acao::DICA, Acao::MANOBRA, Acao::PAUSA, Acao::AJUDA];
+
+	// Ações da arrumação dos navios e da batalha, na ordem da ajuda
+	pub const ARRUMACAO: [Acao;11] = [Acao::CIMA, Acao::BAIXO, Acao::ESQUERDA, Acao::DIREITA,
+		Acao::GIRA, Acao::ESPELHA, Acao::MUDA, Acao::SORTEIA, Acao::INICIA, Acao::PAUSA, Acao::AJUDA];
+	pub const BATALHA: [Acao;15] = [Acao::CIMA, Acao::BAIXO, Acao::ESQUERDA, Acao::DIREITA, Acao::FOGO, Acao::TIRO,
+		Acao::BOMBA, Acao::TORPEDO, Acao::RADAR, Acao::GIRA, Acao::CALOR, Acao::DICA, Acao::MANOBRA, Acao::PAUSA, Acao::AJUDA];
+
+	// Nome usado no arquivo de configuração
+	fn nome(&self) -> &'static str {
+		match self {
+			Acao::CIMA => "cima",
+			Acao::BAIXO => "baixo",
+			Acao::ESQUERDA => "esquerda",
+			Acao::DIREITA => "direita",
+			Acao::GIRA => "gira",
+			Acao::ESPELHA => "espelha",
+			Acao::MUDA => "muda",
+			Acao::SORTEIA => "sorteia",
+			Acao::INICIA => "inicia",
+			Acao::FOGO => "fogo",
+			Acao::TIRO => "tiro",
+			Acao::BOMBA => "bomba",
+			Acao::TORPEDO => "torpedo",
+			Acao::RADAR => "radar",
+			Acao::CALOR => "calor",
+			Acao::DICA => "dica",
+			Acao::MANOBRA => "manobra",
+			Acao::PAUSA => "pausa",
+			Acao::AJUDA => "ajuda",
+		}
+	}
+
+	// Texto da ajuda
+	fn descricao(&self) -> &'static str {
+		match self {
+			Acao::CIMA => "Mover para cima",
+			Acao::BAIXO => "Mover para baixo",
+			Acao::ESQUERDA => "Mover para a esquerda",
+			Acao::DIREITA => "Mover para a direita",
+			Acao::GIRA => "Girar navio ou torpedo",
+			Acao::ESPELHA => "Espelhar navio",
+			Acao::MUDA => "Mudar de navio",
+			Acao::SORTEIA => "Sortear posições da frota",
+			Acao::INICIA => "Iniciar a batalha",
+			Acao::FOGO => "Atirar",
+			Acao::TIRO => "Arma: tiro",
+			Acao::BOMBA => "Arma: bomba",
+			Acao::TORPEDO => "Arma: torpedo",
+			Acao::RADAR => "Arma: radar",
+			Acao::CALOR => "Mapa de calor",
+			Acao::DICA => "Dica: mira na melhor",
+			Acao::MANOBRA => "Manobrar um navio",
+			Acao::PAUSA => "Pausa",
+			Acao::AJUDA => "Esta ajuda",
+		}
+	}
+}
+
+
+// Teclas de cada ação
+#[derive(Debug,Clone)]
+pub struct Teclas {
+	pub nome: String,						// Conjunto predefinido ou arquivo
+	ligacoes: Vec<(Acao,Vec<KeyCode>)>,
+}
+
+impl Teclas {
+	pub const PREDEFINIDAS: [&'static str;3] = ["setas", "wasd", "hjkl"];
+
+	// Conjunto pronto com o nome dado
+	pub fn predefinida(nome: &str) -> Option<Teclas> {
+		let movimento = match nome {
+			"setas" => vec![],
+			"wasd" => vec!['w','s','a','d'],
+			"hjkl" => vec!['k','j','h','l'],
+			_ => return None,
+		};
+		let setas = [KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right];
+		let mut ligacoes = Vec::new();
+		for (i,acao) in [Acao::CIMA, Acao::BAIXO, Acao::ESQUERDA, Acao::DIREITA].into_iter().enumerate() {
+			let mut teclas: Vec<KeyCode> = movimento.get(i).map(|&c| KeyCode::Char(c)).into_iter().collect();
+			teclas.push(setas[i]);
+			ligacoes.push((acao, teclas));
+		}
+		for (acao,teclas) in [
+			(Acao::GIRA, vec![KeyCode::Char('g')]),
+			(Acao::ESPELHA, vec![KeyCode::Char('e')]),
+			(Acao::MUDA, vec![KeyCode::Char('m')]),
+			(Acao::SORTEIA, vec![KeyCode::Char('r')]),
+			(Acao::INICIA, vec![KeyCode::Char('i')]),
+			(Acao::FOGO, vec![KeyCode::Char('f'), KeyCode::Char(' ')]),
+			(Acao::TIRO, vec![KeyCode::Char('1')]),
+			(Acao::BOMBA, vec![KeyCode::Char('2')]),
+			(Acao::TORPEDO, vec![KeyCode::Char('3')]),
+			(Acao::RADAR, vec![KeyCode::Char('4')]),
+			(Acao::CALOR, vec![KeyCode::Char('c')]),
+			(Acao::DICA, vec![KeyCode::Char('p')]),
+			(Acao::MANOBRA, vec![KeyCode::Char('n')]),
+			(Acao::PAUSA, vec![KeyCode::Esc]),
+			(Acao::AJUDA, vec![KeyCode::Char('?')]),
+		] {
+			ligacoes.push((acao, teclas));
+		}
+		Some(Teclas{ nome: String::from(nome), ligacoes })
+	}
+
+	// Teclas do arquivo de configuração, se existir, ou as setas
+	pub fn padrao() -> Result<Teclas, String> {
+		let Some(arquivo) = arquivo_configuracao() else {
+			return Ok(Teclas::predefinida("setas").expect("conjunto 'setas' existe"));
+		};
+		match fs::read_to_string(&arquivo) {
+			Ok(texto) => Teclas::de_texto(&arquivo.display().to_string(), &texto),
+			Err(erro) if erro.kind() == std::io::ErrorKind::NotFound => Ok(Teclas::predefinida("setas").expect("conjunto 'setas' existe")),
+			Err(erro) => Err(tr!("Não conseguiu ler as teclas {}: {}", arquivo.display(), erro)),
+		}
+	}
+
+	// Conjunto predefinido com o nome dado ou lido de um arquivo
+	pub fn carrega(nome: &str) -> Result<Teclas, String> {
+		if let Some(teclas) = Teclas::predefinida(nome) {
+			return Ok(teclas);
+		}
+		let texto = fs::read_to_string(nome)
+			.map_err(|erro| tr!("Não conseguiu ler as teclas {}: {} (predefinidas: {})",
+							nome, erro, Teclas::PREDEFINIDAS.join(", ")))?;
+		Teclas::de_texto(nome, &texto)
+	}
+
+	// Interpreta o arquivo de configuração
+	fn de_texto(nome: &str, texto: &str) -> Result<Teclas, String> {
+		let erro_na = |numero: usize, erro: String| tr!("Teclas {}: linha {}: {}", nome, numero+1, erro);
+		let mut teclas = Teclas::predefinida("setas").expect("conjunto 'setas' existe");
+
+		for (numero,linha) in texto.lines().enumerate() {
+			let linha = linha.split('#').next().unwrap_or("").trim();
+			if linha.is_empty() {
+				continue;
+			}
+			let Some((chave,valor)) = linha.split_once('=') else {
+				return Err(erro_na(numero, String::from(tr!("esperado 'ação = teclas'"))));
+			};
+			let (chave,valor) = (chave.trim(), valor.trim());
+			if chave == "predefinido" {
+				teclas = Teclas::predefinida(valor)
+					.ok_or_else(|| erro_na(numero, tr!("conjunto desconhecido '{}'", valor)))?;
+				continue;
+			}
+			let acao = Acao::TODAS.into_iter().find(|a| a.nome() == chave)
+				.ok_or_else(|| erro_na(numero, tr!("ação desconhecida '{}'", chave)))?;
+			let codigos = valor.split_whitespace()
+				.map(|t| tecla_por_nome(t).ok_or_else(|| erro_na(numero, tr!("tecla desconhecida '{}'", t))))
+				.collect::<Result<Vec<KeyCode>,String>>()?;
+			if codigos.is_empty() {
+				return Err(erro_na(numero, tr!("nenhuma tecla para '{}'", chave)));
+			}
+			if let Some(ligacao) = teclas.ligacoes.iter_mut().find(|(a,_)| *a == acao) {
+				ligacao.1 = codigos;
+			}
+		}
+
+		// Uma tecla não pode ter duas ações
+		for (i,(acao,codigos)) in teclas.ligacoes.iter().enumerate() {
+			for (outra,outros) in teclas.ligacoes[i+1..].iter() {
+				if let Some(repetida) = codigos.iter().find(|c| outros.contains(c)) {
+					return Err(tr!("Teclas {}: '{}' usada em '{}' e '{}'",
+									nome, nome_da_tecla(*repetida), acao.nome(), outra.nome()));
+				}
+			}
+		}
+
+		teclas.nome = String::from(nome);
+		Ok(teclas)
+	}
+
+	// Ação da tecla pressionada, se houver; combinações com Ctrl ou Alt não são ações
+	pub fn acao(&self, evento: &KeyEvent) -> Option<Acao> {
+		if evento.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+			return None;
+		}
+		let codigo = match evento.code {
+			KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+			codigo => codigo,
+		};
+		self.ligacoes.iter().find(|(_,codigos)| codigos.contains(&codigo)).map(|(acao,_)| *acao)
+	}
+
+	// Primeira tecla da ação, como aparece nas instruções
+	pub fn tecla(&self, acao: Acao) -> String {
+		match self.ligacoes.iter().find(|(a,_)| *a == acao).and_then(|(_,codigos)| codigos.first()) {
+			Some(KeyCode::Char(c)) if *c != ' ' => format!("'{}'", c),
+			Some(&codigo) => nome_da_tecla(codigo),
+			None => String::from("?"),
+		}
+	}
+
+	// Primeiras teclas de movimento juntas, para as instruções
+	pub fn movimento(&self) -> String {
+		[Acao::CIMA, Acao::ESQUERDA, Acao::BAIXO, Acao::DIREITA].iter()
+			.filter_map(|&acao| self.ligacoes.iter().find(|(a,_)| *a == acao))
+			.filter_map(|(_,codigos)| codigos.first())
+			.map(|&codigo| nome_da_tecla(codigo))
+			.collect()
+	}
+
+	// Linhas da ajuda para as ações dadas
+	pub fn ajuda(&self, acoes: &[Acao]) -> Vec<String> {
+		let mut linhas: Vec<String> = acoes.iter().map(|&acao| {
+			let codigos = self.ligacoes.iter().find(|(a,_)| *a == acao).map(|(_,c)| c.as_slice()).unwrap_or(&[]);
+			let nomes: Vec<String> = codigos.iter().map(|&c| nome_da_tecla(c)).collect();
+			format!("{:<24}{}", tr!(acao.descricao()), nomes.join(" "))
+		}).collect();
+		linhas.push(format!("{:<24}Ctrl+C Ctrl+Q", tr!("Abortar o jogo")));
+		linhas
+	}
+}
+
+
+// Ctrl+C e Ctrl+Q abortam o jogo em qualquer lugar
+pub fn aborta(evento: &KeyEvent) -> bool {
+	evento.modifiers.contains(KeyModifiers::CONTROL)
+		&& matches!(evento.code, KeyCode::Char('c'|'C'|'q'|'Q'))
+}
+
+
+fn tecla_por_nome(nome: &str) -> Option<KeyCode> {
+	match nome {
+		"seta_cima" => Some(KeyCode::Up),
+		"seta_baixo" => Some(KeyCode::Down),
+		"seta_esquerda" => Some(KeyCode::Left),
+		"seta_direita" => Some(KeyCode::Right),
+		"espaco" => Some(KeyCode::Char(' ')),
+		"enter" => Some(KeyCode::Enter),
+		"tab" => Some(KeyCode::Tab),
+		"esc" => Some(KeyCode::Esc),
+		_ => {
+			let mut caracteres = nome.chars();
+			match (caracteres.next(), caracteres.next()) {
+				(Some(c), None) => Some(KeyCode::Char(c.to_ascii_lowercase())),
+				_ => None,
+			}
+		}
+	}
+}
+
+
+fn nome_da_tecla(codigo: KeyCode) -> String {
+	match codigo {
+		KeyCode::Up => String::from("\u{2191}"),
+		KeyCode::Down => String::from("\u{2193}"),
+		KeyCode::Left => String::from("\u{2190}"),
+		KeyCode::Right => String::from("\u{2192}"),
+		KeyCode::Char(' ') => String::from(tr!("espaço")),
+		KeyCode::Char(c) => String::from(c),
+		KeyCode::Enter => String::from("Enter"),
+		KeyCode::Tab => String::from("Tab"),
+		KeyCode::Esc => String::from("Esc"),
+		_ => String::from("?"),
+	}
+}
+
+
+// Arquivo de configuração das teclas, None se não há diretório do usuário
+fn arquivo_configuracao() -> Option<PathBuf> {
+	let base = match std::env::var_os("XDG_CONFIG_HOME") {
+		Some(configuracao) if !configuracao.is_empty() => PathBuf::from(configuracao),
+		_ => PathBuf::from(std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?).join(".config"),
+	};
+	Some(base.join("batalha_naval").join("teclas.txt"))
+}
@@ -0,0 +1,54 @@
+/*
+	Ciclo de vida do terminal
+
+	- O jogo roda na tela alternativa, ao sair a tela original volta como estava
+	- 'Tela' restaura o terminal quando sai de escopo, mesmo com erro
+	- Em caso de pânico o gancho restaura o terminal antes de mostrar a mensagem,
+	senão ela ficaria na tela alternativa, em raw mode e com as cores do tabuleiro
+*/
+
+
+use std::io::stdout;
+use std::panic;
+
+use crossterm::{ExecutableCommand,QueueableCommand};
+use crossterm::terminal::{self,EnterAlternateScreen,LeaveAlternateScreen};
+use crossterm::cursor;
+use crossterm::style::ResetColor;
+
+use crate::erro::ErroJogo;
+
+
+// Enquanto existir, o jogo está na tela alternativa
+pub struct Tela;
+
+impl Tela {
+	// Entra na tela alternativa e instala o gancho de pânico
+	pub fn abre() -> Result<Tela, ErroJogo> {
+		let anterior = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			restaura();
+			anterior(info);
+		}));
+		stdout().execute(EnterAlternateScreen)?;
+		Ok(Tela)
+	}
+}
+
+impl Drop for Tela {
+	fn drop(&mut self) {
+		restaura();
+	}
+}
+
+
+// Volta o terminal ao normal, ignora falhas porque pode ser chamada durante um pânico
+fn restaura() {
+	let _ = terminal::disable_raw_mode();
+	let mut stdout = stdout();
+	let _ = stdout
+		.queue( ResetColor )
+		.and_then(|s| s.queue( cursor::Show ))
+		.and_then(|s| s.queue( LeaveAlternateScreen ));
+	let _ = stdout.execute( cursor::MoveToColumn(0) );
+}
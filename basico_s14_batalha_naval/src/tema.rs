@@ -0,0 +1,288 @@
+/*
+	Temas: cores e desenhos dos tabuleiros
+
+	- Paleta: cores do mar, da moldura, do terreno e dos navios
+		- clássico e noturno: mar ciano ou ciano escuro, navios nas cores da frota
+		- daltônico: cores da paleta Okabe-Ito, distinguíveis com deuteranopia e protanopia
+		- monocromático: sem cores, navios em vídeo reverso
+	- Glifos: caracteres usados na moldura e nas células
+		- unicode: blocos e cantos de caixa
+		- ascii: apenas caracteres ASCII, para terminais sem Unicode
+	- Acerto e tiro na água têm desenhos diferentes, não só cores diferentes
+	- Acerto que a regra não revela fica com COR_ANONIMA em vez da cor do navio
+	- Com NO_COLOR definida ou TERM=dumb o tema inicial é monocromático com glifos ASCII
+	- Escolhidos com '--tema <nome>' e '--glifos <nome>' ou na tela de opções
+*/
+
+
+use std::env;
+
+use crossterm::style::{Stylize,StyledContent};
+use crossterm::style::Color::{self,*};
+
+use crate::Navio;
+
+
+// Cores de uma paleta, os navios seguem a ordem de 'CORES_FROTA'
+struct Cores {
+	moldura: Color,
+	mar: Color,
+	ilha_frente: Color,
+	ilha_fundo: Color,
+	recife: Color,
+	navios: [Color;6],
+}
+
+// Cores com que a frota é descrita, ver frota.rs
+const CORES_FROTA: [Color;6] = [DarkBlue, DarkMagenta, DarkYellow, DarkRed, DarkGreen, DarkGrey];
+
+// Acerto que não diz o navio, fora das cores dos navios de todas as paletas, ver revelacao.rs
+pub const COR_ANONIMA: Color = Black;
+
+// Paleta com nome, sem cores é monocromática
+pub struct Paleta {
+	pub nome: &'static str,
+	cores: Option<Cores>,
+}
+
+pub const PALETAS: [Paleta;4] = [
+	Paleta{ nome: "clássico", cores: Some(Cores{
+		moldura: Black, mar: Cyan, ilha_frente: DarkGreen, ilha_fundo: DarkYellow, recife: DarkGrey,
+		navios: CORES_FROTA,
+	})},
+	Paleta{ nome: "noturno", cores: Some(Cores{
+		moldura: Black, mar: DarkCyan, ilha_frente: DarkGreen, ilha_fundo: DarkYellow, recife: Black,
+		navios: CORES_FROTA,
+	})},
+	// https://jfly.uni-koeln.de/color/ (Okabe e Ito)
+	Paleta{ nome: "daltônico", cores: Some(Cores{
+		moldura: Black,
+		mar: Rgb{ r: 0, g: 60, b: 100 },
+		ilha_frente: Black,
+		ilha_fundo: Rgb{ r: 230, g: 159, b: 0 },
+		recife: Grey,
+		navios: [
+			Rgb{ r: 86, g: 180, b: 233 },		// Azul celeste
+			Rgb{ r: 204, g: 121, b: 167 },		// Roxo avermelhado
+			Rgb{ r: 240, g: 228, b: 66 },		// Amarelo
+			Rgb{ r: 213, g: 94, b: 0 },			// Vermelhão
+			Rgb{ r: 0, g: 158, b: 115 },		// Verde azulado
+			Grey,
+		],
+	})},
+	Paleta{ nome: "monocromático", cores: None },
+];
+
+
+// Caracteres usados para desenhar os tabuleiros, células ocupam 2 colunas
+pub struct Glifos {
+	pub nome: &'static str,
+	mar: &'static str,
+	acerto: &'static str,
+	agua: &'static str,
+	ilha: &'static str,
+	recife: &'static str,
+	pub cantos_sup: [char;3],		// Esquerda, meio e direita
+	pub cantos_inf: [char;3],
+	pub horizontal: char,
+	pub vertical: char,
+	pub vertical_dupla: char,
+	pub setas: [&'static str;4],	// Cima, baixo, esquerda e direita
+	pub respingo: [&'static str;3],	// Quadros do tiro na água, ver animacao.rs
+	pub afundando: [&'static str;3],	// Quadros do navio afundando
+}
+
+// https://en.wikipedia.org/wiki/List_of_Unicode_characters
+pub const GLIFOS: [Glifos;2] = [
+	Glifos{
+		nome: "unicode",
+		mar: "\u{2588}\u{2588}",
+		acerto: "\u{0496}\u{0496}",		// Outras opções "\u{1F525}", "##"
+		agua: "\u{2022}\u{2022}",
+		ilha: "\u{2593}\u{2593}",
+		recife: "\u{2591}\u{2591}",
+		cantos_sup: ['\u{250C}', '\u{2565}', '\u{2510}'],
+		cantos_inf: ['\u{2514}', '\u{2568}', '\u{2518}'],
+		horizontal: '\u{2500}',
+		vertical: '\u{2502}',
+		vertical_dupla: '\u{2551}',
+		setas: ["\u{2191}", "\u{2193}", "\u{2190}", "\u{2192}"],
+		respingo: ["\u{00B7}\u{00B7}", "\u{25CB}\u{25CB}", "\u{25CC}\u{25CC}"],
+		afundando: ["\u{2592}\u{2592}", "\u{2591}\u{2591}", "\u{2248}\u{2248}"],
+	},
+	Glifos{
+		nome: "ascii",
+		mar: "  ",
+		acerto: "XX",
+		agua: "..",
+		ilha: "##",
+		recife: "%%",
+		cantos_sup: ['+', '+', '+'],
+		cantos_inf: ['+', '+', '+'],
+		horizontal: '-',
+		vertical: '|',
+		vertical_dupla: '|',
+		setas: ["^", "v", "<", ">"],
+		respingo: ["oo", "OO", "()"],
+		afundando: ["%%", "::", "~~"],
+	},
+];
+
+
+// Tema em uso, uma paleta e um conjunto de glifos
+#[derive(Copy,Clone)]
+pub struct Tema {
+	pub paleta: &'static Paleta,
+	pub glifos: &'static Glifos,
+}
+
+impl Tema {
+	// Tema inicial, monocromático e ASCII quando o terminal pede
+	pub fn padrao() -> Tema {
+		let sem_cor = env::var_os("NO_COLOR").is_some_and(|valor| !valor.is_empty());
+		let burro = env::var("TERM").is_ok_and(|termo| termo == "dumb");
+		if sem_cor || burro {
+			Tema{ paleta: &PALETAS[3], glifos: &GLIFOS[1] }
+		} else {
+			Tema{ paleta: &PALETAS[0], glifos: &GLIFOS[0] }
+		}
+	}
+
+	// Paleta pelo nome, aceita também sem acento
+	pub fn paleta(nome: &str) -> Option<&'static Paleta> {
+		PALETAS.iter().find(|paleta| paleta.nome == nome || sem_acento(paleta.nome) == nome)
+	}
+
+	// Conjunto de glifos pelo nome
+	pub fn glifos(nome: &str) -> Option<&'static Glifos> {
+		GLIFOS.iter().find(|glifos| glifos.nome == nome)
+	}
+
+	// Cores da moldura: frente e fundo
+	pub fn moldura(&self) -> (Color,Color) {
+		match &self.paleta.cores {
+			Some(cores) => (cores.moldura, cores.mar),
+			None => (Reset, Reset),
+		}
+	}
+
+	// Célula de mar sem tiro
+	pub fn mar(&self) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(cores) => self.glifos.mar.with(cores.mar).on(cores.mar),
+			None => "  ".stylize(),
+		}
+	}
+
+	// Célula de navio inteiro
+	pub fn navio(&self, navio: &Navio) -> StyledContent<String> {
+		let desenho = format!("{0}{0}", navio.letra);
+		match &self.paleta.cores {
+			Some(cores) => desenho.on(cor_do_navio(cores, navio.cor)),
+			None => desenho.reverse(),
+		}
+	}
+
+	// Pedaço de navio destruído
+	pub fn acerto(&self, navio: &Navio) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(cores) => self.glifos.acerto.on(cor_do_navio(cores, navio.cor)),
+			None => self.glifos.acerto.reverse(),
+		}
+	}
+
+	// Célula que o jogador marcou como navio no quebra-cabeça, sem dizer qual
+	pub fn marcado(&self) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(cores) => self.glifos.mar.with(cores.moldura).on(cores.mar),
+			None => self.glifos.mar.reverse(),
+		}
+	}
+
+	// Chance de navio no mapa de calor, a melhor célula em destaque
+	pub fn calor(&self, texto: String, destaque: bool) -> StyledContent<String> {
+		match (&self.paleta.cores, destaque) {
+			(Some(cores), false) => texto.with(cores.moldura).on(cores.mar),
+			(Some(cores), true) => texto.with(cores.mar).on(cores.moldura),
+			(None, false) => texto.stylize(),
+			(None, true) => texto.reverse(),
+		}
+	}
+
+	// Célula que o tutorial pede para alcançar
+	pub fn destaque(&self) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(cores) => "[]".with(cores.moldura).on(cores.mar).bold(),
+			None => "[]".reverse(),
+		}
+	}
+
+	// Clarão da explosão, alterna com o acerto na animação
+	pub fn clarao(&self) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(_) => self.glifos.acerto.with(Yellow).on(Red),
+			None => self.glifos.acerto.stylize(),
+		}
+	}
+
+	// Quadro do respingo de um tiro na água, None depois do último
+	pub fn respingo(&self, quadro: usize) -> Option<StyledContent<&'static str>> {
+		let respingo = self.glifos.respingo.get(quadro)?;
+		Some(match &self.paleta.cores {
+			Some(cores) => respingo.with(cores.moldura).on(cores.mar),
+			None => respingo.stylize(),
+		})
+	}
+
+	// Quadro do navio afundando
+	pub fn afundando(&self, quadro: usize) -> StyledContent<&'static str> {
+		let afundando = self.glifos.afundando[quadro.min(self.glifos.afundando.len()-1)];
+		match &self.paleta.cores {
+			Some(cores) => afundando.with(cores.moldura).on(cores.mar),
+			None => afundando.stylize(),
+		}
+	}
+
+	// Tiro na água
+	pub fn agua(&self) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(cores) => self.glifos.agua.with(cores.moldura).on(cores.mar),
+			None => self.glifos.agua.stylize(),
+		}
+	}
+
+	// Ilha, igual nos dois tabuleiros
+	pub fn ilha(&self) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(cores) => self.glifos.ilha.with(cores.ilha_frente).on(cores.ilha_fundo),
+			None => self.glifos.ilha.stylize(),
+		}
+	}
+
+	// Recife, igual nos dois tabuleiros
+	pub fn recife(&self) -> StyledContent<&'static str> {
+		match &self.paleta.cores {
+			Some(cores) => self.glifos.recife.with(cores.recife).on(cores.mar),
+			None => self.glifos.recife.stylize(),
+		}
+	}
+}
+
+
+// Cor de um navio na paleta, pela posição da cor dele na frota
+fn cor_do_navio(cores: &Cores, cor: Color) -> Color {
+	CORES_FROTA.iter().position(|&c| c == cor).map_or(cor, |i| cores.navios[i])
+}
+
+// Nome sem acentos, para digitar na linha de comando
+fn sem_acento(nome: &str) -> String {
+	nome.chars().map(|c| match c {
+		'á' | 'â' | 'ã' => 'a',
+		'é' | 'ê' => 'e',
+		'í' => 'i',
+		'ó' | 'ô' | 'õ' => 'o',
+		'ú' => 'u',
+		'ç' => 'c',
+		c => c,
+	}).collect()
+}
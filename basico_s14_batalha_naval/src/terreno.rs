@@ -0,0 +1,144 @@
+/*
+	Terreno do tabuleiro: ilhas e recifes
+
+	O mapa é uma grade quadrada de texto, o lado dela é o lado do tabuleiro:
+
+		# Linhas iniciadas com '#' são comentários
+		'.' mar, 'I' ilha, 'R' recife
+
+	Navios não podem ficar sobre ilhas ou recifes e ninguém atira neles.
+	O mesmo terreno vale para os dois tabuleiros, os dois jogadores o conhecem.
+
+	Exemplo em assets/mapa_exemplo.txt
+*/
+
+
+use std::fs;
+
+use crate::{Mapa,AGUA,ILHA,RECIFE,LADO_MINIMO,LADO_MAXIMO};
+
+
+// Mapas prontos, escolhidos pelo nome
+pub const PREDEFINIDOS: [(&str,&str);3] = [
+	("arquipelago", "
+..........
+..II......
+..II...R..
+.......RR.
+....I.....
+...III....
+....I.....
+.R........
+.RR....II.
+.......I..
+"),
+	("recifes", "
+..........
+.R......R.
+..R....R..
+..........
+....RR....
+....RR....
+..........
+..R....R..
+.R......R.
+..........
+"),
+	("estreito", "
+III.......
+II........
+I.........
+..........
+..........
+..........
+..........
+.........I
+........II
+.......III
+"),
+];
+
+
+// Mar aberto, sem obstáculos
+pub fn mar_aberto(lado: usize) -> Mapa {
+	vec![vec![AGUA;lado];lado]
+}
+
+
+// Mapa predefinido com o nome dado ou lido de um arquivo, sem mapa é mar aberto com o lado pedido
+pub fn carrega(nome: Option<&str>, lado: usize) -> Result<Mapa, String> {
+	match nome {
+		None => Ok(mar_aberto(lado)),
+		Some(nome) => {
+			if let Some((_,texto)) = PREDEFINIDOS.iter().find(|(predefinido,_)| *predefinido == nome) {
+				return le_terreno(texto);
+			}
+			let texto = fs::read_to_string(nome)
+				.map_err(|erro| tr!("Não conseguiu ler o mapa {}: {} (predefinidos: {})",
+								nome, erro, PREDEFINIDOS.map(|(n,_)| n).join(", ")))?;
+			le_terreno(&texto).map_err(|erro| tr!("Mapa {}: {}", nome, erro))
+		}
+	}
+}
+
+
+// Interpreta a grade de texto, a primeira linha define o lado
+pub fn le_terreno(texto: &str) -> Result<Mapa, String> {
+	let mut mapa: Mapa = Vec::new();
+	let mut lado = 0;
+
+	for linha in texto.lines() {
+		let linha = linha.trim_end();
+		if linha.is_empty() || linha.starts_with('#') {
+			continue;
+		}
+		let y = mapa.len();
+		if y == 0 {
+			lado = linha.chars().count();
+			if !(LADO_MINIMO ..= LADO_MAXIMO).contains(&lado) {
+				return Err(tr!("o lado do mapa deve ser entre {} e {}", LADO_MINIMO, LADO_MAXIMO));
+			}
+		}
+		if y >= lado {
+			return Err(tr!("mais de {} linhas", lado));
+		}
+		if linha.chars().count() != lado {
+			return Err(tr!("linha {} do mapa deve ter {} colunas", y+1, lado));
+		}
+		let mut celulas = Vec::new();
+		for c in linha.chars() {
+			celulas.push(match c {
+				'.' => AGUA,
+				'I' | 'i' => ILHA,
+				'R' | 'r' => RECIFE,
+				_ => return Err(tr!("caractere inválido '{}' na linha {} do mapa", c, y+1)),
+			});
+		}
+		mapa.push(celulas);
+	}
+
+	if mapa.is_empty() {
+		return Err(String::from(tr!("mapa vazio")));
+	}
+	if mapa.len() != lado {
+		return Err(tr!("o mapa deve ter {} linhas", lado));
+	}
+	Ok(mapa)
+}
+
+
+// Escreve o terreno na mesma grade lida por 'carrega', navios e tiros são ignorados
+pub fn para_texto(mapa: &Mapa) -> String {
+	let mut texto = String::new();
+	for linha in mapa.iter() {
+		for &celula in linha.iter() {
+			texto.push(match celula {
+				ILHA => 'I',
+				RECIFE => 'R',
+				_ => '.',
+			});
+		}
+		texto.push('\n');
+	}
+	texto
+}
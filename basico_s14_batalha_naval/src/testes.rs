@@ -0,0 +1,992 @@
+/*
+	Testes da interface, desenhando em um 'ConsoleVirtual'
+
+	- Fotografias do texto da tela: moldura, frota arrumada, acertos e tiros na água, linhas de status
+	- Cores e vídeo reverso conferidos célula a célula
+	- Arrumação dos navios com roteiros de teclas
+	- Servidor da partida em rede e saguão, com clientes locais no lugar do navegador
+	- Partidas de vários jogadores: entre bots e a tela com vários tabuleiros
+	- Mapa de calor: chances com tiros na água e acertos, dicas movendo a mira
+	- Animações: quadros do respingo, da explosão e do naufrágio, campainha
+	- Tutorial: roteiro completo, teclas fora de hora não valem
+	- Tabuleiro hexagonal: tela deslocada com a frota sorteada
+	- Quebra-cabeça: resolvido com teclas e dicas
+	- Os glifos ASCII deixam as fotografias legíveis, o idioma é sempre o português
+
+	A lógica sem tela é testada no próprio módulo (desafio.rs, equipes.rs,
+	hexagonal.rs, memoria.rs, perfil.rs, quebra_cabeca.rs, rede.rs, treino.rs),
+	com os jogos e opções de teste daqui.
+*/
+
+
+use crossterm::event::{Event,KeyCode,KeyEvent,KeyModifiers};
+use std::io::{Read,Write};
+use std::net::TcpStream;
+use std::thread;
+
+use crossterm::style::Color;
+
+use crate::*;
+use crate::console_virtual::ConsoleVirtual;
+use crate::tema::{GLIFOS,PALETAS};
+
+
+// Tema com glifos ASCII, na paleta dada
+fn tema_ascii(paleta: usize) -> Tema {
+	Tema{ paleta: &PALETAS[paleta], glifos: &GLIFOS[1] }
+}
+
+// Opções como as da linha de comando sem argumentos, com teclas e tema fixos
+pub(crate) fn opcoes_de_teste(lado: usize) -> Opcoes {
+	Opcoes{
+		lado,
+		armas_especiais: false,
+		arquivo_frota: None,
+		mapa: None,
+		tempo_jogada: None,
+		tempo_total: None,
+		atraso: Duration::ZERO,
+		dificuldade: Dificuldade::FACIL,
+		tema: tema_ascii(3),
+		teclas: Teclas::predefinida("setas").unwrap(),
+		jogador: String::from("teste"),
+		placar: false,
+		memoria: false,
+		esquece_memoria: false,
+		treina: None,
+		animacao: false,
+		som: false,
+		neblina: false,
+		revelacao: Revelacao::TOTAL,
+		bot_revelacao: false,
+		semente: Some(0),
+		servidor: None,
+		saguao: None,
+		vagas: None,
+		dicas: 0,
+		desafio: None,
+		desafios: false,
+		exporta_desafios: None,
+		importa_desafios: None,
+		reprise: None,
+	}
+}
+
+// Jogo com a frota clássica em mar aberto
+pub(crate) fn jogo_de_teste(lado: usize) -> Jogo {
+	Jogo::new(&frota::carrega(None).unwrap(), terreno::mar_aberto(lado)).unwrap()
+}
+
+// Arrumação do humano com as opções de teste e a semente 0
+fn arruma(con: &mut ConsoleVirtual, jogo: &mut Jogo) -> Result<Desfecho, ErroJogo> {
+	arruma_navios_humano(con, jogo, &opcoes_de_teste(jogo.lado()), &mut gerador(0, FLUXO_FROTA_HUMANO))
+}
+
+// Posições dos navios, para comparar arrumações
+pub(crate) fn posicoes(navios: &[Navio]) -> Vec<(usize,usize,String)> {
+	navios.iter().map(|n| (n.popa_x, n.popa_y, format!("{:?}{}", n.direcao, n.espelhado))).collect()
+}
+
+// Ctrl+tecla
+fn ctrl(letra: char) -> Event {
+	Event::Key(KeyEvent::new(KeyCode::Char(letra), KeyModifiers::CONTROL))
+}
+
+
+#[test]
+fn moldura_com_terreno() {
+	let mut con = ConsoleVirtual::new(40, 14);
+	let mut terreno = terreno::mar_aberto(6);
+	terreno[1][2] = ILHA;
+	terreno[4][0] = RECIFE;
+	desenha_moldura(&mut con, 0, 0, &tema_ascii(3), &terreno).unwrap();
+	assert_eq!(con.texto(), "\
++------------+------------+
+|            |            |
+|    ##      |    ##      |
+|            |            |
+|            |            |
+|%%          |%%          |
+|            |            |
++------------+------------+
++112233445566+112233445566+
+ Meus Navios    Inimigo");
+}
+
+#[test]
+fn moldura_unicode() {
+	let mut con = ConsoleVirtual::new(40, 14);
+	let tema = Tema{ paleta: &PALETAS[0], glifos: &GLIFOS[0] };
+	desenha_moldura(&mut con, 0, 0, &tema, &terreno::mar_aberto(6)).unwrap();
+	assert_eq!(con.linha(0), "┌────────────╥────────────┐");
+	assert_eq!(con.linha(3), "│            ║            │");
+	assert_eq!(con.linha(7), "└────────────╨────────────┘");
+	// O mar é o fundo da moldura
+	assert_eq!(con.celula(1, 1).fundo, Color::Cyan);
+}
+
+#[test]
+fn frota_arrumada() {
+	let mut con = ConsoleVirtual::new(90, 20);
+	let jogo = jogo_de_teste(10);
+	desenha_arrumacao(&mut con, &jogo, &opcoes_de_teste(10), 0).unwrap();
+	assert_eq!(con.texto(), "\
++--------------------+--------------------+
+|                    |                    |
+|  PPPPPPPPPP        |                    |
+|                    |                    |
+|  CCCCCCCC          |                    |
+|                    |                    |
+|  DDDDDD            |                    |
+|                    |                    |
+|  SSSS              |                    |
+|                    |                    |
+|                    |                    |
++--------------------+--------------------+
++11223344556677889900+11223344556677889900+
+     Meus Navios            Inimigo
+Mova com ↑←↓→, 'g' gira, 'e' espelha, 'm' muda, 'i' inicia, Esc pausa, '?' ajuda
+Navio: Porta-aviões");
+	// Cursor na popa do navio corrente
+	assert_eq!(con.cursor(), (3, 2));
+}
+
+#[test]
+fn navios_coloridos_ou_em_video_reverso() {
+	let jogo = jogo_de_teste(10);
+
+	let mut con = ConsoleVirtual::new(90, 20);
+	for navio in jogo.navios_humano.iter() {
+		desenha_navio(&mut con, navio, &tema_ascii(0), false).unwrap();
+	}
+	assert_eq!(con.celula(3, 2).fundo, Color::DarkBlue);
+	assert_eq!(con.celula(3, 4).fundo, Color::DarkMagenta);
+	assert!(!con.celula(3, 2).reverso);
+
+	let mut con = ConsoleVirtual::new(90, 20);
+	desenha_navio(&mut con, &jogo.navios_humano[0], &tema_ascii(3), false).unwrap();
+	assert_eq!(con.celula(3, 2).letra, 'P');
+	assert_eq!(con.celula(3, 2).fundo, Color::Reset);
+	assert!(con.celula(3, 2).reverso);
+}
+
+#[test]
+fn acertos_e_tiros_na_agua() {
+	let mut con = ConsoleVirtual::new(40, 14);
+	let mut jogo = jogo_de_teste(6);
+	jogo.navios_humano.truncate(1);
+	jogo.navios_bot.truncate(1);
+	let mut mapa = jogo.mapeia_bot(None);
+	desenha_moldura(&mut con, 0, 0, &tema_ascii(3), &jogo.terreno).unwrap();
+
+	// Bomba abaixo do porta-aviões: 3 pedaços e 6 células de água
+	let impacto = realiza_ataque(&mut con, &mut mapa, &jogo.navios_bot, &Jogador::BOT,
+									(Arma::BOMBA, Direcao::LESTE), (2,2), &tema_ascii(3)).unwrap();
+	assert_eq!(impacto.atingidas, 9);
+	assert_eq!(impacto.acertos, vec![(1,1),(2,1),(3,1)]);
+
+	// Tiro simples na água do tabuleiro humano
+	realiza_ataque(&mut con, &mut jogo.mapeia_humano(None), &jogo.navios_humano, &Jogador::HUMANO,
+									(Arma::TIRO, Direcao::LESTE), (5,5), &tema_ascii(3)).unwrap();
+	assert_eq!(con.texto(), "\
++------------+------------+
+|            |            |
+|            |  XXXXXX    |
+|            |  ......    |
+|            |  ......    |
+|            |            |
+|          ..|            |
++------------+------------+
++112233445566+112233445566+
+ Meus Navios    Inimigo");
+	// Acerto tem desenho diferente da água também nas células, não só na cor
+	assert!(con.celula(16, 2).reverso);
+	assert!(!con.celula(16, 3).reverso);
+	assert_eq!(pedacos_vivos(&mapa), 2);
+}
+
+#[test]
+fn linhas_de_status() {
+	let mut con = ConsoleVirtual::new(40, 14);
+	let jogo = jogo_de_teste(6);
+	desenha_moldura(&mut con, 0, 0, &tema_ascii(3), &jogo.terreno).unwrap();
+	mostra_placar(&mut con, 14, 9, 6).unwrap();
+	escreve_status(&mut con, 1, "Arma: Tiro").unwrap();
+	escreve_status(&mut con, 2, "Radar: nenhum navio na área.").unwrap();
+	escreve_status(&mut con, 2, "O bot usou: Bomba").unwrap();
+	assert_eq!(con.linha(10), "Restam:    14          9");
+	assert_eq!(con.linha(11), "Arma: Tiro");
+	assert_eq!(con.linha(12), "O bot usou: Bomba");
+}
+
+#[test]
+fn arrumacao_move_gira_e_muda_de_navio() {
+	let mut con = ConsoleVirtual::new(90, 20);
+	let mut jogo = jogo_de_teste(10);
+
+	// Porta-aviões vai para a direita e para baixo, não gira porque bateria no cruzador;
+	// cruzador vai para a esquerda; submarino gira
+	con.tecla(KeyCode::Right);
+	con.tecla(KeyCode::Down);
+	con.digita("gm");
+	con.tecla(KeyCode::Left);
+	con.digita("mmgi");
+	assert!(matches!(arruma(&mut con, &mut jogo), Ok(Desfecho::CONTINUA)));
+	assert_eq!(con.eventos_restantes(), 0);
+
+	let porta_avioes = &jogo.navios_humano[0];
+	assert_eq!((porta_avioes.popa_x, porta_avioes.popa_y), (2, 2));
+	assert!(matches!(porta_avioes.direcao, Direcao::LESTE));
+	let cruzador = &jogo.navios_humano[1];
+	assert_eq!((cruzador.popa_x, cruzador.popa_y), (0, 3));
+	assert!(matches!(jogo.navios_humano[3].direcao, Direcao::SUL));
+
+	// A tela acompanha as posições novas
+	assert_eq!(con.texto(), "\
++--------------------+--------------------+
+|                    |                    |
+|                    |                    |
+|    PPPPPPPPPP      |                    |
+|CCCCCCCC            |                    |
+|                    |                    |
+|  DDDDDD            |                    |
+|                    |                    |
+|  SS                |                    |
+|  SS                |                    |
+|                    |                    |
++--------------------+--------------------+
++11223344556677889900+11223344556677889900+
+     Meus Navios            Inimigo
+Mova com ↑←↓→, 'g' gira, 'e' espelha, 'm' muda, 'i' inicia, Esc pausa, '?' ajuda
+Navio: Submarino");
+}
+
+
+#[test]
+fn arrumacao_nao_sai_do_tabuleiro() {
+	let mut con = ConsoleVirtual::new(90, 20);
+	let mut jogo = jogo_de_teste(10);
+	con.tecla(KeyCode::Left);
+	con.tecla(KeyCode::Left);
+	con.tecla(KeyCode::Up);
+	con.tecla(KeyCode::Up);
+	con.digita("i");
+	arruma(&mut con, &mut jogo).unwrap();
+	assert_eq!((jogo.navios_humano[0].popa_x, jogo.navios_humano[0].popa_y), (0, 0));
+}
+
+#[test]
+fn arrumacao_abortada() {
+	let mut con = ConsoleVirtual::new(90, 20);
+	let mut jogo = jogo_de_teste(10);
+	con.tecla(KeyCode::Right);
+	con.envia(ctrl('q'));
+	con.digita("i");
+	assert!(matches!(arruma(&mut con, &mut jogo), Ok(Desfecho::ABORTADO)));
+	assert_eq!(con.eventos_restantes(), 1);
+}
+
+#[test]
+fn arrumacao_em_terminal_pequeno() {
+	let mut con = ConsoleVirtual::new(30, 10);
+	let mut jogo = jogo_de_teste(10);
+	con.envia(Event::Resize(30, 10));
+	assert!(matches!(arruma(&mut con, &mut jogo), Err(ErroJogo::TAMANHO(43, 18))));
+}
+
+#[test]
+fn arrumacao_sorteada() {
+	let mut con = ConsoleVirtual::new(90, 20);
+	let mut jogo = jogo_de_teste(10);
+	let inicial = posicoes(&jogo.navios_humano);
+	con.digita("ri");
+	arruma(&mut con, &mut jogo).unwrap();
+	assert_ne!(posicoes(&jogo.navios_humano), inicial);
+	assert!((0 .. jogo.navios_humano.len()).all(|n| jogo.pode_colocar_navio(&Jogador::HUMANO, n)));
+	// A tela foi redesenhada com a frota sorteada
+	let (x,y) = (jogo.navios_humano[3].popa_x, jogo.navios_humano[3].popa_y);
+	assert_eq!(con.celula(x_para_tela_humano(x), y_para_tela_humano(y)).letra, 'S');
+}
+
+#[test]
+fn mesma_semente_mesmo_sorteio() {
+	let sorteio = |semente: u64| {
+		let mut jogo = jogo_de_teste(10);
+		sorteia_navios(&mut jogo, &Jogador::BOT, &mut gerador(semente, FLUXO_FROTA_BOT));
+		posicoes(&jogo.navios_bot)
+	};
+	assert_eq!(sorteio(42), sorteio(42));
+	assert_ne!(sorteio(42), sorteio(43));
+
+	// Cada rodada tem o seu gerador, uma partida carregada continua igual
+	let mapa = terreno::mar_aberto(10);
+	let tiro = |rodada: u64| bot::posicao_aleatoria(&mapa, &mut gerador(42, FLUXO_RODADAS + rodada));
+	assert_eq!(tiro(5), tiro(5));
+}
+
+#[test]
+fn reprise_repete_a_partida() {
+	let opcoes = Opcoes{ armas_especiais: true, dificuldade: Dificuldade::NORMAL, semente: Some(5), ..opcoes_de_teste(8) };
+	let mut jogo = jogo_de_teste(8);
+	sorteia_navios(&mut jogo, &Jogador::HUMANO, &mut gerador(5, FLUXO_FROTA_HUMANO));
+	sorteia_navios(&mut jogo, &Jogador::BOT, &mut gerador(5, FLUXO_FROTA_BOT));
+	let mut batalha = Batalha::new(&jogo, &opcoes, 5);
+	let mut con = ConsoleVirtual::new(90, 24);
+
+	// Tiro, bomba e torpedo girado para o sul, depois aborta
+	con.digita("f2");
+	con.tecla(KeyCode::Right);
+	con.tecla(KeyCode::Down);
+	con.digita("f3g");
+	con.tecla(KeyCode::Down);
+	con.digita("f");
+	con.envia(ctrl('c'));
+	assert!(matches!(executa_batalha(&mut con, &mut jogo, &opcoes, &mut batalha, None), Ok(Desfecho::ABORTADO)));
+	let texto = batalha.reprise.para_texto().unwrap();
+	assert!(texto.starts_with("# Batalha Naval, reprise\n"));
+	assert!(texto.ends_with("[jogadas]\ntiro 0 0 leste\nbomba 1 1 leste\ntorpedo 1 2 sul\n"));
+
+	// A reprise faz os mesmos ataques e o bot responde igual, com as opções gravadas
+	let mut opcoes_reprise = opcoes_de_teste(6);
+	let (mut jogo_reprise, mut reprise) = reprise::de_texto(&texto, &mut opcoes_reprise).unwrap();
+	let mut con = ConsoleVirtual::new(90, 24);
+	assert!(matches!(executa_batalha(&mut con, &mut jogo_reprise, &opcoes_reprise, &mut reprise, None), Ok(Desfecho::CONTINUA)));
+	assert_eq!((opcoes_reprise.lado, opcoes_reprise.armas_especiais, opcoes_reprise.dificuldade), (8, true, Dificuldade::NORMAL));
+	assert_eq!(reprise.mapa_bot, batalha.mapa_bot);
+	assert_eq!(reprise.mapa_humano, batalha.mapa_humano);
+	assert_eq!(reprise.rodada, 3);
+	assert!(reprise.reprise.para_texto().is_none());
+
+	// Jogada fora do tabuleiro não é aceita
+	let errado = texto.replace("[jogadas]\n", "[jogadas]\nautomatico\ntiro 8 0 leste\n");
+	assert_eq!(reprise::de_texto(&errado, &mut opcoes_reprise).err().unwrap(), "mira fora do tabuleiro");
+}
+
+
+// Partida de vários jogadores com a frota clássica, cada um com a sua frota sorteada
+pub(crate) fn partida_de_teste(lado: usize, jogadores: &str, equipes: Option<&str>) -> equipes::Partida {
+	let vagas = equipes::vagas(jogadores, equipes, Dificuldade::NORMAL).unwrap();
+	let mut jogo = jogo_de_teste(lado);
+	let frotas = equipes::sorteia_frotas(&mut jogo, vagas.len(), 0);
+	let nomes = (1..=vagas.len()).map(|i| format!("j{}", i)).collect();
+	equipes::Partida::new(&jogo, &vagas, nomes, frotas, 0)
+}
+
+
+#[test]
+fn todos_contra_todos_entre_bots() {
+	let joga = || {
+		let mut con = ConsoleVirtual::new(50, 30);
+		let mut partida = partida_de_teste(6, "bot,bot:dificil,bot:facil", None);
+		let desfecho = partida.executa(&mut con, &opcoes_de_teste(6), &mut [None, None, None]).unwrap();
+		assert!(matches!(desfecho, Desfecho::CONTINUA));
+		let vencedora = partida.vencedora().unwrap();
+		assert_eq!((0..3).filter(|&i| partida.vivo(i)).collect::<Vec<_>>(), [vencedora]);
+		con.texto()
+	};
+	// A mesma semente repete a partida inteira
+	let tela = joga();
+	assert_eq!(tela, joga());
+	assert!(tela.contains("VITÓRIA DE 2 j2!!!"));
+	assert!(tela.starts_with("x1 j1           2 j2          x3 j3"));
+}
+
+#[test]
+fn varios_tabuleiros_na_tela() {
+	// O humano (j2) vê a própria frota e a do aliado j3, com o tiro que j3 levou
+	let mut con = ConsoleVirtual::new(60, 30);
+	let mut partida = partida_de_teste(6, "bot,humano,bot", Some("a,b,b"));
+	partida.atira(0, 2, 3, 1);
+	partida.desenha(&mut con, &opcoes_de_teste(6), Some(0), false).unwrap();
+	assert_eq!(con.texto(), " 2[B] j2       >1 j1           3[B] j3\n\
++------------+ +------------+ +------------+
+|PP      DD  | |            | |        SSSS|
+|PP      DDCC| |            | |  PP  ..  DD|
+|PP    SSDDCC| |            | |CCPP      DD|
+|PP    SS  CC| |            | |CCPP      DD|
+|PP        CC| |            | |CCPP        |
+|            | |            | |CCPP        |
++------------+ +------------+ +------------+
+Alvo com 1-3 ou 'm', mire com ↑←↓→, 'f' fogo, Esc pausa
+1 j1 atirou em 3 j3 (3 1): água");
+	// O único alvo do humano é j1, com o título em vídeo reverso
+	assert!(con.celula(16, 0).reverso);
+	assert!(!con.celula(1, 0).reverso);
+
+	// Sem espaço para os três lado a lado, o terceiro desce
+	let mut con = ConsoleVirtual::new(30, 30);
+	partida.desenha(&mut con, &opcoes_de_teste(6), Some(0), false).unwrap();
+	assert_eq!(con.linha(9), " 3[B] j3");
+	assert!(partida.confere_tamanho(&mut ConsoleVirtual::new(30, 18)).is_err());
+}
+
+// Só o submarino do bot, deitado em (3,3) e (4,3)
+fn jogo_do_submarino() -> Jogo {
+	let mut jogo = jogo_de_teste(6);
+	jogo.navios_bot.drain(..3);
+	(jogo.navios_bot[0].popa_x, jogo.navios_bot[0].popa_y) = (3, 3);
+	jogo
+}
+
+#[test]
+fn chances_do_mapa_de_calor() {
+	let jogo = jogo_do_submarino();
+	let original = jogo.mapeia_bot(None);
+	let mut mapa = original.clone();
+
+	// 60 posições, o canto está em 2 e o meio em 4
+	let chances = calor::chances(&mapa, &original, &jogo.navios_bot);
+	assert_eq!(chances[0][0], Some(2.0/60.0));
+	assert_eq!(chances[2][2], Some(4.0/60.0));
+	assert_eq!(calor::melhor(&chances), Some((1,1)));
+
+	// Água e acerto não têm chance, as vizinhas do acerto passam a ser as melhores
+	atinge(&mut mapa, (Arma::TIRO, Direcao::LESTE), (1,1));
+	atinge(&mut mapa, (Arma::TIRO, Direcao::LESTE), (3,3));
+	let chances = calor::chances(&mapa, &original, &jogo.navios_bot);
+	assert_eq!((chances[1][1], chances[3][3]), (None, None));
+	assert_eq!(calor::melhor(&chances), Some((3,2)));
+	for (x,y) in [(2,3), (4,3), (3,4)] {
+		assert!((chances[y][x].unwrap() - chances[2][3].unwrap()).abs() < 1e-9);
+	}
+	assert!(chances[2][3].unwrap() > 5.0*chances[0][5].unwrap());
+
+	let mut con = ConsoleVirtual::new(40, 14);
+	desenha_moldura(&mut con, 0, 0, &tema_ascii(3), &jogo.terreno).unwrap();
+	calor::desenha(&mut con, &chances, &tema_ascii(3)).unwrap();
+	assert_eq!(con.linha(3), "|            |0 0 0 2 0 0 |");
+	assert_eq!(con.linha(4), "|            |0 0 2   2 0 |");
+	// A melhor em destaque
+	assert!(con.celula(20, 3).reverso);
+	assert!(!con.celula(18, 4).reverso);
+
+	// Desligado, o mar volta
+	calor::apaga(&mut con, &chances, &tema_ascii(3)).unwrap();
+	assert_eq!(con.linha(4), "|            |            |");
+}
+
+#[test]
+fn dicas_movem_a_mira_ate_acabar() {
+	let mut jogo = jogo_do_submarino();
+	let opcoes = Opcoes{ dicas: 1, ..opcoes_de_teste(6) };
+	let mut batalha = Batalha::new(&jogo, &opcoes, 0);
+	let mut con = ConsoleVirtual::new(60, 14);
+	desenha_moldura(&mut con, 0, 0, &opcoes.tema, &jogo.terreno).unwrap();
+
+	// Mapa ligado, dica e uma segunda dica que não existe
+	con.digita("cpp");
+	con.envia(ctrl('c'));
+	assert!(matches!(executa_batalha(&mut con, &mut jogo, &opcoes, &mut batalha, None), Ok(Desfecho::ABORTADO)));
+	assert_eq!((batalha.mira_x, batalha.mira_y, batalha.dicas), (1, 1, 0));
+	assert_eq!(con.linha(12), "Nenhuma dica restante.");
+	assert_eq!(con.linha(1), "|            |0 1 1 1 1 0 |");
+}
+
+#[test]
+fn animacao_de_respingo_explosao_e_naufragio() {
+	let mut con = ConsoleVirtual::new(40, 14);
+	let jogo = jogo_do_submarino();
+	let mut mapa = jogo.mapeia_bot(None);
+	let opcoes = Opcoes{ animacao: true, som: true, ..opcoes_de_teste(6) };
+	desenha_moldura(&mut con, 0, 0, &opcoes.tema, &jogo.terreno).unwrap();
+	let mut ataca = |con: &mut ConsoleVirtual, x: usize, y: usize| {
+		let impacto = realiza_ataque(con, &mut mapa, &jogo.navios_bot, &Jogador::BOT,
+										(Arma::TIRO, Direcao::LESTE), (x,y), &opcoes.tema).unwrap();
+		animacao::anima(con, &Jogador::BOT, &impacto, &jogo.navios_bot, 6, &opcoes).unwrap();
+	};
+	let linhas = |con: &ConsoleVirtual, y: usize| -> Vec<String> {
+		con.fotografias().iter().map(|foto| String::from(foto.lines().nth(y).unwrap())).collect()
+	};
+
+	// Tiro na água: anéis do respingo e a água no último quadro
+	con.fotografa_esperas();
+	ataca(&mut con, 0, 0);
+	assert_eq!(linhas(&con, 1), ["|            |oo          |", "|            |OO          |", "|            |()          |", "|            |..          |"]);
+	assert_eq!(con.campainhas(), 0);
+
+	// Acertos piscam, o último pedaço afunda o navio inteiro, com duas campainhas
+	let celulas = jogo.navios_bot[0].celulas();
+	for &(x,y) in celulas[1..].iter() {
+		ataca(&mut con, x, y);
+	}
+	assert_eq!(con.campainhas(), celulas.len()-1);
+	con.fotografa_esperas();
+	ataca(&mut con, celulas[0].0, celulas[0].1);
+	assert_eq!(con.campainhas(), celulas.len()+1);
+	let (x,y) = celulas[0];
+	let coluna = 14 + 2*x;
+	let quadros: Vec<String> = linhas(&con, y+1).iter().map(|linha| String::from(&linha[coluna..coluna+2])).collect();
+	assert_eq!(quadros, ["XX", "XX", "XX", "XX", "%%", "::", "~~"]);
+	for &(x,y) in celulas.iter() {
+		assert_eq!(linhas(&con, y+1)[6][14+2*x..16+2*x], *"~~");
+		assert_eq!(con.linha(y as u16 + 1)[14+2*x..16+2*x], *"XX");
+	}
+
+	// Uma tecla pula a animação depois do primeiro quadro e continua na fila
+	con.fotografa_esperas();
+	con.digita(" ");
+	ataca(&mut con, 5, 4);
+	assert_eq!(con.fotografias().len(), 1);
+	assert_eq!(con.eventos_restantes(), 1);
+	assert_eq!(&con.linha(5)[24..26], "..");
+
+	// Desligada, só o desenho final e nenhuma espera
+	let opcoes = Opcoes{ animacao: false, ..opcoes };
+	con.fotografa_esperas();
+	let impacto = realiza_ataque(&mut con, &mut mapa, &jogo.navios_bot, &Jogador::BOT,
+									(Arma::TIRO, Direcao::LESTE), (5,5), &opcoes.tema).unwrap();
+	animacao::anima(&mut con, &Jogador::BOT, &impacto, &jogo.navios_bot, 6, &opcoes).unwrap();
+	assert!(con.fotografias().is_empty());
+}
+
+#[test]
+fn neblina_com_navios_que_manobram() {
+	let mut jogo = jogo_de_teste(8);
+	let opcoes = Opcoes{ neblina: true, dicas: 3, ..opcoes_de_teste(8) };
+	let mut batalha = Batalha::new(&jogo, &opcoes, 0);
+	let mut con = ConsoleVirtual::new(60, 18);
+
+	// As dicas usariam as posições dos navios de antes das manobras
+	assert_eq!(batalha.dicas, 0);
+	redesenha_batalha(&mut con, &jogo, &batalha, &opcoes).unwrap();
+
+	// Bomba do bot: a água volta ao mapa e só a recente vale para ele
+	let impacto = realiza_ataque(&mut con, &mut batalha.mapa_humano, &jogo.navios_humano, &Jogador::HUMANO,
+									(Arma::BOMBA, Direcao::LESTE), (5,0), &opcoes.tema).unwrap();
+	batalha.neblina.as_mut().unwrap().guarda_agua(&Jogador::HUMANO, &mut batalha.mapa_humano, &impacto, 0);
+	let neblina = batalha.neblina.as_ref().unwrap();
+	assert_eq!((batalha.mapa_humano[0][5], batalha.mapa_humano[1][5]), (AGUA, DESTRUIDO));
+	assert_eq!(neblina.vista_do_bot(&batalha.mapa_humano, 8)[0][5], DESTRUIDO);
+	assert_eq!(neblina.vista_do_bot(&batalha.mapa_humano, 9)[0][5], AGUA);
+
+	// Manobra: o porta-aviões está atingido, o encouraçado desce uma casa e o humano não atira
+	// No meio o terminal muda de tamanho e a tela volta, uma tecla sem ação não faz nada
+	let moldura = con.linha(0);
+	con.queue(crossterm::cursor::MoveTo(0,0)).unwrap().queue(crossterm::style::Print("lixo")).unwrap().flush().unwrap();
+	con.digita("n");
+	con.envia(Event::Resize(60, 18));
+	con.digita("x");
+	con.envia(Event::Key(KeyEvent::from(KeyCode::Down)));
+	con.envia(ctrl('c'));
+	assert!(matches!(executa_batalha(&mut con, &mut jogo, &opcoes, &mut batalha, None), Ok(Desfecho::ABORTADO)));
+	assert_eq!(jogo.navios_humano[1].popa_y, 4);
+	assert_eq!(batalha.mapa_humano[3], vec![AGUA; 8]);
+	assert_eq!(batalha.mapa_humano[4][1..5], [1; 4]);
+	assert_eq!(batalha.ataques, 0);
+	assert_eq!(con.linha(1), "|        ......  |                |");
+	assert_eq!(con.linha(4), "|                |                |");
+	assert_eq!(con.linha(5), "|  CCCCCCCC      |                |");
+	assert_eq!(con.linha(0), moldura);
+
+	// Sem tecla a manobra espera com o relógio andando, esgotado ela é cancelada
+	batalha.relogio = relogio::Relogio::new(Some(Duration::from_millis(200)), None);
+	let inicio = std::time::Instant::now();
+	assert!(matches!(neblina::manobra_humano(&mut con, &mut jogo, &mut batalha, &opcoes), Ok(Manobra::CANCELADA)));
+	assert!(inicio.elapsed() >= Duration::from_millis(200));
+	assert!(con.texto().contains("Tempo:  jogada 0s"));
+
+	// O bot só manobra um navio inteiro perto da mira do humano, o mapa acompanha
+	let antes = posicoes(&jogo.navios_bot);
+	(batalha.mira_x, batalha.mira_y) = (7, 0);
+	assert!(!(0..20).any(|n| neblina::manobra_bot(&mut jogo, &mut batalha, &mut gerador(n, 0))));
+	(batalha.mira_x, batalha.mira_y) = (0, 0);
+	assert!((0..20).any(|n| neblina::manobra_bot(&mut jogo, &mut batalha, &mut gerador(n, 0))));
+	assert_ne!(posicoes(&jogo.navios_bot)[0], antes[0]);
+	assert_eq!(posicoes(&jogo.navios_bot)[1..], antes[1..]);
+	assert_eq!(batalha.mapa_bot, jogo.mapeia_bot(None));
+}
+
+#[test]
+fn revelacao_dos_acertos() {
+	// Submarino do bot atingido uma vez e depois afundado
+	let jogo = jogo_do_submarino();
+	let mut mapa = jogo.mapeia_bot(None);
+	let tema = tema_ascii(0);
+	let mut con = ConsoleVirtual::new(40, 14);
+	let cor = jogo.navios_bot[0].cor;
+	let cores = |revelacao: Revelacao, mapa: &Mapa| revelacao.navios_vistos(&jogo.navios_bot, mapa)[0].cor;
+
+	let vistos = Revelacao::CLASSICA.navios_vistos(&jogo.navios_bot, &mapa);
+	let impacto = realiza_ataque(&mut con, &mut mapa, &vistos, &Jogador::BOT, (Arma::TIRO, Direcao::LESTE), (3,3), &tema).unwrap();
+	assert_eq!(con.celula(20, 4).fundo, tema::COR_ANONIMA);
+	assert_eq!((cores(Revelacao::CLASSICA, &mapa), cores(Revelacao::AFUNDADO, &mapa), cores(Revelacao::TOTAL, &mapa)),
+				(tema::COR_ANONIMA, tema::COR_ANONIMA, cor));
+	assert_eq!(Revelacao::AFUNDADO.aviso_afundados(&impacto, &jogo.navios_bot), "");
+
+	let impacto = realiza_ataque(&mut con, &mut mapa, &vistos, &Jogador::BOT, (Arma::TIRO, Direcao::LESTE), (4,3), &tema).unwrap();
+	assert_eq!((cores(Revelacao::CLASSICA, &mapa), cores(Revelacao::AFUNDADO, &mapa)), (tema::COR_ANONIMA, cor));
+	assert_eq!(Revelacao::CLASSICA.aviso_afundados(&impacto, &jogo.navios_bot), "Você afundou um navio!");
+	assert_eq!(Revelacao::AFUNDADO.aviso_afundados(&impacto, &jogo.navios_bot), "Você afundou: Submarino");
+
+	// Dois acertos em coluna: sem saber o navio o bot tenta a última vizinha, sabendo segue a linha
+	let mut mapa = vec![vec![AGUA; 8]; 8];
+	(mapa[3][3], mapa[4][3]) = (DESTRUIDO, DESTRUIDO);
+	let arsenal = Arsenal::new(false);
+	let mut rng = gerador(0, 0);
+	let mira = |navios: Option<&[usize]>, rng: &mut StdRng| {
+		let mut bot = Bot::new(Dificuldade::NORMAL, 8);
+		bot.registra_acertos(&[(3,3), (3,4)], navios);
+		let (_, x, y, _) = bot.escolhe_ataque(&mapa, &arsenal, rng);
+		(x,y)
+	};
+	assert_eq!(mira(None, &mut rng), (4,4));
+	assert_eq!(mira(Some(&[1,1]), &mut rng), (3,2));
+
+	// Afundado, o bot para de procurar em volta
+	let mut bot = Bot::new(Dificuldade::NORMAL, 8);
+	bot.registra_acertos(&[(3,3), (3,4), (6,6)], None);
+	bot.registra_afundado(&[(3,3), (3,4)]);
+	let mut pendentes = Vec::new();
+	while bot.perseguindo() {
+		let (_, x, y, _) = bot.escolhe_ataque(&mapa, &arsenal, &mut rng);
+		pendentes.push((x,y));
+		mapa[y][x] = DESTRUIDO;
+	}
+	pendentes.sort();
+	assert_eq!(pendentes, [(5,6), (6,5), (6,7), (7,6)]);
+}
+
+// Tiros do bot até afundar a frota do humano sorteada com a semente 3, na regra padrão
+fn tiros_do_bot(dificuldade: Dificuldade, bot_revelacao: bool) -> Vec<(usize,usize)> {
+	let opcoes = Opcoes{ bot_revelacao, ..opcoes_de_teste(10) };
+	let mut jogo = jogo_de_teste(10);
+	sorteia_navios(&mut jogo, &Jogador::HUMANO, &mut gerador(3, FLUXO_FROTA_HUMANO));
+	let mut mapa = jogo.mapeia_humano(None);
+	let arsenal = Arsenal::new(false);
+	let mut bot = Bot::new(dificuldade, 10);
+	let mut con = ConsoleVirtual::new(40, 14);
+	let mut tiros = Vec::new();
+	while pedacos_vivos(&mapa) > 0 {
+		let mut rng = gerador(3, FLUXO_RODADAS + tiros.len() as u64);
+		let (arma, x, y, direcao) = bot.escolhe_ataque(&mapa, &arsenal, &mut rng);
+		let impacto = realiza_ataque(&mut con, &mut mapa, &jogo.navios_humano, &Jogador::HUMANO, (arma,direcao), (x,y), &opcoes.tema).unwrap();
+		opcoes.revelacao.informa_bot(&mut bot, &impacto, &jogo.navios_humano, opcoes.bot_revelacao);
+		tiros.push((x,y));
+	}
+	tiros
+}
+
+#[test]
+fn bot_da_regra_padrao_atira_como_antes() {
+	// Sequências do bot de antes da regra de revelação
+	assert_eq!(tiros_do_bot(Dificuldade::NORMAL, false), [
+		(7,2), (9,2), (5,6), (3,7), (5,4), (1,2), (7,6), (6,6), (9,8), (8,8), (9,9), (9,7), (8,7), (7,7), (6,7),
+		(5,7), (4,7), (5,8), (6,8), (7,8), (8,6), (9,6), (9,5), (8,5), (9,4), (8,4), (9,3), (5,9), (4,1), (3,2),
+		(1,3), (0,0), (0,6), (2,3), (8,3), (6,3), (2,4), (3,9), (4,9), (4,8), (2,9), (1,9), (2,8), (3,8), (7,3),
+		(1,8), (4,0), (3,4), (9,0), (5,3), (2,1), (2,6), (1,5), (2,7), (3,1), (1,7), (0,9), (2,0), (6,4), (5,1),
+		(5,2), (2,2), (6,5), (7,5), (4,4), (3,0), (7,0), (7,4), (1,4), (3,6), (4,6), (3,5)]);
+	assert_eq!(tiros_do_bot(Dificuldade::DIFICIL, false), [
+		(0,2), (8,0), (9,9), (3,3), (5,5), (7,1), (7,7), (8,7), (9,7), (9,8), (8,8), (9,6), (8,6), (9,5), (8,5),
+		(9,4), (8,4), (9,3), (6,7), (5,7), (4,7), (5,8), (5,6), (6,8), (6,6), (7,8), (7,6), (3,7), (4,2), (7,5),
+		(1,1), (4,8), (2,0), (6,2), (1,9), (3,5), (4,5), (2,5), (3,6), (4,6), (2,6), (3,4), (2,2), (0,6), (8,2),
+		(5,3), (9,1), (2,8), (0,0), (3,9), (4,9), (5,9), (2,9)]);
+
+	// Com '--bot-revelacao' o bot sabe os navios e joga diferente
+	assert_ne!(tiros_do_bot(Dificuldade::NORMAL, true), tiros_do_bot(Dificuldade::NORMAL, false));
+}
+
+#[test]
+fn tutorial_passo_a_passo() {
+	// Iniciar antes da hora não vale
+	let mut con = ConsoleVirtual::new(80, 20);
+	con.digita("i");
+	con.envia(ctrl('c'));
+	assert!(matches!(tutorial::executa(&mut con, &opcoes_de_teste(10)), Ok(Desfecho::ABORTADO)));
+	assert!(con.linha(14).starts_with("Passo 1/7: leve o porta-aviões"));
+	assert_eq!(con.linha(15), "Navio: Porta-aviões");
+	assert_eq!(con.linha(16), "Ainda não, siga o passo acima");
+
+	// O porta-aviões vai até (2,2), gira e muda de navio
+	let mut con = ConsoleVirtual::new(80, 20);
+	for codigo in [KeyCode::Right, KeyCode::Right, KeyCode::Down, KeyCode::Down] {
+		con.tecla(codigo);
+	}
+	con.digita("gmi");
+	// Atirar fora do destaque não vale; mira em (5,5), atira e afunda em (6,5)
+	con.digita("f");
+	for _ in 0..5 {
+		con.tecla(KeyCode::Right);
+		con.tecla(KeyCode::Down);
+	}
+	con.digita("f");
+	con.tecla(KeyCode::Right);
+	con.digita("fx");
+	assert!(matches!(tutorial::executa(&mut con, &opcoes_de_teste(10)), Ok(Desfecho::CONTINUA)));
+	assert_eq!(con.eventos_restantes(), 0);
+	assert_eq!(con.linha(14), "Submarino afundado, tutorial concluído! Tecle algo para voltar");
+	// O bot respondeu na água em (9,9) e depois na popa do porta-aviões girado
+	assert_eq!(con.linha(16), "O bot respondeu em 2 2: acertou o seu Porta-aviões");
+	assert_eq!(con.linha(3), "|    XX              |                    |");
+	assert_eq!(con.linha(10), "|SSSS              ..|                    |");
+	assert_eq!(con.linha(6), "|    PP              |          XXXX      |");
+}
+
+#[test]
+fn hexagono_na_tela() {
+	// A mesma semente arruma a frota do humano como na partida
+	let frota = frota::carrega(None).unwrap();
+	let mut navios: Vec<hexagonal::NavioHex> = frota.iter().map(hexagonal::NavioHex::new).collect();
+	hexagonal::sorteia(&mut navios, 8, &mut gerador(0, FLUXO_FROTA_HUMANO));
+	let mut con = ConsoleVirtual::new(60, 20);
+	con.digita("if");
+	con.envia(ctrl('c'));
+	assert!(matches!(hexagonal::partida(&mut con, &opcoes_de_teste(8), &frota, 0), Ok(Desfecho::ABORTADO)));
+	assert_eq!(con.linha(0), format!("+{}+{}+", "-".repeat(17), "-".repeat(17)));
+	assert!(con.linha(10).starts_with("Meus Navios       Inimigo"));
+	assert!(con.linha(12).starts_with("O bot respondeu em "), "{}", con.texto());
+	for navio in navios.iter() {
+		for (x,y) in navio.celulas() {
+			let coluna = 1 + y%2 + 2*x;
+			let desenho: String = con.linha(1+y as u16).chars().skip(coluna).take(2).collect();
+			assert!(desenho == format!("{0}{0}", navio.navio.letra) || desenho == "XX", "{x} {y}: {desenho}");
+		}
+	}
+}
+
+// Dois submarinos no tabuleiro 6x6 cabem nas colunas 0 e 2 de dois jeitos, sem célula revelada
+pub(crate) fn enigma_ambiguo() -> quebra_cabeca::Enigma {
+	let submarino = frota::carrega(None).unwrap().pop().unwrap();
+	let mut solucao = vec![vec![false; 6]; 6];
+	for (x,y) in [(0,0), (0,1), (2,2), (2,3)] {
+		solucao[y][x] = true;
+	}
+	quebra_cabeca::Enigma::new(terreno::mar_aberto(6), vec![submarino.clone(), submarino], &solucao)
+}
+
+#[test]
+fn quebra_cabeca_resolvido_com_teclas() {
+	let mut enigma = enigma_ambiguo();
+	enigma.reveladas.push((0, 0, true));
+	let solucao = quebra_cabeca::resolve(&enigma, 1).pop().unwrap();
+	let mut con = ConsoleVirtual::new(60, 20);
+	// Água com '1', a dica leva a mira até (0,1), navios com fogo e com '2'
+	con.digita("1?f");
+	con.tecla(KeyCode::Down);
+	con.tecla(KeyCode::Right);
+	con.tecla(KeyCode::Right);
+	con.digita("2");
+	con.tecla(KeyCode::Down);
+	con.digita("2");
+	let desfecho = quebra_cabeca::resolve_na_tela(&mut con, &opcoes_de_teste(6), &enigma, &solucao).unwrap();
+	assert!(matches!(desfecho, Desfecho::CONTINUA));
+	let tela = con.texto();
+	assert!(tela.contains("com 1 dicas!"), "{tela}");
+	assert!(tela.contains("  2 0 2 0 0 0"), "{tela}");
+}
+
+
+// Navegador de mentira, fala WebSocket com o servidor
+struct Cliente {
+	fluxo: TcpStream,
+	dados: Vec<u8>,				// Recebido e ainda não lido, pode ter vários quadros
+}
+
+impl Cliente {
+	// Pede o WebSocket, a resposta é lida em 'aceito' para o servidor poder rodar no meio
+	fn pede(porta: u16) -> Cliente {
+		let mut fluxo = TcpStream::connect(("127.0.0.1", porta)).unwrap();
+		fluxo.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		fluxo.write_all(b"GET /ws HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+							Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n").unwrap();
+		Cliente{ fluxo, dados: Vec::new() }
+	}
+
+	// Confere a resposta do aperto de mão
+	fn aceito(&mut self) {
+		let mut resposta = Vec::new();
+		let mut byte = [0u8;1];
+		while !resposta.ends_with(b"\r\n\r\n") {
+			self.fluxo.read_exact(&mut byte).unwrap();
+			resposta.push(byte[0]);
+		}
+		let resposta = String::from_utf8(resposta).unwrap();
+		assert!(resposta.starts_with("HTTP/1.1 101"));
+		assert!(resposta.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+	}
+
+	// Quadro mascarado, como o navegador envia
+	fn envia(&mut self, texto: &str) {
+		let mascara = [0x12, 0x34, 0x56, 0x78];
+		let mut quadro = vec![0x81, 0x80 | texto.len() as u8];
+		quadro.extend_from_slice(&mascara);
+		quadro.extend(texto.bytes().enumerate().map(|(i,b)| b ^ mascara[i%4]));
+		self.fluxo.write_all(&quadro).unwrap();
+	}
+
+	// Próxima mensagem do servidor
+	fn recebe(&mut self) -> String {
+		let mut bloco = [0u8;4096];
+		loop {
+			// O servidor não mascara, o conteúdo vem depois do tamanho
+			let cabecalho = match self.dados.get(1).map(|b| b & 0x7F) {
+				Some(126) if self.dados.len() >= 4 => Some((u16::from_be_bytes([self.dados[2], self.dados[3]]) as usize, 4)),
+				Some(127) if self.dados.len() >= 10 => Some((u64::from_be_bytes(self.dados[2..10].try_into().unwrap()) as usize, 10)),
+				Some(n) if n < 126 => Some((n as usize, 2)),
+				_ => None,
+			};
+			if let Some((tamanho, inicio)) = cabecalho.filter(|(tamanho, inicio)| self.dados.len() >= inicio + tamanho) {
+				let conteudo = self.dados[inicio..inicio+tamanho].to_vec();
+				self.dados.drain(..inicio+tamanho);
+				return String::from_utf8(conteudo).unwrap();
+			}
+			let n = self.fluxo.read(&mut bloco).unwrap();
+			assert!(n > 0, "servidor fechou a conexão");
+			self.dados.extend_from_slice(&bloco[..n]);
+		}
+	}
+
+	// Pula mensagens até uma do tipo dado
+	fn recebe_tipo(&mut self, tipo: &str) -> String {
+		let marca = format!("{{\"tipo\":\"{}\"", tipo);
+		loop {
+			let mensagem = self.recebe();
+			if mensagem.starts_with(&marca) {
+				return mensagem;
+			}
+		}
+	}
+}
+
+// Algumas voltas do saguão, para atender o que os clientes enviaram
+fn roda(saguao: &mut saguao::Saguao, ouvinte: &std::net::TcpListener) {
+	for _ in 0..5 {
+		saguao.atende(ouvinte).unwrap();
+		thread::sleep(Duration::from_millis(10));
+	}
+}
+
+
+#[test]
+fn servidor_com_cliente_local() {
+	let ouvinte = rede::abre(0).unwrap();
+	let porta = ouvinte.local_addr().unwrap().port();
+
+	let cliente = thread::spawn(move || {
+		// Página
+		let mut fluxo = TcpStream::connect(("127.0.0.1", porta)).unwrap();
+		fluxo.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+		let mut pagina = String::new();
+		fluxo.read_to_string(&mut pagina).unwrap();
+		assert!(pagina.starts_with("HTTP/1.1 200 OK"));
+		assert!(pagina.contains("new WebSocket"));
+
+		// WebSocket
+		let mut cliente = Cliente::pede(porta);
+		cliente.aceito();
+		// O nome dos visitantes não é de ninguém, e não grava perfil
+		cliente.envia("nome visitante");
+		cliente.envia("pronto");
+		let recusa = cliente.recebe();
+		let estado = cliente.recebe();
+
+		// Quadro de 4 GiB anunciado: o servidor fecha com 1009 sem esperar o conteúdo
+		cliente.fluxo.write_all(&[0x81, 0xFF, 0, 0, 0, 1, 0, 0, 0, 0, 1, 2, 3, 4]).unwrap();
+		let mut fechamento = [0u8;4];
+		cliente.fluxo.read_exact(&mut fechamento).unwrap();
+		(recusa, estado, fechamento)
+	});
+
+	let mut con = ConsoleVirtual::new(90, 20);
+	let mut remoto = match rede::espera_adversario(&mut con, &ouvinte) {
+		Ok(Ok(remoto)) => remoto,
+		_ => panic!("adversário não conectou"),
+	};
+	assert!(con.linha(0).contains(&format!(":{}/", porta)));
+
+	assert_eq!(remoto.espera_mensagem(&mut con).unwrap().ok(), Some(String::from("pronto")));
+	assert_eq!(remoto.nome, "visitante");
+
+	// O adversário vê a própria frota e não vê a do humano
+	let mut jogo = jogo_de_teste(10);
+	sorteia_navios(&mut jogo, &Jogador::HUMANO, &mut gerador(0, FLUXO_FROTA_HUMANO));
+	sorteia_navios(&mut jogo, &Jogador::BOT, &mut gerador(0, FLUXO_FROTA_BOT));
+	remoto.envia_estado(&jogo, None, rede::Fase::ARRUMACAO, false, "vai").unwrap();
+
+	let erro = loop {
+		if let Err(erro) = remoto.recebe() {
+			break erro;
+		}
+	};
+	assert_eq!(erro.kind(), std::io::ErrorKind::InvalidData);
+
+	let (recusa, estado, fechamento) = cliente.join().unwrap();
+	assert_eq!(recusa, "{\"tipo\":\"erro\",\"mensagem\":\"O nome visitante já é de outro jogador\"}");
+	assert_eq!(fechamento, [0x88, 2, 0x03, 0xF1]);
+	assert!(estado.starts_with("{\"tipo\":\"estado\",\"fase\":\"arrumacao\",\"vez\":false,\"lado\":10,"));
+	let (meu, inimigo) = estado.split_once("\"inimigo\":").unwrap();
+	assert!(meu.contains('P'));
+	assert!(inimigo.starts_with(&format!("[{}", "\"~~~~~~~~~~\",".repeat(9))));
+	assert!(estado.ends_with("\"mensagem\":\"vai\"}"));
+}
+
+
+#[test]
+fn saguao_com_fila_espectador_e_volta() {
+	let ouvinte = rede::abre(0).unwrap();
+	let porta = ouvinte.local_addr().unwrap().port();
+	let mut saguao = saguao::Saguao::new(&opcoes_de_teste(10));
+	let conecta = |saguao: &mut saguao::Saguao| {
+		let mut cliente = Cliente::pede(porta);
+		roda(saguao, &ouvinte);
+		cliente.aceito();
+		cliente
+	};
+
+	// Uma conexão que não termina o pedido não atrasa as outras
+	let mut lento = TcpStream::connect(("127.0.0.1", porta)).unwrap();
+	lento.write_all(b"GET /ws HTTP/1.1\r\nHost: loc").unwrap();
+	let inicio = std::time::Instant::now();
+
+	// A fila junta os dois primeiros na mesma sala, sem nomes para não gravar perfis
+	let mut ana = conecta(&mut saguao);
+	assert!(inicio.elapsed() < Duration::from_secs(1));
+	ana.envia("fila");
+	roda(&mut saguao, &ouvinte);
+	assert!(ana.recebe_tipo("ficha").ends_with("\"sala\":1}"));
+	assert!(ana.recebe_tipo("estado").contains("\"fase\":\"espera\""));
+
+	let mut bia = conecta(&mut saguao);
+	bia.envia("fila");
+	roda(&mut saguao, &ouvinte);
+	let ficha = bia.recebe_tipo("ficha");
+	assert!(ficha.ends_with("\"sala\":1}"));
+	let ficha = &ficha["{\"tipo\":\"ficha\",\"ficha\":\"".len() ..][..16];
+	assert!(ana.recebe_tipo("estado").contains("\"fase\":\"arrumacao\""));
+
+	// O espectador vê a sala na lista e não vê nenhuma frota
+	let mut eva = conecta(&mut saguao);
+	assert!(eva.recebe_tipo("salas").contains("{\"id\":1,\"jogadores\":[\"visitante\",\"visitante\"],\"fase\":\"arrumacao\"}"));
+	eva.envia("assiste 1");
+	ana.envia("pronto");
+	bia.envia("pronto");
+	roda(&mut saguao, &ouvinte);
+	let mut estado = eva.recebe_tipo("estado");
+	while !estado.contains("\"fase\":\"batalha\"") {
+		estado = eva.recebe_tipo("estado");
+	}
+	let tabuleiros = &estado[estado.find("\"meu\"").unwrap() .. estado.find("\"mensagem\"").unwrap()];
+	assert!(!tabuleiros.contains(|c: char| c.is_ascii_uppercase()));
+
+	// Quem cai volta com a ficha
+	drop(bia);
+	roda(&mut saguao, &ouvinte);
+	let mut estado = ana.recebe_tipo("estado");
+	while !estado.contains("caiu") {
+		estado = ana.recebe_tipo("estado");
+	}
+	let mut bia = conecta(&mut saguao);
+	bia.envia(&format!("volta {}", ficha));
+	roda(&mut saguao, &ouvinte);
+	bia.recebe_tipo("ficha");
+	assert!(bia.recebe_tipo("estado").contains("\"fase\":\"batalha\""));
+
+	// Sem voltar dentro da janela, perde por W.O.
+	saguao.janela_volta = Duration::ZERO;
+	drop(bia);
+	roda(&mut saguao, &ouvinte);
+	let mut estado = ana.recebe_tipo("estado");
+	while !estado.contains("\"fase\":\"fim\"") {
+		estado = ana.recebe_tipo("estado");
+	}
+	assert!(estado.contains("W.O.") && estado.contains("Você venceu!"));
+}
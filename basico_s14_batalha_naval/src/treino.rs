@@ -0,0 +1,345 @@
+/*
+	Ambiente de aprendizado por reforço
+
+	Interface no estilo Gym sobre as regras da batalha, para treinar políticas
+	de tiro na CPU, sem terminal nem rede:
+
+	- Ambiente::reinicia(semente): sorteia a frota inimiga como o bot de uma
+	partida com essa semente e devolve a observação inicial
+	- Observação: a grade de acompanhamento do atirador, lado*lado valores linha
+	a linha, OBS_DESCONHECIDA, OBS_AGUA, OBS_ACERTO ou OBS_TERRENO; como na
+	partida, não diz qual navio foi atingido nem quais afundaram
+	- Ação: a célula do tiro simples, y*lado + x
+	- Recompensa: RECOMPENSA_ACERTO no acerto, 0 na água e RECOMPENSA_INVALIDA
+	em célula já tentada, no terreno ou fora do tabuleiro, que não mudam nada
+	- Fim: toda a frota afundada; a partida é truncada depois de LIMITE_PASSOS
+	vezes o número de células, para políticas que só repetem ações inválidas
+
+	Um Lote passa vários ambientes de uma vez, com as observações em um único
+	vetor e o reinício automático de quem termina, com as sementes seguintes.
+
+	'--treina <episódios>' é um exemplo completo: treina uma política tabular,
+	que estima a chance de acerto de cada célula pelo que há nas vizinhas até
+	duas casas de distância, e compara com tiros aleatórios em partidas novas.
+	Usa '--lado', '--frota', '--mapa' e '--seed'.
+*/
+
+
+use std::time::Instant;
+
+use rand::Rng;
+
+use crate::{Jogo,Jogador,Mapa,Opcoes,Arma,Direcao,AGUA,gerador,novo_jogo,sorteia_navios,atinge,eh_obstaculo,tem_navio_inteiro,FLUXO_FROTA_BOT,FLUXO_RODADAS};
+
+
+// Valores da observação
+pub const OBS_DESCONHECIDA: i8 = 0;
+pub const OBS_AGUA: i8 = -1;
+pub const OBS_ACERTO: i8 = 1;
+pub const OBS_TERRENO: i8 = -2;
+
+pub const RECOMPENSA_ACERTO: f64 = 1.0;
+pub const RECOMPENSA_INVALIDA: f64 = -1.0;
+
+// Passos por célula antes de truncar a partida
+const LIMITE_PASSOS: usize = 4;
+
+// Exemplo de treino: ambientes por lote, exploração e partidas de avaliação
+const AMBIENTES_POR_LOTE: usize = 64;
+const EXPLORACAO: f64 = 0.1;
+const PARTIDAS_AVALIACAO: usize = 200;
+
+
+// Resultado de um passo
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Passo {
+	pub recompensa: f64,
+	pub fim: bool,			// Frota afundada
+	pub truncado: bool,		// Passou do limite de passos
+}
+
+
+pub struct Ambiente {
+	base: Jogo,				// Terreno e frota nas posições iniciais
+	mapa: Mapa,				// Navios inimigos e tiros já dados
+	observacao: Vec<i8>,
+	vivos: usize,			// Pedaços de navio ainda não atingidos
+	passos: usize,
+}
+
+impl Ambiente {
+	// Ambiente com o lado, a frota e o mapa das opções
+	pub fn new(opcoes: &Opcoes) -> Result<Ambiente, String> {
+		let base = novo_jogo(opcoes)?;
+		let mut ambiente = Ambiente { mapa: base.terreno.clone(), base, observacao: Vec::new(), vivos: 0, passos: 0 };
+		ambiente.reinicia(0);
+		Ok(ambiente)
+	}
+
+	pub fn lado(&self) -> usize {
+		self.base.lado()
+	}
+
+	// Nova partida, a mesma frota para a mesma semente
+	pub fn reinicia(&mut self, semente: u64) -> &[i8] {
+		let mut jogo = Jogo { terreno: self.base.terreno.clone(), navios_humano: Vec::new(), navios_bot: self.base.navios_bot.clone() };
+		sorteia_navios(&mut jogo, &Jogador::BOT, &mut gerador(semente, FLUXO_FROTA_BOT));
+		self.mapa = jogo.mapeia_bot(None);
+		self.observacao = self.mapa.iter().flatten()
+			.map(|&celula| if eh_obstaculo(celula) { OBS_TERRENO } else { OBS_DESCONHECIDA })
+			.collect();
+		self.vivos = self.mapa.iter().flatten().filter(|&&celula| tem_navio_inteiro(celula)).count();
+		self.passos = 0;
+		&self.observacao
+	}
+
+	pub fn observacao(&self) -> &[i8] {
+		&self.observacao
+	}
+
+	// Tiro simples na célula 'acao'
+	pub fn passo(&mut self, acao: usize) -> Passo {
+		let lado = self.lado();
+		self.passos += 1;
+		let truncado = self.passos >= LIMITE_PASSOS*lado*lado;
+		if acao >= lado*lado || self.observacao[acao] != OBS_DESCONHECIDA {
+			return Passo { recompensa: RECOMPENSA_INVALIDA, fim: false, truncado };
+		}
+
+		let acertou = atinge(&mut self.mapa, (Arma::TIRO, Direcao::LESTE), (acao % lado, acao / lado))
+			.iter()
+			.any(|&(_,_,celula)| celula != AGUA);
+		let recompensa = match acertou {
+			true => {
+				self.observacao[acao] = OBS_ACERTO;
+				self.vivos -= 1;
+				RECOMPENSA_ACERTO
+			}
+			false => {
+				self.observacao[acao] = OBS_AGUA;
+				0.0
+			}
+		};
+		Passo { recompensa, fim: self.vivos == 0, truncado: truncado && self.vivos > 0 }
+	}
+}
+
+
+// Vários ambientes passados juntos
+pub struct Lote {
+	pub ambientes: Vec<Ambiente>,
+	proxima_semente: u64,
+}
+
+impl Lote {
+	// 'quantos' ambientes com as sementes 'semente', 'semente'+1, ...
+	pub fn new(opcoes: &Opcoes, quantos: usize, semente: u64) -> Result<Lote, String> {
+		let mut ambientes = Vec::with_capacity(quantos);
+		for n in 0..quantos {
+			let mut ambiente = Ambiente::new(opcoes)?;
+			ambiente.reinicia(semente.wrapping_add(n as u64));
+			ambientes.push(ambiente);
+		}
+		Ok(Lote { ambientes, proxima_semente: semente.wrapping_add(quantos as u64) })
+	}
+
+	// Observações de todos os ambientes, uma depois da outra
+	pub fn observacoes(&self) -> Vec<i8> {
+		self.ambientes.iter().flat_map(|ambiente| ambiente.observacao().iter().copied()).collect()
+	}
+
+	// Uma ação por ambiente; quem termina recomeça com a próxima semente e já mostra a nova observação
+	pub fn passo(&mut self, acoes: &[usize]) -> Vec<Passo> {
+		assert_eq!(acoes.len(), self.ambientes.len(), "uma ação por ambiente");
+		let mut passos = Vec::with_capacity(acoes.len());
+		for (ambiente,&acao) in self.ambientes.iter_mut().zip(acoes.iter()) {
+			let passo = ambiente.passo(acao);
+			if passo.fim || passo.truncado {
+				ambiente.reinicia(self.proxima_semente);
+				self.proxima_semente = self.proxima_semente.wrapping_add(1);
+			}
+			passos.push(passo);
+		}
+		passos
+	}
+}
+
+
+// Política tabular: chance de acerto estimada para cada vizinhança de célula
+pub struct Tabela {
+	estimativas: Vec<f64>,
+	visitas: Vec<u32>,
+}
+
+// Vizinhas que formam o padrão de uma célula, até duas casas nas quatro direções
+const VIZINHAS: [(i64,i64);8] = [(0,-1), (0,1), (-1,0), (1,0), (0,-2), (0,2), (-2,0), (2,0)];
+
+impl Tabela {
+	pub fn new() -> Tabela {
+		let padroes = 3_usize.pow(VIZINHAS.len() as u32);
+		Tabela { estimativas: vec![0.5; padroes], visitas: vec![0; padroes] }
+	}
+
+	// Cada vizinha vale 0 se desconhecida, 1 se fechada (água, terreno ou fora) e 2 se acerto
+	fn padrao(observacao: &[i8], lado: usize, celula: usize) -> usize {
+		let (x,y) = ((celula % lado) as i64, (celula / lado) as i64);
+		VIZINHAS.iter().fold(0, |padrao, &(dx,dy)| {
+			let (vx,vy) = (x+dx, y+dy);
+			let estado = match vx >= 0 && vy >= 0 && vx < lado as i64 && vy < lado as i64 {
+				false => 1,
+				true => match observacao[vy as usize * lado + vx as usize] {
+					OBS_DESCONHECIDA => 0,
+					OBS_ACERTO => 2,
+					_ => 1,
+				},
+			};
+			padrao*3 + estado
+		})
+	}
+
+	// A célula desconhecida com a maior estimativa, empates sorteados; com 'exploracao' de chance, qualquer uma
+	pub fn escolhe(&self, observacao: &[i8], lado: usize, exploracao: f64, rng: &mut impl Rng) -> usize {
+		let livres = livres(observacao);
+		if livres.is_empty() || rng.gen_bool(exploracao) {
+			return aleatoria(observacao, rng);
+		}
+		let estimativa = |c: usize| self.estimativas[Tabela::padrao(observacao, lado, c)];
+		let maior = livres.iter().map(|&c| estimativa(c)).fold(f64::MIN, f64::max);
+		let melhores: Vec<usize> = livres.into_iter().filter(|&c| estimativa(c) == maior).collect();
+		melhores[rng.gen_range(0..melhores.len())]
+	}
+
+	// Média das recompensas de cada padrão, 'observacao' é a de antes do tiro
+	pub fn aprende(&mut self, observacao: &[i8], lado: usize, acao: usize, recompensa: f64) {
+		let padrao = Tabela::padrao(observacao, lado, acao);
+		self.visitas[padrao] += 1;
+		self.estimativas[padrao] += (recompensa - self.estimativas[padrao]) / self.visitas[padrao] as f64;
+	}
+}
+
+
+// Tiros até afundar a frota, em média, nas partidas com as sementes dadas
+pub fn avalia(opcoes: &Opcoes, sementes: std::ops::Range<u64>, mut escolhe: impl FnMut(&[i8], usize) -> usize) -> Result<f64, String> {
+	let mut ambiente = Ambiente::new(opcoes)?;
+	let partidas = sementes.end - sementes.start;
+	let mut tiros = 0;
+	for semente in sementes {
+		ambiente.reinicia(semente);
+		loop {
+			let acao = escolhe(ambiente.observacao(), ambiente.lado());
+			let passo = ambiente.passo(acao);
+			tiros += 1;
+			if passo.fim || passo.truncado {
+				break;
+			}
+		}
+	}
+	Ok(tiros as f64 / partidas as f64)
+}
+
+
+// Treina a política tabular em lotes e compara com tiros aleatórios em partidas que não viu
+pub fn treina(opcoes: &Opcoes, episodios: u64, semente: u64, mostra: &mut dyn FnMut(String)) -> Result<(Tabela,f64,f64), String> {
+	let mut lote = Lote::new(opcoes, AMBIENTES_POR_LOTE, semente)?;
+	let mut tabela = Tabela::new();
+	let mut rng = gerador(semente, FLUXO_RODADAS);
+	let mut tiros_correntes = vec![0_u64; AMBIENTES_POR_LOTE];
+	let (mut terminados, mut tiros_terminados, mut passos) = (0_u64, 0_u64, 0_u64);
+	let relatorio = (episodios / 5).max(1);
+	let inicio = Instant::now();
+
+	mostra(tr!("Treinando a política tabular: {} partidas em lotes de {}", episodios, AMBIENTES_POR_LOTE));
+	while terminados < episodios {
+		let lado = lote.ambientes[0].lado();
+		let observacoes = lote.observacoes();
+		let acoes: Vec<usize> = observacoes.chunks(lado*lado)
+			.map(|observacao| tabela.escolhe(observacao, lado, EXPLORACAO, &mut rng))
+			.collect();
+		let resultados = lote.passo(&acoes);
+		passos += acoes.len() as u64;
+		for (n,(observacao,passo)) in observacoes.chunks(lado*lado).zip(resultados.iter()).enumerate() {
+			tabela.aprende(observacao, lado, acoes[n], passo.recompensa);
+			tiros_correntes[n] += 1;
+			if passo.fim || passo.truncado {
+				terminados += 1;
+				tiros_terminados += tiros_correntes[n];
+				tiros_correntes[n] = 0;
+				if terminados % relatorio == 0 {
+					mostra(tr!("  {} partidas: média de {} tiros", terminados, format!("{:.1}", tiros_terminados as f64 / relatorio as f64)));
+					tiros_terminados = 0;
+				}
+			}
+		}
+	}
+	let segundos = inicio.elapsed().as_secs_f64().max(1e-9);
+	mostra(tr!("  {} passos por segundo", format!("{:.0}", passos as f64 / segundos)));
+
+	// Avaliação em sementes depois de todas as usadas no treino
+	let novas = semente.wrapping_add(1 << 32);
+	let sementes = novas .. novas + PARTIDAS_AVALIACAO as u64;
+	let mut rng_aleatorio = gerador(semente, FLUXO_RODADAS + 1);
+	let aleatoria = avalia(opcoes, sementes.clone(), |observacao, _| aleatoria(observacao, &mut rng_aleatorio))?;
+	let treinada = avalia(opcoes, sementes, |observacao, lado| tabela.escolhe(observacao, lado, 0.0, &mut rng))?;
+	mostra(tr!("Avaliação em {} partidas novas, tiros para afundar a frota:", PARTIDAS_AVALIACAO));
+	mostra(tr!("  tiros aleatórios: {}", format!("{:.1}", aleatoria)));
+	mostra(tr!("  política treinada: {}", format!("{:.1}", treinada)));
+	Ok((tabela, aleatoria, treinada))
+}
+
+
+// Política aleatória: qualquer célula desconhecida, 0 se não houver
+pub fn aleatoria(observacao: &[i8], rng: &mut impl Rng) -> usize {
+	let livres = livres(observacao);
+	match livres.is_empty() {
+		true => 0,
+		false => livres[rng.gen_range(0..livres.len())],
+	}
+}
+
+
+fn livres(observacao: &[i8]) -> Vec<usize> {
+	(0..observacao.len()).filter(|&c| observacao[c] == OBS_DESCONHECIDA).collect()
+}
+
+
+#[cfg(test)]
+mod testes {
+	use super::*;
+	use crate::testes::{opcoes_de_teste,jogo_de_teste};
+
+	#[test]
+	fn ambiente_de_aprendizado() {
+		let opcoes = opcoes_de_teste(6);
+		let mut ambiente = Ambiente::new(&opcoes).unwrap();
+
+		// A mesma semente sorteia a frota do bot da partida com essa semente
+		ambiente.reinicia(3);
+		let mut jogo = jogo_de_teste(6);
+		sorteia_navios(&mut jogo, &Jogador::BOT, &mut gerador(3, FLUXO_FROTA_BOT));
+		let navios: Vec<usize> = jogo.mapeia_bot(None).concat().iter().enumerate()
+			.filter(|&(_,&celula)| tem_navio_inteiro(celula)).map(|(n,_)| n).collect();
+		assert!(ambiente.observacao().iter().all(|&o| o == OBS_DESCONHECIDA));
+
+		// Água, tiro repetido e fora do tabuleiro, depois afunda tudo
+		let agua = (0..36).find(|c| !navios.contains(c)).unwrap();
+		let passo = |recompensa, fim| Passo { recompensa, fim, truncado: false };
+		assert_eq!(ambiente.passo(agua), passo(0.0, false));
+		assert_eq!(ambiente.observacao()[agua], OBS_AGUA);
+		assert_eq!(ambiente.passo(agua), passo(RECOMPENSA_INVALIDA, false));
+		assert_eq!(ambiente.passo(36), passo(RECOMPENSA_INVALIDA, false));
+		for (n,&celula) in navios.iter().enumerate() {
+			assert_eq!(ambiente.passo(celula), passo(RECOMPENSA_ACERTO, n == navios.len()-1));
+		}
+
+		// No lote quem termina recomeça, só repetir ações inválidas trunca a partida
+		let mut lote = Lote::new(&opcoes, 2, 0).unwrap();
+		assert_eq!(lote.observacoes().len(), 72);
+		let passos: Vec<Passo> = (0..4*36).map(|_| lote.passo(&[99, 99])[0]).collect();
+		assert!(passos[..4*36-1].iter().all(|p| !p.truncado) && passos[4*36-1].truncado);
+		assert_eq!(lote.ambientes[0].observacao(), &[OBS_DESCONHECIDA; 36]);
+
+		// Um treino curto já atira melhor que o acaso
+		let (_, aleatoria, treinada) = treina(&opcoes, 300, 1, &mut |_| {}).unwrap();
+		assert!(treinada < aleatoria - 3.0, "{} {}", treinada, aleatoria);
+	}
+}
@@ -0,0 +1,357 @@
+/*
+	Tutorial para quem nunca jogou, no menu principal
+
+	Um roteiro de passos, cada um com a sua instrução na linha de mensagens.
+	O passo só termina quando é feito certo; teclas dos passos seguintes
+	ainda não valem e as dos passos já feitos continuam valendo:
+
+	- Arrumação: levar o porta-aviões até a célula destacada, girar, mudar
+	de navio e iniciar a batalha
+	- Batalha: levar a mira até a célula destacada, atirar e afundar o
+	submarino na outra célula destacada
+
+	O tabuleiro é sempre o mesmo, 10x10 com a frota padrão em posições fixas,
+	e o bot responde a cada tiro com um tiro roteirizado: primeiro na água,
+	depois no porta-aviões do humano. Esc pausa, como no jogo.
+*/
+
+
+use crossterm::{ExecutableCommand,QueueableCommand};
+use crossterm::cursor;
+use crossterm::event::{Event,KeyEventKind};
+use crossterm::style;
+use crossterm::terminal::{self,Clear};
+
+use crate::{Jogo,Jogador,Mapa,Arma,Direcao,Desfecho,Opcoes,AGUA,DESTRUIDO};
+use crate::{desenha_moldura,desenha_navio,desdesenha_navio,desenho_explosao,escreve_status};
+use crate::{posicao_na_tela,x_para_tela_humano,y_para_tela_humano,realiza_ataque,confere_tamanho};
+use crate::{frota,menu,teclas,terreno};
+use crate::console::Console;
+use crate::erro::ErroJogo;
+use crate::menu::Pausa;
+use crate::teclas::{Acao,Teclas};
+
+
+const LADO_TUTORIAL: usize = 10;
+
+// Popas dos navios da frota padrão, todos virados para LESTE
+const POSICOES_HUMANO: [(usize,usize);4] = [(0,0), (6,4), (5,8), (0,9)];
+const POSICOES_BOT: [(usize,usize);4] = [(0,0), (6,2), (1,6), (5,5)];
+
+// Onde o porta-aviões deve chegar e as duas células do submarino do bot
+const DESTINO: (usize,usize) = (2,2);
+const ALVOS: [(usize,usize);2] = [(5,5), (6,5)];
+
+
+// Passos do tutorial, na ordem em que são feitos
+#[derive(Debug,Copy,Clone,PartialEq,PartialOrd)]
+enum Passo {
+	MOVE,
+	GIRA,
+	MUDA,
+	INICIA,
+	MIRA,
+	ATIRA,
+	AFUNDA,
+	FIM,
+}
+
+impl Passo {
+	fn proximo(self) -> Passo {
+		match self {
+			Passo::MOVE => Passo::GIRA,
+			Passo::GIRA => Passo::MUDA,
+			Passo::MUDA => Passo::INICIA,
+			Passo::INICIA => Passo::MIRA,
+			Passo::MIRA => Passo::ATIRA,
+			Passo::ATIRA => Passo::AFUNDA,
+			Passo::AFUNDA | Passo::FIM => Passo::FIM,
+		}
+	}
+
+	// Instrução mostrada na linha de mensagens
+	fn instrucao(&self, teclas: &Teclas) -> String {
+		match self {
+			Passo::MOVE => tr!("Passo 1/7: leve o porta-aviões com {} até a célula destacada", teclas.movimento()),
+			Passo::GIRA => tr!("Passo 2/7: gire o navio com {}", teclas.tecla(Acao::GIRA)),
+			Passo::MUDA => tr!("Passo 3/7: passe para o próximo navio com {}", teclas.tecla(Acao::MUDA)),
+			Passo::INICIA => tr!("Passo 4/7: frota pronta, comece a batalha com {}", teclas.tecla(Acao::INICIA)),
+			Passo::MIRA => tr!("Passo 5/7: leve a mira com {} até a célula destacada do inimigo", teclas.movimento()),
+			Passo::ATIRA => tr!("Passo 6/7: atire com {}", teclas.tecla(Acao::FOGO)),
+			Passo::AFUNDA => String::from(tr!("Passo 7/7: acertou! Afunde o navio atirando na célula destacada")),
+			Passo::FIM => String::from(tr!("Submarino afundado, tutorial concluído! Tecle algo para voltar")),
+		}
+	}
+}
+
+
+// Estado do tutorial
+struct Tutorial {
+	jogo: Jogo,
+	passo: Passo,
+	corrente: usize,			// Navio sendo arrumado
+	mapa_humano: Mapa,			// Tiros roteirizados do bot, refeito quando a batalha começa
+	mapa_bot: Mapa,				// Tiros do humano
+	mira_x: usize,
+	mira_y: usize,
+	respostas: usize,			// Tiros do bot já dados
+}
+
+impl Tutorial {
+	fn new() -> Tutorial {
+		let mut jogo = Jogo::new(&frota::carrega(None).expect("frota padrão existe"), terreno::mar_aberto(LADO_TUTORIAL))
+			.expect("frota padrão cabe no tabuleiro do tutorial");
+		for (navios,posicoes) in [(&mut jogo.navios_humano, POSICOES_HUMANO), (&mut jogo.navios_bot, POSICOES_BOT)] {
+			for (navio,&(x,y)) in navios.iter_mut().zip(posicoes.iter()) {
+				(navio.popa_x, navio.popa_y, navio.direcao) = (x, y, Direcao::LESTE);
+			}
+		}
+		let mapa_bot = jogo.mapeia_bot(None);
+		Tutorial{ mapa_humano: jogo.mapeia_humano(None), mapa_bot, jogo, passo: Passo::MOVE, corrente: 0, mira_x: 0, mira_y: 0, respostas: 0 }
+	}
+
+	fn na_batalha(&self) -> bool {
+		self.passo >= Passo::MIRA
+	}
+
+	// Tela inteira, depois da pausa ou da ajuda
+	fn desenha(&self, con: &mut dyn Console, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+		let tema = &opcoes.tema;
+		desenha_moldura(con, 0, 0, tema, &self.jogo.terreno)?;
+		for navio in self.jogo.navios_humano.iter() {
+			desenha_navio(con, navio, tema, false)?;
+		}
+		let originais = [
+			(Jogador::HUMANO, &self.mapa_humano, self.jogo.mapeia_humano(None), &self.jogo.navios_humano),
+			(Jogador::BOT, &self.mapa_bot, self.jogo.mapeia_bot(None), &self.jogo.navios_bot),
+		];
+		for (jogador, mapa, original, navios) in originais.iter() {
+			for (y,linha) in mapa.iter().enumerate() {
+				for (x,&celula) in linha.iter().enumerate() {
+					if celula == DESTRUIDO {
+						con
+							.queue( posicao_na_tela(jogador, x, y, LADO_TUTORIAL) )?
+							.queue( style::PrintStyledContent(desenho_explosao(original[y][x], navios, tema)) )?;
+					}
+				}
+			}
+		}
+		self.escreve_passo(con, opcoes)?;
+		if !self.na_batalha() {
+			escreve_status(con, 1, &tr!("Navio: {}", tr!(self.jogo.navios_humano[self.corrente].nome.as_str())))?;
+		}
+		self.destaca(con, opcoes)
+	}
+
+	// Instrução do passo na primeira linha de mensagens
+	fn escreve_passo(&self, con: &mut dyn Console, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+		con
+			.queue( cursor::RestorePosition )?
+			.queue( Clear(terminal::ClearType::CurrentLine) )?
+			.queue( style::Print(self.passo.instrucao(&opcoes.teclas)) )?
+			.flush()?;
+		Ok(())
+	}
+
+	// Desenha a célula destacada do passo e volta o cursor para o navio ou para a mira
+	fn destaca(&self, con: &mut dyn Console, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+		let destaque = match self.passo {
+			Passo::MOVE => Some((Jogador::HUMANO, DESTINO)),
+			Passo::MIRA | Passo::ATIRA => Some((Jogador::BOT, ALVOS[0])),
+			Passo::AFUNDA => Some((Jogador::BOT, ALVOS[1])),
+			_ => None,
+		};
+		if let Some((jogador,(x,y))) = destaque {
+			// Um navio por cima esconde o destaque
+			let coberto = matches!(jogador, Jogador::HUMANO) && self.jogo.navios_humano.iter().any(|n| n.celulas().contains(&(x,y)));
+			if !coberto {
+				con
+					.queue( posicao_na_tela(&jogador, x, y, LADO_TUTORIAL) )?
+					.queue( style::PrintStyledContent(opcoes.tema.destaque()) )?;
+			}
+		}
+		self.posiciona_cursor(con)
+	}
+
+	fn posiciona_cursor(&self, con: &mut dyn Console) -> Result<(), ErroJogo> {
+		let posicao = match self.na_batalha() {
+			true => posicao_na_tela(&Jogador::BOT, self.mira_x, self.mira_y, LADO_TUTORIAL),
+			false => {
+				let navio = &self.jogo.navios_humano[self.corrente];
+				cursor::MoveTo(x_para_tela_humano(navio.popa_x), y_para_tela_humano(navio.popa_y))
+			}
+		};
+		con.execute(posicao)?;
+		Ok(())
+	}
+
+	// Passo feito, mostra o próximo
+	fn avanca(&mut self, con: &mut dyn Console, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+		self.passo = self.passo.proximo();
+		self.escreve_passo(con, opcoes)?;
+		self.destaca(con, opcoes)
+	}
+
+	// Move o navio corrente se couber, o destaque embaixo dele reaparece quando ele sai
+	fn move_navio(&mut self, con: &mut dyn Console, opcoes: &Opcoes, direcao: Direcao) -> Result<(), ErroJogo> {
+		if !self.jogo.pode_mover_navio(&Jogador::HUMANO, self.corrente, direcao) {
+			return Ok(());
+		}
+		desdesenha_navio(con, &self.jogo.navios_humano[self.corrente], &opcoes.tema)?;
+		let navio = &mut self.jogo.navios_humano[self.corrente];
+		match direcao {
+			Direcao::NORTE => navio.popa_y -= 1,
+			Direcao::SUL => navio.popa_y += 1,
+			Direcao::LESTE => navio.popa_x += 1,
+			Direcao::OESTE => navio.popa_x -= 1,
+		}
+		desenha_navio(con, &self.jogo.navios_humano[self.corrente], &opcoes.tema, true)?;
+		let navio = &self.jogo.navios_humano[self.corrente];
+		if self.passo == Passo::MOVE && (navio.popa_x, navio.popa_y) == DESTINO {
+			return self.avanca(con, opcoes);
+		}
+		self.destaca(con, opcoes)
+	}
+
+	// Tiro do humano na célula destacada e a resposta roteirizada do bot
+	fn atira(&mut self, con: &mut dyn Console, opcoes: &Opcoes) -> Result<(), ErroJogo> {
+		realiza_ataque(con, &mut self.mapa_bot, &self.jogo.navios_bot, &Jogador::BOT,
+						(Arma::TIRO, Direcao::LESTE), (self.mira_x, self.mira_y), &opcoes.tema)?;
+
+		// Primeiro na água, do canto de baixo para cima, depois na popa do porta-aviões
+		let (x,y) = match self.respostas {
+			0 => (0..LADO_TUTORIAL*LADO_TUTORIAL).rev()
+					.map(|i| (i % LADO_TUTORIAL, i / LADO_TUTORIAL))
+					.find(|&(x,y)| self.mapa_humano[y][x] == AGUA)
+					.expect("tabuleiro tem água"),
+			_ => self.jogo.navios_humano[0].celulas()[0],
+		};
+		self.respostas += 1;
+		let impacto = realiza_ataque(con, &mut self.mapa_humano, &self.jogo.navios_humano, &Jogador::HUMANO,
+										(Arma::TIRO, Direcao::LESTE), (x,y), &opcoes.tema)?;
+		let aviso = match impacto.acertos.is_empty() {
+			true => tr!("O bot respondeu em {} {}: água", x, y),
+			false => tr!("O bot respondeu em {} {}: acertou o seu {}", x, y, tr!(self.jogo.navios_humano[0].nome.as_str())),
+		};
+		escreve_status(con, 2, &aviso)?;
+		self.avanca(con, opcoes)
+	}
+}
+
+
+// Roda o tutorial até o fim, CONTINUA quando foi concluído
+pub fn executa(con: &mut dyn Console, opcoes: &Opcoes) -> Result<Desfecho, ErroJogo> {
+	if let Err(erro @ ErroJogo::TAMANHO(..)) = confere_tamanho(con, LADO_TUTORIAL) {
+		menu::mostra_texto(con, &[erro.to_string()])?;
+		return Ok(Desfecho::MENU);
+	}
+
+	let mut tutorial = Tutorial::new();
+	tutorial.desenha(con, opcoes)?;
+	con.modo_bruto(true)?;
+
+	let desfecho = loop {
+		let key_event = match con.le_evento()? {
+			Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+			Event::Resize(_colunas,_linhas) => {
+				confere_tamanho(con, LADO_TUTORIAL)?;
+				continue;
+			}
+			_ => continue,
+		};
+		if teclas::aborta(&key_event) {
+			break Desfecho::ABORTADO;
+		}
+		if tutorial.passo == Passo::FIM {
+			break Desfecho::CONTINUA;
+		}
+
+		let lado = LADO_TUTORIAL;
+		let passo = tutorial.passo;
+		let mira = (tutorial.mira_x, tutorial.mira_y);
+		match opcoes.teclas.acao(&key_event) {
+			Some(Acao::PAUSA) => {
+				con.modo_bruto(false)?;
+				match menu::pausa(con, false, &opcoes.teclas)? {
+					Pausa::CONTINUAR | Pausa::SALVAR => {}
+					Pausa::REINICIAR => tutorial = Tutorial::new(),
+					Pausa::SAIR => break Desfecho::MENU,
+					Pausa::ABORTAR => break Desfecho::ABORTADO,
+				}
+				tutorial.desenha(con, opcoes)?;
+				con.modo_bruto(true)?;
+			}
+			Some(Acao::AJUDA) => {
+				let acoes: &[Acao] = if tutorial.na_batalha() { &Acao::BATALHA } else { &Acao::ARRUMACAO };
+				menu::mostra_ajuda(con, &opcoes.teclas.ajuda(acoes))?;
+				con.modo_bruto(false)?;
+				tutorial.desenha(con, opcoes)?;
+				con.modo_bruto(true)?;
+			}
+
+			// Arrumação
+			Some(Acao::CIMA) if passo < Passo::MIRA => tutorial.move_navio(con, opcoes, Direcao::NORTE)?,
+			Some(Acao::BAIXO) if passo < Passo::MIRA => tutorial.move_navio(con, opcoes, Direcao::SUL)?,
+			Some(Acao::DIREITA) if passo < Passo::MIRA => tutorial.move_navio(con, opcoes, Direcao::LESTE)?,
+			Some(Acao::ESQUERDA) if passo < Passo::MIRA => tutorial.move_navio(con, opcoes, Direcao::OESTE)?,
+			Some(Acao::GIRA) if (Passo::GIRA ..= Passo::INICIA).contains(&passo) => {
+				if tutorial.jogo.pode_girar_navio(&Jogador::HUMANO, tutorial.corrente) {
+					desdesenha_navio(con, &tutorial.jogo.navios_humano[tutorial.corrente], &opcoes.tema)?;
+					tutorial.jogo.navios_humano[tutorial.corrente].gira();
+					desenha_navio(con, &tutorial.jogo.navios_humano[tutorial.corrente], &opcoes.tema, true)?;
+					if passo == Passo::GIRA {
+						tutorial.avanca(con, opcoes)?;
+					}
+				} else {
+					escreve_status(con, 2, tr!("Não há espaço para girar aqui, mova o navio antes"))?;
+					tutorial.posiciona_cursor(con)?;
+				}
+			}
+			Some(Acao::MUDA) if (Passo::MUDA ..= Passo::INICIA).contains(&passo) => {
+				tutorial.corrente = (tutorial.corrente+1) % tutorial.jogo.navios_humano.len();
+				escreve_status(con, 1, &tr!("Navio: {}", tr!(tutorial.jogo.navios_humano[tutorial.corrente].nome.as_str())))?;
+				if passo == Passo::MUDA {
+					tutorial.avanca(con, opcoes)?;
+				} else {
+					tutorial.posiciona_cursor(con)?;
+				}
+			}
+			Some(Acao::INICIA) if passo == Passo::INICIA => {
+				tutorial.mapa_humano = tutorial.jogo.mapeia_humano(None);
+				con
+					.queue( cursor::RestorePosition )?
+					.queue( Clear(terminal::ClearType::FromCursorDown) )?;
+				tutorial.avanca(con, opcoes)?;
+			}
+
+			// Batalha
+			Some(Acao::CIMA) => tutorial.mira_y = tutorial.mira_y.saturating_sub(1),
+			Some(Acao::BAIXO) => tutorial.mira_y = (tutorial.mira_y+1).min(lado-1),
+			Some(Acao::DIREITA) => tutorial.mira_x = (tutorial.mira_x+1).min(lado-1),
+			Some(Acao::ESQUERDA) => tutorial.mira_x = tutorial.mira_x.saturating_sub(1),
+			Some(Acao::FOGO) if passo == Passo::ATIRA && mira == ALVOS[0] => tutorial.atira(con, opcoes)?,
+			Some(Acao::FOGO) if passo == Passo::AFUNDA && mira == ALVOS[1] => tutorial.atira(con, opcoes)?,
+			Some(Acao::FOGO) if tutorial.na_batalha() => {
+				escreve_status(con, 2, tr!("Mire na célula destacada antes de atirar"))?;
+				tutorial.posiciona_cursor(con)?;
+			}
+
+			// Teclas dos próximos passos ainda não valem
+			Some(_) => {
+				escreve_status(con, 2, tr!("Ainda não, siga o passo acima"))?;
+				tutorial.posiciona_cursor(con)?;
+			}
+			None => {}
+		}
+
+		// Mira que chegou ao alvo
+		if tutorial.passo == Passo::MIRA && (tutorial.mira_x, tutorial.mira_y) == ALVOS[0] {
+			tutorial.avanca(con, opcoes)?;
+		} else if tutorial.na_batalha() && mira != (tutorial.mira_x, tutorial.mira_y) {
+			tutorial.posiciona_cursor(con)?;
+		}
+	};
+
+	con.modo_bruto(false)?;
+	Ok(desfecho)
+}
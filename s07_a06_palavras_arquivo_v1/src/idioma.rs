@@ -0,0 +1,96 @@
+/*
+	Idioma das mensagens, cópia reduzida do idioma.rs da Batalha Naval
+	com o catálogo só deste programa
+
+	- O texto em português é a chave, o catálogo 'INGLES' traz a tradução
+	- Marcadores '{}' são trocados pelos argumentos, na ordem, com a macro 'tr!'
+	- O idioma vem de LC_ALL, LC_MESSAGES ou LANG, ou da opção '--lang pt|en'
+*/
+
+
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool,Ordering};
+
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Idioma {
+	PT,
+	EN,
+}
+
+impl Idioma {
+	// Idioma pelo código, aceita também 'pt_BR', 'en_US.UTF-8' e semelhantes
+	pub fn por_codigo(codigo: &str) -> Option<Idioma> {
+		let codigo = codigo.to_lowercase();
+		if codigo.starts_with("pt") {
+			Some(Idioma::PT)
+		} else if codigo.starts_with("en") {
+			Some(Idioma::EN)
+		} else {
+			None
+		}
+	}
+
+	// Idioma do ambiente, português quando não definido
+	pub fn do_ambiente() -> Idioma {
+		let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+			.filter_map(|variavel| env::var(variavel).ok())
+			.find(|valor| !valor.is_empty());
+		match locale.as_deref() {
+			None | Some("C") | Some("POSIX") => Idioma::PT,
+			Some(valor) if valor.starts_with("C.") => Idioma::PT,
+			Some(valor) => Idioma::por_codigo(valor).unwrap_or(Idioma::EN),
+		}
+	}
+}
+
+
+static INGLES_ATIVO: AtomicBool = AtomicBool::new(false);
+
+// Troca o idioma das mensagens
+pub fn escolhe(idioma: Idioma) {
+	INGLES_ATIVO.store(idioma == Idioma::EN, Ordering::Relaxed);
+}
+
+// Mensagem no idioma atual
+pub fn traduz(texto: &str) -> &str {
+	match INGLES_ATIVO.load(Ordering::Relaxed) {
+		false => texto,
+		true => INGLES.iter().find(|(pt,_)| *pt == texto).map_or(texto, |(_,en)| en),
+	}
+}
+
+// Troca cada '{}' do modelo pelo próximo argumento
+pub fn formata(modelo: &str, argumentos: &[&dyn Display]) -> String {
+	let mut partes = modelo.split("{}");
+	let mut texto = String::from(partes.next().unwrap_or(""));
+	for (i,parte) in partes.enumerate() {
+		if let Some(argumento) = argumentos.get(i) {
+			texto.push_str(&argumento.to_string());
+		}
+		texto.push_str(parte);
+	}
+	texto
+}
+
+
+// Mensagem traduzida, com argumentos vira String
+macro_rules! tr {
+	($texto:expr) => {
+		$crate::idioma::traduz($texto)
+	};
+	($modelo:expr, $($argumento:expr),+ $(,)?) => {
+		$crate::idioma::formata($crate::idioma::traduz($modelo), &[$(&$argumento),+])
+	};
+}
+
+
+// Catálogo em inglês
+const INGLES: &[(&str,&str)] = &[
+	("Uso: s07_a06_lendo_arquivo_txt [--lang pt|en] <nome do arquivo>",
+		"Usage: s07_a06_lendo_arquivo_txt [--lang pt|en] <file name>"),
+	("Abrindo {} ...", "Opening {} ..."),
+	("Arquivo não encontrado: {}", "File not found: {}"),
+	("Não conseguiu ler o arquivo", "Could not read the file"),
+];
@@ -8,9 +8,16 @@ by Steve Klabnik and Carol Nichols, with contributions from the Rust Community
 This version of the text assumes you’re using Rust 1.67.1 (released 2023-02-09) or later
 https://doc.rust-lang.org/stable/book/
 
+
+Mensagens em português ou inglês, pelo LANG ou com '--lang pt|en', ver idioma.rs
 */
 
 
+#[macro_use]
+mod idioma;
+
+use idioma::Idioma;
+
 use std::process;
 use std::env;
 
@@ -38,19 +45,33 @@ impl Teste {
 
 fn main() {
 
-	let args: Vec<String> = env::args().collect();
+	let mut args: Vec<String> = env::args().collect();
+
+	// Idioma das mensagens, '--lang' tem precedência sobre o ambiente
+	let mut idioma = Idioma::do_ambiente();
+	if args.len() > 1 && args[1] == "--lang" {
+		match args.get(2).and_then(|codigo| Idioma::por_codigo(codigo)) {
+			Some(escolhido) => {
+				idioma = escolhido;
+				args.drain(1..3);
+			}
+			None => args.clear(),		// Mostra o uso
+		}
+	}
+	idioma::escolhe(idioma);
+
 	if args.len() != 2 {
-		println!("Uso: s07_a06_lendo_arquivo_txt <nome do arquivo>");
+		println!("{}", tr!("Uso: s07_a06_lendo_arquivo_txt [--lang pt|en] <nome do arquivo>"));
 		process::exit(1);
 	}
 
 	// Abre o arquivo txt para leitura
 	let mut reader: BufReader<File>;
-	println!("Abrindo {} ...", args[1]);
+	println!("{}", tr!("Abrindo {} ...", args[1]));
 	if let Ok(file) = File::open(&args[1]) {
 		reader = BufReader::new(file);
 	} else {
-		println!("Arquivo não encontrado: {}", args[1]);
+		println!("{}", tr!("Arquivo não encontrado: {}", args[1]));
 		process::exit(1);
 	}
 
@@ -61,7 +82,7 @@ fn main() {
 	// Le o arquivo e insere na tabela hash
 	loop {
 		let mut linha_lida = String::new();
-		let n_bytes = reader.read_line(&mut linha_lida).expect("Não conseguiu ler o arquivo");
+		let n_bytes = reader.read_line(&mut linha_lida).expect(tr!("Não conseguiu ler o arquivo"));
 
 		if n_bytes == 0 {
 			// Terminou o arquivo